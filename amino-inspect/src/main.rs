@@ -0,0 +1,542 @@
+//! `amino-inspect` prints the tag and wire type structure of amino encoded bytes without a
+//! schema, and computes the prefix and disambiguation bytes of registered amino names.
+//!
+//! ```text
+//! amino-inspect [--hex | --base64 | --raw] [--name NAME]... [--names FILE] [INPUT]
+//! amino-inspect disfix NAME...
+//! ```
+//!
+//! The input is read from `INPUT`, or from stdin if it is omitted. Without a format flag, input
+//! is decoded as hex or base64, whichever is valid. Input which is valid in both formats is
+//! decoded as the one which is a message, with a note on stderr, and is rejected if that does not
+//! tell them apart. Length-delimited values which parse as a message are printed as nested
+//! messages, and 4-byte prefixes of the given amino names are recognised wherever a value starts
+//! with one.
+
+extern crate prost_amino as prost;
+
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Write as FmtWrite;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process;
+use std::str;
+
+use prost::amino::name_to_disfix;
+use prost::encoding::decode_varint;
+
+const USAGE: &str = "\
+usage: amino-inspect [--hex | --base64 | --raw] [--name NAME]... [--names FILE] [INPUT]
+       amino-inspect disfix NAME...
+
+options:
+    --hex           decode the input as hex
+    --base64        decode the input as standard or url-safe base64
+    --raw           use the input bytes as is
+    --name NAME     recognise the registered prefix of an amino name
+    --names FILE    recognise the prefixes of the amino names in FILE, one per line
+    -h, --help      print this message
+";
+
+/// Maximum nesting depth when guessing nested messages.
+const MAX_DEPTH: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Format {
+    Auto,
+    Hex,
+    Base64,
+    Raw,
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("disfix") => disfix_command(&args[1..]),
+        _ => inspect_command(&args),
+    };
+    match result {
+        Ok(output) => {
+            let stdout = io::stdout();
+            let _ = stdout.lock().write_all(output.as_bytes());
+        }
+        Err(error) => {
+            eprintln!("amino-inspect: {}", error);
+            process::exit(1);
+        }
+    }
+}
+
+fn disfix_command(args: &[String]) -> Result<String, String> {
+    if args.is_empty() {
+        return Err(format!("missing amino name\n\n{}", USAGE));
+    }
+    let mut output = String::new();
+    for name in args {
+        let (disamb, prefix) = name_to_disfix(name);
+        writeln!(
+            output,
+            "{}\n  disamb: {}\n  prefix: {}\n  disfix: {}{}",
+            name,
+            to_hex(&disamb),
+            to_hex(&prefix),
+            to_hex(&disamb),
+            to_hex(&prefix)
+        )
+        .unwrap();
+    }
+    Ok(output)
+}
+
+fn inspect_command(args: &[String]) -> Result<String, String> {
+    let mut format = Format::Auto;
+    let mut names = Vec::new();
+    let mut input = None;
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(USAGE.to_string()),
+            "--hex" => format = Format::Hex,
+            "--base64" => format = Format::Base64,
+            "--raw" => format = Format::Raw,
+            "--name" => match args.next() {
+                Some(name) => names.push(name.clone()),
+                None => return Err("--name requires an argument".to_string()),
+            },
+            "--names" => match args.next() {
+                Some(path) => {
+                    let contents = fs::read_to_string(path)
+                        .map_err(|error| format!("failed to read {}: {}", path, error))?;
+                    names.extend(
+                        contents
+                            .lines()
+                            .map(str::trim)
+                            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                            .map(str::to_string),
+                    );
+                }
+                None => return Err("--names requires an argument".to_string()),
+            },
+            flag if flag.starts_with("--") => {
+                return Err(format!("unknown option {}\n\n{}", flag, USAGE));
+            }
+            value if input.is_none() => input = Some(value.as_bytes().to_vec()),
+            _ => return Err(format!("unexpected argument {}\n\n{}", arg, USAGE)),
+        }
+    }
+
+    let input = match input {
+        Some(input) => input,
+        None => {
+            let mut input = Vec::new();
+            io::stdin()
+                .read_to_end(&mut input)
+                .map_err(|error| format!("failed to read stdin: {}", error))?;
+            input
+        }
+    };
+    let (bytes, note) = decode_input(&input, format)?;
+    if let Some(note) = note {
+        eprintln!("amino-inspect: {}", note);
+    }
+
+    let inspector = Inspector::new(&names);
+    Ok(inspector.inspect(&bytes))
+}
+
+/// Decodes the input in the given format, and returns the bytes along with a note about the
+/// format that was guessed when the input is both valid hex and valid base64.
+fn decode_input(input: &[u8], format: Format) -> Result<(Vec<u8>, Option<String>), String> {
+    let text = || str::from_utf8(input).map_err(|_| "input is not valid UTF-8 text".to_string());
+    match format {
+        Format::Raw => Ok((input.to_vec(), None)),
+        Format::Hex => Ok((from_hex(text()?)?, None)),
+        Format::Base64 => Ok((from_base64(text()?)?, None)),
+        Format::Auto => {
+            let text = text()?;
+            let trimmed = text.trim();
+            if trimmed.starts_with("0x") || trimmed.starts_with("0X") {
+                return Ok((from_hex(text)?, None));
+            }
+            let hex = if trimmed
+                .chars()
+                .all(|c| c.is_ascii_hexdigit() || c.is_whitespace())
+            {
+                from_hex(text).ok()
+            } else {
+                None
+            };
+            let (hex, base64) = match (hex, from_base64(text)) {
+                (Some(hex), Ok(base64)) => (hex, base64),
+                (Some(hex), Err(_)) => return Ok((hex, None)),
+                (None, base64) => return Ok((base64?, None)),
+            };
+
+            // Most hex strings are also valid base64, so the format which decodes to a message
+            // is used, and the input is rejected if that does not tell the two apart.
+            let ambiguous = "the input is both valid hex and valid base64";
+            let choose = "use --hex or --base64 to choose the format";
+            let (bytes, name) = match (is_message(&hex), is_message(&base64)) {
+                (true, false) => (hex, "hex"),
+                (false, true) => (base64, "base64"),
+                _ => return Err(format!("{}, {}", ambiguous, choose)),
+            };
+            let note = format!(
+                "{}, and was decoded as {} since only that is a message; {}",
+                ambiguous, name, choose
+            );
+            Ok((bytes, Some(note)))
+        }
+    }
+}
+
+fn from_hex(text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim();
+    let text = if text.starts_with("0x") || text.starts_with("0X") {
+        &text[2..]
+    } else {
+        text
+    };
+    let digits = text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            c.to_digit(16)
+                .map(|d| d as u8)
+                .ok_or_else(|| format!("invalid hex digit {:?}", c))
+        })
+        .collect::<Result<Vec<u8>, String>>()?;
+    if digits.len() % 2 != 0 {
+        return Err("hex input has an odd number of digits".to_string());
+    }
+    Ok(digits
+        .chunks(2)
+        .map(|pair| pair[0] << 4 | pair[1])
+        .collect())
+}
+
+/// Decodes standard or url-safe base64, with or without padding.
+fn from_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for c in text
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .take_while(|&c| c != '=')
+    {
+        let value = match c {
+            'A'..='Z' => c as u32 - 'A' as u32,
+            'a'..='z' => c as u32 - 'a' as u32 + 26,
+            '0'..='9' => c as u32 - '0' as u32 + 52,
+            '+' | '-' => 62,
+            '/' | '_' => 63,
+            _ => return Err(format!("invalid base64 character {:?}", c)),
+        };
+        acc = acc << 6 | value;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+            acc &= (1 << bits) - 1;
+        }
+    }
+    if bits >= 6 {
+        return Err("truncated base64 input".to_string());
+    }
+    Ok(bytes)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// A field parsed without a schema.
+#[derive(Debug, PartialEq)]
+enum Value<'a> {
+    Varint(u64),
+    SixtyFourBit(u64),
+    LengthDelimited(&'a [u8]),
+    ThirtyTwoBit(u32),
+}
+
+/// Parses `buf` as a sequence of fields, or returns `None` if it is not a well formed message.
+fn parse_fields<'a>(mut buf: &'a [u8]) -> Option<Vec<(u32, Value<'a>)>> {
+    let mut fields = Vec::new();
+    while !buf.is_empty() {
+        let key = decode_varint(&mut buf).ok()?;
+        if key > u64::from(u32::max_value()) {
+            return None;
+        }
+        let tag = key as u32 >> 3;
+        if tag == 0 {
+            return None;
+        }
+        let value = match key & 0x07 {
+            0 => Value::Varint(decode_varint(&mut buf).ok()?),
+            1 => {
+                if buf.len() < 8 {
+                    return None;
+                }
+                let mut value = 0u64;
+                for (i, &b) in buf[..8].iter().enumerate() {
+                    value |= u64::from(b) << (8 * i);
+                }
+                buf = &buf[8..];
+                Value::SixtyFourBit(value)
+            }
+            2 => {
+                let len = decode_varint(&mut buf).ok()?;
+                if len > buf.len() as u64 {
+                    return None;
+                }
+                let (value, rest) = buf.split_at(len as usize);
+                buf = rest;
+                Value::LengthDelimited(value)
+            }
+            5 => {
+                if buf.len() < 4 {
+                    return None;
+                }
+                let mut value = 0u32;
+                for (i, &b) in buf[..4].iter().enumerate() {
+                    value |= u32::from(b) << (8 * i);
+                }
+                buf = &buf[4..];
+                Value::ThirtyTwoBit(value)
+            }
+            _ => return None,
+        };
+        fields.push((tag, value));
+    }
+    Some(fields)
+}
+
+/// Returns `true` if `buf` is a well formed message, possibly preceded by its length like a
+/// registered type.
+fn is_message(buf: &[u8]) -> bool {
+    let mut rest = buf;
+    match decode_varint(&mut rest) {
+        Ok(len) if len == rest.len() as u64 && len > 0 => true,
+        _ => !buf.is_empty() && parse_fields(buf).is_some(),
+    }
+}
+
+struct Inspector {
+    prefixes: HashMap<[u8; 4], String>,
+}
+
+impl Inspector {
+    fn new(names: &[String]) -> Inspector {
+        let prefixes = names
+            .iter()
+            .map(|name| (name_to_disfix(name).1, name.clone()))
+            .collect();
+        Inspector { prefixes }
+    }
+
+    /// Returns the amino name whose prefix starts `buf`, if any.
+    fn prefix(&self, buf: &[u8]) -> Option<&str> {
+        if buf.len() < 4 {
+            return None;
+        }
+        let mut prefix = [0; 4];
+        prefix.copy_from_slice(&buf[..4]);
+        self.prefixes.get(&prefix).map(String::as_str)
+    }
+
+    fn inspect(&self, buf: &[u8]) -> String {
+        let mut output = String::new();
+
+        // Registered types are encoded with a leading length which covers the prefix and fields.
+        let mut body = buf;
+        let mut rest = buf;
+        if let Ok(len) = decode_varint(&mut rest) {
+            if len == rest.len() as u64 && len > 0 {
+                writeln!(output, "length: {}", len).unwrap();
+                body = rest;
+            }
+        }
+
+        match self.prefix(body) {
+            Some(name) => {
+                writeln!(output, "prefix: {} ({})", to_hex(&body[..4]), name).unwrap();
+                self.write_message(&mut output, &body[4..], 0);
+            }
+            None => {
+                if parse_fields(body).is_some() {
+                    self.write_message(&mut output, body, 0);
+                } else if parse_fields(buf).is_some() {
+                    output.clear();
+                    self.write_message(&mut output, buf, 0);
+                } else {
+                    writeln!(output, "not a message: {}", to_hex(buf)).unwrap();
+                }
+            }
+        }
+        output
+    }
+
+    fn write_message(&self, output: &mut String, buf: &[u8], depth: usize) {
+        let fields = match parse_fields(buf) {
+            Some(fields) => fields,
+            None => {
+                indent(output, depth);
+                writeln!(output, "malformed: {}", to_hex(buf)).unwrap();
+                return;
+            }
+        };
+        for (tag, value) in fields {
+            indent(output, depth);
+            match value {
+                Value::Varint(value) => {
+                    write!(output, "{}: varint {}", tag, value).unwrap();
+                    let zigzag = (value >> 1) as i64 ^ -((value & 1) as i64);
+                    if zigzag as u64 != value {
+                        write!(output, " (zigzag {})", zigzag).unwrap();
+                    }
+                    if (value as i64) < 0 {
+                        write!(output, " (signed {})", value as i64).unwrap();
+                    }
+                    writeln!(output).unwrap();
+                }
+                Value::SixtyFourBit(value) => writeln!(
+                    output,
+                    "{}: fixed64 {} (signed {}) (double {})",
+                    tag,
+                    value,
+                    value as i64,
+                    f64::from_bits(value)
+                )
+                .unwrap(),
+                Value::ThirtyTwoBit(value) => writeln!(
+                    output,
+                    "{}: fixed32 {} (signed {}) (float {})",
+                    tag,
+                    value,
+                    value as i32,
+                    f32::from_bits(value)
+                )
+                .unwrap(),
+                Value::LengthDelimited(value) => {
+                    self.write_length_delimited(output, tag, value, depth)
+                }
+            }
+        }
+    }
+
+    fn write_length_delimited(&self, output: &mut String, tag: u32, buf: &[u8], depth: usize) {
+        if let Some(name) = self.prefix(buf) {
+            writeln!(
+                output,
+                "{}: registered {} ({}) {{",
+                tag,
+                to_hex(&buf[..4]),
+                name
+            )
+            .unwrap();
+            self.write_message(output, &buf[4..], depth + 1);
+            indent(output, depth);
+            writeln!(output, "}}").unwrap();
+            return;
+        }
+
+        // Printable text is almost never a well formed message, but may happen to parse as one.
+        if let Ok(text) = str::from_utf8(buf) {
+            if !buf.is_empty() && text.chars().all(|c| !c.is_control()) {
+                writeln!(output, "{}: string {:?}", tag, text).unwrap();
+                return;
+            }
+        }
+
+        if !buf.is_empty() && depth < MAX_DEPTH && parse_fields(buf).is_some() {
+            writeln!(output, "{}: message ({} bytes) {{", tag, buf.len()).unwrap();
+            self.write_message(output, buf, depth + 1);
+            indent(output, depth);
+            writeln!(output, "}}").unwrap();
+        } else {
+            writeln!(output, "{}: bytes ({}) {}", tag, buf.len(), to_hex(buf)).unwrap();
+        }
+    }
+}
+
+fn indent(output: &mut String, depth: usize) {
+    for _ in 0..depth {
+        output.push_str("  ");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_formats() {
+        let bytes = vec![0xfb, 0xff, 0x00, 0x61];
+        assert_eq!(
+            decode_input(b"fbff 0061\n", Format::Hex),
+            Ok((bytes.clone(), None))
+        );
+        assert_eq!(
+            decode_input(b"0xFBFF0061", Format::Auto),
+            Ok((bytes.clone(), None))
+        );
+        assert_eq!(
+            decode_input(b"+/8AYQ==", Format::Auto),
+            Ok((bytes.clone(), None))
+        );
+        assert_eq!(
+            decode_input(b"-_8AYQ", Format::Base64),
+            Ok((bytes.clone(), None))
+        );
+        assert!(decode_input(b"abc", Format::Hex).is_err());
+        assert!(decode_input(b"A", Format::Base64).is_err());
+    }
+
+    #[test]
+    fn ambiguous_input() {
+        // Neither the hex nor the base64 decoding of these digits is a message.
+        assert!(decode_input(b"fbff 0061\n", Format::Auto).is_err());
+
+        // Only the hex decoding of a message is a message, and the note says so.
+        let (bytes, note) = decode_input(b"0a026869", Format::Auto).unwrap();
+        assert_eq!(bytes, [0x0a, 0x02, 0x68, 0x69]);
+        assert!(note.unwrap().contains("decoded as hex"));
+    }
+
+    #[test]
+    fn inspect_registered() {
+        let buf = from_hex(
+            "24bf58caef0a1e0a14a3b2ccdd7186f1685f21f2482af4fb3446a84b3510021
+             81e2014283c",
+        )
+        .unwrap();
+        let inspector = Inspector::new(&["tendermint/socketpv/SignHeartbeatMsg".to_string()]);
+        assert_eq!(
+            inspector.inspect(&buf),
+            "\
+length: 36
+prefix: bf58caef (tendermint/socketpv/SignHeartbeatMsg)
+1: message (30 bytes) {
+  1: bytes (20) a3b2ccdd7186f1685f21f2482af4fb3446a84b35
+  2: varint 2 (zigzag 1)
+  3: varint 30 (zigzag 15)
+  4: varint 20 (zigzag 10)
+  5: varint 60 (zigzag 30)
+}
+"
+        );
+    }
+
+    #[test]
+    fn inspect_unregistered() {
+        let inspector = Inspector::new(&[]);
+        assert_eq!(
+            inspector.inspect(&[0x0a, 0x02, 0x68, 0x69, 0x15, 0x00, 0x00, 0x80, 0x3f]),
+            "1: string \"hi\"\n2: fixed32 1065353216 (signed 1065353216) (float 1)\n"
+        );
+        assert_eq!(inspector.inspect(&[0xff]), "not a message: ff\n");
+    }
+}
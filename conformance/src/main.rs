@@ -11,6 +11,7 @@ include!(concat!(env!("OUT_DIR"), "/conformance.rs"));
 use std::io::{self, Read, Write};
 
 use bytes::{ByteOrder, LittleEndian};
+use prost::reflect::Reflect;
 use prost::Message;
 
 use tests::protobuf_test_messages::proto2::TestAllTypesProto2;
@@ -56,40 +57,64 @@ fn main() {
 }
 
 fn handle_request(request: ConformanceRequest) -> conformance_response::Result {
-    match request.requested_output_format() {
-        WireFormat::Unspecified => {
-            return conformance_response::Result::ParseError(
-                "output format unspecified".to_string(),
-            );
-        }
-        WireFormat::Json => {
-            return conformance_response::Result::Skipped(
-                "JSON output is not supported".to_string(),
-            );
-        }
-        WireFormat::Protobuf => (),
-    };
+    match &*request.message_type {
+        "protobuf_test_messages.proto2.TestAllTypesProto2" => handle::<TestAllTypesProto2>(request),
+        "protobuf_test_messages.proto3.TestAllTypesProto3" => handle::<TestAllTypesProto3>(request),
+        _ => conformance_response::Result::ParseError(format!(
+            "unknown message type: {}",
+            request.message_type
+        )),
+    }
+}
 
-    let buf = match request.payload {
+fn handle<M>(request: ConformanceRequest) -> conformance_response::Result
+where
+    M: Message + Reflect + Default,
+{
+    let output_format = request.requested_output_format();
+    if output_format == WireFormat::Unspecified {
+        return conformance_response::Result::ParseError("output format unspecified".to_string());
+    }
+
+    let message = match request.payload {
         None => return conformance_response::Result::ParseError("no payload".to_string()),
-        Some(conformance_request::Payload::JsonPayload(_)) => {
-            return conformance_response::Result::Skipped("JSON input is not supported".to_string())
+        Some(conformance_request::Payload::ProtobufPayload(buf)) => {
+            if output_format == WireFormat::Protobuf {
+                return protobuf_roundtrip::<M>(&buf);
+            }
+            match M::decode(&buf[..]) {
+                Ok(message) => message,
+                Err(error) => return conformance_response::Result::ParseError(error.to_string()),
+            }
+        }
+        Some(conformance_request::Payload::JsonPayload(json)) => {
+            match prost::json::from_json::<M>(&json) {
+                Ok(message) => message,
+                Err(error) => return conformance_response::Result::ParseError(error.to_string()),
+            }
         }
-        Some(conformance_request::Payload::ProtobufPayload(buf)) => buf,
     };
 
-    let roundtrip = match &*request.message_type {
-        "protobuf_test_messages.proto2.TestAllTypesProto2" => roundtrip::<TestAllTypesProto2>(&buf),
-        "protobuf_test_messages.proto3.TestAllTypesProto3" => roundtrip::<TestAllTypesProto3>(&buf),
+    match output_format {
+        WireFormat::Json => match prost::json::to_json(&message) {
+            Ok(json) => conformance_response::Result::JsonPayload(json),
+            Err(error) => conformance_response::Result::SerializeError(error.to_string()),
+        },
         _ => {
-            return conformance_response::Result::ParseError(format!(
-                "unknown message type: {}",
-                request.message_type
-            ))
+            let mut buf = Vec::new();
+            match message.encode(&mut buf) {
+                Ok(()) => conformance_response::Result::ProtobufPayload(buf),
+                Err(error) => conformance_response::Result::SerializeError(error.to_string()),
+            }
         }
-    };
+    }
+}
 
-    match roundtrip {
+fn protobuf_roundtrip<M>(buf: &[u8]) -> conformance_response::Result
+where
+    M: Message + Default,
+{
+    match roundtrip::<M>(buf) {
         RoundtripResult::Ok(buf) => conformance_response::Result::ProtobufPayload(buf),
         RoundtripResult::DecodeError(error) => {
             conformance_response::Result::ParseError(error.to_string())
@@ -0,0 +1,245 @@
+use failure::Error;
+use proc_macro2::{Span, TokenStream};
+use syn::{Ident, Type, Visibility};
+
+use field::{reflect_path, reflect_type, scalar, type_arg, Field, Label};
+
+/// The shape of a field, which determines the signature of its setter.
+#[derive(Clone, Copy, PartialEq)]
+enum Shape {
+    /// A value which is set as is.
+    Single,
+    /// A value which is wrapped in `Some`.
+    Optional,
+    /// A collection which is set from an iterator of values.
+    Repeated,
+}
+
+impl Shape {
+    fn of(field: &Field) -> Shape {
+        match *field {
+            // Fixed-size arrays are set as a whole.
+            Field::Scalar(ref scalar) if scalar.array_len.is_some() => Shape::Single,
+            Field::Scalar(ref scalar) => match scalar.kind {
+                scalar::Kind::Plain(..) | scalar::Kind::Required(..) => Shape::Single,
+                scalar::Kind::Optional(..) => Shape::Optional,
+                scalar::Kind::Repeated | scalar::Kind::Packed => Shape::Repeated,
+            },
+            Field::Message(ref message) => match message.label {
+                Label::Required => Shape::Single,
+                Label::Optional => Shape::Optional,
+                Label::Repeated => Shape::Repeated,
+            },
+            Field::Group(ref group) => match group.label {
+                Label::Required => Shape::Single,
+                Label::Optional => Shape::Optional,
+                Label::Repeated => Shape::Repeated,
+            },
+            Field::Map(..) => Shape::Repeated,
+            Field::Oneof(..) => Shape::Optional,
+            Field::Extensions(..) => Shape::Single,
+            Field::Proxy(ref proxy) => Shape::of(&proxy.inner),
+        }
+    }
+}
+
+/// How the values passed to a setter are converted to the field type.
+#[derive(Clone, Copy, PartialEq)]
+enum Conversion {
+    /// Values have the field type.
+    None,
+    /// Values are converted with `Into`.
+    Into,
+    /// Values are enumerations, stored as `i32`.
+    Enumeration,
+}
+
+/// Returns whether the builder of a message must set the field before building it.
+fn is_required(field: &Field) -> bool {
+    match *field {
+        Field::Scalar(ref scalar) => match scalar.kind {
+            scalar::Kind::Required(..) => true,
+            _ => false,
+        },
+        Field::Message(ref message) => message.label == Label::Required,
+        Field::Group(ref group) => group.label == Label::Required,
+        Field::Proxy(ref proxy) => is_required(&proxy.inner),
+        Field::Map(..) | Field::Oneof(..) | Field::Extensions(..) => false,
+    }
+}
+
+/// Returns the type of the values passed to the setter of a field, and how they are converted.
+fn value_type(field: &Field, shape: Shape, ty: &Type) -> Result<(TokenStream, Conversion), Error> {
+    if let Field::Scalar(ref scalar) = *field {
+        if scalar.array_len.is_some() {
+            return Ok((quote!(#ty), Conversion::None));
+        }
+        return Ok(match scalar.ty {
+            scalar::Ty::Enumeration(ref path) => (reflect_path(path), Conversion::Enumeration),
+            scalar::Ty::String | scalar::Ty::Bytes => (scalar.ty.rust_type(), Conversion::Into),
+            _ => (scalar.ty.rust_type(), Conversion::None),
+        });
+    }
+    let ty = match (field, shape) {
+        (&Field::Map(..), _) => {
+            let ty = reflect_type(ty);
+            return Ok((
+                quote!(<#ty as ::std::iter::IntoIterator>::Item),
+                Conversion::None,
+            ));
+        }
+        (_, Shape::Single) => ty,
+        (_, Shape::Optional) => match type_arg(ty, "Option") {
+            Some(ty) => ty,
+            None => bail!("optional field must have an Option type"),
+        },
+        (_, Shape::Repeated) => match type_arg(ty, "Vec") {
+            Some(ty) => ty,
+            None => bail!("repeated field must have a Vec type"),
+        },
+    };
+    Ok((reflect_type(ty), Conversion::Into))
+}
+
+/// Returns the setter method of a field in the builder.
+fn setter(
+    field_ident: &Ident,
+    field: &Field,
+    ty: &Type,
+    index: Option<usize>,
+) -> Result<TokenStream, Error> {
+    let shape = Shape::of(field);
+    let (value_ty, conversion) = value_type(field, shape, ty)?;
+
+    let (generics, where_clause, param_ty) = match conversion {
+        Conversion::Into => (
+            quote!(<T>),
+            quote!(where T: ::std::convert::Into<#value_ty>),
+            quote!(T),
+        ),
+        _ => (quote!(), quote!(), value_ty.clone()),
+    };
+    let convert = match conversion {
+        Conversion::None => quote!(value),
+        Conversion::Into => quote!(::std::convert::Into::into(value)),
+        Conversion::Enumeration => quote!(value as i32),
+    };
+    let set = match index {
+        Some(index) => quote!(self.set[#index] = true;),
+        None => quote!(),
+    };
+    let doc = format!("Sets the `{}` field.", field_ident);
+
+    Ok(match shape {
+        Shape::Single | Shape::Optional => {
+            let value = if shape == Shape::Optional {
+                quote!(::std::option::Option::Some(#convert))
+            } else {
+                convert
+            };
+            quote! {
+                #[doc = #doc]
+                pub fn #field_ident #generics(mut self, value: #param_ty) -> Self #where_clause {
+                    self.message.#field_ident = #value;
+                    #set
+                    self
+                }
+            }
+        }
+        Shape::Repeated => {
+            let (generics, where_clause) = match conversion {
+                Conversion::Into => (
+                    quote!(<I, T>),
+                    quote!(where I: ::std::iter::IntoIterator<Item = T>,
+                                 T: ::std::convert::Into<#value_ty>),
+                ),
+                _ => (
+                    quote!(<I>),
+                    quote!(where I: ::std::iter::IntoIterator<Item = #param_ty>),
+                ),
+            };
+            quote! {
+                #[doc = #doc]
+                pub fn #field_ident #generics(mut self, values: I) -> Self #where_clause {
+                    self.message.#field_ident = values.into_iter().map(|value| #convert).collect();
+                    #set
+                    self
+                }
+            }
+        }
+    })
+}
+
+/// Returns the builder struct of a message, and its implementation.
+///
+/// The struct is defined next to the message, and the implementation is placed in the module of
+/// the generated impls.
+pub fn builder(
+    vis: &Visibility,
+    ident: &Ident,
+    fields: &[(Ident, Field, Type)],
+) -> Result<(TokenStream, TokenStream), Error> {
+    let builder = Ident::new(&format!("{}Builder", ident), Span::call_site());
+
+    let mut required = Vec::new();
+    let mut setters = Vec::new();
+    for &(ref field_ident, ref field, ref ty) in fields {
+        // Extensions are set on the built message with `set_extension`.
+        if let Field::Extensions(..) = *field {
+            continue;
+        }
+        let index = if is_required(field) {
+            required.push(field_ident);
+            Some(required.len() - 1)
+        } else {
+            None
+        };
+        setters.push(setter(field_ident, field, ty, index).map_err(|err| {
+            err.context(format!("invalid builder field {}.{}", ident, field_ident))
+        })?);
+    }
+    let num_required = required.len();
+    let checks = required.iter().enumerate().map(|(index, field_ident)| {
+        quote! {
+            if !self.set[#index] {
+                return ::std::result::Result::Err(
+                    _prost::BuildError::new(stringify!(#ident), stringify!(#field_ident)),
+                );
+            }
+        }
+    });
+
+    let doc = format!("A builder for `{}` messages.", ident);
+    let builder_doc = format!("Returns a builder for `{}` messages.", ident);
+    let definition = quote! {
+        #[doc = #doc]
+        #vis struct #builder {
+            message: #ident,
+            set: [bool; #num_required],
+        }
+    };
+    let implementation = quote! {
+        #[allow(dead_code)]
+        impl #ident {
+            #[doc = #builder_doc]
+            pub fn builder() -> #builder {
+                #builder {
+                    message: ::std::default::Default::default(),
+                    set: [false; #num_required],
+                }
+            }
+        }
+
+        #[allow(dead_code)]
+        impl #builder {
+            #(#setters)*
+
+            /// Builds the message, or returns an error if a required field was not set.
+            pub fn build(self) -> ::std::result::Result<#ident, _prost::BuildError> {
+                #(#checks)*
+                ::std::result::Result::Ok(self.message)
+            }
+        }
+    };
+    Ok((definition, implementation))
+}
@@ -0,0 +1,138 @@
+use failure::Error;
+use itertools::Itertools;
+use proc_macro2::TokenStream;
+use syn::{Ident, Lit, Meta, MetaNameValue};
+
+/// The extension set of an extendable message, e.g.
+/// `#[prost(extensions = "1000..536870912")]`.
+#[derive(Clone)]
+pub struct Field {
+    /// The extension ranges of the message, as half-open `(start, end)` ranges.
+    pub ranges: Vec<(u32, u32)>,
+}
+
+impl Field {
+    pub fn new(attrs: &[Meta]) -> Result<Option<Field>, Error> {
+        let mut ranges = None;
+        let mut unknown_attrs = Vec::new();
+
+        for attr in attrs {
+            if attr.name() == "extensions" {
+                if ranges.is_some() {
+                    bail!("duplicate extensions attribute");
+                }
+                ranges = Some(ranges_attr(attr)?);
+            } else {
+                unknown_attrs.push(attr);
+            }
+        }
+
+        let ranges = match ranges {
+            Some(ranges) => ranges,
+            None => return Ok(None),
+        };
+
+        match unknown_attrs.len() {
+            0 => (),
+            1 => bail!(
+                "unknown attribute for extensions field: {:?}",
+                unknown_attrs[0]
+            ),
+            _ => bail!(
+                "unknown attributes for extensions field: {:?}",
+                unknown_attrs
+            ),
+        }
+
+        Ok(Some(Field { ranges }))
+    }
+
+    /// Returns a guard expression which matches the tags of the extension ranges.
+    pub fn guard(&self) -> TokenStream {
+        let ranges = Itertools::intersperse(
+            self.ranges
+                .iter()
+                .map(|&(start, end)| quote!((#start..#end).contains(&tag))),
+            quote!(||),
+        );
+        quote!(#(#ranges)*)
+    }
+
+    /// Returns a statement which encodes the extension fields.
+    pub fn encode(&self, ident: TokenStream) -> TokenStream {
+        quote!(#ident.encode(buf);)
+    }
+
+    /// Returns an expression which evaluates to the result of merging an extension field.
+    pub fn merge(&self, ident: TokenStream) -> TokenStream {
+        quote!(#ident.merge_field(tag, wire_type, buf))
+    }
+
+    /// Returns an expression which evaluates to the encoded length of the extension fields.
+    pub fn encoded_len(&self, ident: TokenStream) -> TokenStream {
+        quote!(#ident.encoded_len())
+    }
+
+    /// Returns a statement which clears the extension fields.
+    pub fn clear(&self, ident: TokenStream) -> TokenStream {
+        quote!(#ident.clear())
+    }
+
+    /// Returns the typed extension accessors of the message.
+    pub fn methods(&self, ident: &Ident) -> TokenStream {
+        quote! {
+            /// Returns `true` if the extension is set.
+            pub fn has_extension<T>(&self, extension: &_prost::extension::Extension<Self, T>) -> bool {
+                self.#ident.has(extension)
+            }
+
+            /// Returns the value of the extension, or `None` if it's not set.
+            pub fn get_extension<T>(
+                &self,
+                extension: &_prost::extension::Extension<Self, T>,
+            ) -> ::std::result::Result<::std::option::Option<T>, _prost::DecodeError>
+            where
+                T: ::std::default::Default,
+            {
+                self.#ident.get(extension)
+            }
+
+            /// Sets the value of the extension.
+            pub fn set_extension<T>(&mut self, extension: &_prost::extension::Extension<Self, T>, value: T) {
+                self.#ident.set(extension, &value)
+            }
+
+            /// Clears the extension.
+            pub fn clear_extension<T>(&mut self, extension: &_prost::extension::Extension<Self, T>) {
+                self.#ident.clear_extension(extension)
+            }
+        }
+    }
+}
+
+/// Parses the extension ranges of an extensions attribute, e.g. `"100..200, 1000..536870912"`.
+fn ranges_attr(attr: &Meta) -> Result<Vec<(u32, u32)>, Error> {
+    let value = match *attr {
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(ref lit),
+            ..
+        }) => lit.value(),
+        _ => bail!("invalid extensions attribute: {:?}", attr),
+    };
+    let mut ranges = Vec::new();
+    for range in value.split(',') {
+        let mut bounds = range.splitn(2, "..");
+        let start = bounds.next().unwrap_or("").trim();
+        let end = match bounds.next() {
+            Some(end) => end.trim(),
+            None => bail!("invalid extension range: {}", range),
+        };
+        let start = start.parse::<u32>()?;
+        let end = end.parse::<u32>()?;
+        if start >= end {
+            bail!("empty extension range: {}", range);
+        }
+        ranges.push((start, end));
+    }
+    Ok(ranges)
+}
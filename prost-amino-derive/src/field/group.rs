@@ -0,0 +1,164 @@
+use failure::Error;
+use proc_macro2::TokenStream;
+use syn::{Meta, Type};
+
+use field::{
+    field_descriptor, reflect_type, set_bool, set_option, tag_attr, type_arg, word_attr,
+    DescriptorOptions, Label,
+};
+
+#[derive(Clone, Debug)]
+pub struct Field {
+    pub label: Label,
+    pub tag: u32,
+}
+
+impl Field {
+    pub fn new(attrs: &[Meta], inferred_tag: Option<u32>) -> Result<Option<Field>, Error> {
+        let mut group = false;
+        let mut label = None;
+        let mut tag = None;
+        let mut boxed = false;
+
+        let mut unknown_attrs = Vec::new();
+
+        for attr in attrs {
+            if word_attr("group", attr) {
+                set_bool(&mut group, "duplicate group attribute")?;
+            } else if word_attr("boxed", attr) {
+                set_bool(&mut boxed, "duplicate boxed attribute")?;
+            } else if let Some(t) = tag_attr(attr)? {
+                set_option(&mut tag, t, "duplicate tag attributes")?;
+            } else if let Some(l) = Label::from_attr(attr) {
+                set_option(&mut label, l, "duplicate label attributes")?;
+            } else {
+                unknown_attrs.push(attr);
+            }
+        }
+
+        if !group {
+            return Ok(None);
+        }
+
+        match unknown_attrs.len() {
+            0 => (),
+            1 => bail!("unknown attribute for group field: {:?}", unknown_attrs[0]),
+            _ => bail!("unknown attributes for group field: {:?}", unknown_attrs),
+        }
+
+        let tag = match tag.or(inferred_tag) {
+            Some(tag) => tag,
+            None => bail!("group field is missing a tag attribute"),
+        };
+
+        Ok(Some(Field {
+            label: label.unwrap_or(Label::Optional),
+            tag: tag,
+        }))
+    }
+
+    pub fn new_oneof(attrs: &[Meta]) -> Result<Option<Field>, Error> {
+        if let Some(mut field) = Field::new(attrs, None)? {
+            if let Some(attr) = attrs.iter().find(|attr| Label::from_attr(attr).is_some()) {
+                bail!("invalid attribute for oneof field: {}", attr.name());
+            }
+            field.label = Label::Required;
+            Ok(Some(field))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn encode(&self, ident: TokenStream) -> TokenStream {
+        let tag = self.tag;
+        match self.label {
+            Label::Optional => quote! {
+                if let Some(ref msg) = #ident {
+                    _prost::encoding::group::encode(#tag, msg, buf);
+                }
+            },
+            Label::Required => quote! {
+                _prost::encoding::group::encode(#tag, &#ident, buf);
+            },
+            Label::Repeated => quote! {
+                _prost::encoding::group::encode_repeated(#tag, &#ident, buf);
+            },
+        }
+    }
+
+    pub fn merge(&self, ident: TokenStream) -> TokenStream {
+        let tag = self.tag;
+        match self.label {
+            Label::Optional => quote! {
+                _prost::encoding::group::merge(#tag,
+                                               wire_type,
+                                               #ident.get_or_insert_with(Default::default),
+                                               buf,
+                                               depth)
+            },
+            Label::Required => quote! {
+                _prost::encoding::group::merge(#tag, wire_type, &mut #ident, buf, depth)
+            },
+            Label::Repeated => quote! {
+                _prost::encoding::group::merge_repeated(#tag, wire_type, &mut #ident, buf, depth)
+            },
+        }
+    }
+
+    pub fn encoded_len(&self, ident: TokenStream) -> TokenStream {
+        let tag = self.tag;
+        match self.label {
+            Label::Optional => quote! {
+                #ident.as_ref().map_or(0, |msg| _prost::encoding::group::encoded_len(#tag, msg))
+            },
+            Label::Required => quote! {
+                _prost::encoding::group::encoded_len(#tag, &#ident)
+            },
+            Label::Repeated => quote! {
+                _prost::encoding::group::encoded_len_repeated(#tag, &#ident)
+            },
+        }
+    }
+
+    pub fn clear(&self, ident: TokenStream) -> TokenStream {
+        match self.label {
+            Label::Optional => quote!(#ident = ::std::option::Option::None),
+            Label::Required => quote!(#ident.clear()),
+            Label::Repeated => quote!(#ident.clear()),
+        }
+    }
+
+    /// Returns an expression which evaluates to the reflection descriptor of the field.
+    ///
+    /// `ty` is the declared type of the field, which is unwrapped to find the group type.
+    pub fn descriptor(
+        &self,
+        name: &str,
+        proto_name: &str,
+        json_name: &str,
+        ty: &Type,
+    ) -> TokenStream {
+        let ty = match self.label {
+            Label::Optional => type_arg(ty, "Option").unwrap_or(ty),
+            Label::Required => ty,
+            Label::Repeated => type_arg(ty, "Vec").unwrap_or(ty),
+        };
+        let ty = reflect_type(type_arg(ty, "Box").unwrap_or(ty));
+        let label = match self.label {
+            Label::Optional => quote!(Optional),
+            Label::Required => quote!(Required),
+            Label::Repeated => quote!(Repeated),
+        };
+        field_descriptor(
+            name,
+            proto_name,
+            json_name,
+            &[self.tag],
+            label,
+            quote!(_prost::reflect::FieldKind::Group(
+                <#ty as _prost::reflect::Reflect>::descriptor
+            )),
+            DescriptorOptions::default(),
+        )
+    }
+}
@@ -1,8 +1,11 @@
 use failure::Error;
 use proc_macro2::{Span, TokenStream};
-use syn::{Ident, Lit, Meta, MetaNameValue, NestedMeta};
+use syn::{Ident, Lit, Meta, MetaNameValue, NestedMeta, Type};
 
-use field::{scalar, set_option, tag_attr};
+use field::{
+    field_descriptor, reflect_path, reflect_type, scalar, set_bool, set_option, tag_attr, type_arg,
+    word_attr, DescriptorOptions,
+};
 
 #[derive(Clone, Debug)]
 pub enum MapTy {
@@ -33,7 +36,11 @@ fn fake_scalar(ty: scalar::Ty) -> scalar::Field {
         ty,
         kind,
         tag: 0, // Not used here
+        amino_name: None,
         amino_prefix: vec![],
+        array_len: None,
+        write_empty: false,
+        is_unsafe: false,
     }
 }
 
@@ -43,16 +50,21 @@ pub struct Field {
     pub key_ty: scalar::Ty,
     pub value_ty: ValueTy,
     pub tag: u32,
+    // go-amino `amino:"unsafe"`: float values are allowed in amino messages.
+    pub is_unsafe: bool,
 }
 
 impl Field {
     pub fn new(attrs: &[Meta], inferred_tag: Option<u32>) -> Result<Option<Field>, Error> {
         let mut types = None;
         let mut tag = None;
+        let mut is_unsafe = false;
 
         for attr in attrs {
             if let Some(t) = tag_attr(attr)? {
                 set_option(&mut tag, t, "duplicate tag attributes")?;
+            } else if word_attr("unsafe", attr) {
+                set_bool(&mut is_unsafe, "duplicate unsafe attributes")?;
             } else if let Some(map_ty) = MapTy::from_str(&attr.name().to_string()) {
                 let (k, v): (String, String) = match *attr {
                     Meta::NameValue(MetaNameValue {
@@ -99,12 +111,18 @@ impl Field {
         }
 
         Ok(match (types, tag.or(inferred_tag)) {
-            (Some((map_ty, key_ty, val_ty)), Some(tag)) => Some(Field {
-                map_ty: map_ty,
-                key_ty: key_ty,
-                value_ty: val_ty,
-                tag: tag,
-            }),
+            (Some((map_ty, key_ty, val_ty)), Some(tag)) => {
+                if is_unsafe && !val_ty.is_float() {
+                    bail!("unsafe attribute may only be applied to float and double map values");
+                }
+                Some(Field {
+                    map_ty,
+                    key_ty,
+                    value_ty: val_ty,
+                    tag,
+                    is_unsafe,
+                })
+            }
             _ => None,
         })
     }
@@ -122,6 +140,7 @@ impl Field {
         let module = self.map_ty.module();
         match self.value_ty {
             ValueTy::Scalar(scalar::Ty::Enumeration(ref ty)) => {
+                let ty = reflect_path(ty);
                 let default = quote!(#ty::default() as i32);
                 quote! {
                     _prost::encoding::#module::encode_with_default(#ke, #kl,
@@ -159,6 +178,7 @@ impl Field {
         let module = self.map_ty.module();
         match self.value_ty {
             ValueTy::Scalar(scalar::Ty::Enumeration(ref ty)) => {
+                let ty = reflect_path(ty);
                 let default = quote!(#ty::default() as i32);
                 quote! {
                     _prost::encoding::#module::merge_with_default(#km, _prost::encoding::int32::merge,
@@ -185,6 +205,7 @@ impl Field {
         let module = self.map_ty.module();
         match self.value_ty {
             ValueTy::Scalar(scalar::Ty::Enumeration(ref ty)) => {
+                let ty = reflect_path(ty);
                 let default = quote!(#ty::default() as i32);
                 quote! {
                     _prost::encoding::#module::encoded_len_with_default(
@@ -211,6 +232,7 @@ impl Field {
     /// Returns methods to embed in the message.
     pub fn methods(&self, ident: &Ident) -> Option<TokenStream> {
         if let ValueTy::Scalar(scalar::Ty::Enumeration(ref ty)) = self.value_ty {
+            let ty = reflect_path(ty);
             let key_ty = self.key_ty.rust_type();
             let key_ref_ty = self.key_ty.rust_ref_type();
 
@@ -235,6 +257,44 @@ impl Field {
         }
     }
 
+    /// Returns an expression which evaluates to the reflection descriptor of the field.
+    ///
+    /// `ty` is the declared map type of the field, which is used to find the value message type.
+    pub fn descriptor(
+        &self,
+        name: &str,
+        proto_name: &str,
+        json_name: &str,
+        ty: &Type,
+    ) -> TokenStream {
+        let key = self.key_ty.reflect_type();
+        let value = match self.value_ty {
+            ValueTy::Scalar(ref value_ty) => value_ty.reflect_kind(),
+            ValueTy::Message => {
+                let map_ty = match self.map_ty {
+                    MapTy::HashMap => "HashMap",
+                    MapTy::BTreeMap => "BTreeMap",
+                };
+                let value_ty = reflect_type(type_arg(ty, map_ty).unwrap_or(ty));
+                quote!(_prost::reflect::FieldKind::Message(
+                    <#value_ty as _prost::reflect::Reflect>::descriptor
+                ))
+            }
+        };
+        field_descriptor(
+            name,
+            proto_name,
+            json_name,
+            &[self.tag],
+            quote!(Repeated),
+            quote!(_prost::reflect::FieldKind::Map {
+                key: #key,
+                value: &#value,
+            }),
+            DescriptorOptions::default(),
+        )
+    }
+
     /// Returns a newtype wrapper around the map, implementing nicer Debug
     ///
     /// The Debug tries to convert any enumerations met into the variants if possible, instead of
@@ -319,6 +379,14 @@ impl ValueTy {
         }
     }
 
+    /// Returns true if the values are `float` or `double`.
+    pub fn is_float(&self) -> bool {
+        match *self {
+            ValueTy::Scalar(ref ty) => ty.is_float(),
+            ValueTy::Message => false,
+        }
+    }
+
     /// Returns a newtype wrapper around the ValueTy for nicer debug.
     ///
     /// If the contained value is enumeration, it tries to convert it to the variant. If not, it
@@ -1,8 +1,11 @@
 use failure::Error;
 use proc_macro2::TokenStream;
-use syn::Meta;
+use syn::{Meta, Type};
 
-use field::{amino_name_attr, set_bool, set_option, tag_attr, word_attr, Label};
+use field::{
+    amino_name_attr, field_descriptor, reflect_type, set_bool, set_option, tag_attr, type_arg,
+    word_attr, DescriptorOptions, Label,
+};
 
 use super::compute_disfix;
 
@@ -10,6 +13,10 @@ use super::compute_disfix;
 pub struct Field {
     pub label: Label,
     pub tag: u32,
+    /// Whether the elements of a repeated field are optional, encoded like the elements of a
+    /// go-amino slice of struct pointers.
+    pub nullable: bool,
+    pub amino_name: Option<String>,
     // this is to be able to de/encode registered type aliases:
     pub amino_prefix: Vec<u8>,
 }
@@ -20,6 +27,7 @@ impl Field {
         let mut label = None;
         let mut tag = None;
         let mut boxed = false;
+        let mut nullable = false;
         let mut amino_name = None;
 
         let mut unknown_attrs = Vec::new();
@@ -29,6 +37,8 @@ impl Field {
                 set_bool(&mut message, "duplicate message attribute")?;
             } else if word_attr("boxed", attr) {
                 set_bool(&mut boxed, "duplicate boxed attribute")?;
+            } else if word_attr("nullable", attr) {
+                set_bool(&mut nullable, "duplicate nullable attribute")?;
             } else if let Some(t) = tag_attr(attr)? {
                 set_option(&mut tag, t, "duplicate tag attributes")?;
             } else if let Some(l) = Label::from_attr(attr) {
@@ -58,8 +68,16 @@ impl Field {
             None => bail!("message field is missing a tag attribute"),
         };
 
+        let label = label.unwrap_or(Label::Optional);
+        if nullable && label != Label::Repeated {
+            bail!("nullable attribute is only valid for repeated message fields");
+        }
+        if nullable && amino_name.is_some() {
+            bail!("nullable message fields can't have an amino_name attribute");
+        }
+
         let amino_prefix: Vec<u8> = match amino_name {
-            Some(n) => {
+            Some(ref n) => {
                 let (_dis, pre) = compute_disfix(n.as_str());
                 pre
             }
@@ -67,8 +85,10 @@ impl Field {
         };
 
         Ok(Some(Field {
-            label: label.unwrap_or(Label::Optional),
+            label: label,
             tag: tag,
+            nullable: nullable,
+            amino_name: amino_name,
             amino_prefix: amino_prefix,
         }))
     }
@@ -99,6 +119,9 @@ impl Field {
                 buf.put(pre.as_ref());
                 _prost::encoding::message::encode(#tag, &#ident, buf);
             },
+            Label::Repeated if self.nullable => quote! {
+                _prost::encoding::message::encode_repeated_nullable(#tag, &#ident, buf);
+            },
             Label::Repeated => quote! {
                 for msg in &#ident {
                     let pre = vec![#(#amino_prefix),*];
@@ -119,6 +142,9 @@ impl Field {
             Label::Required => quote! {
                 _prost::encoding::message::merge(wire_type, &mut #ident, buf)
             },
+            Label::Repeated if self.nullable => quote! {
+                _prost::encoding::message::merge_repeated_nullable(wire_type, &mut #ident, buf)
+            },
             Label::Repeated => quote! {
                 _prost::encoding::message::merge_repeated(wire_type, &mut #ident, buf)
             },
@@ -135,6 +161,9 @@ impl Field {
             Label::Required => quote! {
                 _prost::encoding::message::encoded_len(#tag, &#ident)
             },
+            Label::Repeated if self.nullable => quote! {
+                _prost::encoding::message::encoded_len_repeated_nullable(#tag, &#ident)
+            },
             Label::Repeated => quote! {
                 _prost::encoding::message::encoded_len_repeated(#tag, &#ident)
             },
@@ -148,4 +177,46 @@ impl Field {
             Label::Repeated => quote!(#ident.clear()),
         }
     }
+
+    /// Returns an expression which evaluates to the reflection descriptor of the field.
+    ///
+    /// `ty` is the declared type of the field, which is unwrapped to find the message type.
+    pub fn descriptor(
+        &self,
+        name: &str,
+        proto_name: &str,
+        json_name: &str,
+        ty: &Type,
+    ) -> TokenStream {
+        let ty = match self.label {
+            Label::Optional => type_arg(ty, "Option").unwrap_or(ty),
+            Label::Required => ty,
+            Label::Repeated => type_arg(ty, "Vec").unwrap_or(ty),
+        };
+        let ty = if self.nullable {
+            type_arg(ty, "Option").unwrap_or(ty)
+        } else {
+            ty
+        };
+        let ty = reflect_type(type_arg(ty, "Box").unwrap_or(ty));
+        let label = match self.label {
+            Label::Optional => quote!(Optional),
+            Label::Required => quote!(Required),
+            Label::Repeated => quote!(Repeated),
+        };
+        field_descriptor(
+            name,
+            proto_name,
+            json_name,
+            &[self.tag],
+            label,
+            quote!(_prost::reflect::FieldKind::Message(
+                <#ty as _prost::reflect::Reflect>::descriptor
+            )),
+            DescriptorOptions {
+                amino_name: self.amino_name.as_deref(),
+                ..DescriptorOptions::default()
+            },
+        )
+    }
 }
@@ -1,14 +1,20 @@
+pub mod extensions;
+mod group;
 mod map;
 mod message;
 mod oneof;
-mod scalar;
+mod proxy;
+pub mod scalar;
 
 use std::fmt;
 use std::slice;
 
 use failure::Error;
-use proc_macro2::TokenStream;
-use syn::{Attribute, Ident, Lit, LitBool, Meta, MetaList, MetaNameValue, NestedMeta};
+use proc_macro2::{Span, TokenStream};
+use syn::{
+    Attribute, GenericArgument, Ident, Lit, LitBool, Meta, MetaList, MetaNameValue, NestedMeta,
+    Path, PathArguments, PathSegment, Type, TypePath,
+};
 
 use super::compute_disfix;
 
@@ -18,50 +24,81 @@ pub enum Field {
     Scalar(scalar::Field),
     /// A message field.
     Message(message::Field),
+    /// A group field.
+    Group(group::Field),
     /// A map field.
     Map(map::Field),
     /// A oneof field.
     Oneof(oneof::Field),
+    /// A scalar or message field encoded as a proxy type.
+    Proxy(proxy::Field),
+    /// The extension set of an extendable message.
+    Extensions(extensions::Field),
 }
 
 impl Field {
-    /// Creates a new `Field` from an iterator of field attributes.
+    /// Creates a new `Field` from an iterator of field attributes and the Rust type of the field.
     ///
     /// If the meta items are invalid, an error will be returned.
     /// If the field should be ignored, `None` is returned.
-    pub fn new(attrs: Vec<Attribute>, inferred_tag: Option<u32>) -> Result<Option<Field>, Error> {
-        let attrs = prost_attrs(attrs)?;
+    pub fn new(
+        attrs: Vec<Attribute>,
+        inferred_tag: Option<u32>,
+        ty: &Type,
+    ) -> Result<Option<Field>, Error> {
+        let mut attrs = prost_attrs(attrs)?;
 
         // TODO: check for ignore attribute.
 
-        let field = if let Some(field) = scalar::Field::new(&attrs, inferred_tag)? {
+        let mut proxy = None;
+        for attr in &attrs {
+            if let Some(ty) = proxy::proxy_attr(attr)? {
+                set_option(&mut proxy, ty, "duplicate proxy attributes")?;
+            }
+        }
+        attrs.retain(|attr| {
+            attr.name() != "proxy" && attr.name() != "json_name" && attr.name() != "proto_name"
+        });
+
+        let field = if let Some(field) = scalar::Field::new(&attrs, inferred_tag, ty)? {
             Field::Scalar(field)
         } else if let Some(field) = message::Field::new(&attrs, inferred_tag)? {
             Field::Message(field)
+        } else if let Some(field) = group::Field::new(&attrs, inferred_tag)? {
+            Field::Group(field)
         } else if let Some(field) = map::Field::new(&attrs, inferred_tag)? {
             Field::Map(field)
         } else if let Some(field) = oneof::Field::new(&attrs)? {
             Field::Oneof(field)
+        } else if let Some(field) = extensions::Field::new(&attrs)? {
+            Field::Extensions(field)
         } else {
             bail!("no type attribute");
         };
 
-        Ok(Some(field))
+        match proxy {
+            Some(proxy) => Ok(Some(Field::Proxy(proxy::Field::new(field, proxy, ty)?))),
+            None => Ok(Some(field)),
+        }
     }
 
-    /// Creates a new oneof `Field` from an iterator of field attributes.
+    /// Creates a new oneof `Field` from an iterator of field attributes and the Rust type of the
+    /// variant field.
     ///
     /// If the meta items are invalid, an error will be returned.
     /// If the field should be ignored, `None` is returned.
-    pub fn new_oneof(attrs: Vec<Attribute>) -> Result<Option<Field>, Error> {
-        let attrs = prost_attrs(attrs)?;
+    pub fn new_oneof(attrs: Vec<Attribute>, ty: &Type) -> Result<Option<Field>, Error> {
+        let mut attrs = prost_attrs(attrs)?;
+        attrs.retain(|attr| attr.name() != "json_name" && attr.name() != "proto_name");
 
         // TODO: check for ignore attribute.
 
-        let field = if let Some(field) = scalar::Field::new_oneof(&attrs)? {
+        let field = if let Some(field) = scalar::Field::new_oneof(&attrs, ty)? {
             Field::Scalar(field)
         } else if let Some(field) = message::Field::new_oneof(&attrs)? {
             Field::Message(field)
+        } else if let Some(field) = group::Field::new_oneof(&attrs)? {
+            Field::Group(field)
         } else if let Some(field) = map::Field::new_oneof(&attrs)? {
             Field::Map(field)
         } else {
@@ -75,8 +112,11 @@ impl Field {
         match *self {
             Field::Scalar(ref scalar) => vec![scalar.tag],
             Field::Message(ref message) => vec![message.tag],
+            Field::Group(ref group) => vec![group.tag],
             Field::Map(ref map) => vec![map.tag],
             Field::Oneof(ref oneof) => oneof.tags.clone(),
+            Field::Proxy(ref proxy) => proxy.inner.tags(),
+            Field::Extensions(..) => Vec::new(),
         }
     }
 
@@ -85,8 +125,11 @@ impl Field {
         match *self {
             Field::Scalar(ref scalar) => scalar.encode(ident),
             Field::Message(ref message) => message.encode(ident),
+            Field::Group(ref group) => group.encode(ident),
             Field::Map(ref map) => map.encode(ident),
             Field::Oneof(ref oneof) => oneof.encode(ident),
+            Field::Proxy(ref proxy) => proxy.encode(ident),
+            Field::Extensions(ref extensions) => extensions.encode(ident),
         }
     }
 
@@ -96,8 +139,11 @@ impl Field {
         match *self {
             Field::Scalar(ref scalar) => scalar.merge(ident),
             Field::Message(ref message) => message.merge(ident),
+            Field::Group(ref group) => group.merge(ident),
             Field::Map(ref map) => map.merge(ident),
             Field::Oneof(ref oneof) => oneof.merge(ident),
+            Field::Proxy(ref proxy) => proxy.merge(ident),
+            Field::Extensions(ref extensions) => extensions.merge(ident),
         }
     }
 
@@ -107,7 +153,10 @@ impl Field {
             Field::Scalar(ref scalar) => scalar.encoded_len(ident),
             Field::Map(ref map) => map.encoded_len(ident),
             Field::Message(ref msg) => msg.encoded_len(ident),
+            Field::Group(ref group) => group.encoded_len(ident),
             Field::Oneof(ref oneof) => oneof.encoded_len(ident),
+            Field::Proxy(ref proxy) => proxy.encoded_len(ident),
+            Field::Extensions(ref extensions) => extensions.encoded_len(ident),
         }
     }
 
@@ -116,14 +165,18 @@ impl Field {
         match *self {
             Field::Scalar(ref scalar) => scalar.clear(ident),
             Field::Message(ref message) => message.clear(ident),
+            Field::Group(ref group) => group.clear(ident),
             Field::Map(ref map) => map.clear(ident),
             Field::Oneof(ref oneof) => oneof.clear(ident),
+            Field::Proxy(ref proxy) => proxy.clear(ident),
+            Field::Extensions(ref extensions) => extensions.clear(ident),
         }
     }
 
     pub fn default(&self) -> TokenStream {
         match *self {
             Field::Scalar(ref scalar) => scalar.default(),
+            Field::Proxy(ref proxy) => proxy.default(),
             _ => quote!(::std::default::Default::default()),
         }
     }
@@ -153,10 +206,43 @@ impl Field {
         }
     }
 
+    /// Returns an expression which evaluates to the reflection descriptor of the field.
+    ///
+    /// `ty` is the declared type of the field, used to link nested message descriptors.
+    pub fn descriptor(
+        &self,
+        name: &str,
+        proto_name: &str,
+        json_name: &str,
+        ty: &Type,
+    ) -> TokenStream {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.descriptor(name, proto_name, json_name),
+            Field::Message(ref message) => message.descriptor(name, proto_name, json_name, ty),
+            Field::Group(ref group) => group.descriptor(name, proto_name, json_name, ty),
+            Field::Map(ref map) => map.descriptor(name, proto_name, json_name, ty),
+            Field::Oneof(ref oneof) => oneof.descriptor(name, proto_name, json_name),
+            Field::Proxy(ref proxy) => proxy.descriptor(name, proto_name, json_name),
+            Field::Extensions(..) => panic!("extension sets have no field descriptor"),
+        }
+    }
+
+    /// Returns `true` if the field has `float` or `double` values without the `unsafe` attribute,
+    /// which amino messages require for them.
+    pub fn is_unsafe_float(&self) -> bool {
+        match *self {
+            Field::Scalar(ref scalar) => scalar.ty.is_float() && !scalar.is_unsafe,
+            Field::Map(ref map) => map.value_ty.is_float() && !map.is_unsafe,
+            Field::Proxy(ref proxy) => proxy.inner.is_unsafe_float(),
+            _ => false,
+        }
+    }
+
     pub fn methods(&self, ident: &Ident) -> Option<TokenStream> {
         match *self {
             Field::Scalar(ref scalar) => scalar.methods(ident),
             Field::Map(ref map) => map.methods(ident),
+            Field::Extensions(ref extensions) => Some(extensions.methods(ident)),
             _ => None,
         }
     }
@@ -212,14 +298,135 @@ impl fmt::Display for Label {
     }
 }
 
-/// Get the items belonging to the 'prost' list attribute, e.g. `#[prost(foo, bar="baz")]`.
-fn prost_attrs(attrs: Vec<Attribute>) -> Result<Vec<Meta>, Error> {
+/// The attributes of a field which are listed in its `FieldDescriptor` besides its type.
+#[derive(Default)]
+struct DescriptorOptions<'a> {
+    /// The amino name of a registered field type.
+    amino_name: Option<&'a str>,
+    /// Whether plain fields are encoded even if they have the default value.
+    write_empty: bool,
+}
+
+/// Returns a `prost_amino::reflect::FieldDescriptor` struct expression.
+fn field_descriptor(
+    name: &str,
+    proto_name: &str,
+    json_name: &str,
+    tags: &[u32],
+    label: TokenStream,
+    kind: TokenStream,
+    options: DescriptorOptions,
+) -> TokenStream {
+    let (amino_name, prefix) = match options.amino_name {
+        Some(amino_name) => {
+            let (_, prefix) = compute_disfix(amino_name);
+            (
+                quote!(::std::option::Option::Some(#amino_name)),
+                quote!(::std::option::Option::Some([#(#prefix),*])),
+            )
+        }
+        None => (
+            quote!(::std::option::Option::None),
+            quote!(::std::option::Option::None),
+        ),
+    };
+    let write_empty = options.write_empty;
+    quote! {
+        _prost::reflect::FieldDescriptor {
+            name: #name,
+            proto_name: #proto_name,
+            json_name: #json_name,
+            tags: &[#(#tags),*],
+            label: _prost::reflect::Label::#label,
+            kind: #kind,
+            amino_name: #amino_name,
+            prefix: #prefix,
+            write_empty: #write_empty,
+        }
+    }
+}
+
+/// Returns the last type argument of `ty` if it is a `wrapper<..>` type, e.g. `T` for
+/// `Option<T>` or `V` for `HashMap<K, V>`.
+pub fn type_arg<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let segment = match *ty {
+        Type::Path(TypePath {
+            qself: None,
+            ref path,
+        }) => path.segments.iter().last()?,
+        _ => return None,
+    };
+    if segment.ident != wrapper {
+        return None;
+    }
+    match segment.arguments {
+        PathArguments::AngleBracketed(ref args) => match args.args.iter().last() {
+            Some(&GenericArgument::Type(ref ty)) => Some(ty),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Returns a field type which resolves from within the module of the generated impls.
+///
+/// Types are brought into scope with `use super::*`, so only `super` relative paths have to be
+/// adjusted, including the ones in type arguments, e.g. `Option<super::Foo>`.
+pub fn reflect_type(ty: &Type) -> TokenStream {
+    let mut ty = ty.clone();
+    adjust_super_paths(&mut ty);
+    quote!(#ty)
+}
+
+/// Returns a path which resolves from within the module of the generated impls, like
+/// `reflect_type`, e.g. the path of an enumeration type. Absolute paths are kept as is.
+pub fn reflect_path(path: &Path) -> TokenStream {
+    let mut path = path.clone();
+    adjust_super_path(&mut path);
+    quote!(#path)
+}
+
+fn adjust_super_paths(ty: &mut Type) {
+    if let Type::Path(TypePath {
+        qself: None,
+        ref mut path,
+    }) = *ty
+    {
+        adjust_super_path(path);
+    }
+}
+
+fn adjust_super_path(path: &mut Path) {
+    if path.leading_colon.is_none()
+        && path
+            .segments
+            .iter()
+            .next()
+            .map_or(false, |s| s.ident == "super")
+    {
+        path.segments
+            .insert(0, PathSegment::from(Ident::new("super", Span::call_site())));
+    }
+    for segment in path.segments.iter_mut() {
+        if let PathArguments::AngleBracketed(ref mut args) = segment.arguments {
+            for arg in args.args.iter_mut() {
+                if let GenericArgument::Type(ref mut ty) = *arg {
+                    adjust_super_paths(ty);
+                }
+            }
+        }
+    }
+}
+
+/// Get the items belonging to the 'prost' list attribute, e.g. `#[prost(foo, bar="baz")]`. The
+/// attribute may also be spelled `#[prost_amino(...)]`.
+pub fn prost_attrs(attrs: Vec<Attribute>) -> Result<Vec<Meta>, Error> {
     Ok(attrs
         .iter()
         .flat_map(Attribute::interpret_meta)
         .flat_map(|meta| match meta {
             Meta::List(MetaList { ident, nested, .. }) => {
-                if ident == "prost_amino" {
+                if ident == "prost" || ident == "prost_amino" {
                     nested.into_iter().collect()
                 } else {
                     Vec::new()
@@ -290,7 +497,7 @@ fn bool_attr(key: &str, attr: &Meta) -> Result<Option<bool>, Error> {
 }
 
 /// Checks if an attribute matches a word.
-fn word_attr(key: &str, attr: &Meta) -> bool {
+pub fn word_attr(key: &str, attr: &Meta) -> bool {
     if let Meta::Word(ref ident) = *attr {
         ident == key
     } else {
@@ -325,6 +532,68 @@ fn tag_attr(attr: &Meta) -> Result<Option<u32>, Error> {
     }
 }
 
+/// Unpacks a `key = "value"` attribute, returning the value.
+/// If the key doesn't match the attribute, `None` is returned.
+pub fn str_attr(key: &str, attr: &Meta) -> Result<Option<String>, Error> {
+    if attr.name() != key {
+        return Ok(None);
+    }
+    match *attr {
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(ref lit),
+            ..
+        }) => Ok(Some(lit.value())),
+        _ => bail!("invalid {} attribute: {:?}", key, attr),
+    }
+}
+
+/// Returns the JSON name of a field or oneof variant, which is set with a `json_name` attribute.
+///
+/// The JSON name defaults to the `lowerCamelCase` form of the Rust name, as protoc derives it from
+/// the protobuf field name.
+pub fn json_name(attrs: &[Attribute], name: &str) -> Result<String, Error> {
+    let mut json_name = None;
+    for attr in prost_attrs(attrs.to_vec())? {
+        if let Some(value) = str_attr("json_name", &attr)? {
+            set_option(&mut json_name, value, "duplicate json_name attributes")?;
+        }
+    }
+    Ok(json_name.unwrap_or_else(|| {
+        // The protoc algorithm, applied to the Rust name of the field or variant, with the first
+        // character lowercased.
+        let name = name.trim_start_matches("r#").trim_end_matches('_');
+        let mut json_name = String::with_capacity(name.len());
+        let mut capitalize_next = false;
+        for c in name.chars() {
+            if c == '_' {
+                capitalize_next = true;
+            } else if capitalize_next {
+                json_name.push(c.to_ascii_uppercase());
+                capitalize_next = false;
+            } else {
+                json_name.push(c);
+            }
+        }
+        let mut chars = json_name.chars();
+        match chars.next() {
+            Some(first) => first.to_lowercase().chain(chars).collect(),
+            None => json_name.clone(),
+        }
+    }))
+}
+
+/// Returns the protobuf name of a field or oneof variant, which is set with a `proto_name`
+/// attribute, or `default` if there is none.
+pub fn proto_name(attrs: &[Attribute], default: String) -> Result<String, Error> {
+    let mut proto_name = None;
+    for attr in prost_attrs(attrs.to_vec())? {
+        if let Some(value) = str_attr("proto_name", &attr)? {
+            set_option(&mut proto_name, value, "duplicate proto_name attributes")?;
+        }
+    }
+    Ok(proto_name.unwrap_or(default))
+}
+
 fn amino_name_attr(attr: &Meta) -> Result<Option<String>, Error> {
     if attr.name() != "amino_name" {
         return Ok(None);
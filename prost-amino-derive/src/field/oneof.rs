@@ -2,7 +2,7 @@ use failure::Error;
 use proc_macro2::TokenStream;
 use syn::{parse_str, Lit, Meta, MetaNameValue, NestedMeta, Path};
 
-use field::{set_option, tags_attr};
+use field::{field_descriptor, reflect_path, set_option, tags_attr, DescriptorOptions};
 
 #[derive(Clone)]
 pub struct Field {
@@ -76,7 +76,7 @@ impl Field {
     pub fn merge(&self, ident: TokenStream) -> TokenStream {
         let ty = &self.ty;
         quote! {
-            #ty::merge(&mut #ident, tag, wire_type, buf)
+            #ty::merge(&mut #ident, tag, wire_type, buf, depth)
         }
     }
 
@@ -91,4 +91,18 @@ impl Field {
     pub fn clear(&self, ident: TokenStream) -> TokenStream {
         quote!(#ident = ::std::option::Option::None)
     }
+
+    /// Returns an expression which evaluates to the reflection descriptor of the field.
+    pub fn descriptor(&self, name: &str, proto_name: &str, json_name: &str) -> TokenStream {
+        let ty = reflect_path(&self.ty);
+        field_descriptor(
+            name,
+            proto_name,
+            json_name,
+            &self.tags,
+            quote!(Optional),
+            quote!(_prost::reflect::FieldKind::Oneof(#ty::descriptor)),
+            DescriptorOptions::default(),
+        )
+    }
 }
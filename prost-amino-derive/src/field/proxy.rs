@@ -0,0 +1,213 @@
+use failure::Error;
+use proc_macro2::TokenStream;
+use syn::{self, Lit, Meta, MetaNameValue, Type};
+
+use field::{message, reflect_type, scalar, Field as Inner, Label};
+
+/// A scalar or message field with a Rust type which is converted to and from a proxy type, which
+/// is encoded in its place, e.g. a `std::time::SystemTime` field encoded as a
+/// `prost_types::Timestamp`.
+///
+/// The conversions are done with the `prost_amino::proxy::Proxy` trait, which converts from a
+/// borrow of the field when it is encoded, and may reject decoded values. Since the default value
+/// of the proxy type may be rejected, fields with a single value default to the `Default` value of
+/// their Rust type instead, or to the Unix epoch for `SystemTime`.
+#[derive(Clone)]
+pub struct Field {
+    pub inner: Box<Inner>,
+    pub ty: Type,
+    shape: Shape,
+    epoch_default: bool,
+}
+
+/// The shape of the field type: a single value, an `Option`, or a `Vec`.
+#[derive(Clone, Copy)]
+enum Shape {
+    Single,
+    Optional,
+    Repeated,
+}
+
+impl Field {
+    pub fn new(inner: Inner, ty: Type, field_ty: &Type) -> Result<Field, Error> {
+        let shape = match inner {
+            Inner::Scalar(ref scalar) => match scalar.kind {
+                scalar::Kind::Plain(..) | scalar::Kind::Required(..) => Shape::Single,
+                scalar::Kind::Optional(..) => Shape::Optional,
+                scalar::Kind::Repeated | scalar::Kind::Packed => Shape::Repeated,
+            },
+            Inner::Message(message::Field { label, .. }) => match label {
+                Label::Required => Shape::Single,
+                Label::Optional => Shape::Optional,
+                Label::Repeated => Shape::Repeated,
+            },
+            _ => bail!("proxy attribute may only be applied to scalar and message fields"),
+        };
+        // `SystemTime` does not implement `Default`.
+        let epoch_default = match *field_ty {
+            Type::Path(ref path) => path
+                .path
+                .segments
+                .last()
+                .map_or(false, |segment| segment.value().ident == "SystemTime"),
+            _ => false,
+        };
+        Ok(Field {
+            inner: Box::new(inner),
+            ty,
+            shape,
+            epoch_default,
+        })
+    }
+
+    /// Returns a statement which binds `proxy` to the field converted to the proxy type.
+    fn convert(&self, ident: &TokenStream) -> TokenStream {
+        let ty = reflect_type(&self.ty);
+        match self.shape {
+            Shape::Single => quote! {
+                let proxy: #ty = _prost::proxy::Proxy::<#ty>::to_proxy(&#ident);
+            },
+            Shape::Optional => quote! {
+                let proxy: ::std::option::Option<#ty> = (#ident)
+                    .as_ref()
+                    .map(_prost::proxy::Proxy::<#ty>::to_proxy);
+            },
+            Shape::Repeated => quote! {
+                let proxy: ::std::vec::Vec<#ty> = (#ident)
+                    .iter()
+                    .map(_prost::proxy::Proxy::<#ty>::to_proxy)
+                    .collect();
+            },
+        }
+    }
+
+    pub fn encode(&self, ident: TokenStream) -> TokenStream {
+        let convert = self.convert(&ident);
+        let encode = self.inner.encode(quote!(proxy));
+        quote! {
+            {
+                #convert
+                #encode
+            }
+        }
+    }
+
+    /// Returns an expression which merges a decoded value into the proxy of the field, and
+    /// converts it back.
+    pub fn merge(&self, ident: TokenStream) -> TokenStream {
+        let ty = reflect_type(&self.ty);
+        let merge = self.inner.merge(quote!(proxy));
+        // Scalars are replaced by decoded values rather than merged with them, and strings and
+        // bytes are appended to, so they are decoded into an empty proxy.
+        let scalar = match *self.inner {
+            Inner::Scalar(..) => true,
+            _ => false,
+        };
+        match self.shape {
+            Shape::Single if scalar => quote! {
+                {
+                    let mut proxy: #ty = ::std::default::Default::default();
+                    #merge
+                        .and_then(|()| _prost::proxy::Proxy::<#ty>::from_proxy(proxy))
+                        .map(|value| #ident = value)
+                }
+            },
+            Shape::Optional if scalar => quote! {
+                {
+                    let mut proxy: ::std::option::Option<#ty> = ::std::option::Option::None;
+                    #merge
+                        .and_then(|()| {
+                            proxy.map(_prost::proxy::Proxy::<#ty>::from_proxy).transpose()
+                        })
+                        .map(|value| #ident = value)
+                }
+            },
+            Shape::Single => quote! {
+                {
+                    let mut proxy: #ty = _prost::proxy::Proxy::<#ty>::to_proxy(&#ident);
+                    #merge
+                        .and_then(|()| _prost::proxy::Proxy::<#ty>::from_proxy(proxy))
+                        .map(|value| #ident = value)
+                }
+            },
+            Shape::Optional => quote! {
+                {
+                    let mut proxy: ::std::option::Option<#ty> = (#ident)
+                        .as_ref()
+                        .map(_prost::proxy::Proxy::<#ty>::to_proxy);
+                    #merge
+                        .and_then(|()| {
+                            proxy.map(_prost::proxy::Proxy::<#ty>::from_proxy).transpose()
+                        })
+                        .map(|value| #ident = value)
+                }
+            },
+            // Repeated values are appended, so only the decoded values are converted.
+            Shape::Repeated => quote! {
+                {
+                    let mut proxy: ::std::vec::Vec<#ty> = ::std::vec::Vec::new();
+                    #merge.and_then(|()| {
+                        for value in proxy {
+                            (#ident).push(_prost::proxy::Proxy::<#ty>::from_proxy(value)?);
+                        }
+                        ::std::result::Result::Ok(())
+                    })
+                }
+            },
+        }
+    }
+
+    pub fn encoded_len(&self, ident: TokenStream) -> TokenStream {
+        let convert = self.convert(&ident);
+        let encoded_len = self.inner.encoded_len(quote!(proxy));
+        quote! {
+            {
+                #convert
+                #encoded_len
+            }
+        }
+    }
+
+    pub fn clear(&self, ident: TokenStream) -> TokenStream {
+        match self.shape {
+            Shape::Single => {
+                let default = self.default();
+                quote!(#ident = #default)
+            }
+            Shape::Optional => quote!(#ident = ::std::option::Option::None),
+            Shape::Repeated => quote!(#ident.clear()),
+        }
+    }
+
+    /// Returns an expression which evaluates to the default value of the field.
+    pub fn default(&self) -> TokenStream {
+        match self.shape {
+            Shape::Single if self.epoch_default => quote!(::std::time::UNIX_EPOCH),
+            Shape::Single => quote!(::std::default::Default::default()),
+            Shape::Optional => quote!(::std::option::Option::None),
+            Shape::Repeated => quote!(::std::vec::Vec::new()),
+        }
+    }
+
+    /// Returns an expression which evaluates to the reflection descriptor of the field, which
+    /// describes the proxy type.
+    pub fn descriptor(&self, name: &str, proto_name: &str, json_name: &str) -> TokenStream {
+        self.inner.descriptor(name, proto_name, json_name, &self.ty)
+    }
+}
+
+/// Parses a `proxy = "Type"` attribute.
+pub fn proxy_attr(attr: &Meta) -> Result<Option<Type>, Error> {
+    if attr.name() != "proxy" {
+        return Ok(None);
+    }
+    match *attr {
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(ref lit),
+            ..
+        }) => syn::parse_str(&lit.value())
+            .map(Some)
+            .map_err(|_| format_err!("invalid proxy type: {}", lit.value())),
+        _ => bail!("invalid proxy attribute: {:?}", attr),
+    }
+}
@@ -4,11 +4,14 @@ use failure::Error;
 use proc_macro2::{Span, TokenStream};
 use quote;
 use syn::{
-    self, parse_str, FloatSuffix, Ident, IntSuffix, Lit, LitByteStr, Meta, MetaList, MetaNameValue,
-    NestedMeta, Path,
+    self, parse_str, Expr, FloatSuffix, Ident, IntSuffix, Lit, LitByteStr, Meta, MetaList,
+    MetaNameValue, NestedMeta, Path, Type,
 };
 
-use field::{amino_name_attr, bool_attr, set_option, tag_attr, Label};
+use field::{
+    amino_name_attr, bool_attr, field_descriptor, reflect_path, set_bool, set_option, str_attr,
+    tag_attr, word_attr, DescriptorOptions, Label,
+};
 
 use super::compute_disfix;
 
@@ -18,18 +21,32 @@ pub struct Field {
     pub ty: Ty,
     pub kind: Kind,
     pub tag: u32,
+    pub amino_name: Option<String>,
     // this is to be able to de/encode registered type aliases:
     pub amino_prefix: Vec<u8>,
+    // the length of fixed-size array fields, e.g. `[u8; 20]` bytes or `[i64; 4]` packed fields:
+    pub array_len: Option<Expr>,
+    // go-amino `amino:"write_empty"`: plain fields are encoded even if they hold the default value.
+    pub write_empty: bool,
+    // go-amino `amino:"unsafe"`: float fields are allowed in amino messages.
+    pub is_unsafe: bool,
 }
 
 impl Field {
-    pub fn new(attrs: &[Meta], inferred_tag: Option<u32>) -> Result<Option<Field>, Error> {
+    pub fn new(
+        attrs: &[Meta],
+        inferred_tag: Option<u32>,
+        field_ty: &Type,
+    ) -> Result<Option<Field>, Error> {
         let mut ty = None;
         let mut label = None;
         let mut packed = None;
         let mut default = None;
         let mut tag = None;
         let mut amino_name = None;
+        let mut binary = None;
+        let mut write_empty = false;
+        let mut is_unsafe = false;
 
         let mut unknown_attrs = Vec::new();
 
@@ -42,6 +59,12 @@ impl Field {
                 set_option(&mut tag, t, "duplicate tag attributes")?;
             } else if let Some(n) = amino_name_attr(attr)? {
                 set_option(&mut amino_name, n, "duplicate amino_name attributes")?;
+            } else if let Some(b) = str_attr("binary", attr)? {
+                set_option(&mut binary, b, "duplicate binary attributes")?;
+            } else if word_attr("write_empty", attr) {
+                set_bool(&mut write_empty, "duplicate write_empty attributes")?;
+            } else if word_attr("unsafe", attr) {
+                set_bool(&mut is_unsafe, "duplicate unsafe attributes")?;
             } else if let Some(l) = Label::from_attr(attr) {
                 set_option(&mut label, l, "duplicate label attributes")?;
             } else if let Some(d) = DefaultValue::from_attr(attr)? {
@@ -66,6 +89,21 @@ impl Field {
             None => bail!("missing tag attribute"),
         };
 
+        // go-amino `binary:"fixed64"` and `binary:"fixed32"` integers are the fixed width types.
+        let ty = match binary {
+            None => ty,
+            Some(binary) => match (binary.as_str(), ty) {
+                ("fixed64", Ty::Int64) => Ty::Sfixed64,
+                ("fixed64", Ty::Uint64) => Ty::Fixed64,
+                ("fixed32", Ty::Int32) => Ty::Sfixed32,
+                ("fixed32", Ty::Uint32) => Ty::Fixed32,
+                (binary, ty) => bail!("invalid binary attribute for {} field: {:?}", ty, binary),
+            },
+        };
+        if is_unsafe && !ty.is_float() {
+            bail!("unsafe attribute may only be applied to float and double fields");
+        }
+
         let has_default = default.is_some();
         let default = default.map_or_else(
             || Ok(DefaultValue::new(&ty)),
@@ -93,8 +131,32 @@ impl Field {
             }
             (Some(Label::Repeated), _, false) => Kind::Repeated,
         };
+        let array_len = match *field_ty {
+            Type::Array(ref array) => Some(array.len.clone()),
+            _ => None,
+        };
+        if write_empty {
+            match kind {
+                Kind::Plain(..) => (),
+                _ => bail!("write_empty attribute may only be applied to plain fields"),
+            }
+        }
+        if array_len.is_some() {
+            match kind {
+                Kind::Plain(..) | Kind::Required(..) if ty == Ty::Bytes => (),
+                Kind::Packed => (),
+                Kind::Repeated if !ty.is_numeric() => {
+                    bail!("fixed-size arrays of strings or bytes are not supported")
+                }
+                Kind::Repeated => bail!("fixed-size array fields may not be unpacked"),
+                _ => bail!("fixed-size array fields must be bytes or repeated fields"),
+            }
+            if amino_name.is_some() {
+                bail!("amino_name attribute may not be applied to fixed-size array fields");
+            }
+        }
         let amino_prefix: Vec<u8> = match amino_name {
-            Some(n) => {
+            Some(ref n) => {
                 let (_dis, pre) = compute_disfix(n.as_str());
                 pre
             }
@@ -105,12 +167,16 @@ impl Field {
             ty: ty,
             kind: kind,
             tag: tag,
+            amino_name: amino_name,
             amino_prefix: amino_prefix,
+            array_len: array_len,
+            write_empty: write_empty,
+            is_unsafe: is_unsafe,
         }))
     }
 
-    pub fn new_oneof(attrs: &[Meta]) -> Result<Option<Field>, Error> {
-        if let Some(mut field) = Field::new(attrs, None)? {
+    pub fn new_oneof(attrs: &[Meta], field_ty: &Type) -> Result<Option<Field>, Error> {
+        if let Some(mut field) = Field::new(attrs, None, field_ty)? {
             match field.kind {
                 Kind::Plain(default) => {
                     field.kind = Kind::Required(default);
@@ -142,28 +208,45 @@ impl Field {
         let tag = self.tag;
 
         match self.kind {
+            // Like go-amino, fixed-size byte arrays are encoded even if they only hold zeros.
+            Kind::Plain(..) | Kind::Required(..) if self.array_len.is_some() => quote! {
+                _prost::encoding::bytes::encode_array(#tag, &#ident, buf);
+            },
             Kind::Plain(ref default) => {
                 let default = default.typed();
-                if self.amino_prefix.len() > 0 {
+                let encode = if self.amino_prefix.len() > 0 {
                     let pre = &self.amino_prefix;
+                    quote!(#encode_fn(#tag, &#ident, &vec![#(#pre),*], buf);)
+                } else {
+                    quote!(#encode_fn(#tag, &#ident, buf);)
+                };
+                if self.write_empty {
+                    encode
+                } else {
                     quote! {
                         if #ident != #default {
-                            #encode_fn(#tag, &#ident, &vec![#(#pre),*], buf);
+                            #encode
+                        }
+                    }
+                }
+            }
+            // Optional values are encoded whenever they are set, including default values.
+            Kind::Optional(..) => {
+                if self.amino_prefix.len() > 0 {
+                    let pre = &self.amino_prefix;
+                    quote! {
+                        if let ::std::option::Option::Some(ref value) = #ident {
+                            #encode_fn(#tag, value, &vec![#(#pre),*], buf);
                         }
                     }
                 } else {
                     quote! {
-                        if #ident != #default {
-                            #encode_fn(#tag, &#ident, buf);
+                        if let ::std::option::Option::Some(ref value) = #ident {
+                            #encode_fn(#tag, value, buf);
                         }
                     }
                 }
             }
-            Kind::Optional(..) => quote! {
-                if let ::std::option::Option::Some(ref value) = #ident {
-                    #encode_fn(#tag, value, buf);
-                }
-            },
             Kind::Required(..) | Kind::Repeated | Kind::Packed => quote! {
                 #encode_fn(#tag, &#ident, buf);
             },
@@ -175,6 +258,7 @@ impl Field {
     pub fn merge(&self, ident: TokenStream) -> TokenStream {
         let module = self.ty.module();
         let merge_fn = match self.kind {
+            _ if self.array_len.is_some() => quote!(merge_array),
             Kind::Plain(..) | Kind::Optional(..) | Kind::Required(..) => quote!(merge),
             Kind::Repeated | Kind::Packed => quote!(merge_repeated),
         };
@@ -186,6 +270,16 @@ impl Field {
             quote!(_prost::encoding::#module::#merge_fn)
         };
         let pre = &self.amino_prefix;
+        if let Some(ref len) = self.array_len {
+            // The array type is spelled out, since oneof values are merged into a value of
+            // inferred type.
+            return quote! {
+                {
+                    let value: &mut [_; #len] = &mut #ident;
+                    #merge_fn(wire_type, value, buf)
+                }
+            };
+        }
         match self.kind {
             Kind::Plain(..) | Kind::Required(..) | Kind::Repeated | Kind::Packed => {
                 if decode_with_prefix {
@@ -198,11 +292,22 @@ impl Field {
                     }
                 }
             }
-            Kind::Optional(..) => quote! {
-                #merge_fn(wire_type,
-                          #ident.get_or_insert_with(Default::default),
-                          buf)
-            },
+            Kind::Optional(..) => {
+                if decode_with_prefix {
+                    quote! {
+                        #merge_fn(wire_type,
+                                  #ident.get_or_insert_with(Default::default),
+                                  &vec![#(#pre),*],
+                                  buf)
+                    }
+                } else {
+                    quote! {
+                        #merge_fn(wire_type,
+                                  #ident.get_or_insert_with(Default::default),
+                                  buf)
+                    }
+                }
+            }
         }
     }
 
@@ -219,22 +324,38 @@ impl Field {
         let is_amino_prefixed = self.amino_prefix.len() > 0;
 
         match self.kind {
+            Kind::Plain(..) | Kind::Required(..) if self.array_len.is_some() => quote! {
+                _prost::encoding::bytes::encoded_len_array(#tag, &#ident)
+            },
             Kind::Plain(ref default) => {
                 let default = default.typed();
-                quote! {
-                    if #ident != #default {
-                        if #is_amino_prefixed {
-                            #encoded_len_fn(#tag, &#ident) + 5
+                let encoded_len = quote! {
+                    if #is_amino_prefixed {
+                        #encoded_len_fn(#tag, &#ident) + 5
+                    } else {
+                        #encoded_len_fn(#tag, &#ident)
+                    }
+                };
+                if self.write_empty {
+                    encoded_len
+                } else {
+                    quote! {
+                        if #ident != #default {
+                            #encoded_len
                         } else {
-                            #encoded_len_fn(#tag, &#ident)
+                            0
                         }
-                    } else {
-                        0
                     }
                 }
             }
             Kind::Optional(..) => quote! {
-                #ident.as_ref().map_or(0, |value| #encoded_len_fn(#tag, value))
+                #ident.as_ref().map_or(0, |value| {
+                    if #is_amino_prefixed {
+                        #encoded_len_fn(#tag, value) + 5
+                    } else {
+                        #encoded_len_fn(#tag, value)
+                    }
+                })
             },
             Kind::Required(..) | Kind::Repeated | Kind::Packed => quote! {
                 #encoded_len_fn(#tag, &#ident)
@@ -243,6 +364,10 @@ impl Field {
     }
 
     pub fn clear(&self, ident: TokenStream) -> TokenStream {
+        if self.array_len.is_some() {
+            let default = self.default();
+            return quote!(#ident = #default);
+        }
         match self.kind {
             Kind::Plain(ref default) | Kind::Required(ref default) => {
                 let default = default.typed();
@@ -258,6 +383,9 @@ impl Field {
 
     /// Returns an expression which evaluates to the default value of the field.
     pub fn default(&self) -> TokenStream {
+        if let Some(ref len) = self.array_len {
+            return quote!([::std::default::Default::default(); #len]);
+        }
         match self.kind {
             Kind::Plain(ref value) | Kind::Required(ref value) => value.owned(),
             Kind::Optional(_) => quote!(::std::option::Option::None),
@@ -265,14 +393,38 @@ impl Field {
         }
     }
 
+    /// Returns an expression which evaluates to the reflection descriptor of the field.
+    pub fn descriptor(&self, name: &str, proto_name: &str, json_name: &str) -> TokenStream {
+        let label = match self.kind {
+            Kind::Plain(..) => quote!(Plain),
+            Kind::Optional(..) => quote!(Optional),
+            Kind::Required(..) => quote!(Required),
+            Kind::Repeated => quote!(Repeated),
+            Kind::Packed => quote!(Packed),
+        };
+        field_descriptor(
+            name,
+            proto_name,
+            json_name,
+            &[self.tag],
+            label,
+            self.ty.reflect_kind(),
+            DescriptorOptions {
+                amino_name: self.amino_name.as_deref(),
+                write_empty: self.write_empty,
+            },
+        )
+    }
+
     /// An inner debug wrapper, around the base type.
     fn debug_inner(&self, wrap_name: TokenStream) -> TokenStream {
         if let Ty::Enumeration(ref ty) = self.ty {
+            let ty = reflect_path(ty);
             quote! {
                 struct #wrap_name<'a>(&'a i32);
                 impl<'a> ::std::fmt::Debug for #wrap_name<'a> {
                     fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
-                        match super::#ty::from_i32(*self.0) {
+                        match #ty::from_i32(*self.0) {
                             None => ::std::fmt::Debug::fmt(&self.0, f),
                             Some(en) => ::std::fmt::Debug::fmt(&en, f),
                         }
@@ -303,7 +455,7 @@ impl Field {
             },
             Kind::Repeated | Kind::Packed => {
                 quote! {
-                    struct #wrapper_name<'a>(&'a ::std::vec::Vec<#inner_ty>);
+                    struct #wrapper_name<'a>(&'a [#inner_ty]);
                     impl<'a> ::std::fmt::Debug for #wrapper_name<'a> {
                         fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
                             let mut vec_builder = f.debug_list();
@@ -324,38 +476,46 @@ impl Field {
         let set = Ident::new(&format!("set_{}", ident), Span::call_site());
         let push = Ident::new(&format!("push_{}", ident), Span::call_site());
         if let Ty::Enumeration(ref ty) = self.ty {
+            let ty = reflect_path(ty);
             Some(match self.kind {
                 Kind::Plain(ref default) | Kind::Required(ref default) => {
                     quote! {
-                        pub fn #ident(&self) -> super::#ty {
-                            super::#ty::from_i32(self.#ident).unwrap_or(super::#default)
+                        pub fn #ident(&self) -> #ty {
+                            #ty::from_i32(self.#ident).unwrap_or(#default)
                         }
 
-                        pub fn #set(&mut self, value: super::#ty) {
+                        pub fn #set(&mut self, value: #ty) {
                             self.#ident = value as i32;
                         }
                     }
                 }
                 Kind::Optional(ref default) => {
                     quote! {
-                        pub fn #ident(&self) -> super::#ty {
-                            self.#ident.and_then(super::#ty::from_i32).unwrap_or(super::#default)
+                        pub fn #ident(&self) -> #ty {
+                            self.#ident.and_then(#ty::from_i32).unwrap_or(#default)
                         }
 
-                        pub fn #set(&mut self, value: super::#ty) {
+                        pub fn #set(&mut self, value: #ty) {
                             self.#ident = ::std::option::Option::Some(value as i32);
                         }
                     }
                 }
                 Kind::Repeated | Kind::Packed => {
+                    let push = if self.array_len.is_none() {
+                        quote! {
+                            pub fn #push(&mut self, value: #ty) {
+                                self.#ident.push(value as i32);
+                            }
+                        }
+                    } else {
+                        quote!()
+                    };
                     quote! {
                         pub fn #ident(&self) -> ::std::iter::FilterMap<::std::iter::Cloned<::std::slice::Iter<i32>>,
-                                                                       fn(i32) -> Option<super::#ty>> {
-                            self.#ident.iter().cloned().filter_map(super::#ty::from_i32)
-                        }
-                        pub fn #push(&mut self, value: super::#ty) {
-                            self.#ident.push(value as i32);
+                                                                       fn(i32) -> Option<#ty>> {
+                            self.#ident.iter().cloned().filter_map(#ty::from_i32)
                         }
+                        #push
                     }
                 }
             })
@@ -397,6 +557,10 @@ pub enum Ty {
     Fixed64,
     Sfixed32,
     Sfixed64,
+    Int8,
+    Int16,
+    Uint8,
+    Uint16,
     Bool,
     String,
     Bytes,
@@ -418,6 +582,10 @@ impl Ty {
             Meta::Word(ref name) if name == "fixed64" => Ty::Fixed64,
             Meta::Word(ref name) if name == "sfixed32" => Ty::Sfixed32,
             Meta::Word(ref name) if name == "sfixed64" => Ty::Sfixed64,
+            Meta::Word(ref name) if name == "int8" => Ty::Int8,
+            Meta::Word(ref name) if name == "int16" => Ty::Int16,
+            Meta::Word(ref name) if name == "uint8" => Ty::Uint8,
+            Meta::Word(ref name) if name == "uint16" => Ty::Uint16,
             Meta::Word(ref name) if name == "bool" => Ty::Bool,
             Meta::Word(ref name) if name == "string" => Ty::String,
             Meta::Word(ref name) if name == "bytes" => Ty::Bytes,
@@ -463,6 +631,10 @@ impl Ty {
             "fixed64" => Ty::Fixed64,
             "sfixed32" => Ty::Sfixed32,
             "sfixed64" => Ty::Sfixed64,
+            "int8" => Ty::Int8,
+            "int16" => Ty::Int16,
+            "uint8" => Ty::Uint8,
+            "uint16" => Ty::Uint16,
             "bool" => Ty::Bool,
             "string" => Ty::String,
             "bytes" => Ty::Bytes,
@@ -499,6 +671,10 @@ impl Ty {
             Ty::Fixed64 => "fixed64",
             Ty::Sfixed32 => "sfixed32",
             Ty::Sfixed64 => "sfixed64",
+            Ty::Int8 => "int8",
+            Ty::Int16 => "int16",
+            Ty::Uint8 => "uint8",
+            Ty::Uint16 => "uint16",
             Ty::Bool => "bool",
             Ty::String => "string",
             Ty::Bytes => "bytes",
@@ -530,6 +706,10 @@ impl Ty {
             Ty::Fixed64 => quote!(u64),
             Ty::Sfixed32 => quote!(i32),
             Ty::Sfixed64 => quote!(i64),
+            Ty::Int8 => quote!(i8),
+            Ty::Int16 => quote!(i16),
+            Ty::Uint8 => quote!(u8),
+            Ty::Uint16 => quote!(u16),
             Ty::Bool => quote!(bool),
             Ty::String => quote!(&str),
             Ty::Bytes => quote!(&[u8]),
@@ -537,6 +717,36 @@ impl Ty {
         }
     }
 
+    /// Returns an expression which evaluates to the reflection kind of the type.
+    pub fn reflect_kind(&self) -> TokenStream {
+        match *self {
+            Ty::Enumeration(ref ty) => {
+                let ty = reflect_path(ty);
+                quote!(_prost::reflect::FieldKind::Enumeration(#ty::descriptor))
+            }
+            _ => {
+                let ty = self.reflect_type();
+                quote!(_prost::reflect::FieldKind::Scalar(#ty))
+            }
+        }
+    }
+
+    /// Returns the `prost_amino::reflect::Type` variant of a non-enumeration type.
+    ///
+    /// Small integers are described by the 32-bit protobuf type with the same encoding.
+    pub fn reflect_type(&self) -> TokenStream {
+        let name = match *self {
+            Ty::Int8 | Ty::Int16 => "sint32",
+            Ty::Uint8 | Ty::Uint16 => "uint32",
+            _ => self.as_str(),
+        };
+        let variant = Ident::new(
+            &format!("{}{}", name[..1].to_uppercase(), &name[1..]),
+            Span::call_site(),
+        );
+        quote!(_prost::reflect::Type::#variant)
+    }
+
     pub fn module(&self) -> Ident {
         match *self {
             Ty::Enumeration(..) => Ident::new("int32", Span::call_site()),
@@ -548,6 +758,11 @@ impl Ty {
     pub fn is_numeric(&self) -> bool {
         *self != Ty::String && *self != Ty::Bytes
     }
+
+    /// Returns true if the scalar type is `float` or `double`.
+    pub fn is_float(&self) -> bool {
+        *self == Ty::Float || *self == Ty::Double
+    }
 }
 
 impl fmt::Debug for Ty {
@@ -586,6 +801,10 @@ pub enum DefaultValue {
     I64(i64),
     U32(u32),
     U64(u64),
+    I8(i8),
+    I16(i16),
+    U8(u8),
+    U16(u16),
     Bool(bool),
     String(String),
     Bytes(Vec<u8>),
@@ -636,6 +855,30 @@ impl DefaultValue {
             {
                 DefaultValue::U64(lit.value())
             }
+            Lit::Int(ref lit)
+                if *ty == Ty::Int8
+                    && (lit.suffix() == IntSuffix::I8 || lit.suffix() == IntSuffix::None) =>
+            {
+                DefaultValue::I8(lit.value() as _)
+            }
+            Lit::Int(ref lit)
+                if *ty == Ty::Int16
+                    && (lit.suffix() == IntSuffix::I16 || lit.suffix() == IntSuffix::None) =>
+            {
+                DefaultValue::I16(lit.value() as _)
+            }
+            Lit::Int(ref lit)
+                if *ty == Ty::Uint8
+                    && (lit.suffix() == IntSuffix::U8 || lit.suffix() == IntSuffix::None) =>
+            {
+                DefaultValue::U8(lit.value() as _)
+            }
+            Lit::Int(ref lit)
+                if *ty == Ty::Uint16
+                    && (lit.suffix() == IntSuffix::U16 || lit.suffix() == IntSuffix::None) =>
+            {
+                DefaultValue::U16(lit.value() as _)
+            }
 
             Lit::Float(ref lit)
                 if *ty == Ty::Float
@@ -662,6 +905,7 @@ impl DefaultValue {
                 let value = value.trim();
 
                 if let Ty::Enumeration(ref path) = *ty {
+                    let path = reflect_path(path);
                     let variant = Ident::new(value, Span::call_site());
                     return Ok(DefaultValue::Enumeration(quote!(#path::#variant)));
                 }
@@ -724,6 +968,22 @@ impl DefaultValue {
                                 return Ok(DefaultValue::I64((!lit.value() + 1) as i64));
                             }
 
+                            Lit::Int(ref lit)
+                                if *ty == Ty::Int8
+                                    && (lit.suffix() == IntSuffix::I8
+                                        || lit.suffix() == IntSuffix::None) =>
+                            {
+                                return Ok(DefaultValue::I8((!lit.value() + 1) as i8));
+                            }
+
+                            Lit::Int(ref lit)
+                                if *ty == Ty::Int16
+                                    && (lit.suffix() == IntSuffix::I16
+                                        || lit.suffix() == IntSuffix::None) =>
+                            {
+                                return Ok(DefaultValue::I16((!lit.value() + 1) as i16));
+                            }
+
                             Lit::Float(ref lit)
                                 if *ty == Ty::Float
                                     && (lit.suffix() == FloatSuffix::F32
@@ -777,12 +1037,17 @@ impl DefaultValue {
             Ty::Int64 | Ty::Sint64 | Ty::Sfixed64 => DefaultValue::I64(0),
             Ty::Uint32 | Ty::Fixed32 => DefaultValue::U32(0),
             Ty::Uint64 | Ty::Fixed64 => DefaultValue::U64(0),
+            Ty::Int8 => DefaultValue::I8(0),
+            Ty::Int16 => DefaultValue::I16(0),
+            Ty::Uint8 => DefaultValue::U8(0),
+            Ty::Uint16 => DefaultValue::U16(0),
 
             Ty::Bool => DefaultValue::Bool(false),
             Ty::String => DefaultValue::String(String::new()),
             Ty::Bytes => DefaultValue::Bytes(Vec::new()),
             Ty::Enumeration(ref path) => {
-                return DefaultValue::Enumeration(quote!(#path::default()))
+                let path = reflect_path(path);
+                return DefaultValue::Enumeration(quote!(#path::default()));
             }
         }
     }
@@ -805,7 +1070,7 @@ impl DefaultValue {
 
     pub fn typed(&self) -> TokenStream {
         if let DefaultValue::Enumeration(_) = *self {
-            quote!(super::#self as i32)
+            quote!(#self as i32)
         } else {
             quote!(#self)
         }
@@ -821,6 +1086,10 @@ impl quote::ToTokens for DefaultValue {
             DefaultValue::I64(value) => value.to_tokens(tokens),
             DefaultValue::U32(value) => value.to_tokens(tokens),
             DefaultValue::U64(value) => value.to_tokens(tokens),
+            DefaultValue::I8(value) => value.to_tokens(tokens),
+            DefaultValue::I16(value) => value.to_tokens(tokens),
+            DefaultValue::U8(value) => value.to_tokens(tokens),
+            DefaultValue::U16(value) => value.to_tokens(tokens),
             DefaultValue::Bool(value) => value.to_tokens(tokens),
             DefaultValue::String(ref value) => value.to_tokens(tokens),
             DefaultValue::Bytes(ref value) => {
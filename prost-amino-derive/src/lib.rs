@@ -2,6 +2,7 @@
 // The `quote!` macro requires deep recursion.
 #![recursion_limit = "4096"]
 
+extern crate heck;
 extern crate itertools;
 extern crate proc_macro;
 extern crate proc_macro2;
@@ -14,6 +15,7 @@ extern crate failure;
 extern crate quote;
 
 use failure::Error;
+use heck::{ShoutySnakeCase, SnakeCase};
 use itertools::Itertools;
 use proc_macro::TokenStream;
 use proc_macro2::Span;
@@ -24,6 +26,7 @@ use syn::{
     Variant,
 };
 
+mod builder;
 mod field;
 
 use field::Field;
@@ -33,8 +36,9 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
 
     let top_level_attrs: Vec<syn::Attribute> = input.attrs;
     let amino_name_attrs: Vec<syn::Attribute> = top_level_attrs
-        .into_iter()
+        .iter()
         .filter(|a| a.path.segments.first().unwrap().value().ident == "amino_name")
+        .cloned()
         .collect();
     if amino_name_attrs.len() > 1 {
         bail!("got more than one registered amino_name");
@@ -70,19 +74,34 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
         }
     };
 
-    let prefix: Option<Vec<u8>> = {
+    let descriptor_amino_name = match amino_name {
+        Some(ref reg) => {
+            let reg = reg.trim_matches('"');
+            quote!(::std::option::Option::Some(#reg))
+        }
+        None => quote!(::std::option::Option::None),
+    };
+
+    // The amino name, disambiguation bytes and prefix bytes of a registered type.
+    let registration: Option<(String, Vec<u8>, Vec<u8>)> = {
         match amino_name {
             Some(mut reg) => {
                 assert_eq!(reg.remove(0), '"');
                 let s = reg.len() - 1;
                 assert_eq!(reg.remove(s), '"');
-                let (_dis, pre) = compute_disfix(&reg[..]);
+                let (dis, pre) = compute_disfix(&reg[..]);
 
-                Some(pre)
+                Some((reg, dis, pre))
             }
             None => None,
         }
     };
+    let prefix: Option<Vec<u8>> = registration.as_ref().map(|&(_, _, ref pre)| pre.clone());
+
+    let descriptor_prefix = match prefix {
+        Some(ref p) => quote!(::std::option::Option::Some([#(#p),*])),
+        None => quote!(::std::option::Option::None),
+    };
 
     let comp_prefix = match prefix {
         Some(p) => {
@@ -95,7 +114,34 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
         None => quote!(),
     };
 
+    let mut builder = false;
+    let mut amino = false;
+    let mut full_name = None;
+    for attr in field::prost_attrs(top_level_attrs)? {
+        if field::word_attr("builder", &attr) {
+            field::set_bool(&mut builder, "duplicate builder attributes")?;
+        } else if field::word_attr("amino", &attr) {
+            field::set_bool(&mut amino, "duplicate amino attributes")?;
+        } else if let Some(name) = field::str_attr("full_name", &attr)? {
+            field::set_option(&mut full_name, name, "duplicate full_name attributes")?;
+        } else {
+            bail!("unknown message attribute: {:?}", attr);
+        }
+    }
+
     let ident = input.ident;
+    let full_name = full_name.unwrap_or_else(|| ident.to_string());
+
+    let amino_registered = match registration {
+        Some((ref name, ref dis, ref pre)) => quote! {
+            impl _prost::amino::AminoRegistered for #ident {
+                const AMINO_NAME: &'static str = #name;
+                const PREFIX: [u8; 4] = [#(#pre),*];
+                const DISAMB: [u8; 3] = [#(#dis),*];
+            }
+        },
+        None => quote!(),
+    };
 
     let variant_data = match input.data {
         Data::Struct(variant_data) => variant_data,
@@ -126,17 +172,35 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
     };
 
     let mut next_tag: u32 = 0;
-    let mut fields = fields
+    let mut names = Vec::new();
+    let fields = fields
         .into_iter()
         .enumerate()
         .flat_map(|(idx, field)| {
             let field_ident = field
                 .ident
                 .unwrap_or_else(|| Ident::new(&idx.to_string(), Span::call_site()));
-            match Field::new(field.attrs, Some(next_tag)) {
+            let ty = field.ty;
+            // The protobuf name defaults to the Rust name, without the trailing underscore of
+            // escaped keywords.
+            let name = field_ident.to_string();
+            let default_proto_name = name.trim_start_matches("r#").trim_end_matches('_');
+            let proto_name = field::proto_name(&field.attrs, default_proto_name.to_string());
+            let json_name = field::json_name(&field.attrs, &name);
+            let (proto_name, json_name) = match (proto_name, json_name) {
+                (Ok(proto_name), Ok(json_name)) => (proto_name, json_name),
+                (Err(err), _) | (_, Err(err)) => {
+                    return Some(Err(err.context(format!(
+                        "invalid message field {}.{}",
+                        ident, field_ident
+                    ))))
+                }
+            };
+            match Field::new(field.attrs, Some(next_tag), &ty) {
                 Ok(Some(field)) => {
                     next_tag = field.tags().iter().max().map(|t| t + 1).unwrap_or(next_tag);
-                    Some(Ok((field_ident, field)))
+                    names.push((proto_name, json_name));
+                    Some(Ok((field_ident, field, ty)))
                 }
                 Ok(None) => None,
                 Err(err) => Some(Err(
@@ -144,7 +208,60 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
                 )),
             }
         })
-        .collect::<Result<Vec<(Ident, Field)>, failure::Context<String>>>()?;
+        .collect::<Result<Vec<(Ident, Field, syn::Type)>, failure::Context<String>>>()?;
+
+    // Like go-amino, amino messages only allow floating point fields which opt in with `unsafe`,
+    // since their encoding isn't deterministic across platforms. Registered types are amino
+    // messages. The variants of oneof fields are checked by assertions on their oneof types.
+    let mut amino_checks = Vec::new();
+    if amino || is_registered {
+        for (field_ident, field, _) in &fields {
+            if field.is_unsafe_float() {
+                bail!(
+                    "invalid message field {}.{}: floating point fields of amino messages \
+                     must have the unsafe attribute",
+                    ident,
+                    field_ident
+                );
+            }
+            if let Field::Oneof(ref oneof) = *field {
+                let ty = field::reflect_path(&oneof.ty);
+                let message = format!(
+                    "invalid message field {}.{}: floating point oneof variants of amino messages \
+                     must have the unsafe attribute",
+                    ident, field_ident
+                );
+                amino_checks.push(quote!(
+                    const _: () = assert!(!#ty::HAS_UNSAFE_FLOAT, #message);
+                ));
+            }
+        }
+    }
+
+    // Descriptors list the fields in declaration order.
+    // Extension sets are not listed, extensions are described by their `Extension` descriptors.
+    let field_descriptors = fields
+        .iter()
+        .zip(&names)
+        .filter_map(
+            |(&(ref field_ident, ref field, ref ty), &(ref proto_name, ref json_name))| match *field
+            {
+                Field::Extensions(..) => None,
+                _ => Some(field.descriptor(&field_ident.to_string(), proto_name, json_name, ty)),
+            },
+        )
+        .collect::<Vec<_>>();
+
+    let (builder_definition, builder_impl) = if builder {
+        builder::builder(&input.vis, &ident, &fields)?
+    } else {
+        (quote!(), quote!())
+    };
+
+    let mut fields = fields
+        .into_iter()
+        .map(|(field_ident, field, _)| (field_ident, field))
+        .collect::<Vec<_>>();
 
     // We want Debug to be in declaration order
     let unsorted_fields = fields.clone();
@@ -153,7 +270,9 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
     // TODO: This encodes oneof fields in the position of their lowest tag,
     // regardless of the currently occupied variant, is that consequential?
     // See: https://developers.google.com/protocol-buffers/docs/encoding#order
-    fields.sort_by_key(|&(_, ref field)| field.tags().into_iter().min().unwrap());
+    // Extension sets have no tags of their own, and are encoded last.
+    fields
+        .sort_by_key(|&(_, ref field)| field.tags().into_iter().min().unwrap_or(u32::max_value()));
     let fields = fields;
 
     let mut tags = fields
@@ -181,12 +300,20 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
 
     let merge = fields.iter().map(|&(ref field_ident, ref field)| {
         let merge = field.merge(quote!(self.#field_ident));
-        let tags = field
-            .tags()
-            .into_iter()
-            .map(|tag| quote!(#tag))
-            .intersperse(quote!(|));
-        quote!(#(#tags)* => #merge.map_err(|mut error| {
+        let tags = match *field {
+            Field::Extensions(ref extensions) => {
+                let guard = extensions.guard();
+                quote!(tag if #guard)
+            }
+            _ => {
+                let tags = Itertools::intersperse(
+                    field.tags().into_iter().map(|tag| quote!(#tag)),
+                    quote!(|),
+                );
+                quote!(#(#tags)*)
+            }
+        };
+        quote!(#tags => #merge.map_err(|mut error| {
             error.push(STRUCT_NAME, stringify!(#field_ident));
             error
         }),)
@@ -248,12 +375,16 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
     };
 
     let expanded = quote! {
+        #builder_definition
+
         #[allow(non_snake_case, unused_attributes)]
         mod #module {
             extern crate prost_amino as _prost;
 
             use super::*;
 
+            #(#amino_checks)*
+
             impl _prost::Message for #ident {
                 #[allow(unused_variables)]
                 fn encode_raw<B>(&self, buf: &mut B) where B: _prost::bytes::BufMut  {
@@ -281,16 +412,28 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
                     }
                     if buf.remaining() > 0 {
                         let (tag, wire_type) = _prost::encoding::decode_key(buf)?;
-                        match tag {
-                            #(#merge)*
-                            _ => _prost::encoding::skip_field(wire_type, buf),
-                        }
+                        self.merge_tagged_field(tag, wire_type, buf, 0)
                     } else {
                         Ok(())
                     }
 
                 }
 
+                #[allow(unused_variables)]
+                fn merge_tagged_field<B>(&mut self,
+                                         tag: u32,
+                                         wire_type: _prost::encoding::WireType,
+                                         buf: &mut B,
+                                         depth: u32)
+                                         -> ::std::result::Result<(), _prost::DecodeError>
+                where B: _prost::bytes::Buf {
+                    #struct_name
+                    match tag {
+                        #(#merge)*
+                        _ => _prost::encoding::skip_field(wire_type, tag, buf),
+                    }
+                }
+
                 #[inline]
                 fn encoded_len(&self) -> usize {
                     let len = 0 #(+ #encoded_len)*;
@@ -304,8 +447,30 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
                 fn clear(&mut self) {
                     #(#clear;)*
                 }
+
+                fn reflect_descriptor()
+                    -> ::std::option::Option<&'static _prost::reflect::MessageDescriptor> {
+                    ::std::option::Option::Some(&DESCRIPTOR)
+                }
             }
 
+            static DESCRIPTOR: _prost::reflect::MessageDescriptor =
+                _prost::reflect::MessageDescriptor {
+                    name: stringify!(#ident),
+                    full_name: #full_name,
+                    amino_name: #descriptor_amino_name,
+                    prefix: #descriptor_prefix,
+                    fields: &[#(#field_descriptors),*],
+                };
+
+            impl _prost::reflect::Reflect for #ident {
+                fn descriptor() -> &'static _prost::reflect::MessageDescriptor {
+                    &DESCRIPTOR
+                }
+            }
+
+            #amino_registered
+
             impl Default for #ident {
                 fn default() -> #ident {
                     #ident {
@@ -323,12 +488,14 @@ fn try_message(input: TokenStream) -> Result<TokenStream, Error> {
             }
 
             #methods
+
+            #builder_impl
         };
     };
     Ok(expanded.into())
 }
 
-#[proc_macro_derive(Message, attributes(prost_amino, amino_name, aminoDisamb))]
+#[proc_macro_derive(Message, attributes(prost, prost_amino, amino_name, aminoDisamb))]
 pub fn message(input: TokenStream) -> TokenStream {
     try_message(input).unwrap()
 }
@@ -337,6 +504,16 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
     let input: DeriveInput = syn::parse(input)?;
     let ident = input.ident;
 
+    let mut full_name = None;
+    for attr in field::prost_attrs(input.attrs)? {
+        if let Some(name) = field::str_attr("full_name", &attr)? {
+            field::set_option(&mut full_name, name, "duplicate full_name attributes")?;
+        } else {
+            bail!("unknown enumeration attribute: {:?}", attr);
+        }
+    }
+    let full_name = full_name.unwrap_or_else(|| ident.to_string());
+
     if !input.generics.params.is_empty() || input.generics.where_clause.is_some() {
         bail!("Message may not be derived for generic type");
     }
@@ -347,15 +524,26 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
         Data::Union(..) => bail!("Enumeration can not be derived for a union"),
     };
 
-    // Map the variants into 'fields'.
-    let mut variants: Vec<(Ident, Expr)> = Vec::new();
+    // Map the variants into 'fields', with the protobuf names of the values.
+    let mut variants: Vec<(Ident, Expr, String)> = Vec::new();
     for Variant {
+        attrs,
         ident,
         fields,
         discriminant,
-        ..
     } in punctuated_variants
     {
+        // The protobuf name defaults to the `SHOUTY_SNAKE_CASE` form of the variant name.
+        let mut proto_name = None;
+        for attr in field::prost_attrs(attrs)? {
+            if let Some(name) = field::str_attr("proto_name", &attr)? {
+                field::set_option(&mut proto_name, name, "duplicate proto_name attributes")?;
+            } else {
+                bail!("unknown enumeration variant attribute: {:?}", attr);
+            }
+        }
+        let proto_name = proto_name.unwrap_or_else(|| ident.to_string().to_shouty_snake_case());
+
         match fields {
             Fields::Unit => (),
             Fields::Named(_) | Fields::Unnamed(_) => {
@@ -364,7 +552,7 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
         }
 
         match discriminant {
-            Some((_, expr)) => variants.push((ident, expr)),
+            Some((_, expr)) => variants.push((ident, expr, proto_name)),
             None => bail!("Enumeration variants must have a disriminant"),
         }
     }
@@ -379,10 +567,20 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
     let module = Ident::new(&format!("{}_ENUMERATION", ident), Span::call_site());
     let is_valid = variants
         .iter()
-        .map(|&(_, ref value)| quote!(#value => true));
-    let from = variants.iter().map(
-        |&(ref variant, ref value)| quote!(#value => ::std::option::Option::Some(#ident::#variant)),
-    );
+        .map(|&(_, ref value, _)| quote!(#value => true));
+    let from = variants.iter().map(|&(ref variant, ref value, _)| {
+        quote!(#value => ::std::option::Option::Some(#ident::#variant))
+    });
+
+    let values = variants.iter().map(|&(ref variant, _, ref proto_name)| {
+        quote! {
+            _prost::reflect::EnumValueDescriptor {
+                name: stringify!(#variant),
+                proto_name: #proto_name,
+                number: #ident::#variant as i32,
+            }
+        }
+    });
 
     let is_valid_doc = format!("Returns `true` if `value` is a variant of `{}`.", ident);
     let from_i32_doc = format!(
@@ -393,9 +591,20 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
     let expanded = quote! {
         #[allow(non_snake_case, unused_attributes)]
         mod #module {
+            extern crate prost_amino as _prost;
             use super::*;
 
+            static DESCRIPTOR: _prost::reflect::EnumDescriptor = _prost::reflect::EnumDescriptor {
+                name: stringify!(#ident),
+                full_name: #full_name,
+                values: &[#(#values),*],
+            };
+
             impl #ident {
+                /// Returns the reflection descriptor of the enumeration.
+                pub fn descriptor() -> &'static _prost::reflect::EnumDescriptor {
+                    &DESCRIPTOR
+                }
 
                 #[doc=#is_valid_doc]
                 pub fn is_valid(value: i32) -> bool {
@@ -431,7 +640,7 @@ fn try_enumeration(input: TokenStream) -> Result<TokenStream, Error> {
     Ok(expanded.into())
 }
 
-#[proc_macro_derive(Enumeration, attributes(prost_amino))]
+#[proc_macro_derive(Enumeration, attributes(prost, prost_amino))]
 pub fn enumeration(input: TokenStream) -> TokenStream {
     try_enumeration(input).unwrap()
 }
@@ -453,6 +662,7 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
 
     // Map the variants into 'fields'.
     let mut fields: Vec<(Ident, Field)> = Vec::new();
+    let mut field_descriptors = Vec::new();
     for Variant {
         attrs,
         ident: variant_ident,
@@ -470,8 +680,18 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
         if variant_fields.len() != 1 {
             bail!("Oneof enum variants must have a single field");
         }
-        match Field::new_oneof(attrs)? {
-            Some(field) => fields.push((variant_ident, field)),
+        let proto_name = field::proto_name(&attrs, variant_ident.to_string().to_snake_case())?;
+        let json_name = field::json_name(&attrs, &variant_ident.to_string())?;
+        match Field::new_oneof(attrs, &variant_fields[0].ty)? {
+            Some(field) => {
+                field_descriptors.push(field.descriptor(
+                    &variant_ident.to_string(),
+                    &proto_name,
+                    &json_name,
+                    &variant_fields[0].ty,
+                ));
+                fields.push((variant_ident, field));
+            }
             None => bail!("invalid oneof variant: oneof variants may not be ignored"),
         }
     }
@@ -498,6 +718,8 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
     // Put impls in a special module, so that 'extern crate' can be used.
     let module = Ident::new(&format!("{}_ONEOF", ident), Span::call_site());
 
+    let has_unsafe_float = fields.iter().any(|(_, field)| field.is_unsafe_float());
+
     let encode = fields.iter().map(|&(ref variant_ident, ref field)| {
         let encode = field.encode(quote!(*value));
         quote!(#ident::#variant_ident(ref value) => { #encode })
@@ -535,19 +757,36 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
             extern crate prost_amino as _prost;
             use super::*;
 
+            static DESCRIPTOR: _prost::reflect::OneofDescriptor = _prost::reflect::OneofDescriptor {
+                name: stringify!(#ident),
+                fields: &[#(#field_descriptors),*],
+            };
+
             impl #ident {
+                /// Returns the reflection descriptor of the oneof.
+                pub fn descriptor() -> &'static _prost::reflect::OneofDescriptor {
+                    &DESCRIPTOR
+                }
+
+                /// Whether a variant has `float` or `double` values without the `unsafe`
+                /// attribute, which is checked by the amino messages containing the oneof.
+                #[doc(hidden)]
+                pub const HAS_UNSAFE_FLOAT: bool = #has_unsafe_float;
+
                 pub fn encode<B>(&self, buf: &mut B) where B: _prost::bytes::BufMut {
                     match *self {
                         #(#encode,)*
                     }
                 }
 
+                #[allow(unused_variables)]
                 pub fn merge<B>(field: &mut ::std::option::Option<#ident>,
                                 tag: u32,
                                 wire_type: _prost::encoding::WireType,
-                                buf: &mut B)
+                                buf: &mut B,
+                                depth: u32)
                                 -> ::std::result::Result<(), _prost::DecodeError>
-                where B: _prost::bytes::BufMut {
+                where B: _prost::bytes::Buf {
                     match tag {
                         #(#merge,)*
                         _ => unreachable!(concat!("invalid ", stringify!(#ident), " tag: {}"), tag),
@@ -575,7 +814,7 @@ fn try_oneof(input: TokenStream) -> Result<TokenStream, Error> {
     Ok(expanded.into())
 }
 
-#[proc_macro_derive(Oneof, attributes(prost_amino))]
+#[proc_macro_derive(Oneof, attributes(prost, prost_amino))]
 pub fn oneof(input: TokenStream) -> TokenStream {
     try_oneof(input).unwrap()
 }
@@ -7,7 +7,8 @@
 //!     2. The bundled `protoc`.
 //!     3. The `protoc` on the `PATH`.
 //!
-//! If no `protoc` binary is available in these locations, the build fails.
+//! If no `protoc` binary is available in these locations, `protoc` is assumed to be on the `PATH`
+//! when compiling `.proto` files, which fails unless the built-in parser is used instead.
 //!
 //! The following locations are checked for the Protobuf include directory in decreasing priority:
 //!
@@ -96,10 +97,7 @@ fn main() {
     let protoc = env_protoc()
         .or_else(bundled_protoc)
         .or_else(path_protoc)
-        .expect(
-            "Failed to find the protoc binary. The PROTOC environment variable is not set, \
-        there is no bundled protoc for this platform, and protoc is not in the PATH",
-        );
+        .unwrap_or_else(|| PathBuf::from("protoc"));
 
     let protoc_include = env_protoc_include().unwrap_or_else(bundled_protoc_include);
 
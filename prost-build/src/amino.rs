@@ -0,0 +1,10 @@
+//! Support for the amino registration options defined in `amino/amino.proto`.
+
+/// The contents of `amino/amino.proto`.
+pub const AMINO_PROTO: &str = include_str!("../proto/amino/amino.proto");
+
+/// The field number of the `(amino.name)` message option.
+pub const NAME_OPTION: u32 = 50101;
+
+/// The field number of the `(amino.field_name)` field option.
+pub const FIELD_NAME_OPTION: u32 = 50102;
@@ -4,6 +4,7 @@ use std::collections::{HashMap, HashSet};
 
 use itertools::{Either, Itertools};
 use multimap::MultiMap;
+use prost_types::descriptor_proto::ExtensionRange;
 use prost_types::field_descriptor_proto::{Label, Type};
 use prost_types::source_code_info::Location;
 use prost_types::{
@@ -11,8 +12,11 @@ use prost_types::{
     FileDescriptorProto, OneofDescriptorProto, ServiceDescriptorProto, SourceCodeInfo,
 };
 
+use amino;
 use ast::{Comments, Method, Service};
-use ident::{match_ident, to_snake, to_upper_camel};
+use custom_options::CustomOptions;
+use gogo;
+use ident::{match_ident, to_json_name, to_snake, to_upper_camel, to_upper_snake};
 use message_graph::MessageGraph;
 use Config;
 use Module;
@@ -33,7 +37,10 @@ enum Syntax {
 
 pub struct CodeGenerator<'a> {
     config: &'a mut Config,
+    custom_options: &'a CustomOptions,
     package: String,
+    file_package_len: usize,
+    registered: Vec<String>,
     source_info: SourceCodeInfo,
     syntax: Syntax,
     message_graph: &'a MessageGraph,
@@ -43,12 +50,15 @@ pub struct CodeGenerator<'a> {
 }
 
 impl<'a> CodeGenerator<'a> {
+    /// Generates the code for a file, and returns the paths of the amino registered message types
+    /// relative to the file's module.
     pub fn generate(
         config: &mut Config,
         message_graph: &MessageGraph,
+        custom_options: &CustomOptions,
         file: FileDescriptorProto,
         buf: &mut String,
-    ) {
+    ) -> Vec<String> {
         let mut source_info = file
             .source_code_info
             .expect("no source code info in request");
@@ -66,9 +76,13 @@ impl<'a> CodeGenerator<'a> {
             Some(s) => panic!("unknown syntax: {}", s),
         };
 
+        let package = file.package.unwrap();
         let mut code_gen = CodeGenerator {
             config: config,
-            package: file.package.unwrap(),
+            custom_options: custom_options,
+            file_package_len: package.len(),
+            package: package,
+            registered: Vec::new(),
             source_info: source_info,
             syntax: syntax,
             message_graph: message_graph,
@@ -99,6 +113,14 @@ impl<'a> CodeGenerator<'a> {
         }
         code_gen.path.pop();
 
+        code_gen.path.push(7);
+        for (idx, extension) in file.extension.into_iter().enumerate() {
+            code_gen.path.push(idx as i32);
+            code_gen.append_extension(extension);
+            code_gen.path.pop();
+        }
+        code_gen.path.pop();
+
         if code_gen.config.service_generator.is_some() {
             code_gen.path.push(6);
             for (idx, service) in file.service.into_iter().enumerate() {
@@ -118,6 +140,8 @@ impl<'a> CodeGenerator<'a> {
 
             code_gen.path.pop();
         }
+
+        code_gen.registered
     }
 
     fn append_message(&mut self, message: DescriptorProto) {
@@ -126,8 +150,10 @@ impl<'a> CodeGenerator<'a> {
         let message_name = message.name().to_string();
         let fq_message_name = format!(".{}.{}", self.package, message.name());
 
-        // Skip Protobuf well-known types.
-        if self.well_known_type(&fq_message_name).is_some() {
+        // Skip Protobuf well-known types and extern types.
+        if self.well_known_type(&fq_message_name).is_some()
+            || self.extern_path(&fq_message_name).is_some()
+        {
             return;
         }
 
@@ -160,26 +186,61 @@ impl<'a> CodeGenerator<'a> {
             });
 
         // Split the fields into a vector of the normal fields, and oneof fields.
-        // Path indexes are preserved so that comments can be retrieved.
+        // Path indexes are preserved so that comments can be retrieved. Proto3 optional fields
+        // are normal fields; their synthetic oneofs are not generated.
         type Fields = Vec<(FieldDescriptorProto, usize)>;
         type OneofFields = MultiMap<i32, (FieldDescriptorProto, usize)>;
         let (fields, mut oneof_fields): (Fields, OneofFields) = message
             .field
             .into_iter()
             .enumerate()
-            .partition_map(|(idx, field)| {
-                if let Some(oneof_index) = field.oneof_index {
+            .partition_map(|(idx, field)| match field.oneof_index {
+                Some(oneof_index) if !field.proto3_optional() => {
                     Either::Right((oneof_index, (field, idx)))
-                } else {
-                    Either::Left((field, idx))
                 }
+                _ => Either::Left((field, idx)),
             });
 
-        assert_eq!(oneof_fields.len(), message.oneof_decl.len());
+        let synthetic_oneofs = fields
+            .iter()
+            .filter(|&&(ref field, _)| field.proto3_optional())
+            .count();
+        assert_eq!(
+            oneof_fields.len() + synthetic_oneofs,
+            message.oneof_decl.len()
+        );
 
         self.append_doc();
         self.push_indent();
         self.buf.push_str("#[derive(Clone, PartialEq, Message)]\n");
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[prost(full_name=\"{}\")]\n",
+            fq_message_name.trim_start_matches('.')
+        ));
+        if let Some(amino_name) = self.amino_name(&fq_message_name, None) {
+            self.push_indent();
+            self.buf.push_str(&format!(
+                "#[amino_name = \"{}\"]\n",
+                amino_name.escape_default()
+            ));
+            let path = self.package[self.file_package_len..]
+                .split('.')
+                .filter(|s| !s.is_empty())
+                .map(to_snake)
+                .chain(Some(to_upper_camel(&message_name)))
+                .join("::");
+            self.registered.push(path);
+        }
+        if self
+            .config
+            .builders
+            .iter()
+            .any(|matcher| match_ident(matcher, &fq_message_name, None))
+        {
+            self.push_indent();
+            self.buf.push_str("#[prost(builder)]\n");
+        }
         self.append_type_attributes(&fq_message_name);
         self.push_indent();
         self.buf.push_str("pub struct ");
@@ -205,7 +266,9 @@ impl<'a> CodeGenerator<'a> {
         self.path.pop();
 
         self.path.push(8);
-        for (idx, oneof) in message.oneof_decl.iter().enumerate() {
+        // The synthetic oneofs follow the declared oneofs.
+        let oneof_decls = message.oneof_decl.len() - synthetic_oneofs;
+        for (idx, oneof) in message.oneof_decl.iter().take(oneof_decls).enumerate() {
             let idx = idx as i32;
             self.path.push(idx);
             self.append_oneof_field(
@@ -218,11 +281,19 @@ impl<'a> CodeGenerator<'a> {
         }
         self.path.pop();
 
+        if !message.extension_range.is_empty() {
+            self.append_extension_set(&message.extension_range);
+        }
+
         self.depth -= 1;
         self.push_indent();
         self.buf.push_str("}\n");
 
-        if !message.enum_type.is_empty() || !nested_types.is_empty() || !oneof_fields.is_empty() {
+        if !message.enum_type.is_empty()
+            || !nested_types.is_empty()
+            || !oneof_fields.is_empty()
+            || !message.extension.is_empty()
+        {
             self.push_mod(&message_name);
             self.path.push(3);
             for (nested_type, idx) in nested_types {
@@ -240,7 +311,7 @@ impl<'a> CodeGenerator<'a> {
             }
             self.path.pop();
 
-            for (idx, oneof) in message.oneof_decl.into_iter().enumerate() {
+            for (idx, oneof) in message.oneof_decl.into_iter().take(oneof_decls).enumerate() {
                 let idx = idx as i32;
                 self.append_oneof(
                     &fq_message_name,
@@ -250,6 +321,14 @@ impl<'a> CodeGenerator<'a> {
                 );
             }
 
+            self.path.push(6);
+            for (idx, extension) in message.extension.into_iter().enumerate() {
+                self.path.push(idx as i32);
+                self.append_extension(extension);
+                self.path.pop();
+            }
+            self.path.pop();
+
             self.pop_mod();
         }
     }
@@ -278,21 +357,120 @@ impl<'a> CodeGenerator<'a> {
         }
     }
 
-    fn append_field(&mut self, msg_name: &str, field: FieldDescriptorProto) {
-        // TODO(danburkert/prost#19): support groups.
-        let type_ = field.type_();
-        if type_ == Type::Group {
-            return;
+    /// Returns the amino name of a message type, or of a field if `field_name` is set.
+    fn amino_name(&self, msg_name: &str, field_name: Option<&str>) -> Option<String> {
+        let fq_name = match field_name {
+            Some(field_name) => format!("{}.{}", msg_name, field_name),
+            None => msg_name.to_string(),
+        };
+        self.config
+            .amino_names
+            .iter()
+            .find(|&&(ref matcher, _)| {
+                if matcher.starts_with('.') {
+                    *matcher == fq_name
+                } else {
+                    fq_name.ends_with(&format!(".{}", matcher))
+                }
+            })
+            .map(|&(_, ref amino_name)| amino_name.clone())
+            .or_else(|| match field_name {
+                Some(..) => self
+                    .custom_options
+                    .field_string(&fq_name, amino::FIELD_NAME_OPTION),
+                None => self
+                    .custom_options
+                    .message_string(&fq_name, amino::NAME_OPTION),
+            })
+    }
+
+    /// Returns the Rust name of a field, which is set by the `(gogoproto.customname)` option, or
+    /// by the `(gogoproto.embed)` option of a message field.
+    fn field_name(&self, msg_name: &str, field: &FieldDescriptorProto) -> String {
+        let fq_name = format!("{}.{}", msg_name, field.name());
+        if let Some(customname) = self
+            .custom_options
+            .field_string(&fq_name, gogo::CUSTOMNAME_OPTION)
+        {
+            return to_snake(&customname);
+        }
+        let embed = self.custom_options.field_bool(&fq_name, gogo::EMBED_OPTION);
+        if field.type_() == Type::Message && embed == Some(true) {
+            if let Some(type_name) = field.type_name().rsplit('.').next() {
+                return to_snake(type_name);
+            }
+        }
+        to_snake(field.name())
+    }
+
+    /// Appends the Rust attributes set with the `(gogoproto.moretags)` option of a field. Go struct
+    /// tags, which are also set with the option, are ignored.
+    fn append_more_tags(&mut self, msg_name: &str, field_name: &str) {
+        let fq_name = format!("{}.{}", msg_name, field_name);
+        if let Some(more_tags) = self
+            .custom_options
+            .field_string(&fq_name, gogo::MORETAGS_OPTION)
+        {
+            if more_tags.trim_start().starts_with("#[") {
+                self.push_indent();
+                self.buf.push_str(more_tags.trim());
+                self.buf.push('\n');
+            }
+        }
+    }
+
+    /// Returns the Rust type of a field set by the `(gogoproto.stdtime)`,
+    /// `(gogoproto.stdduration)`, `(gogoproto.casttype)` or `(gogoproto.customtype)` options. The
+    /// field is encoded as a proxy of its Protobuf type.
+    fn custom_type(&self, fq_name: &str, field: &FieldDescriptorProto) -> Option<String> {
+        let options = self.custom_options;
+        if field.type_() == Type::Message {
+            let stdtime = options.field_bool(fq_name, gogo::STDTIME_OPTION) == Some(true);
+            if stdtime && field.type_name() == ".google.protobuf.Timestamp" {
+                return Some("::std::time::SystemTime".to_string());
+            }
+            let stdduration = options.field_bool(fq_name, gogo::STDDURATION_OPTION) == Some(true);
+            if stdduration && field.type_name() == ".google.protobuf.Duration" {
+                return Some("::std::time::Duration".to_string());
+            }
+        }
+
+        let go_type = options
+            .field_string(fq_name, gogo::CASTTYPE_OPTION)
+            .or_else(|| options.field_string(fq_name, gogo::CUSTOMTYPE_OPTION))?;
+        let rust_type = self.config.custom_types.get(&go_type).cloned();
+        if rust_type.is_none() {
+            debug!("    no Rust type for Go type {} of {}", go_type, fq_name);
         }
+        rust_type
+    }
 
+    fn append_field(&mut self, msg_name: &str, field: FieldDescriptorProto) {
+        let type_ = field.type_();
         let repeated = field.label == Some(Label::Repeated as i32);
-        let optional = self.optional(&field);
         let ty = self.resolve_type(&field);
 
         let boxed = !repeated
-            && type_ == Type::Message
+            && (type_ == Type::Message || type_ == Type::Group)
             && self.message_graph.is_nested(field.type_name(), msg_name);
 
+        // A message field with `(gogoproto.nullable) = false` is a required field of the message
+        // type instead of an `Option`, unless it must be boxed to break a recursion.
+        let fq_name = format!("{}.{}", msg_name, field.name());
+        let required = type_ == Type::Message
+            && !boxed
+            && self.optional(&field)
+            && self
+                .custom_options
+                .field_bool(&fq_name, gogo::NULLABLE_OPTION)
+                == Some(false);
+        let optional = self.optional(&field) && !required;
+        let custom_type = if boxed {
+            None
+        } else {
+            self.custom_type(&fq_name, &field)
+        };
+
         debug!(
             "    field: {:?}, type: {:?}, boxed: {}",
             field.name(),
@@ -300,6 +478,8 @@ impl<'a> CodeGenerator<'a> {
             boxed
         );
 
+        let field_name = self.field_name(msg_name, &field);
+
         self.append_doc();
         self.push_indent();
         self.buf.push_str("#[prost(");
@@ -310,6 +490,8 @@ impl<'a> CodeGenerator<'a> {
             Label::Optional => {
                 if optional {
                     self.buf.push_str(", optional");
+                } else if required {
+                    self.buf.push_str(", required");
                 }
             }
             Label::Required => self.buf.push_str(", required"),
@@ -352,11 +534,25 @@ impl<'a> CodeGenerator<'a> {
             }
         }
 
-        self.buf.push_str("\")]\n");
+        self.buf.push('"');
+        if let Some(amino_name) = self.amino_name(msg_name, Some(field.name())) {
+            self.buf
+                .push_str(&format!(", amino_name=\"{}\"", amino_name.escape_default()));
+        }
+        if custom_type.is_some() {
+            self.buf.push_str(&format!(", proxy=\"{}\"", ty));
+        }
+        self.buf.push_str(&proto_name_attr(
+            field.name(),
+            field_name.trim_end_matches('_'),
+        ));
+        self.buf.push_str(&json_name_attr(&field, &field_name));
+        self.buf.push_str(")]\n");
         self.append_field_attributes(msg_name, field.name());
+        self.append_more_tags(msg_name, field.name());
         self.push_indent();
         self.buf.push_str("pub ");
-        self.buf.push_str(&to_snake(field.name()));
+        self.buf.push_str(&field_name);
         self.buf.push_str(": ");
         if repeated {
             self.buf.push_str("::std::vec::Vec<");
@@ -366,7 +562,8 @@ impl<'a> CodeGenerator<'a> {
         if boxed {
             self.buf.push_str("::std::boxed::Box<");
         }
-        self.buf.push_str(&ty);
+        self.buf
+            .push_str(custom_type.as_ref().map_or(&ty[..], String::as_str));
         if boxed {
             self.buf.push_str(">");
         }
@@ -409,21 +606,22 @@ impl<'a> CodeGenerator<'a> {
 
         let key_tag = self.field_type_tag(key);
         let value_tag = self.map_value_type_tag(value);
+        let field_name = self.field_name(msg_name, &field);
         self.buf.push_str(&format!(
-            "#[prost({}=\"{}, {}\", tag=\"{}\")]\n",
+            "#[prost({}=\"{}, {}\", tag=\"{}\"{}{})]\n",
             annotation_ty,
             key_tag,
             value_tag,
-            field.number()
+            field.number(),
+            proto_name_attr(field.name(), field_name.trim_end_matches('_')),
+            json_name_attr(&field, &field_name)
         ));
         self.append_field_attributes(msg_name, field.name());
+        self.append_more_tags(msg_name, field.name());
         self.push_indent();
         self.buf.push_str(&format!(
             "pub {}: ::std::collections::{}<{}, {}>,\n",
-            to_snake(field.name()),
-            rust_ty,
-            key_ty,
-            value_ty
+            field_name, rust_ty, key_ty, value_ty
         ));
     }
 
@@ -439,22 +637,23 @@ impl<'a> CodeGenerator<'a> {
             to_snake(message_name),
             to_upper_camel(oneof.name())
         );
+        let field_name = to_snake(oneof.name());
         self.append_doc();
         self.push_indent();
         self.buf.push_str(&format!(
-            "#[prost(oneof=\"{}\", tags=\"{}\")]\n",
+            "#[prost(oneof=\"{}\", tags=\"{}\"{})]\n",
             name,
             fields
                 .iter()
                 .map(|&(ref field, _)| field.number())
-                .join(", ")
+                .join(", "),
+            proto_name_attr(oneof.name(), field_name.trim_end_matches('_'))
         ));
         self.append_field_attributes(fq_message_name, oneof.name());
         self.push_indent();
         self.buf.push_str(&format!(
             "pub {}: ::std::option::Option<{}>,\n",
-            to_snake(oneof.name()),
-            name
+            field_name, name
         ));
     }
 
@@ -483,30 +682,28 @@ impl<'a> CodeGenerator<'a> {
         self.path.push(2);
         self.depth += 1;
         for (field, idx) in fields {
-            // TODO(danburkert/prost#19): support groups.
             let type_ = field.type_();
-            if type_ == Type::Group {
-                continue;
-            }
-
             self.path.push(idx as i32);
             self.append_doc();
             self.path.pop();
 
             self.push_indent();
             let ty_tag = self.field_type_tag(&field);
+            let variant_name = to_upper_camel(field.name());
             self.buf.push_str(&format!(
-                "#[prost({}, tag=\"{}\")]\n",
+                "#[prost({}, tag=\"{}\"{}{})]\n",
                 ty_tag,
-                field.number()
+                field.number(),
+                proto_name_attr(field.name(), to_snake(&variant_name).trim_end_matches('_')),
+                json_name_attr(&field, &variant_name)
             ));
             self.append_field_attributes(&oneof_name, field.name());
 
             self.push_indent();
             let ty = self.resolve_type(&field);
 
-            let boxed =
-                type_ == Type::Message && self.message_graph.is_nested(field.type_name(), msg_name);
+            let boxed = (type_ == Type::Message || type_ == Type::Group)
+                && self.message_graph.is_nested(field.type_name(), msg_name);
 
             debug!(
                 "    oneof: {:?}, type: {:?}, boxed: {}",
@@ -530,6 +727,95 @@ impl<'a> CodeGenerator<'a> {
         self.buf.push_str("}\n");
     }
 
+    /// Appends the extension set field of an extendable message.
+    fn append_extension_set(&mut self, ranges: &[ExtensionRange]) {
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[prost(extensions=\"{}\")]\n",
+            ranges
+                .iter()
+                .map(|range| format!("{}..{}", range.start(), range.end()))
+                .join(", ")
+        ));
+        self.push_indent();
+        self.buf
+            .push_str("pub extension_set: ::prost_amino::extension::ExtensionSet,\n");
+    }
+
+    /// Appends the descriptor constant of an extension declared in an `extend` block.
+    fn append_extension(&mut self, field: FieldDescriptorProto) {
+        let type_ = field.type_();
+        let extendee = match self.well_known_type(field.extendee()) {
+            Some(ty) => ty.to_string(),
+            None => self.resolve_ident(field.extendee()),
+        };
+        let name = if self.package.is_empty() {
+            field.name().to_string()
+        } else {
+            format!("{}.{}", self.package, field.name())
+        };
+        debug!("    extension: {:?}, extendee: {:?}", name, extendee);
+
+        let repeated = field.label == Some(Label::Repeated as i32);
+        let ty = self.resolve_type(&field);
+        let ty = if repeated {
+            format!("::std::vec::Vec<{}>", ty)
+        } else {
+            ty.into_owned()
+        };
+        let module = match type_ {
+            Type::Enum => Cow::Borrowed("int32"),
+            Type::Message => Cow::Borrowed("message"),
+            _ => self.field_type_tag(&field),
+        };
+        // Groups are merged up to the end group key with the extension's tag.
+        let merge_tag = if type_ == Type::Group {
+            format!("{}, ", field.number())
+        } else {
+            String::new()
+        };
+        let (encode, merge) = if !repeated {
+            ("encode", "merge")
+        } else if can_pack(&field) && field.options.as_ref().map_or(false, |o| o.packed()) {
+            ("encode_packed", "merge_repeated")
+        } else {
+            ("encode_repeated", "merge_repeated")
+        };
+
+        self.append_doc();
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "pub const {}: ::prost_amino::extension::Extension<{}, {}> =\n",
+            to_upper_snake(field.name()),
+            extendee,
+            ty
+        ));
+        self.depth += 1;
+        self.push_indent();
+        self.buf.push_str("::prost_amino::extension::Extension {\n");
+        self.depth += 1;
+        self.push_indent();
+        self.buf.push_str(&format!("name: \"{}\",\n", name));
+        self.push_indent();
+        self.buf.push_str(&format!("number: {},\n", field.number()));
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "encode: |tag, value, buf| ::prost_amino::encoding::{}::{}(tag, value, buf),\n",
+            module, encode
+        ));
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "merge: |wire_type, value, buf| ::prost_amino::encoding::{}::{}({}wire_type, value, buf),\n",
+            module, merge, merge_tag
+        ));
+        self.push_indent();
+        self.buf.push_str("extendee: ::std::marker::PhantomData,\n");
+        self.depth -= 1;
+        self.push_indent();
+        self.buf.push_str("};\n");
+        self.depth -= 1;
+    }
+
     fn location(&self) -> &Location {
         let idx = self
             .source_info
@@ -547,11 +833,13 @@ impl<'a> CodeGenerator<'a> {
     fn append_enum(&mut self, desc: EnumDescriptorProto) {
         debug!("  enum: {:?}", desc.name());
 
-        // Skip Protobuf well-known types.
+        // Skip Protobuf well-known types and extern types.
         let enum_name = &desc.name();
         let enum_values = &desc.value;
         let fq_enum_name = format!(".{}.{}", self.package, enum_name);
-        if self.well_known_type(&fq_enum_name).is_some() {
+        if self.well_known_type(&fq_enum_name).is_some()
+            || self.extern_path(&fq_enum_name).is_some()
+        {
             return;
         }
 
@@ -560,6 +848,11 @@ impl<'a> CodeGenerator<'a> {
         self.buf.push_str(
             "#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]\n",
         );
+        self.push_indent();
+        self.buf.push_str(&format!(
+            "#[prost(full_name=\"{}\")]\n",
+            fq_enum_name.trim_start_matches('.')
+        ));
         self.append_type_attributes(&fq_enum_name);
         self.push_indent();
         self.buf.push_str("pub enum ");
@@ -601,7 +894,6 @@ impl<'a> CodeGenerator<'a> {
     ) {
         self.append_doc();
         self.append_field_attributes(fq_enum_name, &value.name());
-        self.push_indent();
         let name = to_upper_camel(value.name());
         let name_unprefixed = match prefix_to_strip {
             Some(prefix) => {
@@ -615,6 +907,13 @@ impl<'a> CodeGenerator<'a> {
             }
             None => name,
         };
+        // The derive infers the protobuf name of the value from the variant name.
+        if to_upper_snake(&name_unprefixed) != value.name() {
+            self.push_indent();
+            self.buf
+                .push_str(&format!("#[prost(proto_name=\"{}\")]\n", value.name()));
+        }
+        self.push_indent();
         self.buf.push_str(&name_unprefixed);
         self.buf.push_str(" = ");
         self.buf.push_str(&value.number().to_string());
@@ -731,6 +1030,10 @@ impl<'a> CodeGenerator<'a> {
         // protoc should always give fully qualified identifiers.
         assert_eq!(".", &pb_ident[..1]);
 
+        if let Some(path) = self.extern_path(pb_ident) {
+            return path;
+        }
+
         let mut local_path = self.package.split('.').peekable();
 
         let mut ident_path = pb_ident[1..].split('.');
@@ -787,6 +1090,9 @@ impl<'a> CodeGenerator<'a> {
     }
 
     fn optional(&self, field: &FieldDescriptorProto) -> bool {
+        if field.proto3_optional() {
+            return true;
+        }
         if field.label() != Label::Optional {
             return false;
         }
@@ -797,6 +1103,36 @@ impl<'a> CodeGenerator<'a> {
         }
     }
 
+    /// Returns the Rust path of a type declared with `Config::extern_path`, or `None` if the
+    /// type is not an extern type.
+    fn extern_path(&self, pb_ident: &str) -> Option<String> {
+        let (proto_path, rust_path) = self
+            .config
+            .extern_paths
+            .iter()
+            .filter(|&&(ref proto_path, _)| match_ident(proto_path, pb_ident, None))
+            .max_by_key(|&&(ref proto_path, _)| proto_path.len())?;
+
+        if !proto_path.starts_with('.') {
+            return Some(rust_path.clone());
+        }
+
+        // Append the unmatched part of the type name to the Rust path.
+        let matched = proto_path.split('.').filter(|s| !s.is_empty()).count();
+        let ident_path: Vec<_> = pb_ident[1..].split('.').skip(matched).collect();
+        let (ident_type, ident_path) = match ident_path.split_last() {
+            Some(split) => split,
+            None => return Some(rust_path.clone()),
+        };
+        Some(
+            Some(rust_path.clone())
+                .into_iter()
+                .chain(ident_path.iter().map(|s| to_snake(s)))
+                .chain(Some(to_upper_camel(ident_type)).into_iter())
+                .join("::"),
+        )
+    }
+
     /// Returns the prost_types name for a well-known Protobuf type, or `None` if the provided
     /// message type is not a well-known type, or prost_types has been disabled.
     fn well_known_type(&self, fq_msg_type: &str) -> Option<&'static str> {
@@ -860,6 +1196,60 @@ impl<'a> CodeGenerator<'a> {
     }
 }
 
+/// Appends a `register_all` function returning the descriptors of the registered message types of
+/// a module.
+pub fn append_register_all(buf: &mut String, types: &[String]) {
+    buf.push_str(
+        "/// Returns the reflection descriptors of the amino registered message types in this \
+         package.\n",
+    );
+    buf.push_str(
+        "pub fn register_all() -> \
+         ::std::vec::Vec<&'static ::prost_amino::reflect::MessageDescriptor> {\n",
+    );
+    buf.push_str("    vec![\n");
+    for ty in types {
+        buf.push_str(&format!(
+            "        <{} as ::prost_amino::reflect::Reflect>::descriptor(),\n",
+            ty
+        ));
+    }
+    buf.push_str("    ]\n");
+    buf.push_str("}\n");
+}
+
+/// Returns the `json_name` attribute of a field or oneof variant, or an empty string if the derive
+/// infers the same JSON name from the Rust name.
+fn json_name_attr(field: &FieldDescriptorProto, rust_name: &str) -> String {
+    let json_name = field
+        .json_name
+        .clone()
+        .unwrap_or_else(|| to_json_name(field.name()));
+    // The derive strips the trailing underscore of escaped keywords, and lowercases the first
+    // character of variant names.
+    let inferred = to_json_name(rust_name.trim_end_matches('_'));
+    let mut chars = inferred.chars();
+    let inferred = match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect::<String>(),
+        None => inferred.clone(),
+    };
+    if json_name == inferred {
+        String::new()
+    } else {
+        format!(", json_name=\"{}\"", json_name)
+    }
+}
+
+/// Returns the `proto_name` attribute of a field, oneof or oneof variant, or an empty string if the
+/// derive infers the same protobuf name from the Rust name.
+fn proto_name_attr(proto_name: &str, inferred: &str) -> String {
+    if proto_name == inferred {
+        String::new()
+    } else {
+        format!(", proto_name=\"{}\"", proto_name)
+    }
+}
+
 /// Returns `true` if the repeated field type can be packed.
 fn can_pack(field: &FieldDescriptorProto) -> bool {
     match field.type_() {
@@ -0,0 +1,270 @@
+//! Custom options of messages and fields, such as the amino and gogoproto options.
+//!
+//! Custom options are extensions of the `google.protobuf` option messages. `prost_types` retains
+//! them in the extension sets of the options messages, but the built-in parser does not set them in
+//! the descriptors it produces. The options are instead read directly from the encoded
+//! descriptors, or collected by the built-in parser.
+
+use std::collections::{BTreeMap, HashMap};
+
+use prost::encoding::{decode_key, decode_varint, skip_field, WireType};
+use prost::DecodeError;
+
+/// The value of a custom option.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Varint(u64),
+    LengthDelimited(Vec<u8>),
+}
+
+/// The custom options set on messages and fields, keyed by fully qualified message name (e.g.
+/// `.foo.Bar`) and fully qualified field name (e.g. `.foo.Bar.baz`), and by field number.
+#[derive(Debug, Default, PartialEq)]
+pub struct CustomOptions {
+    messages: HashMap<String, BTreeMap<u32, Value>>,
+    fields: HashMap<String, BTreeMap<u32, Value>>,
+}
+
+impl CustomOptions {
+    /// Reads the custom options from an encoded `FileDescriptorSet`.
+    pub fn from_descriptor_set(descriptor_set: &[u8]) -> Result<CustomOptions, DecodeError> {
+        // FileDescriptorSet.file
+        CustomOptions::decode(descriptor_set, 1)
+    }
+
+    /// Reads the custom options from an encoded `CodeGeneratorRequest`.
+    pub fn from_request(request: &[u8]) -> Result<CustomOptions, DecodeError> {
+        // CodeGeneratorRequest.proto_file
+        CustomOptions::decode(request, 15)
+    }
+
+    /// Reads the custom options of the `FileDescriptorProto`s in field `files_tag` of a message.
+    fn decode(buf: &[u8], files_tag: u32) -> Result<CustomOptions, DecodeError> {
+        let mut options = CustomOptions::default();
+        for (tag, file) in length_delimited_fields(buf)? {
+            if tag != files_tag {
+                continue;
+            }
+            let fields = length_delimited_fields(file)?;
+            let package = last_string(&fields, 2)?.unwrap_or_default();
+            let prefix = if package.is_empty() {
+                String::new()
+            } else {
+                format!(".{}", package)
+            };
+            for message in fields.iter().filter(|&&(tag, _)| tag == 4) {
+                options.decode_message(&prefix, message.1)?;
+            }
+        }
+        Ok(options)
+    }
+
+    /// Reads the custom options of a `DescriptorProto`, its fields and its nested messages.
+    fn decode_message(&mut self, prefix: &str, message: &[u8]) -> Result<(), DecodeError> {
+        let fields = length_delimited_fields(message)?;
+        let name = format!(
+            "{}.{}",
+            prefix,
+            last_string(&fields, 1)?.unwrap_or_default()
+        );
+
+        for &(tag, value) in &fields {
+            match tag {
+                // DescriptorProto.field
+                2 => {
+                    let field = length_delimited_fields(value)?;
+                    let field_name =
+                        format!("{}.{}", name, last_string(&field, 1)?.unwrap_or_default());
+                    // FieldDescriptorProto.options
+                    for &(_, options) in field.iter().filter(|&&(tag, _)| tag == 8) {
+                        for (number, value) in option_values(options)? {
+                            self.insert_field_option(field_name.clone(), number, value);
+                        }
+                    }
+                }
+                // DescriptorProto.nested_type
+                3 => self.decode_message(&name, value)?,
+                // DescriptorProto.options
+                7 => {
+                    for (number, value) in option_values(value)? {
+                        self.insert_message_option(name.clone(), number, value);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets an option of a message, replacing any previous value.
+    pub fn insert_message_option(&mut self, message: String, number: u32, value: Value) {
+        self.messages
+            .entry(message)
+            .or_default()
+            .insert(number, value);
+    }
+
+    /// Sets an option of a field, replacing any previous value.
+    pub fn insert_field_option(&mut self, field: String, number: u32, value: Value) {
+        self.fields.entry(field).or_default().insert(number, value);
+    }
+
+    /// Returns the value of a string option of a message.
+    pub fn message_string(&self, message: &str, number: u32) -> Option<String> {
+        string(self.messages.get(message)?.get(&number)?)
+    }
+
+    /// Returns the value of a string option of a field.
+    pub fn field_string(&self, field: &str, number: u32) -> Option<String> {
+        string(self.fields.get(field)?.get(&number)?)
+    }
+
+    /// Returns the value of a bool option of a field.
+    pub fn field_bool(&self, field: &str, number: u32) -> Option<bool> {
+        match *self.fields.get(field)?.get(&number)? {
+            Value::Varint(value) => Some(value != 0),
+            Value::LengthDelimited(..) => None,
+        }
+    }
+}
+
+fn string(value: &Value) -> Option<String> {
+    match *value {
+        Value::LengthDelimited(ref value) => String::from_utf8(value.clone()).ok(),
+        Value::Varint(..) => None,
+    }
+}
+
+/// The first field number of the extension range of the options messages.
+const FIRST_EXTENSION: u32 = 1000;
+
+/// Returns the varint and length delimited extension fields of an encoded options message.
+fn option_values(mut buf: &[u8]) -> Result<Vec<(u32, Value)>, DecodeError> {
+    let mut values = Vec::new();
+    while !buf.is_empty() {
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        match wire_type {
+            WireType::Varint => {
+                let value = decode_varint(&mut buf)?;
+                if tag >= FIRST_EXTENSION {
+                    values.push((tag, Value::Varint(value)));
+                }
+            }
+            WireType::LengthDelimited => {
+                let value = length_delimited(&mut buf)?;
+                if tag >= FIRST_EXTENSION {
+                    values.push((tag, Value::LengthDelimited(value.to_vec())));
+                }
+            }
+            _ => skip_field(wire_type, tag, &mut buf)?,
+        }
+    }
+    Ok(values)
+}
+
+/// Returns the last value of a string field, since the last value wins when merging messages.
+fn last_string(fields: &[(u32, &[u8])], tag: u32) -> Result<Option<String>, DecodeError> {
+    match fields.iter().rev().find(|&&(t, _)| t == tag) {
+        Some(&(_, value)) => String::from_utf8(value.to_vec())
+            .map(Some)
+            .map_err(|_| DecodeError::new("invalid string value: data is not UTF-8 encoded")),
+        None => Ok(None),
+    }
+}
+
+/// Splits an encoded message into its length delimited fields, skipping all other fields.
+fn length_delimited_fields(mut buf: &[u8]) -> Result<Vec<(u32, &[u8])>, DecodeError> {
+    let mut fields = Vec::new();
+    while !buf.is_empty() {
+        let (tag, wire_type) = decode_key(&mut buf)?;
+        if wire_type != WireType::LengthDelimited {
+            skip_field(wire_type, tag, &mut buf)?;
+            continue;
+        }
+        fields.push((tag, length_delimited(&mut buf)?));
+    }
+    Ok(fields)
+}
+
+/// Decodes the value of a length delimited field.
+fn length_delimited<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], DecodeError> {
+    let len = decode_varint(buf)?;
+    if len > buf.len() as u64 {
+        return Err(DecodeError::new("buffer underflow"));
+    }
+    let (value, rest) = buf.split_at(len as usize);
+    *buf = rest;
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost::encoding::{encode_key, encode_varint};
+
+    fn field(tag: u32, value: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        encode_key(tag, WireType::LengthDelimited, &mut buf);
+        encode_varint(value.len() as u64, &mut buf);
+        buf.extend_from_slice(value);
+        buf
+    }
+
+    #[test]
+    fn test_decode() {
+        let message_options = [
+            // MessageOptions.map_entry
+            &[0x38, 0x00][..],
+            &field(50101, b"tendermint/PubKeyEd25519")[..],
+        ]
+        .concat();
+        let mut field_options = field(50102, b"tendermint/PubKeyEd25519");
+        encode_key(65001, WireType::Varint, &mut field_options);
+        encode_varint(0, &mut field_options);
+        let pub_key = [
+            &field(1, b"pub_key")[..],
+            &[0x18, 0x01],
+            &field(8, &field_options),
+        ]
+        .concat();
+        let nested = [&field(1, b"Nested")[..], &field(7, &message_options)].concat();
+        let message = [
+            &field(1, b"Validator")[..],
+            &field(2, &pub_key),
+            &field(2, &field(1, b"power")),
+            &field(3, &nested),
+        ]
+        .concat();
+        let file = [&field(2, b"tendermint.types")[..], &field(4, &message)].concat();
+
+        let options = CustomOptions::from_descriptor_set(&field(1, &file)).unwrap();
+        assert_eq!(
+            options.field_string(".tendermint.types.Validator.pub_key", 50102),
+            Some("tendermint/PubKeyEd25519".to_string())
+        );
+        assert_eq!(
+            options.field_bool(".tendermint.types.Validator.pub_key", 65001),
+            Some(false)
+        );
+        assert_eq!(
+            options.field_bool(".tendermint.types.Validator.power", 65001),
+            None
+        );
+        assert_eq!(
+            options.message_string(".tendermint.types.Validator.Nested", 50101),
+            Some("tendermint/PubKeyEd25519".to_string())
+        );
+        assert_eq!(
+            options.message_string(".tendermint.types.Validator", 50101),
+            None
+        );
+
+        let request = [&field(2, b"btree_map=.")[..], &field(15, &file)].concat();
+        assert_eq!(CustomOptions::from_request(&request).unwrap(), options);
+        assert_eq!(
+            CustomOptions::from_request(&field(1, &file)).unwrap(),
+            CustomOptions::default()
+        );
+    }
+}
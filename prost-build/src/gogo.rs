@@ -0,0 +1,28 @@
+//! Support for the gogoproto field options defined in `gogoproto/gogo.proto`.
+
+/// The contents of `gogoproto/gogo.proto`.
+pub const GOGO_PROTO: &str = include_str!("../proto/gogoproto/gogo.proto");
+
+/// The field number of the `(gogoproto.nullable)` field option.
+pub const NULLABLE_OPTION: u32 = 65001;
+
+/// The field number of the `(gogoproto.embed)` field option.
+pub const EMBED_OPTION: u32 = 65002;
+
+/// The field number of the `(gogoproto.customtype)` field option.
+pub const CUSTOMTYPE_OPTION: u32 = 65003;
+
+/// The field number of the `(gogoproto.customname)` field option.
+pub const CUSTOMNAME_OPTION: u32 = 65004;
+
+/// The field number of the `(gogoproto.moretags)` field option.
+pub const MORETAGS_OPTION: u32 = 65006;
+
+/// The field number of the `(gogoproto.casttype)` field option.
+pub const CASTTYPE_OPTION: u32 = 65007;
+
+/// The field number of the `(gogoproto.stdtime)` field option.
+pub const STDTIME_OPTION: u32 = 65010;
+
+/// The field number of the `(gogoproto.stdduration)` field option.
+pub const STDDURATION_OPTION: u32 = 65011;
@@ -1,6 +1,6 @@
 //! Utility functions for working with identifiers.
 
-use heck::{CamelCase, SnakeCase};
+use heck::{CamelCase, ShoutySnakeCase, SnakeCase};
 
 /// Converts a `camelCase` or `SCREAMING_SNAKE_CASE` identifier to a `lower_snake` case Rust field
 /// identifier.
@@ -35,6 +35,28 @@ pub fn to_upper_camel(s: &str) -> String {
     ident
 }
 
+/// Converts a `snake_case` identifier to an `UPPER_SNAKE` case Rust constant identifier.
+pub fn to_upper_snake(s: &str) -> String {
+    s.to_shouty_snake_case()
+}
+
+/// Converts a `snake_case` protobuf field name to its `lowerCamelCase` JSON name, as protoc does.
+pub fn to_json_name(s: &str) -> String {
+    let mut json_name = String::with_capacity(s.len());
+    let mut capitalize_next = false;
+    for c in s.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            json_name.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            json_name.push(c);
+        }
+    }
+    json_name
+}
+
 /// Matches a 'matcher' against a fully qualified identifier.
 pub fn match_ident(matcher: &str, msg: &str, field: Option<&str>) -> bool {
     assert_eq!(b'.', msg.as_bytes()[0]);
@@ -128,6 +150,23 @@ mod tests {
         assert_eq!("Self_", &to_upper_camel("self"));
     }
 
+    #[test]
+    fn test_to_upper_snake() {
+        assert_eq!("NULLABLE", &to_upper_snake("nullable"));
+        assert_eq!("GOPROTO_GETTERS", &to_upper_snake("goproto_getters"));
+        assert_eq!("AMINO_NAME", &to_upper_snake("aminoName"));
+        assert_eq!("TYPE", &to_upper_snake("type"));
+    }
+
+    #[test]
+    fn test_to_json_name() {
+        assert_eq!("fooBar", &to_json_name("foo_bar"));
+        assert_eq!("fieldName2", &to_json_name("fieldName2"));
+        assert_eq!("FieldName3", &to_json_name("_field_name3"));
+        assert_eq!("field0Name6", &to_json_name("field_0_name6"));
+        assert_eq!("FIELDNAME11", &to_json_name("FIELD_NAME11"));
+    }
+
     #[test]
     fn test_match_ident() {
         // Prefix matches
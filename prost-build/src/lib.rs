@@ -106,7 +106,11 @@
 //! the prost-build crate is used. Pre-compiled `protoc` binaries exist for Linux, macOS, and
 //! Windows systems. If no pre-compiled `protoc` is available for the host platform, then the
 //! `protoc` or `protoc.exe` binary on the `PATH` is used. If `protoc` is not available in any of
-//! these fallback locations, then the build fails.
+//! these fallback locations, then compiling `.proto` files with `protoc` fails.
+//!
+//! Alternatively, `prost-build` can parse `.proto` files with a built-in parser, configured with
+//! [`Config::builtin_parser`](struct.Config.html#method.builtin_parser), in which case `protoc`
+//! is not needed at all.
 //!
 //! If `PROTOC_INCLUDE` is not found in the environment, then the Protobuf include directory bundled
 //! in the prost-build crate is be used.
@@ -122,10 +126,16 @@ extern crate tempdir;
 #[macro_use]
 extern crate log;
 
+mod amino;
 mod ast;
 mod code_generator;
+mod custom_options;
+mod gogo;
 mod ident;
 mod message_graph;
+mod parser;
+mod plugin;
+mod socket_service;
 
 use std::collections::HashMap;
 use std::default;
@@ -136,11 +146,15 @@ use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use prost::Message;
+use prost_types::compiler::{code_generator_response, CodeGeneratorRequest, CodeGeneratorResponse};
 use prost_types::{FileDescriptorProto, FileDescriptorSet};
 
 pub use ast::{Comments, Method, Service};
-use code_generator::{module, CodeGenerator};
+use code_generator::{append_register_all, module, CodeGenerator};
+use custom_options::CustomOptions;
+use ident::match_ident;
 use message_graph::MessageGraph;
+pub use socket_service::SocketServiceGenerator;
 
 type Module = Vec<String>;
 
@@ -157,7 +171,8 @@ type Module = Vec<String>;
 /// details like how errors are handled or if it is asynchronous. Then the user provides an
 /// implementation of the generated trait in the application code and plugs it into the framework.
 ///
-/// Such framework isn't part of Prost at present.
+/// [`SocketServiceGenerator`](struct.SocketServiceGenerator.html) is such a generator for services
+/// which exchange length-delimited messages over a stream.
 pub trait ServiceGenerator {
     /// Generates a Rust interface or implementation for a service, writing the
     /// result to `buf`.
@@ -185,10 +200,17 @@ pub trait ServiceGenerator {
 pub struct Config {
     service_generator: Option<Box<ServiceGenerator>>,
     btree_map: Vec<String>,
+    builders: Vec<String>,
     type_attributes: Vec<(String, String)>,
     field_attributes: Vec<(String, String)>,
+    amino_names: Vec<(String, String)>,
+    extern_paths: Vec<(String, String)>,
+    custom_types: HashMap<String, String>,
     prost_types: bool,
     strip_enum_prefix: bool,
+    builtin_parser: bool,
+    out_dir: Option<PathBuf>,
+    include_file: Option<PathBuf>,
 }
 
 impl Config {
@@ -254,6 +276,35 @@ impl Config {
         self
     }
 
+    /// Configure the code generator to generate builder types for messages.
+    ///
+    /// For each matching message `Foo`, a `FooBuilder` is generated, and returned by
+    /// `Foo::builder()`. The builder has a setter per field, which takes the field by value:
+    /// string and bytes fields accept anything which converts `Into` them, enumeration fields
+    /// accept the enumeration type, and repeated and map fields accept an iterator of values.
+    /// `build()` returns an error if a `required` field was not set.
+    ///
+    /// # Arguments
+    ///
+    /// **`paths`** - paths to specific messages or packages. It works the same way as in
+    /// [`btree_map`](#method.btree_map), just with the field name omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let mut config = prost_build::Config::new();
+    /// // Generate builders for all messages in the 'foo.bar' package.
+    /// config.builder(&[".foo.bar"]);
+    /// ```
+    pub fn builder<I, S>(&mut self, paths: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        self.builders = paths.into_iter().map(|s| s.as_ref().to_string()).collect();
+        self
+    }
+
     /// Add additional attribute to matched fields.
     ///
     /// # Arguments
@@ -336,6 +387,126 @@ impl Config {
         self
     }
 
+    /// Registers a message type or field with an amino name.
+    ///
+    /// # Arguments
+    ///
+    /// **`path`** - a path matching a message type or a field. Paths are matched the same way as
+    /// in [`btree_map`](#method.btree_map), except that the path must end with the name of the
+    /// message type or field: a path naming a message type does not match the message's fields
+    /// or nested types, and a package path does not match anything.
+    ///
+    /// **`name`** - the amino name. Matched message types are generated with an
+    /// `#[amino_name = "..."]` attribute, so that they are encoded with the amino prefix bytes of
+    /// the name, and matched fields with an `amino_name` field attribute.
+    ///
+    /// Message types and fields may also be registered in the `.proto` files, with the
+    /// `(amino.name)` message option and the `(amino.field_name)` field option defined in
+    /// `amino/amino.proto`. Names registered with this method take precedence over the options.
+    ///
+    /// Every generated module containing registered message types gets a `register_all` function,
+    /// returning the reflection descriptors of the registered types.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let mut config = prost_build::Config::new();
+    /// // Register a message type.
+    /// config.amino_name(".tendermint.crypto.PubKeyEd25519", "tendermint/PubKeyEd25519");
+    ///
+    /// // Register a field holding amino encoded public keys.
+    /// config.amino_name(".tendermint.types.Validator.pub_key", "tendermint/PubKeyEd25519");
+    /// ```
+    pub fn amino_name<P, N>(&mut self, path: P, name: N) -> &mut Self
+    where
+        P: AsRef<str>,
+        N: AsRef<str>,
+    {
+        self.amino_names
+            .push((path.as_ref().to_string(), name.as_ref().to_string()));
+        self
+    }
+
+    /// Declares an externally provided Protobuf package or type.
+    ///
+    /// Types matched by an extern path are not generated. Generated code refers to them through
+    /// the Rust path instead, so that types already generated in another crate can be reused.
+    ///
+    /// # Arguments
+    ///
+    /// **`proto_path`** - a path matching Protobuf packages or types. Paths are matched the same
+    /// way as in [`btree_map`](#method.btree_map). For a fully qualified path, the rest of the
+    /// type name is appended to the Rust path, converting nested message names to module names
+    /// like generated code does. A relative path matches types by name, so the Rust path must name
+    /// the type itself. If several paths match a type, the longest one is used.
+    ///
+    /// **`rust_path`** - the fully qualified Rust path of the package or type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let mut config = prost_build::Config::new();
+    /// // `.tendermint.crypto.PublicKey` is `::tm_crypto::proto::PublicKey`, and
+    /// // `.tendermint.crypto.PublicKey.Inner` is `::tm_crypto::proto::public_key::Inner`.
+    /// config.extern_path(".tendermint.crypto", "::tm_crypto::proto");
+    ///
+    /// // Map a single type.
+    /// config.extern_path(".tendermint.types.BlockID", "::tm_types::BlockId");
+    /// ```
+    pub fn extern_path<P, R>(&mut self, proto_path: P, rust_path: R) -> &mut Self
+    where
+        P: AsRef<str>,
+        R: AsRef<str>,
+    {
+        self.extern_paths.push((
+            proto_path.as_ref().to_string(),
+            rust_path.as_ref().to_string(),
+        ));
+        self
+    }
+
+    /// Maps a Go type named by the `(gogoproto.casttype)` and `(gogoproto.customtype)` field
+    /// options to a Rust type.
+    ///
+    /// Fields with either option are generated with the Rust type, and are encoded as the type
+    /// they would otherwise have. The Rust type must implement `Clone`, `Debug` and `PartialEq`,
+    /// and a `From` conversion from the Protobuf field type, e.g. `Vec<u8>` for a `bytes` field,
+    /// or the generated message type for a message field. The Protobuf field type must implement
+    /// `From` for a reference to the Rust type. Types which can't represent every value of the
+    /// field type may implement `prost_amino::proxy::Proxy` instead, to reject them when they are
+    /// decoded. Rust types of fields which are neither repeated nor optional must also implement
+    /// `Default`, which is their value when they are not decoded. Fields naming a Go type which is
+    /// not mapped keep their Protobuf field type.
+    ///
+    /// The `(gogoproto.stdtime)` and `(gogoproto.stdduration)` options don't need to be mapped:
+    /// such fields are generated as `std::time::SystemTime` and `std::time::Duration`.
+    ///
+    /// # Arguments
+    ///
+    /// **`go_type`** - the Go type as written in the option, e.g.
+    /// `github.com/tendermint/tendermint/libs/bytes.HexBytes`.
+    ///
+    /// **`rust_type`** - the fully qualified Rust type.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # let mut config = prost_build::Config::new();
+    /// config.custom_type(
+    ///     "github.com/tendermint/tendermint/libs/bytes.HexBytes",
+    ///     "::tendermint::HexBytes",
+    /// );
+    /// ```
+    pub fn custom_type<G, R>(&mut self, go_type: G, rust_type: R) -> &mut Self
+    where
+        G: AsRef<str>,
+        R: AsRef<str>,
+    {
+        self.custom_types
+            .insert(go_type.as_ref().to_string(), rust_type.as_ref().to_string());
+        self
+    }
+
     /// Configures the code generator to use the provided service generator.
     pub fn service_generator(&mut self, service_generator: Box<ServiceGenerator>) -> &mut Self {
         self.service_generator = Some(service_generator);
@@ -360,6 +531,62 @@ impl Config {
         self
     }
 
+    /// Configures `prost-build` to parse `.proto` files with its built-in parser instead of
+    /// `protoc`.
+    ///
+    /// The built-in parser produces the same descriptors as `protoc`, including the comments
+    /// used for documentation, and resolves imports from the include directories, the Protobuf
+    /// include directory, and the bundled amino and gogoproto options. This allows building
+    /// without a `protoc` binary for the host platform.
+    pub fn builtin_parser(&mut self) -> &mut Self {
+        self.builtin_parser = true;
+        self
+    }
+
+    /// Configures the output directory where generated Rust files will be written.
+    ///
+    /// If unset, defaults to the `OUT_DIR` environment variable. `OUT_DIR` is set by Cargo when
+    /// executing build scripts, so `out_dir` typically does not need to be configured.
+    pub fn out_dir<P>(&mut self, path: P) -> &mut Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.out_dir = Some(path.into());
+        self
+    }
+
+    /// Configures the code generator to write an include file, which nests the modules of all
+    /// generated packages in a hierarchy of `pub mod`s matching the package names.
+    ///
+    /// The path is relative to the output directory. Including the file once makes references
+    /// between packages resolve, instead of nesting the generated modules by hand. Without an
+    /// output directory configured with `out_dir`, the modules are included from `OUT_DIR`.
+    ///
+    /// # Example
+    ///
+    /// In `build.rs`:
+    ///
+    /// ```norun
+    /// # let mut config = prost_build::Config::new();
+    /// config.include_file("_includes.rs");
+    /// ```
+    ///
+    /// In the crate, where the packages `foo` and `foo.bar` are available as `protos::foo` and
+    /// `protos::foo::bar`:
+    ///
+    /// ```norun
+    /// pub mod protos {
+    ///     include!(concat!(env!("OUT_DIR"), "/_includes.rs"));
+    /// }
+    /// ```
+    pub fn include_file<P>(&mut self, path: P) -> &mut Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.include_file = Some(path.into());
+        self
+    }
+
     /// Compile `.proto` files into Rust files during a Cargo build with additional code generator
     /// configuration options.
     ///
@@ -383,71 +610,215 @@ impl Config {
     where
         P: AsRef<Path>,
     {
-        let target: PathBuf = env::var_os("OUT_DIR")
-            .ok_or_else(|| Error::new(ErrorKind::Other, "OUT_DIR environment variable is not set"))?
-            .into();
-
         // TODO: This should probably emit 'rerun-if-changed=PATH' directives for cargo, however
         // according to [1] if any are output then those paths replace the default crate root,
         // which is undesirable. Figure out how to do it in an additive way; perhaps gcc-rs has
         // this figured out.
         // [1]: http://doc.crates.io/build-script.html#outputs-of-the-build-script
 
-        let tmp = tempdir::TempDir::new("prost-build")?;
-        let descriptor_set = tmp.path().join("prost-descriptor-set");
+        let (descriptor_set, custom_options) = if self.builtin_parser {
+            parser::parse(protos, includes)?
+        } else {
+            run_protoc(protos, includes)?
+        };
 
-        let mut cmd = Command::new(protoc());
-        cmd.arg("--include_imports")
-            .arg("--include_source_info")
-            .arg("-o")
-            .arg(&descriptor_set);
+        self.write_modules(descriptor_set.file, &custom_options)
+    }
 
-        for include in includes {
-            cmd.arg("-I").arg(include.as_ref());
-        }
+    /// Compile a `FileDescriptorSet` into Rust files with additional code generator configuration
+    /// options.
+    ///
+    /// This allows generating code from descriptors produced by other tools, such as a checked in
+    /// descriptor set, without running `protoc`. Like `compile_protos`, the descriptors should
+    /// include the imported files and the source info, otherwise generated code lacks
+    /// documentation comments.
+    ///
+    /// Custom options, such as the `(amino.name)` and `(gogoproto.nullable)` options, are kept in
+    /// the `extension_set` of the options messages when the descriptor set is decoded, and are
+    /// used like the options of compiled `.proto` files.
+    ///
+    /// # Example `build.rs`
+    ///
+    /// ```norun
+    /// extern crate prost_build;
+    /// extern crate prost_types;
+    ///
+    /// fn main() {
+    ///     let descriptor_set = prost_types::FileDescriptorSet {
+    ///         file: Vec::new(),
+    ///     };
+    ///     prost_build::Config::new()
+    ///         .out_dir("src/generated")
+    ///         .compile_fds(descriptor_set)
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn compile_fds(&mut self, descriptor_set: FileDescriptorSet) -> Result<()> {
+        // The custom options are read from the encoded extension fields of the options.
+        let mut buf = Vec::new();
+        descriptor_set
+            .encode(&mut buf)
+            .expect("a Vec has sufficient capacity");
+        let custom_options = CustomOptions::from_descriptor_set(&buf)?;
+        self.write_modules(descriptor_set.file, &custom_options)
+    }
 
-        // Set the protoc include after the user includes in case the user wants to
-        // override one of the built-in .protos.
-        cmd.arg("-I").arg(protoc_include());
+    /// Compile an encoded `FileDescriptorSet` file, such as one written by `protoc
+    /// --descriptor_set_out` or `buf build`, into Rust files with additional code generator
+    /// configuration options.
+    ///
+    /// Like `compile_fds`, the amino and gogoproto options set in the descriptor set are used.
+    ///
+    /// # Example `build.rs`
+    ///
+    /// ```norun
+    /// extern crate prost_build;
+    ///
+    /// fn main() {
+    ///     prost_build::Config::new()
+    ///         .compile_fds_file("src/descriptor_set.bin")
+    ///         .unwrap();
+    /// }
+    /// ```
+    pub fn compile_fds_file<P>(&mut self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let mut buf = Vec::new();
+        fs::File::open(path)?.read_to_end(&mut buf)?;
+        let custom_options = CustomOptions::from_descriptor_set(&buf)?;
+        let descriptor_set = FileDescriptorSet::decode(&buf[..])?;
+        self.write_modules(descriptor_set.file, &custom_options)
+    }
+
+    /// Generates code for an encoded `CodeGeneratorRequest`, as a `protoc` plugin.
+    ///
+    /// Code is generated for the request's files to generate, with this configuration and the
+    /// options in the request parameter, e.g. `btree_map=.,amino_name=.foo.Bar=foo/Bar`. See the
+    /// `protoc-gen-prost-amino` plugin for the supported options. Invalid parameters are reported
+    /// in the response error, as expected by `protoc`.
+    pub fn compile_request(&mut self, request: &[u8]) -> Result<CodeGeneratorResponse> {
+        let custom_options = CustomOptions::from_request(request)?;
+        let request = CodeGeneratorRequest::decode(request)?;
 
-        for proto in protos {
-            cmd.arg(proto.as_ref());
+        if let Some(ref parameter) = request.parameter {
+            if let Err(error) = plugin::apply_parameter(self, parameter) {
+                return Ok(CodeGeneratorResponse {
+                    error: Some(error),
+                    file: Vec::new(),
+                });
+            }
         }
 
-        let output = cmd.output()?;
-        if !output.status.success() {
-            return Err(Error::new(
-                ErrorKind::Other,
-                format!("protoc failed: {}", String::from_utf8_lossy(&output.stderr)),
-            ));
+        let modules = self.generate(
+            request.proto_file,
+            Some(&request.file_to_generate[..]),
+            &custom_options,
+        );
+        let module_names: Vec<_> = modules.keys().cloned().collect();
+        let mut file: Vec<_> = modules
+            .into_iter()
+            .map(|(module, content)| code_generator_response::File {
+                name: Some(module_filename(&module)),
+                insertion_point: None,
+                content: Some(content),
+            })
+            .collect();
+        file.sort_by(|a, b| a.name.cmp(&b.name));
+
+        if let Some(ref include_file) = self.include_file {
+            file.push(code_generator_response::File {
+                name: Some(include_file.to_string_lossy().into_owned()),
+                insertion_point: None,
+                content: Some(include_file_content(module_names, false)),
+            });
         }
 
-        let mut buf = Vec::new();
-        fs::File::open(descriptor_set)?.read_to_end(&mut buf)?;
-        let descriptor_set = FileDescriptorSet::decode(&buf)?;
+        Ok(CodeGeneratorResponse { error: None, file })
+    }
 
-        let modules = self.generate(descriptor_set.file);
+    /// Generates the Rust modules for the files, and writes them to the output directory.
+    fn write_modules(
+        &mut self,
+        files: Vec<FileDescriptorProto>,
+        custom_options: &CustomOptions,
+    ) -> Result<()> {
+        let target: PathBuf = match self.out_dir {
+            Some(ref out_dir) => out_dir.clone(),
+            None => env::var_os("OUT_DIR")
+                .ok_or_else(|| {
+                    Error::new(ErrorKind::Other, "OUT_DIR environment variable is not set")
+                })?
+                .into(),
+        };
+
+        let modules = self.generate(files, None, custom_options);
+        let module_names: Vec<_> = modules.keys().cloned().collect();
         for (module, content) in modules {
-            let mut filename = module.join(".");
-            filename.push_str(".rs");
+            let filename = module_filename(&module);
             trace!("writing: {:?}", filename);
             let mut file = fs::File::create(target.join(filename))?;
             file.write_all(content.as_bytes())?;
             file.flush()?;
         }
 
+        if let Some(ref include_file) = self.include_file {
+            trace!("writing: {:?}", include_file);
+            let content = include_file_content(module_names, self.out_dir.is_none());
+            let mut file = fs::File::create(target.join(include_file))?;
+            file.write_all(content.as_bytes())?;
+            file.flush()?;
+        }
+
         Ok(())
     }
 
-    fn generate(&mut self, files: Vec<FileDescriptorProto>) -> HashMap<Module, String> {
+    /// Generates the Rust modules for the files, or only for the files named in
+    /// `file_to_generate` if set. All files are used to resolve message types.
+    fn generate(
+        &mut self,
+        files: Vec<FileDescriptorProto>,
+        file_to_generate: Option<&[String]>,
+        custom_options: &CustomOptions,
+    ) -> HashMap<Module, String> {
         let mut modules = HashMap::new();
+        let mut registered = HashMap::new();
 
         let message_graph = MessageGraph::new(&files);
 
         for file in files {
+            if let Some(file_to_generate) = file_to_generate {
+                if !file_to_generate
+                    .iter()
+                    .any(|name| Some(name) == file.name.as_ref())
+                {
+                    continue;
+                }
+            }
+            // Files of extern packages are not generated, so that their modules are not
+            // overwritten with empty ones.
+            let package = format!(".{}", file.package());
+            if self.extern_paths.iter().any(|&(ref proto_path, _)| {
+                proto_path.starts_with('.') && match_ident(proto_path, &package, None)
+            }) {
+                continue;
+            }
             let module = module(&file);
-            let mut buf = modules.entry(module).or_insert_with(String::new);
-            CodeGenerator::generate(self, &message_graph, file, &mut buf);
+            let mut buf = modules.entry(module.clone()).or_insert_with(String::new);
+            let types =
+                CodeGenerator::generate(self, &message_graph, custom_options, file, &mut buf);
+            registered
+                .entry(module)
+                .or_insert_with(Vec::new)
+                .extend(types);
+        }
+
+        // Files of the same package share a module, so `register_all` is generated once all
+        // files have been generated.
+        for (module, types) in registered {
+            if !types.is_empty() {
+                append_register_all(modules.get_mut(&module).unwrap(), &types);
+            }
         }
         modules
     }
@@ -458,10 +829,17 @@ impl default::Default for Config {
         Config {
             service_generator: None,
             btree_map: Vec::new(),
+            builders: Vec::new(),
             type_attributes: Vec::new(),
             field_attributes: Vec::new(),
+            amino_names: Vec::new(),
+            extern_paths: Vec::new(),
+            custom_types: HashMap::new(),
             prost_types: true,
             strip_enum_prefix: true,
+            builtin_parser: false,
+            out_dir: None,
+            include_file: None,
         }
     }
 }
@@ -514,6 +892,111 @@ where
     Config::new().compile_protos(protos, includes)
 }
 
+/// Returns the name of the file a module is written to.
+fn module_filename(module: &Module) -> String {
+    let mut filename = module.join(".");
+    filename.push_str(".rs");
+    filename
+}
+
+/// Returns the content of an include file for the generated modules.
+///
+/// The modules are included with paths relative to the include file, or relative to `OUT_DIR` if
+/// `out_dir_env` is set.
+fn include_file_content(mut modules: Vec<Module>, out_dir_env: bool) -> String {
+    // Sorting puts every module right before its submodules.
+    modules.sort();
+
+    let mut buf = String::new();
+    // The modules which are currently open.
+    let mut open: &[String] = &[];
+    for module in &modules {
+        while !module.starts_with(open) {
+            open = &open[..open.len() - 1];
+            buf.push_str(&"    ".repeat(open.len()));
+            buf.push_str("}\n");
+        }
+        for (depth, name) in module.iter().enumerate().skip(open.len()) {
+            buf.push_str(&"    ".repeat(depth));
+            buf.push_str(&format!("pub mod {} {{\n", name));
+        }
+        open = module;
+
+        buf.push_str(&"    ".repeat(open.len()));
+        if out_dir_env {
+            buf.push_str(&format!(
+                "include!(concat!(env!(\"OUT_DIR\"), \"/{}\"));\n",
+                module_filename(module)
+            ));
+        } else {
+            buf.push_str(&format!("include!(\"{}\");\n", module_filename(module)));
+        }
+    }
+    while !open.is_empty() {
+        open = &open[..open.len() - 1];
+        buf.push_str(&"    ".repeat(open.len()));
+        buf.push_str("}\n");
+    }
+    buf
+}
+
+/// Parses `.proto` files with `protoc`, returning the descriptors and the custom options of their
+/// messages and fields.
+fn run_protoc<P>(protos: &[P], includes: &[P]) -> Result<(FileDescriptorSet, CustomOptions)>
+where
+    P: AsRef<Path>,
+{
+    let tmp = tempdir::TempDir::new("prost-build")?;
+    let descriptor_set = tmp.path().join("prost-descriptor-set");
+
+    // Make the amino and gogoproto options available as "amino/amino.proto" and
+    // "gogoproto/gogo.proto".
+    let options_include = tmp.path().join("include");
+    fs::create_dir_all(options_include.join("amino"))?;
+    fs::write(
+        options_include.join("amino").join("amino.proto"),
+        amino::AMINO_PROTO,
+    )?;
+    fs::create_dir_all(options_include.join("gogoproto"))?;
+    fs::write(
+        options_include.join("gogoproto").join("gogo.proto"),
+        gogo::GOGO_PROTO,
+    )?;
+
+    let mut cmd = Command::new(protoc());
+    cmd.arg("--include_imports")
+        .arg("--include_source_info")
+        .arg("-o")
+        .arg(&descriptor_set);
+
+    for include in includes {
+        cmd.arg("-I").arg(include.as_ref());
+    }
+
+    // Set the protoc and options includes after the user includes in case the user wants to
+    // override one of the built-in .protos.
+    cmd.arg("-I").arg(protoc_include());
+    cmd.arg("-I").arg(&options_include);
+
+    for proto in protos {
+        cmd.arg(proto.as_ref());
+    }
+
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Err(Error::new(
+            ErrorKind::Other,
+            format!("protoc failed: {}", String::from_utf8_lossy(&output.stderr)),
+        ));
+    }
+
+    let mut buf = Vec::new();
+    fs::File::open(descriptor_set)?.read_to_end(&mut buf)?;
+    let custom_options = CustomOptions::from_descriptor_set(&buf)?;
+    let descriptor_set = FileDescriptorSet::decode(&buf[..])?;
+    Ok((descriptor_set, custom_options))
+}
+
 /// Returns the path to the `protoc` binary.
 pub fn protoc() -> &'static Path {
     #[cfg(not(feature = "docs-rs"))]
@@ -574,9 +1057,171 @@ mod tests {
 
     #[test]
     fn smoke_test() {
-        let _ = env_logger::init();
+        let _ = env_logger::try_init();
+        Config::new()
+            .service_generator(Box::new(ServiceTraitGenerator))
+            .compile_protos(&["src/smoke_test.proto"], &["src"])
+            .unwrap();
+    }
+
+    #[test]
+    fn compile_fds() {
+        let (descriptor_set, _) = parser::parse(&["src/smoke_test.proto"], &["src"]).unwrap();
+        let tmp = tempdir::TempDir::new("prost-build").unwrap();
+
+        fs::create_dir(tmp.path().join("fds")).unwrap();
+        Config::new()
+            .service_generator(Box::new(ServiceTraitGenerator))
+            .out_dir(tmp.path().join("fds"))
+            .compile_fds(descriptor_set.clone())
+            .unwrap();
+        let generated = fs::read_to_string(tmp.path().join("fds").join("smoke_test.rs")).unwrap();
+        assert!(generated.contains("pub struct SmokeRequest"));
+        assert!(generated.contains("trait SmokeService"));
+
+        let mut buf = Vec::new();
+        descriptor_set.encode(&mut buf).unwrap();
+        fs::write(tmp.path().join("descriptor_set.bin"), buf).unwrap();
+        Config::new()
+            .service_generator(Box::new(ServiceTraitGenerator))
+            .out_dir(tmp.path())
+            .compile_fds_file(tmp.path().join("descriptor_set.bin"))
+            .unwrap();
+        assert_eq!(
+            fs::read_to_string(tmp.path().join("smoke_test.rs")).unwrap(),
+            generated
+        );
+    }
+
+    #[test]
+    fn compile_fds_options() {
+        let protos = &[
+            "../tests/src/amino_options.proto",
+            "../tests/src/gogo_options.proto",
+        ];
+        let includes = &["../tests/src"];
+        let (descriptor_set, _) = run_protoc(protos, includes).unwrap();
+        let tmp = tempdir::TempDir::new("prost-build").unwrap();
+
+        fs::create_dir(tmp.path().join("fds")).unwrap();
+        Config::new()
+            .out_dir(tmp.path().join("fds"))
+            .compile_fds(descriptor_set)
+            .unwrap();
+        Config::new()
+            .out_dir(tmp.path())
+            .compile_protos(protos, includes)
+            .unwrap();
+        for module in &["amino_options.rs", "gogo_options.rs"] {
+            assert_eq!(
+                fs::read_to_string(tmp.path().join("fds").join(module)).unwrap(),
+                fs::read_to_string(tmp.path().join(module)).unwrap()
+            );
+        }
+        let generated = fs::read_to_string(tmp.path().join("fds").join("gogo_options.rs")).unwrap();
+        assert!(generated.contains("pub prev_commit:"));
+        let generated =
+            fs::read_to_string(tmp.path().join("fds").join("amino_options.rs")).unwrap();
+        assert!(generated.contains("tendermint/PubKeyEd25519"));
+    }
+
+    #[test]
+    fn compile_request() {
+        let (descriptor_set, _) = parser::parse(&["src/smoke_test.proto"], &["src"]).unwrap();
+        let mut request = CodeGeneratorRequest {
+            file_to_generate: vec!["smoke_test.proto".to_string()],
+            parameter: Some("retain_enum_prefix,btree_map=.".to_string()),
+            proto_file: descriptor_set.file,
+            compiler_version: None,
+        };
+
+        let mut buf = Vec::new();
+        request.encode(&mut buf).unwrap();
+        let response = Config::new()
+            .service_generator(Box::new(ServiceTraitGenerator))
+            .compile_request(&buf)
+            .unwrap();
+        assert_eq!(response.error, None);
+        assert_eq!(response.file.len(), 1);
+        assert_eq!(response.file[0].name(), "smoke_test.rs");
+        assert!(response.file[0].content().contains("trait SmokeService"));
+
+        request.parameter = Some("btree_map".to_string());
+        let mut buf = Vec::new();
+        request.encode(&mut buf).unwrap();
+        let response = Config::new().compile_request(&buf).unwrap();
+        assert_eq!(
+            response.error,
+            Some("invalid plugin parameter option: btree_map".to_string())
+        );
+        assert!(response.file.is_empty());
+    }
+
+    #[test]
+    fn test_include_file_content() {
+        let modules = vec![
+            vec!["foo".to_string(), "bar".to_string(), "baz".to_string()],
+            vec!["qux".to_string()],
+            vec!["foo".to_string()],
+            vec!["foo".to_string(), "quux".to_string()],
+        ];
+        assert_eq!(
+            include_file_content(modules.clone(), false),
+            "\
+pub mod foo {
+    include!(\"foo.rs\");
+    pub mod bar {
+        pub mod baz {
+            include!(\"foo.bar.baz.rs\");
+        }
+    }
+    pub mod quux {
+        include!(\"foo.quux.rs\");
+    }
+}
+pub mod qux {
+    include!(\"qux.rs\");
+}
+"
+        );
+        assert!(include_file_content(modules, true)
+            .contains("include!(concat!(env!(\"OUT_DIR\"), \"/foo.quux.rs\"));"));
+    }
+
+    #[test]
+    fn extern_path() {
+        let tmp = tempdir::TempDir::new("prost-build").unwrap();
+        Config::new()
+            .builtin_parser()
+            .out_dir(tmp.path())
+            .extern_path(".nesting", "::nesting")
+            .extern_path(".nesting.B", "::other::Bee")
+            .extern_path("C", "::c::C")
+            .compile_protos(&["../tests/src/extern_paths.proto"], &["../tests/src"])
+            .unwrap();
+
+        // The extern package is not generated, but the package containing an extern type is.
+        assert!(!tmp.path().join("nesting.rs").exists());
+        let recursive_oneof = fs::read_to_string(tmp.path().join("recursive_oneof.rs")).unwrap();
+        assert!(recursive_oneof.contains("pub struct B"));
+        assert!(!recursive_oneof.contains("pub struct C"));
+        assert!(recursive_oneof.contains("C(::c::C)"));
+
+        let generated = fs::read_to_string(tmp.path().join("extern_paths.rs")).unwrap();
+        assert!(generated.contains("pub a: ::std::option::Option<::nesting::A>"));
+        assert!(generated.contains("pub b: ::std::vec::Vec<::other::Bee>"));
+        assert!(generated.contains("pub c: ::std::option::Option<::c::C>"));
+        assert!(
+            generated.contains("pub recursive: ::std::option::Option<super::recursive_oneof::A>")
+        );
+    }
+
+    #[test]
+    fn builtin_parser_smoke_test() {
+        let _ = env_logger::try_init();
         Config::new()
             .service_generator(Box::new(ServiceTraitGenerator))
+            .builtin_parser()
             .compile_protos(&["src/smoke_test.proto"], &["src"])
             .unwrap();
     }
@@ -0,0 +1,1874 @@
+//! Parses the tokens of a `.proto` file into a `FileDescriptorProto`.
+//!
+//! The structure of the parser follows the parser of `protoc`, so that the descriptors, source
+//! locations and comments match the ones produced by `protoc`. Type names are left as written in
+//! the file; they are resolved once all imports have been parsed.
+
+use std::collections::HashSet;
+use std::i32;
+use std::i64;
+use std::mem;
+use std::u32;
+use std::u64;
+
+use prost_types::descriptor_proto::{ExtensionRange, ReservedRange};
+use prost_types::enum_descriptor_proto::EnumReservedRange;
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::source_code_info::Location;
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, EnumOptions, EnumValueDescriptorProto, EnumValueOptions,
+    ExtensionRangeOptions, FieldDescriptorProto, FieldOptions, FileDescriptorProto, FileOptions,
+    MessageOptions, MethodDescriptorProto, MethodOptions, OneofDescriptorProto, OneofOptions,
+    ServiceDescriptorProto, ServiceOptions, SourceCodeInfo,
+};
+
+use super::tokenizer::{parse_float, parse_integer, parse_string_append, TokenKind, Tokenizer};
+use super::validate::{ErrorLocation, ErrorLocations};
+use super::ParseError;
+
+/// The largest field number.
+pub const MAX_FIELD_NUMBER: i32 = (1 << 29) - 1;
+
+/// The end of an extension or reserved range which extends to `max`, until the message options
+/// are known.
+const MAX_RANGE_SENTINEL: i32 = -1;
+
+/// A parsed `.proto` file.
+pub struct Parsed {
+    pub file: FileDescriptorProto,
+    /// The custom options set on messages and fields, which are resolved once the extensions of
+    /// the imports are known.
+    pub custom_options: Vec<CustomOption>,
+    /// The locations of the names, numbers, types and default values of declarations, which
+    /// errors found once the types are resolved are reported at.
+    pub error_locations: ErrorLocations,
+}
+
+/// A custom option set on a message or field, e.g. `[(gogoproto.nullable) = false]`.
+pub struct CustomOption {
+    /// The source location path of the message or field.
+    pub owner: Vec<i32>,
+    /// The extension name, as written between the parentheses.
+    pub name: String,
+    pub value: OptionValue,
+    pub line: i32,
+    pub column: i32,
+}
+
+/// Parses a `.proto` file.
+pub fn parse(source: &str) -> Result<Parsed, ParseError> {
+    let mut parser = Parser {
+        input: Tokenizer::new(source),
+        locations: Vec::new(),
+        upcoming_doc_comments: String::new(),
+        upcoming_detached_comments: Vec::new(),
+        syntax: "proto2".to_string(),
+        custom_options: Vec::new(),
+        error_locations: ErrorLocations::new(),
+    };
+    let mut file = FileDescriptorProto::default();
+    parser.parse_file(&mut file)?;
+    file.source_code_info = Some(SourceCodeInfo {
+        location: parser.locations,
+    });
+    Ok(Parsed {
+        file,
+        custom_options: parser.custom_options,
+        error_locations: parser.error_locations,
+    })
+}
+
+/// A location in the source which is being recorded.
+///
+/// Locations are added to the source code info when they are started, and their span is
+/// completed once the declaration has been parsed. Locations of declarations which are not
+/// recorded still take part in the assignment of comments.
+struct Recorder {
+    index: Option<usize>,
+    path: Vec<i32>,
+    line: i32,
+    column: i32,
+}
+
+impl Recorder {
+    fn unrecorded() -> Recorder {
+        Recorder {
+            index: None,
+            path: Vec::new(),
+            line: 0,
+            column: 0,
+        }
+    }
+
+    fn child(&self, path: &[i32]) -> Vec<i32> {
+        let mut child = self.path.clone();
+        child.extend_from_slice(path);
+        child
+    }
+}
+
+/// The options message an option is set on.
+enum Options<'a> {
+    File(&'a mut FileOptions),
+    Message(&'a mut MessageOptions),
+    Field(&'a mut FieldOptions),
+    /// Oneof options, which have no standard options.
+    Oneof,
+    Enum(&'a mut EnumOptions),
+    EnumValue(&'a mut EnumValueOptions),
+    Service(&'a mut ServiceOptions),
+    Method(&'a mut MethodOptions),
+    /// Extension range options, which have no standard options.
+    ExtensionRange,
+}
+
+/// The value of an option. Aggregate values and floats are not used by the standard or supported
+/// custom options, and are only checked for syntax.
+pub enum OptionValue {
+    Identifier(String),
+    /// An integer, with its magnitude and whether it is negated.
+    Integer(u64, bool),
+    Float,
+    String(Vec<u8>),
+    Aggregate,
+}
+
+/// The type of a field, as written.
+enum FieldType {
+    Scalar(Type),
+    Named(String),
+}
+
+struct Parser<'a> {
+    input: Tokenizer<'a>,
+    locations: Vec<Location>,
+    upcoming_doc_comments: String,
+    upcoming_detached_comments: Vec<String>,
+    syntax: String,
+    custom_options: Vec<CustomOption>,
+    error_locations: ErrorLocations,
+}
+
+impl<'a> Parser<'a> {
+    // Token helpers.
+
+    fn error<S>(&self, message: S) -> ParseError
+    where
+        S: Into<String>,
+    {
+        let token = self.input.current();
+        ParseError::new(token.line, token.column, message)
+    }
+
+    fn at_end(&self) -> bool {
+        self.input.current().kind == TokenKind::End
+    }
+
+    fn looking_at(&self, text: &str) -> bool {
+        self.input.current().text == text
+    }
+
+    fn looking_at_kind(&self, kind: TokenKind) -> bool {
+        self.input.current().kind == kind
+    }
+
+    fn try_consume(&mut self, text: &str) -> Result<bool, ParseError> {
+        if self.looking_at(text) {
+            self.input.next()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn consume(&mut self, text: &str) -> Result<(), ParseError> {
+        let error = format!("Expected \"{}\".", text);
+        self.consume_with(text, &error)
+    }
+
+    fn consume_with(&mut self, text: &str, error: &str) -> Result<(), ParseError> {
+        if self.try_consume(text)? {
+            Ok(())
+        } else {
+            Err(self.error(error))
+        }
+    }
+
+    fn consume_identifier(&mut self, error: &str) -> Result<String, ParseError> {
+        if self.looking_at_kind(TokenKind::Identifier) {
+            let identifier = self.input.current().text.clone();
+            self.input.next()?;
+            Ok(identifier)
+        } else {
+            Err(self.error(error))
+        }
+    }
+
+    fn consume_integer(&mut self, error: &str) -> Result<i32, ParseError> {
+        self.consume_integer64(i32::MAX as u64, error)
+            .map(|value| value as i32)
+    }
+
+    fn consume_signed_integer(&mut self, error: &str) -> Result<i32, ParseError> {
+        let negative = self.try_consume("-")?;
+        let max_value = if negative {
+            i32::MAX as u64 + 1
+        } else {
+            i32::MAX as u64
+        };
+        let value = self.consume_integer64(max_value, error)? as i64;
+        let value = if negative { -value } else { value };
+        Ok(value as i32)
+    }
+
+    fn consume_integer64(&mut self, max_value: u64, error: &str) -> Result<u64, ParseError> {
+        if !self.looking_at_kind(TokenKind::Integer) {
+            return Err(self.error(error));
+        }
+        match parse_integer(&self.input.current().text, max_value) {
+            Some(value) => {
+                self.input.next()?;
+                Ok(value)
+            }
+            None => Err(self.error("Integer out of range.")),
+        }
+    }
+
+    fn consume_number(&mut self, error: &str) -> Result<f64, ParseError> {
+        let value = match self.input.current().kind {
+            TokenKind::Float => parse_float(&self.input.current().text),
+            TokenKind::Integer => match parse_integer(&self.input.current().text, u64::MAX) {
+                Some(value) => value as f64,
+                None => return Err(self.error("Integer out of range.")),
+            },
+            _ if self.looking_at("inf") => ::std::f64::INFINITY,
+            _ if self.looking_at("nan") => ::std::f64::NAN,
+            _ => return Err(self.error(error)),
+        };
+        self.input.next()?;
+        Ok(value)
+    }
+
+    /// Consumes a string, concatenating adjacent string literals.
+    fn consume_string(&mut self, error: &str) -> Result<Vec<u8>, ParseError> {
+        if !self.looking_at_kind(TokenKind::String) {
+            return Err(self.error(error));
+        }
+        let mut value = Vec::new();
+        while self.looking_at_kind(TokenKind::String) {
+            parse_string_append(&self.input.current().text, &mut value);
+            self.input.next()?;
+        }
+        Ok(value)
+    }
+
+    fn consume_utf8_string(&mut self, error: &str) -> Result<String, ParseError> {
+        self.consume_string(error)
+            .map(|value| String::from_utf8_lossy(&value).into_owned())
+    }
+
+    // Source locations and comments.
+
+    /// Starts recording a location at the current token.
+    fn record(&mut self, path: Vec<i32>) -> Recorder {
+        let (line, column) = {
+            let token = self.input.current();
+            (token.line, token.column)
+        };
+        self.record_at(path, line, column)
+    }
+
+    fn record_at(&mut self, path: Vec<i32>, line: i32, column: i32) -> Recorder {
+        self.locations.push(Location {
+            path: path.clone(),
+            ..Location::default()
+        });
+        Recorder {
+            index: Some(self.locations.len() - 1),
+            path,
+            line,
+            column,
+        }
+    }
+
+    /// Records the current token as the location of errors in a part of the declaration at `path`.
+    fn record_error_location(&mut self, path: &[i32], part: ErrorLocation) {
+        let token = self.input.current();
+        self.error_locations
+            .entry((path.to_vec(), part))
+            .or_insert((token.line, token.column));
+    }
+
+    /// Completes a location, ending it at the previous token.
+    fn finish(&mut self, recorder: &Recorder) {
+        if let Some(index) = recorder.index {
+            let end = self.input.previous();
+            let span = &mut self.locations[index].span;
+            span.push(recorder.line);
+            span.push(recorder.column);
+            if end.line != recorder.line {
+                span.push(end.line);
+            }
+            span.push(end.end_column);
+        }
+    }
+
+    fn consume_end_of_declaration(
+        &mut self,
+        text: &str,
+        location: Option<&Recorder>,
+    ) -> Result<(), ParseError> {
+        if self.try_consume_end_of_declaration(text, location)? {
+            Ok(())
+        } else {
+            Err(self.error(format!("Expected \"{}\".", text)))
+        }
+    }
+
+    /// Consumes the token ending a declaration, attaching the comments around the declaration to
+    /// its location.
+    ///
+    /// The leading comments of a declaration are the comments preceding its first token, which
+    /// are held until the declaration ends; its trailing comments follow the end of the
+    /// declaration.
+    fn try_consume_end_of_declaration(
+        &mut self,
+        text: &str,
+        location: Option<&Recorder>,
+    ) -> Result<bool, ParseError> {
+        if !self.looking_at(text) {
+            return Ok(false);
+        }
+
+        let comments = self.input.next_with_comments()?;
+        let leading = mem::replace(&mut self.upcoming_doc_comments, comments.leading);
+
+        match location {
+            Some(location) => {
+                let detached =
+                    mem::replace(&mut self.upcoming_detached_comments, comments.detached);
+                if let Some(index) = location.index {
+                    let location = &mut self.locations[index];
+                    if !leading.is_empty() {
+                        location.leading_comments = Some(leading);
+                    }
+                    if !comments.trailing.is_empty() {
+                        location.trailing_comments = Some(comments.trailing);
+                    }
+                    location.leading_detached_comments = detached;
+                }
+            }
+            // Pending detached comments are dropped at the end of a scope.
+            None if text == "}" => self.upcoming_detached_comments = comments.detached,
+            None => self.upcoming_detached_comments.extend(comments.detached),
+        }
+        Ok(true)
+    }
+
+    // Files.
+
+    fn parse_file(&mut self, file: &mut FileDescriptorProto) -> Result<(), ParseError> {
+        let comments = self.input.next_with_comments()?;
+        self.upcoming_detached_comments = comments.detached;
+        self.upcoming_doc_comments = comments.leading;
+
+        let root = self.record(Vec::new());
+        if self.looking_at("syntax") {
+            self.parse_syntax(&root)?;
+        }
+        if self.syntax == "proto3" {
+            file.syntax = Some(self.syntax.clone());
+        }
+
+        while !self.at_end() {
+            self.parse_top_level_statement(file, &root)?;
+        }
+        self.finish(&root);
+        Ok(())
+    }
+
+    fn parse_syntax(&mut self, root: &Recorder) -> Result<(), ParseError> {
+        let location = self.record(root.child(&[12]));
+        self.consume_with(
+            "syntax",
+            "File must begin with a syntax statement, e.g. 'syntax = \"proto2\";'.",
+        )?;
+        self.consume("=")?;
+        let syntax = self.consume_utf8_string("Expected syntax identifier.")?;
+        if syntax != "proto2" && syntax != "proto3" {
+            return Err(self.error(format!(
+                "Unrecognized syntax identifier \"{}\".  This parser only recognizes \"proto2\" \
+                 and \"proto3\".",
+                syntax
+            )));
+        }
+        self.syntax = syntax;
+        self.consume_end_of_declaration(";", Some(&location))?;
+        self.finish(&location);
+        Ok(())
+    }
+
+    fn parse_top_level_statement(
+        &mut self,
+        file: &mut FileDescriptorProto,
+        root: &Recorder,
+    ) -> Result<(), ParseError> {
+        if self.try_consume_end_of_declaration(";", None)? {
+            // An empty statement.
+            Ok(())
+        } else if self.looking_at("message") {
+            let location = self.record(root.child(&[4, file.message_type.len() as i32]));
+            let mut message = DescriptorProto::default();
+            self.parse_message_definition(&mut message, &location)?;
+            file.message_type.push(message);
+            self.finish(&location);
+            Ok(())
+        } else if self.looking_at("enum") {
+            let location = self.record(root.child(&[5, file.enum_type.len() as i32]));
+            let mut enum_type = EnumDescriptorProto::default();
+            self.parse_enum_definition(&mut enum_type, &location)?;
+            file.enum_type.push(enum_type);
+            self.finish(&location);
+            Ok(())
+        } else if self.looking_at("service") {
+            let location = self.record(root.child(&[6, file.service.len() as i32]));
+            let mut service = ServiceDescriptorProto::default();
+            self.parse_service_definition(&mut service, &location)?;
+            file.service.push(service);
+            self.finish(&location);
+            Ok(())
+        } else if self.looking_at("extend") {
+            let location = self.record(root.child(&[7]));
+            self.parse_extend(
+                &mut file.extension,
+                &mut file.message_type,
+                root,
+                4,
+                &location,
+            )?;
+            self.finish(&location);
+            Ok(())
+        } else if self.looking_at("import") {
+            self.parse_import(file, root)
+        } else if self.looking_at("package") {
+            self.parse_package(file, root)
+        } else if self.looking_at("option") {
+            let options = file.options.get_or_insert_with(FileOptions::default);
+            self.parse_option(Options::File(options), &root.path, true)
+        } else {
+            Err(self.error("Expected top-level statement (e.g. \"message\")."))
+        }
+    }
+
+    fn parse_import(
+        &mut self,
+        file: &mut FileDescriptorProto,
+        root: &Recorder,
+    ) -> Result<(), ParseError> {
+        let location = self.record(root.child(&[3, file.dependency.len() as i32]));
+        self.consume("import")?;
+        let index = file.dependency.len() as i32;
+        if self.try_consume("public")? {
+            file.public_dependency.push(index);
+        } else if self.try_consume("weak")? {
+            file.weak_dependency.push(index);
+        }
+        let import = self.consume_utf8_string("Expected a string naming the file to import.")?;
+        file.dependency.push(import);
+        self.consume_end_of_declaration(";", Some(&location))?;
+        self.finish(&location);
+        Ok(())
+    }
+
+    fn parse_package(
+        &mut self,
+        file: &mut FileDescriptorProto,
+        root: &Recorder,
+    ) -> Result<(), ParseError> {
+        if file.package.is_some() {
+            return Err(self.error("Multiple package definitions."));
+        }
+        let location = self.record(root.child(&[2]));
+        self.consume("package")?;
+        let mut package = self.consume_identifier("Expected identifier.")?;
+        while self.try_consume(".")? {
+            package.push('.');
+            package.push_str(&self.consume_identifier("Expected identifier.")?);
+        }
+        file.package = Some(package);
+        self.consume_end_of_declaration(";", Some(&location))?;
+        self.finish(&location);
+        Ok(())
+    }
+
+    // Messages.
+
+    fn parse_message_definition(
+        &mut self,
+        message: &mut DescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.consume("message")?;
+        self.record_error_location(&location.path, ErrorLocation::Name);
+        message.name = Some(self.consume_identifier("Expected message name.")?);
+        self.parse_message_block(message, location)
+    }
+
+    fn parse_message_block(
+        &mut self,
+        message: &mut DescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.consume_end_of_declaration("{", Some(location))?;
+        while !self.try_consume_end_of_declaration("}", None)? {
+            if self.at_end() {
+                return Err(self.error("Reached end of input in message definition (missing '}')."));
+            }
+            self.parse_message_statement(message, location)?;
+        }
+
+        // Ranges extending to `max` end at the largest field number, which depends on the
+        // message options.
+        let max = match message.options {
+            Some(MessageOptions {
+                message_set_wire_format: Some(true),
+                ..
+            }) => i32::MAX,
+            _ => MAX_FIELD_NUMBER + 1,
+        };
+        for range in &mut message.extension_range {
+            if range.end == Some(MAX_RANGE_SENTINEL) {
+                range.end = Some(max);
+            }
+        }
+        for range in &mut message.reserved_range {
+            if range.end == Some(MAX_RANGE_SENTINEL) {
+                range.end = Some(max);
+            }
+        }
+
+        if self.syntax == "proto3" {
+            generate_synthetic_oneofs(message);
+        }
+        Ok(())
+    }
+
+    fn parse_message_statement(
+        &mut self,
+        message: &mut DescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        if self.try_consume_end_of_declaration(";", None)? {
+            // An empty statement.
+        } else if self.looking_at("message") {
+            let nested_location =
+                self.record(location.child(&[3, message.nested_type.len() as i32]));
+            let mut nested = DescriptorProto::default();
+            self.parse_message_definition(&mut nested, &nested_location)?;
+            message.nested_type.push(nested);
+            self.finish(&nested_location);
+        } else if self.looking_at("enum") {
+            let enum_location = self.record(location.child(&[4, message.enum_type.len() as i32]));
+            let mut enum_type = EnumDescriptorProto::default();
+            self.parse_enum_definition(&mut enum_type, &enum_location)?;
+            message.enum_type.push(enum_type);
+            self.finish(&enum_location);
+        } else if self.looking_at("extensions") {
+            let extensions_location = self.record(location.child(&[5]));
+            self.parse_extensions(message, &extensions_location)?;
+            self.finish(&extensions_location);
+        } else if self.looking_at("reserved") {
+            self.parse_reserved(message, location)?;
+        } else if self.looking_at("extend") {
+            let extend_location = self.record(location.child(&[6]));
+            self.parse_extend(
+                &mut message.extension,
+                &mut message.nested_type,
+                location,
+                3,
+                &extend_location,
+            )?;
+            self.finish(&extend_location);
+        } else if self.looking_at("option") {
+            let options = message.options.get_or_insert_with(MessageOptions::default);
+            self.parse_option(Options::Message(options), &location.path, true)?;
+        } else if self.looking_at("oneof") {
+            let oneof_index = message.oneof_decl.len() as i32;
+            let oneof_location = self.record(location.child(&[8, oneof_index]));
+            let mut oneof = OneofDescriptorProto::default();
+            self.parse_oneof(&mut oneof, message, oneof_index, &oneof_location, location)?;
+            message.oneof_decl.push(oneof);
+            self.finish(&oneof_location);
+        } else {
+            let field_location = self.record(location.child(&[2, message.field.len() as i32]));
+            let mut field = FieldDescriptorProto::default();
+            let proto3_optional = self.syntax == "proto3" && self.looking_at("optional");
+            self.parse_message_field(
+                &mut field,
+                &mut message.nested_type,
+                location,
+                3,
+                &field_location,
+            )?;
+            if proto3_optional {
+                field.proto3_optional = Some(true);
+            }
+            message.field.push(field);
+            self.finish(&field_location);
+        }
+        Ok(())
+    }
+
+    /// Parses a field, adding the message types declared by groups and map fields to `messages`.
+    fn parse_message_field(
+        &mut self,
+        field: &mut FieldDescriptorProto,
+        messages: &mut Vec<DescriptorProto>,
+        parent: &Recorder,
+        nested_type_number: i32,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        let label = if self.try_consume("optional")? {
+            Some(Label::Optional)
+        } else if self.try_consume("repeated")? {
+            Some(Label::Repeated)
+        } else if self.try_consume("required")? {
+            Some(Label::Required)
+        } else {
+            None
+        };
+        if let Some(label) = label {
+            field.label = Some(label as i32);
+        }
+        self.parse_message_field_no_label(field, messages, parent, nested_type_number, location)
+    }
+
+    fn parse_message_field_no_label(
+        &mut self,
+        field: &mut FieldDescriptorProto,
+        messages: &mut Vec<DescriptorProto>,
+        parent: &Recorder,
+        nested_type_number: i32,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        let mut map_types = None;
+        let mut field_type = None;
+        self.record_error_location(&location.path, ErrorLocation::Type);
+        if self.try_consume("map")? {
+            if self.looking_at("<") {
+                if field.oneof_index.is_some() {
+                    return Err(self.error("Map fields are not allowed in oneofs."));
+                }
+                if field.label.is_some() {
+                    return Err(self.error(
+                        "Field labels (required/optional/repeated) are not allowed on map fields.",
+                    ));
+                }
+                if field.extendee.is_some() {
+                    return Err(self.error("Map fields are not allowed to be extensions."));
+                }
+                field.label = Some(Label::Repeated as i32);
+                self.consume("<")?;
+                let key = self.parse_type()?;
+                self.consume(",")?;
+                let value = self.parse_type()?;
+                self.consume(">")?;
+                map_types = Some((key, value));
+            } else {
+                // A message or enum type named "map".
+                field_type = Some(FieldType::Named("map".to_string()));
+            }
+        }
+
+        if map_types.is_none() {
+            if field.label.is_none() {
+                if self.syntax != "proto3" {
+                    return Err(self.error("Expected \"required\", \"optional\", or \"repeated\"."));
+                }
+                field.label = Some(Label::Optional as i32);
+            }
+            let field_type = match field_type {
+                Some(field_type) => field_type,
+                None => self.parse_type()?,
+            };
+            set_type(field, field_type);
+        }
+
+        let (name_line, name_column) = {
+            let token = self.input.current();
+            (token.line, token.column)
+        };
+        self.record_error_location(&location.path, ErrorLocation::Name);
+        field.name = Some(self.consume_identifier("Expected field name.")?);
+        self.consume_with("=", "Missing field number.")?;
+        self.record_error_location(&location.path, ErrorLocation::Number);
+        field.number = Some(self.consume_integer("Expected field number.")?);
+        self.parse_field_options(field, location)?;
+
+        if field.type_ == Some(Type::Group as i32) {
+            // A group declares both a nested message type and a field, so the locations overlap.
+            let group_location = self.record_at(
+                parent.child(&[nested_type_number, messages.len() as i32]),
+                location.line,
+                location.column,
+            );
+            self.error_locations.insert(
+                (group_location.path.clone(), ErrorLocation::Name),
+                (name_line, name_column),
+            );
+            let name = field.name.take().unwrap_or_default();
+            if !name.starts_with(|c: char| c.is_ascii_uppercase()) {
+                return Err(ParseError::new(
+                    name_line,
+                    name_column,
+                    "Group names must start with a capital letter.",
+                ));
+            }
+            // The field is named after the group, in lowercase.
+            field.name = Some(name.to_ascii_lowercase());
+            field.type_name = Some(name.clone());
+
+            let mut group = DescriptorProto {
+                name: Some(name),
+                ..DescriptorProto::default()
+            };
+            if !self.looking_at("{") {
+                return Err(self.error("Missing group body."));
+            }
+            self.parse_message_block(&mut group, &group_location)?;
+            messages.push(group);
+            self.finish(&group_location);
+        } else {
+            self.consume_end_of_declaration(";", Some(location))?;
+        }
+
+        if let Some((key, value)) = map_types {
+            messages.push(map_entry(field, key, value));
+        }
+        Ok(())
+    }
+
+    fn parse_type(&mut self) -> Result<FieldType, ParseError> {
+        let scalar = match &self.input.current().text[..] {
+            "double" => Some(Type::Double),
+            "float" => Some(Type::Float),
+            "int64" => Some(Type::Int64),
+            "uint64" => Some(Type::Uint64),
+            "int32" => Some(Type::Int32),
+            "fixed64" => Some(Type::Fixed64),
+            "fixed32" => Some(Type::Fixed32),
+            "bool" => Some(Type::Bool),
+            "string" => Some(Type::String),
+            "group" => Some(Type::Group),
+            "bytes" => Some(Type::Bytes),
+            "uint32" => Some(Type::Uint32),
+            "sfixed32" => Some(Type::Sfixed32),
+            "sfixed64" => Some(Type::Sfixed64),
+            "sint32" => Some(Type::Sint32),
+            "sint64" => Some(Type::Sint64),
+            _ => None,
+        };
+        match scalar {
+            Some(scalar) => {
+                self.input.next()?;
+                Ok(FieldType::Scalar(scalar))
+            }
+            None => self.parse_user_defined_type().map(FieldType::Named),
+        }
+    }
+
+    fn parse_user_defined_type(&mut self) -> Result<String, ParseError> {
+        let mut name = String::new();
+        if self.try_consume(".")? {
+            name.push('.');
+        }
+        name.push_str(&self.consume_identifier("Expected type name.")?);
+        while self.try_consume(".")? {
+            name.push('.');
+            name.push_str(&self.consume_identifier("Expected identifier.")?);
+        }
+        Ok(name)
+    }
+
+    fn parse_field_options(
+        &mut self,
+        field: &mut FieldDescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        if !self.try_consume("[")? {
+            return Ok(());
+        }
+        loop {
+            if self.looking_at("default") {
+                self.parse_default_assignment(field, location)?;
+            } else if self.looking_at("json_name") {
+                if field.json_name.is_some() {
+                    return Err(self.error("Already set option \"json_name\"."));
+                }
+                self.consume("json_name")?;
+                self.consume("=")?;
+                field.json_name = Some(self.consume_utf8_string("Expected string for JSON name.")?);
+            } else {
+                let options = field.options.get_or_insert_with(FieldOptions::default);
+                self.parse_option(Options::Field(options), &location.path, false)?;
+            }
+            if !self.try_consume(",")? {
+                break;
+            }
+        }
+        self.consume("]")
+    }
+
+    /// Parses a default value, formatting it the way `protoc` does.
+    fn parse_default_assignment(
+        &mut self,
+        field: &mut FieldDescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        if field.default_value.is_some() {
+            return Err(self.error("Already set option \"default\"."));
+        }
+        self.consume("default")?;
+        self.consume("=")?;
+        self.record_error_location(&location.path, ErrorLocation::DefaultValue);
+
+        let field_type = match field.type_.and_then(Type::from_i32) {
+            Some(field_type) => field_type,
+            None => {
+                // The type is a message or enum type, which isn't known until types are
+                // resolved. The value is checked once the type is known.
+                field.default_value = Some(self.input.current().text.clone());
+                self.input.next()?;
+                return Ok(());
+            }
+        };
+
+        let default_value = match field_type {
+            Type::Int32
+            | Type::Int64
+            | Type::Sint32
+            | Type::Sint64
+            | Type::Sfixed32
+            | Type::Sfixed64 => {
+                let mut max_value = match field_type {
+                    Type::Int32 | Type::Sint32 | Type::Sfixed32 => i32::MAX as u64,
+                    _ => i64::MAX as u64,
+                };
+                let negative = self.try_consume("-")?;
+                if negative {
+                    // Two's complement has one more negative value than positive.
+                    max_value += 1;
+                }
+                let value =
+                    self.consume_integer64(max_value, "Expected integer for field default value.")?;
+                if negative && value != 0 {
+                    format!("-{}", value)
+                } else {
+                    value.to_string()
+                }
+            }
+            Type::Uint32 | Type::Uint64 | Type::Fixed32 | Type::Fixed64 => {
+                let max_value = match field_type {
+                    Type::Uint32 | Type::Fixed32 => u64::from(u32::MAX),
+                    _ => u64::MAX,
+                };
+                if self.looking_at("-") {
+                    return Err(self.error("Unsigned field can't have negative default value."));
+                }
+                self.consume_integer64(max_value, "Expected integer for field default value.")?
+                    .to_string()
+            }
+            Type::Float | Type::Double => {
+                let negative = self.try_consume("-")?;
+                let mut value = self.consume_number("Expected number.")?;
+                if negative {
+                    value = -value;
+                }
+                if field_type == Type::Float {
+                    simple_ftoa(value as f32)
+                } else {
+                    simple_dtoa(value)
+                }
+            }
+            Type::Bool => {
+                if self.try_consume("true")? {
+                    "true".to_string()
+                } else if self.try_consume("false")? {
+                    "false".to_string()
+                } else {
+                    return Err(self.error("Expected \"true\" or \"false\"."));
+                }
+            }
+            Type::String => self.consume_utf8_string("Expected string for field default value.")?,
+            Type::Bytes => c_escape(&self.consume_string("Expected string.")?),
+            Type::Enum => {
+                self.consume_identifier("Expected enum identifier for field default value.")?
+            }
+            Type::Message | Type::Group => {
+                return Err(self.error("Messages can't have default values."));
+            }
+        };
+        field.default_value = Some(default_value);
+        Ok(())
+    }
+
+    fn parse_oneof(
+        &mut self,
+        oneof: &mut OneofDescriptorProto,
+        message: &mut DescriptorProto,
+        oneof_index: i32,
+        oneof_location: &Recorder,
+        message_location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.consume("oneof")?;
+        oneof.name = Some(self.consume_identifier("Expected oneof name.")?);
+        self.consume_end_of_declaration("{", Some(oneof_location))?;
+
+        loop {
+            if self.at_end() {
+                return Err(self.error("Reached end of input in oneof definition (missing '}')."));
+            }
+
+            if self.looking_at("option") {
+                oneof.options.get_or_insert_with(OneofOptions::default);
+                self.parse_option(Options::Oneof, &oneof_location.path, true)?;
+            } else {
+                if self.looking_at("required")
+                    || self.looking_at("optional")
+                    || self.looking_at("repeated")
+                {
+                    return Err(self.error(
+                        "Fields in oneofs must not have labels (required / optional / repeated).",
+                    ));
+                }
+                let field_location =
+                    self.record(message_location.child(&[2, message.field.len() as i32]));
+                let mut field = FieldDescriptorProto {
+                    label: Some(Label::Optional as i32),
+                    oneof_index: Some(oneof_index),
+                    ..FieldDescriptorProto::default()
+                };
+                self.parse_message_field_no_label(
+                    &mut field,
+                    &mut message.nested_type,
+                    message_location,
+                    3,
+                    &field_location,
+                )?;
+                message.field.push(field);
+                self.finish(&field_location);
+            }
+
+            if self.try_consume_end_of_declaration("}", None)? {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse_extensions(
+        &mut self,
+        message: &mut DescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.consume("extensions")?;
+        let first_range = message.extension_range.len();
+        loop {
+            let start = self.consume_integer("Expected field number range.")?;
+            let end = if self.try_consume("to")? {
+                if self.try_consume("max")? {
+                    MAX_RANGE_SENTINEL
+                } else {
+                    self.consume_integer("Expected integer.")?.wrapping_add(1)
+                }
+            } else {
+                start.wrapping_add(1)
+            };
+            // The end of the range is exclusive.
+            message.extension_range.push(ExtensionRange {
+                start: Some(start),
+                end: Some(end),
+                options: None,
+            });
+            if !self.try_consume(",")? {
+                break;
+            }
+        }
+
+        if self.try_consume("[")? {
+            // The options apply to all ranges of the statement.
+            let options = ExtensionRangeOptions::default();
+            loop {
+                self.parse_option(Options::ExtensionRange, &location.path, false)?;
+                if !self.try_consume(",")? {
+                    break;
+                }
+            }
+            self.consume("]")?;
+            for range in &mut message.extension_range[first_range..] {
+                range.options = Some(options.clone());
+            }
+        }
+
+        self.consume_end_of_declaration(";", Some(location))
+    }
+
+    fn parse_reserved(
+        &mut self,
+        message: &mut DescriptorProto,
+        message_location: &Recorder,
+    ) -> Result<(), ParseError> {
+        let (line, column) = {
+            let token = self.input.current();
+            (token.line, token.column)
+        };
+        self.consume("reserved")?;
+        if self.looking_at_kind(TokenKind::String) {
+            let location = self.record_at(message_location.child(&[10]), line, column);
+            self.parse_reserved_names(&mut message.reserved_name, &location)?;
+            self.finish(&location);
+            return Ok(());
+        }
+
+        let location = self.record_at(message_location.child(&[9]), line, column);
+        let mut first = true;
+        loop {
+            let start = self.consume_integer(if first {
+                "Expected field name or number range."
+            } else {
+                "Expected field number range."
+            })?;
+            let end = if self.try_consume("to")? {
+                if self.try_consume("max")? {
+                    MAX_RANGE_SENTINEL
+                } else {
+                    self.consume_integer("Expected integer.")?.wrapping_add(1)
+                }
+            } else {
+                start.wrapping_add(1)
+            };
+            message.reserved_range.push(ReservedRange {
+                start: Some(start),
+                end: Some(end),
+            });
+            first = false;
+            if !self.try_consume(",")? {
+                break;
+            }
+        }
+        self.consume_end_of_declaration(";", Some(&location))?;
+        self.finish(&location);
+        Ok(())
+    }
+
+    fn parse_reserved_names(
+        &mut self,
+        names: &mut Vec<String>,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        loop {
+            names.push(self.consume_utf8_string("Expected field name.")?);
+            if !self.try_consume(",")? {
+                break;
+            }
+        }
+        self.consume_end_of_declaration(";", Some(location))
+    }
+
+    /// Parses an `extend` block, adding the extension fields to `extensions`.
+    fn parse_extend(
+        &mut self,
+        extensions: &mut Vec<FieldDescriptorProto>,
+        messages: &mut Vec<DescriptorProto>,
+        parent: &Recorder,
+        nested_type_number: i32,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.consume("extend")?;
+        let extendee = self.parse_user_defined_type()?;
+        self.consume_end_of_declaration("{", Some(location))?;
+
+        loop {
+            if self.at_end() {
+                return Err(self.error("Reached end of input in extend definition (missing '}')."));
+            }
+            let field_location = self.record(location.child(&[extensions.len() as i32]));
+            let mut field = FieldDescriptorProto {
+                extendee: Some(extendee.clone()),
+                ..FieldDescriptorProto::default()
+            };
+            self.parse_message_field(
+                &mut field,
+                messages,
+                parent,
+                nested_type_number,
+                &field_location,
+            )?;
+            extensions.push(field);
+            self.finish(&field_location);
+
+            if self.try_consume_end_of_declaration("}", None)? {
+                return Ok(());
+            }
+        }
+    }
+
+    // Enums.
+
+    fn parse_enum_definition(
+        &mut self,
+        enum_type: &mut EnumDescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.consume("enum")?;
+        self.record_error_location(&location.path, ErrorLocation::Name);
+        enum_type.name = Some(self.consume_identifier("Expected enum name.")?);
+        self.consume_end_of_declaration("{", Some(location))?;
+
+        while !self.try_consume_end_of_declaration("}", None)? {
+            if self.at_end() {
+                return Err(self.error("Reached end of input in enum definition (missing '}')."));
+            }
+            self.parse_enum_statement(enum_type, location)?;
+        }
+        Ok(())
+    }
+
+    fn parse_enum_statement(
+        &mut self,
+        enum_type: &mut EnumDescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        if self.try_consume_end_of_declaration(";", None)? {
+            // An empty statement.
+            Ok(())
+        } else if self.looking_at("option") {
+            let options = enum_type.options.get_or_insert_with(EnumOptions::default);
+            self.parse_option(Options::Enum(options), &location.path, true)
+        } else if self.looking_at("reserved") {
+            self.parse_enum_reserved(enum_type, location)
+        } else {
+            let value_location = self.record(location.child(&[2, enum_type.value.len() as i32]));
+            let mut value = EnumValueDescriptorProto::default();
+            self.parse_enum_constant(&mut value, &value_location)?;
+            enum_type.value.push(value);
+            self.finish(&value_location);
+            Ok(())
+        }
+    }
+
+    fn parse_enum_constant(
+        &mut self,
+        value: &mut EnumValueDescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.record_error_location(&location.path, ErrorLocation::Name);
+        value.name = Some(self.consume_identifier("Expected enum constant name.")?);
+        self.consume_with("=", "Missing numeric value for enum constant.")?;
+        self.record_error_location(&location.path, ErrorLocation::Number);
+        value.number = Some(self.consume_signed_integer("Expected integer.")?);
+
+        if self.try_consume("[")? {
+            loop {
+                let options = value.options.get_or_insert_with(EnumValueOptions::default);
+                self.parse_option(Options::EnumValue(options), &location.path, false)?;
+                if !self.try_consume(",")? {
+                    break;
+                }
+            }
+            self.consume("]")?;
+        }
+
+        self.consume_end_of_declaration(";", Some(location))
+    }
+
+    fn parse_enum_reserved(
+        &mut self,
+        enum_type: &mut EnumDescriptorProto,
+        enum_location: &Recorder,
+    ) -> Result<(), ParseError> {
+        let (line, column) = {
+            let token = self.input.current();
+            (token.line, token.column)
+        };
+        self.consume("reserved")?;
+        if self.looking_at_kind(TokenKind::String) {
+            let location = self.record_at(enum_location.child(&[5]), line, column);
+            self.parse_reserved_names(&mut enum_type.reserved_name, &location)?;
+            self.finish(&location);
+            return Ok(());
+        }
+
+        let location = self.record_at(enum_location.child(&[4]), line, column);
+        let mut first = true;
+        loop {
+            let start = self.consume_signed_integer(if first {
+                "Expected enum value or number range."
+            } else {
+                "Expected enum number range."
+            })?;
+            // The end of an enum range is inclusive.
+            let end = if self.try_consume("to")? {
+                if self.try_consume("max")? {
+                    i32::MAX
+                } else {
+                    self.consume_signed_integer("Expected integer.")?
+                }
+            } else {
+                start
+            };
+            enum_type.reserved_range.push(EnumReservedRange {
+                start: Some(start),
+                end: Some(end),
+            });
+            first = false;
+            if !self.try_consume(",")? {
+                break;
+            }
+        }
+        self.consume_end_of_declaration(";", Some(&location))?;
+        self.finish(&location);
+        Ok(())
+    }
+
+    // Services.
+
+    fn parse_service_definition(
+        &mut self,
+        service: &mut ServiceDescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.consume("service")?;
+        service.name = Some(self.consume_identifier("Expected service name.")?);
+        self.consume_end_of_declaration("{", Some(location))?;
+
+        while !self.try_consume_end_of_declaration("}", None)? {
+            if self.at_end() {
+                return Err(self.error("Reached end of input in service definition (missing '}')."));
+            }
+            if self.try_consume_end_of_declaration(";", None)? {
+                // An empty statement.
+            } else if self.looking_at("option") {
+                let options = service.options.get_or_insert_with(ServiceOptions::default);
+                self.parse_option(Options::Service(options), &location.path, true)?;
+            } else {
+                let method_location =
+                    self.record(location.child(&[2, service.method.len() as i32]));
+                let mut method = MethodDescriptorProto::default();
+                self.parse_service_method(&mut method, &method_location)?;
+                service.method.push(method);
+                self.finish(&method_location);
+            }
+        }
+        Ok(())
+    }
+
+    fn parse_service_method(
+        &mut self,
+        method: &mut MethodDescriptorProto,
+        location: &Recorder,
+    ) -> Result<(), ParseError> {
+        self.consume("rpc")?;
+        method.name = Some(self.consume_identifier("Expected method name.")?);
+
+        self.consume("(")?;
+        if self.try_consume("stream")? {
+            method.client_streaming = Some(true);
+        }
+        method.input_type = Some(self.parse_user_defined_type()?);
+        self.consume(")")?;
+
+        self.consume("returns")?;
+        self.consume("(")?;
+        if self.try_consume("stream")? {
+            method.server_streaming = Some(true);
+        }
+        method.output_type = Some(self.parse_user_defined_type()?);
+        self.consume(")")?;
+
+        if !self.looking_at("{") {
+            return self.consume_end_of_declaration(";", Some(location));
+        }
+
+        self.consume_end_of_declaration("{", Some(location))?;
+        while !self.try_consume_end_of_declaration("}", None)? {
+            if self.at_end() {
+                return Err(self.error("Reached end of input in method options (missing '}')."));
+            }
+            if !self.try_consume_end_of_declaration(";", None)? {
+                let options = method.options.get_or_insert_with(MethodOptions::default);
+                self.parse_option(Options::Method(options), &location.path, true)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Options.
+
+    /// Parses an option statement, or an option in a bracketed list when `statement` is false.
+    ///
+    /// `owner` is the location path of the declaration the option belongs to.
+    fn parse_option(
+        &mut self,
+        options: Options,
+        owner: &[i32],
+        statement: bool,
+    ) -> Result<(), ParseError> {
+        if statement {
+            self.consume("option")?;
+        }
+        let (line, column) = {
+            let token = self.input.current();
+            (token.line, token.column)
+        };
+
+        let mut name = vec![self.parse_option_name_part()?];
+        while self.try_consume(".")? {
+            name.push(self.parse_option_name_part()?);
+        }
+        self.consume("=")?;
+        let value = self.parse_option_value()?;
+
+        self.interpret_option(options, owner, &name, value, line, column)
+            .map_err(|message| ParseError::new(line, column, message))?;
+
+        if statement {
+            // Options are not recorded, but their comments must not be attached elsewhere.
+            self.consume_end_of_declaration(";", Some(&Recorder::unrecorded()))?;
+        }
+        Ok(())
+    }
+
+    /// Parses a part of an option name, returning the name and whether it names an extension.
+    fn parse_option_name_part(&mut self) -> Result<(String, bool), ParseError> {
+        if !self.try_consume("(")? {
+            return self
+                .consume_identifier("Expected identifier.")
+                .map(|name| (name, false));
+        }
+        let mut name = String::new();
+        if self.looking_at_kind(TokenKind::Identifier) {
+            name.push_str(&self.consume_identifier("Expected identifier.")?);
+        }
+        while self.try_consume(".")? {
+            name.push('.');
+            name.push_str(&self.consume_identifier("Expected identifier.")?);
+        }
+        self.consume(")")?;
+        Ok((name, true))
+    }
+
+    fn parse_option_value(&mut self) -> Result<OptionValue, ParseError> {
+        let negative = self.try_consume("-")?;
+        match self.input.current().kind {
+            TokenKind::Start | TokenKind::End => {
+                Err(self.error("Unexpected end of stream while parsing option value."))
+            }
+            TokenKind::Identifier => {
+                if negative {
+                    return Err(self.error("Invalid '-' symbol before identifier."));
+                }
+                self.consume_identifier("Expected identifier.")
+                    .map(OptionValue::Identifier)
+            }
+            TokenKind::Integer => {
+                let max_value = if negative {
+                    i64::MAX as u64 + 1
+                } else {
+                    u64::MAX
+                };
+                self.consume_integer64(max_value, "Expected integer.")
+                    .map(|value| OptionValue::Integer(value, negative))
+            }
+            TokenKind::Float => {
+                self.consume_number("Expected number.")?;
+                Ok(OptionValue::Float)
+            }
+            TokenKind::String => {
+                if negative {
+                    return Err(self.error("Invalid '-' symbol before string."));
+                }
+                self.consume_string("Expected string.")
+                    .map(OptionValue::String)
+            }
+            TokenKind::Symbol => {
+                if !self.looking_at("{") {
+                    return Err(self.error("Expected option value."));
+                }
+                self.skip_aggregate_value()?;
+                Ok(OptionValue::Aggregate)
+            }
+        }
+    }
+
+    /// Skips an aggregate option value in braces.
+    fn skip_aggregate_value(&mut self) -> Result<(), ParseError> {
+        self.consume("{")?;
+        let mut depth = 1;
+        while !self.at_end() {
+            if self.looking_at("{") {
+                depth += 1;
+            } else if self.looking_at("}") {
+                depth -= 1;
+                if depth == 0 {
+                    self.input.next()?;
+                    return Ok(());
+                }
+            }
+            self.input.next()?;
+        }
+        Err(self.error("Unexpected end of stream while parsing aggregate value."))
+    }
+
+    /// Sets a standard option on its options message.
+    ///
+    /// Custom options are extensions, which the `prost_types` options messages can't hold. The
+    /// custom options of messages and fields are collected separately, and the others are ignored.
+    fn interpret_option(
+        &mut self,
+        options: Options,
+        owner: &[i32],
+        name: &[(String, bool)],
+        value: OptionValue,
+        line: i32,
+        column: i32,
+    ) -> Result<(), String> {
+        if name[0].1 {
+            match options {
+                Options::Message(..) | Options::Field(..) if name.len() == 1 => {
+                    self.custom_options.push(CustomOption {
+                        owner: owner.to_vec(),
+                        name: name[0].0.clone(),
+                        value,
+                        line,
+                        column,
+                    })
+                }
+                _ => (),
+            }
+            return Ok(());
+        }
+
+        let option = &name[0].0[..];
+        if name.len() > 1 {
+            return Err(format!(
+                "Option \"{}\" is an atomic type, not a message.",
+                option
+            ));
+        }
+        let unknown = || Err(format!("Option \"{}\" unknown.", option));
+
+        match options {
+            Options::File(options) => {
+                let full_name = format!("google.protobuf.FileOptions.{}", option);
+                let full_name = &full_name[..];
+                match option {
+                    "java_package" => options.java_package = Some(string_value(&value, full_name)?),
+                    "java_outer_classname" => {
+                        options.java_outer_classname = Some(string_value(&value, full_name)?)
+                    }
+                    "java_multiple_files" => {
+                        options.java_multiple_files = Some(bool_value(&value, full_name)?)
+                    }
+                    "java_generate_equals_and_hash" => {
+                        options.java_generate_equals_and_hash = Some(bool_value(&value, full_name)?)
+                    }
+                    "java_string_check_utf8" => {
+                        options.java_string_check_utf8 = Some(bool_value(&value, full_name)?)
+                    }
+                    "optimize_for" => {
+                        options.optimize_for = Some(enum_value(
+                            &value,
+                            full_name,
+                            "google.protobuf.FileOptions.OptimizeMode",
+                            &[("SPEED", 1), ("CODE_SIZE", 2), ("LITE_RUNTIME", 3)],
+                        )?)
+                    }
+                    "go_package" => options.go_package = Some(string_value(&value, full_name)?),
+                    "cc_generic_services" => {
+                        options.cc_generic_services = Some(bool_value(&value, full_name)?)
+                    }
+                    "java_generic_services" => {
+                        options.java_generic_services = Some(bool_value(&value, full_name)?)
+                    }
+                    "py_generic_services" => {
+                        options.py_generic_services = Some(bool_value(&value, full_name)?)
+                    }
+                    "php_generic_services" => {
+                        options.php_generic_services = Some(bool_value(&value, full_name)?)
+                    }
+                    "deprecated" => options.deprecated = Some(bool_value(&value, full_name)?),
+                    "cc_enable_arenas" => {
+                        options.cc_enable_arenas = Some(bool_value(&value, full_name)?)
+                    }
+                    "objc_class_prefix" => {
+                        options.objc_class_prefix = Some(string_value(&value, full_name)?)
+                    }
+                    "csharp_namespace" => {
+                        options.csharp_namespace = Some(string_value(&value, full_name)?)
+                    }
+                    "swift_prefix" => options.swift_prefix = Some(string_value(&value, full_name)?),
+                    "php_class_prefix" => {
+                        options.php_class_prefix = Some(string_value(&value, full_name)?)
+                    }
+                    "php_namespace" => {
+                        options.php_namespace = Some(string_value(&value, full_name)?)
+                    }
+                    _ => return unknown(),
+                }
+            }
+            Options::Message(options) => {
+                let full_name = format!("google.protobuf.MessageOptions.{}", option);
+                let full_name = &full_name[..];
+                match option {
+                    "message_set_wire_format" => {
+                        options.message_set_wire_format = Some(bool_value(&value, full_name)?)
+                    }
+                    "no_standard_descriptor_accessor" => {
+                        options.no_standard_descriptor_accessor =
+                            Some(bool_value(&value, full_name)?)
+                    }
+                    "deprecated" => options.deprecated = Some(bool_value(&value, full_name)?),
+                    "map_entry" => {
+                        return Err("map_entry should not be set explicitly. Use \
+                                    map<KeyType, ValueType> instead."
+                            .to_string());
+                    }
+                    _ => return unknown(),
+                }
+            }
+            Options::Field(options) => {
+                let full_name = format!("google.protobuf.FieldOptions.{}", option);
+                let full_name = &full_name[..];
+                match option {
+                    "ctype" => {
+                        options.ctype = Some(enum_value(
+                            &value,
+                            full_name,
+                            "google.protobuf.FieldOptions.CType",
+                            &[("STRING", 0), ("CORD", 1), ("STRING_PIECE", 2)],
+                        )?)
+                    }
+                    "packed" => options.packed = Some(bool_value(&value, full_name)?),
+                    "jstype" => {
+                        options.jstype = Some(enum_value(
+                            &value,
+                            full_name,
+                            "google.protobuf.FieldOptions.JSType",
+                            &[("JS_NORMAL", 0), ("JS_STRING", 1), ("JS_NUMBER", 2)],
+                        )?)
+                    }
+                    "lazy" => options.lazy = Some(bool_value(&value, full_name)?),
+                    "deprecated" => options.deprecated = Some(bool_value(&value, full_name)?),
+                    "weak" => options.weak = Some(bool_value(&value, full_name)?),
+                    _ => return unknown(),
+                }
+            }
+            Options::Enum(options) => {
+                let full_name = format!("google.protobuf.EnumOptions.{}", option);
+                match option {
+                    "allow_alias" => options.allow_alias = Some(bool_value(&value, &full_name)?),
+                    "deprecated" => options.deprecated = Some(bool_value(&value, &full_name)?),
+                    _ => return unknown(),
+                }
+            }
+            Options::EnumValue(options) => {
+                let full_name = format!("google.protobuf.EnumValueOptions.{}", option);
+                match option {
+                    "deprecated" => options.deprecated = Some(bool_value(&value, &full_name)?),
+                    _ => return unknown(),
+                }
+            }
+            Options::Service(options) => {
+                let full_name = format!("google.protobuf.ServiceOptions.{}", option);
+                match option {
+                    "deprecated" => options.deprecated = Some(bool_value(&value, &full_name)?),
+                    _ => return unknown(),
+                }
+            }
+            Options::Method(options) => {
+                let full_name = format!("google.protobuf.MethodOptions.{}", option);
+                let full_name = &full_name[..];
+                match option {
+                    "deprecated" => options.deprecated = Some(bool_value(&value, full_name)?),
+                    "idempotency_level" => {
+                        options.idempotency_level = Some(enum_value(
+                            &value,
+                            full_name,
+                            "google.protobuf.MethodOptions.IdempotencyLevel",
+                            &[
+                                ("IDEMPOTENCY_UNKNOWN", 0),
+                                ("NO_SIDE_EFFECTS", 1),
+                                ("IDEMPOTENT", 2),
+                            ],
+                        )?)
+                    }
+                    _ => return unknown(),
+                }
+            }
+            Options::Oneof | Options::ExtensionRange => return unknown(),
+        }
+        Ok(())
+    }
+}
+
+fn set_type(field: &mut FieldDescriptorProto, field_type: FieldType) {
+    match field_type {
+        FieldType::Scalar(scalar) => field.type_ = Some(scalar as i32),
+        FieldType::Named(name) => field.type_name = Some(name),
+    }
+}
+
+/// Returns the message type of the entries of a map field, and sets the field type to it.
+fn map_entry(
+    field: &mut FieldDescriptorProto,
+    key: FieldType,
+    value: FieldType,
+) -> DescriptorProto {
+    let name = map_entry_name(field.name.as_ref().unwrap());
+    field.type_name = Some(name.clone());
+
+    let mut key_field = FieldDescriptorProto {
+        name: Some("key".to_string()),
+        number: Some(1),
+        label: Some(Label::Optional as i32),
+        ..FieldDescriptorProto::default()
+    };
+    set_type(&mut key_field, key);
+    let mut value_field = FieldDescriptorProto {
+        name: Some("value".to_string()),
+        number: Some(2),
+        label: Some(Label::Optional as i32),
+        ..FieldDescriptorProto::default()
+    };
+    set_type(&mut value_field, value);
+
+    DescriptorProto {
+        name: Some(name),
+        field: vec![key_field, value_field],
+        options: Some(MessageOptions {
+            map_entry: Some(true),
+            ..MessageOptions::default()
+        }),
+        ..DescriptorProto::default()
+    }
+}
+
+/// Adds a oneof for each proto3 optional field of the message, as protoc does, so that older
+/// proto3 implementations track the presence of the fields. The oneofs are named after the fields,
+/// prefixed with `_`, and follow the declared oneofs.
+fn generate_synthetic_oneofs(message: &mut DescriptorProto) {
+    let mut names = message
+        .field
+        .iter()
+        .map(|field| field.name().to_string())
+        .chain(
+            message
+                .oneof_decl
+                .iter()
+                .map(|oneof| oneof.name().to_string()),
+        )
+        .collect::<HashSet<_>>();
+    for field in &mut message.field {
+        if !field.proto3_optional() {
+            continue;
+        }
+        let mut name = field.name().to_string();
+        if !name.starts_with('_') {
+            name.insert(0, '_');
+        }
+        while names.contains(&name) {
+            name.insert(0, 'X');
+        }
+        names.insert(name.clone());
+        field.oneof_index = Some(message.oneof_decl.len() as i32);
+        message.oneof_decl.push(OneofDescriptorProto {
+            name: Some(name),
+            ..OneofDescriptorProto::default()
+        });
+    }
+}
+
+/// Returns the name of the entry message type of a map field, e.g. `FooBarEntry` for `foo_bar`.
+fn map_entry_name(field_name: &str) -> String {
+    let mut name = String::with_capacity(field_name.len() + 5);
+    let mut capitalize_next = true;
+    for c in field_name.chars() {
+        if c == '_' {
+            capitalize_next = true;
+        } else if capitalize_next {
+            name.push(c.to_ascii_uppercase());
+            capitalize_next = false;
+        } else {
+            name.push(c);
+        }
+    }
+    name.push_str("Entry");
+    name
+}
+
+pub fn bool_value(value: &OptionValue, option: &str) -> Result<bool, String> {
+    match *value {
+        OptionValue::Identifier(ref identifier) if identifier == "true" => Ok(true),
+        OptionValue::Identifier(ref identifier) if identifier == "false" => Ok(false),
+        OptionValue::Identifier(..) => Err(format!(
+            "Value must be \"true\" or \"false\" for boolean option \"{}\".",
+            option
+        )),
+        _ => Err(format!(
+            "Value must be identifier for boolean option \"{}\".",
+            option
+        )),
+    }
+}
+
+pub fn string_value(value: &OptionValue, option: &str) -> Result<String, String> {
+    match *value {
+        OptionValue::String(ref value) => Ok(String::from_utf8_lossy(value).into_owned()),
+        _ => Err(format!(
+            "Value must be quoted string for string option \"{}\".",
+            option
+        )),
+    }
+}
+
+fn enum_value(
+    value: &OptionValue,
+    option: &str,
+    enum_name: &str,
+    values: &[(&str, i32)],
+) -> Result<i32, String> {
+    match *value {
+        OptionValue::Identifier(ref identifier) => values
+            .iter()
+            .find(|&&(name, _)| name == identifier)
+            .map(|&(_, number)| number)
+            .ok_or_else(|| {
+                format!(
+                    "Enum type \"{}\" has no value named \"{}\" for option \"{}\".",
+                    enum_name, identifier, option
+                )
+            }),
+        _ => Err(format!(
+            "Value must be identifier for enum-valued option \"{}\".",
+            option
+        )),
+    }
+}
+
+/// Escapes bytes the way `protoc` escapes default values of `bytes` fields.
+fn c_escape(bytes: &[u8]) -> String {
+    let mut escaped = String::with_capacity(bytes.len());
+    for &byte in bytes {
+        match byte {
+            b'\n' => escaped.push_str("\\n"),
+            b'\r' => escaped.push_str("\\r"),
+            b'\t' => escaped.push_str("\\t"),
+            b'"' => escaped.push_str("\\\""),
+            b'\'' => escaped.push_str("\\'"),
+            b'\\' => escaped.push_str("\\\\"),
+            b' '..=b'~' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("\\{:03o}", byte)),
+        }
+    }
+    escaped
+}
+
+/// Formats a double like `protoc`, with the shortest of 15 or 17 significant digits which
+/// round-trips.
+fn simple_dtoa(value: f64) -> String {
+    if let Some(special) = special_float(value) {
+        return special.to_string();
+    }
+    let short = format_g(value, 15);
+    if short.parse::<f64>().ok() == Some(value) {
+        short
+    } else {
+        format_g(value, 17)
+    }
+}
+
+/// Formats a float like `protoc`, with the shortest of 6 or 9 significant digits which
+/// round-trips.
+fn simple_ftoa(value: f32) -> String {
+    if let Some(special) = special_float(f64::from(value)) {
+        return special.to_string();
+    }
+    let short = format_g(f64::from(value), 6);
+    if short.parse::<f32>().ok() == Some(value) {
+        short
+    } else {
+        format_g(f64::from(value), 9)
+    }
+}
+
+fn special_float(value: f64) -> Option<&'static str> {
+    if value.is_nan() {
+        Some("nan")
+    } else if value == ::std::f64::INFINITY {
+        Some("inf")
+    } else if value == ::std::f64::NEG_INFINITY {
+        Some("-inf")
+    } else {
+        None
+    }
+}
+
+/// Formats a finite value like the `%.<precision>g` format of `printf`.
+fn format_g(value: f64, precision: usize) -> String {
+    if value == 0.0 {
+        return if value.is_sign_negative() { "-0" } else { "0" }.to_string();
+    }
+
+    // Round to the precision to find the decimal exponent.
+    let scientific = format!("{:.*e}", precision - 1, value);
+    let exponent_index = scientific.find('e').unwrap();
+    let exponent: i32 = scientific[exponent_index + 1..].parse().unwrap();
+
+    if exponent < -4 || exponent >= precision as i32 {
+        let mantissa = strip_trailing_zeros(&scientific[..exponent_index]);
+        let sign = if exponent < 0 { '-' } else { '+' };
+        format!("{}e{}{:02}", mantissa, sign, exponent.abs())
+    } else {
+        let decimals = (precision as i32 - 1 - exponent) as usize;
+        strip_trailing_zeros(&format!("{:.*}", decimals, value)).to_string()
+    }
+}
+
+fn strip_trailing_zeros(number: &str) -> &str {
+    if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_floats() {
+        assert_eq!(simple_dtoa(0.1), "0.1");
+        assert_eq!(simple_dtoa(1e20), "1e+20");
+        assert_eq!(simple_dtoa(1.5e-7), "1.5e-07");
+        assert_eq!(simple_dtoa(123456789.0), "123456789");
+        assert_eq!(simple_dtoa(-0.0), "-0");
+        assert_eq!(simple_dtoa(::std::f64::NEG_INFINITY), "-inf");
+        assert_eq!(simple_dtoa(0.1 + 0.2), "0.30000000000000004");
+        assert_eq!(simple_ftoa(0.1), "0.1");
+        assert_eq!(simple_ftoa(::std::f32::consts::PI), "3.14159274");
+        assert_eq!(simple_ftoa(1e10), "1e+10");
+    }
+
+    #[test]
+    fn test_c_escape() {
+        assert_eq!(c_escape(b"a\"b\0\n\xff"), "a\\\"b\\000\\n\\377");
+    }
+
+    #[test]
+    fn test_map_entry_name() {
+        assert_eq!(map_entry_name("map_a"), "MapAEntry");
+        assert_eq!(map_entry_name("t"), "TEntry");
+        assert_eq!(map_entry_name("fooBar_baz"), "FooBarBazEntry");
+    }
+}
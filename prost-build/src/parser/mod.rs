@@ -0,0 +1,762 @@
+//! A built-in parser for `.proto` files, used instead of `protoc` when configured with
+//! `Config::builtin_parser`.
+//!
+//! The parser produces the same `FileDescriptorSet` as `protoc --include_imports
+//! --include_source_info`: files are listed with their imports first, type names are fully
+//! qualified, and the source code info holds the locations and comments of the declarations
+//! (syntax, package, imports, messages, fields, oneofs, enums, enum values, services, methods,
+//! extensions and reserved ranges). Custom options are not retained in the descriptors; the
+//! custom options of messages and fields are returned separately.
+//!
+//! Once the type names are resolved, the files are validated like `protoc` validates them, and
+//! errors such as conflicting field numbers are reported with the messages `protoc` uses.
+
+mod grammar;
+mod resolve;
+mod tokenizer;
+mod validate;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Component, Path, PathBuf};
+
+use prost_types::field_descriptor_proto::Type;
+use prost_types::{DescriptorProto, FieldDescriptorProto, FileDescriptorProto, FileDescriptorSet};
+
+use self::grammar::{CustomOption, OptionValue};
+use amino;
+use custom_options::{CustomOptions, Value};
+use gogo;
+
+/// An error in a `.proto` file, located by its 0-based line and column.
+#[derive(Debug)]
+pub struct ParseError {
+    line: i32,
+    column: i32,
+    message: String,
+}
+
+impl ParseError {
+    fn new<S>(line: i32, column: i32, message: S) -> ParseError
+    where
+        S: Into<String>,
+    {
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Lines and columns are reported 1-based, like `protoc` does.
+        write!(f, "{}:{}: {}", self.line + 1, self.column + 1, self.message)
+    }
+}
+
+/// Parses `.proto` files and their imports.
+///
+/// Returns the descriptors of the files, along with the custom options of their messages and
+/// fields.
+pub fn parse<P>(protos: &[P], includes: &[P]) -> Result<(FileDescriptorSet, CustomOptions)>
+where
+    P: AsRef<Path>,
+{
+    // The protoc includes and the bundled amino and gogoproto options follow the user includes, so
+    // that the user can override the built-in .protos, like when running protoc.
+    let mut include_dirs: Vec<PathBuf> = includes
+        .iter()
+        .map(|include| include.as_ref().to_path_buf())
+        .collect();
+    include_dirs.push(::protoc_include().to_path_buf());
+
+    let mut loader = Loader {
+        includes: include_dirs,
+        files: Vec::new(),
+        custom_options: Vec::new(),
+        error_locations: Vec::new(),
+        loaded: HashSet::new(),
+        loading: Vec::new(),
+    };
+    for proto in protos {
+        let name = loader.virtual_name(proto.as_ref())?;
+        loader.load(&name)?;
+    }
+
+    resolve::resolve(&mut loader.files).map_err(|error| Error::new(ErrorKind::Other, error))?;
+    validate::validate(&loader.files, &loader.error_locations)
+        .map_err(|error| Error::new(ErrorKind::Other, error))?;
+    let custom_options = resolve_custom_options(&loader.files, &loader.custom_options)
+        .map_err(|error| Error::new(ErrorKind::Other, error))?;
+    Ok((FileDescriptorSet { file: loader.files }, custom_options))
+}
+
+struct Loader {
+    includes: Vec<PathBuf>,
+    /// The parsed files, in dependency order.
+    files: Vec<FileDescriptorProto>,
+    /// The unresolved custom options of each file.
+    custom_options: Vec<Vec<CustomOption>>,
+    /// The error locations recorded when parsing each file.
+    error_locations: Vec<validate::ErrorLocations>,
+    loaded: HashSet<String>,
+    /// The files being loaded, used to detect import cycles.
+    loading: Vec<String>,
+}
+
+impl Loader {
+    /// Returns the name of a `.proto` file relative to the include directory containing it.
+    fn virtual_name(&self, proto: &Path) -> Result<String> {
+        for include in &self.includes {
+            if let Some(name) = relative_name(include, proto) {
+                return Ok(name);
+            }
+        }
+        // The paths may differ in being relative or absolute.
+        if let Ok(proto) = proto.canonicalize() {
+            for include in &self.includes {
+                if let Ok(include) = include.canonicalize() {
+                    if let Some(name) = relative_name(&include, &proto) {
+                        return Ok(name);
+                    }
+                }
+            }
+        }
+        Err(Error::new(
+            ErrorKind::Other,
+            format!(
+                "{}: File does not reside within any path specified using --proto_path (or -I).",
+                proto.display()
+            ),
+        ))
+    }
+
+    /// Reads a `.proto` file from the first include directory containing it.
+    fn read(&self, name: &str) -> Result<String> {
+        for include in &self.includes {
+            let path = include.join(name);
+            if path.is_file() {
+                return fs::read_to_string(path);
+            }
+        }
+        if name == "amino/amino.proto" {
+            return Ok(amino::AMINO_PROTO.to_string());
+        }
+        if name == "gogoproto/gogo.proto" {
+            return Ok(gogo::GOGO_PROTO.to_string());
+        }
+        Err(Error::new(
+            ErrorKind::NotFound,
+            format!("{}: File not found.", name),
+        ))
+    }
+
+    /// Parses a file after its imports, unless it has already been parsed.
+    fn load(&mut self, name: &str) -> Result<()> {
+        if self.loaded.contains(name) {
+            return Ok(());
+        }
+        if self.loading.iter().any(|loading| loading == name) {
+            return Err(Error::new(
+                ErrorKind::Other,
+                format!(
+                    "File recursively imports itself: {} -> {}",
+                    self.loading.join(" -> "),
+                    name
+                ),
+            ));
+        }
+
+        let source = self.read(name)?;
+        let parsed = grammar::parse(&source)
+            .map_err(|error| Error::new(ErrorKind::Other, format!("{}:{}", name, error)))?;
+        let mut file = parsed.file;
+        file.name = Some(name.to_string());
+
+        self.loading.push(name.to_string());
+        for dependency in &file.dependency {
+            self.load(dependency).map_err(|error| {
+                Error::new(
+                    error.kind(),
+                    format!(
+                        "{}\n{}: Import \"{}\" was not found or had errors.",
+                        error, name, dependency
+                    ),
+                )
+            })?;
+        }
+        self.loading.pop();
+
+        self.loaded.insert(name.to_string());
+        self.files.push(file);
+        self.custom_options.push(parsed.custom_options);
+        self.error_locations.push(parsed.error_locations);
+        Ok(())
+    }
+}
+
+/// Resolves the custom options of the messages and fields of the files to the extensions they
+/// set, and converts their values.
+///
+/// Only the scalar extension types with varint or length delimited encodings are supported; the
+/// options of other types are checked to name an extension, and are otherwise ignored.
+fn resolve_custom_options(
+    files: &[FileDescriptorProto],
+    custom_options: &[Vec<CustomOption>],
+) -> ::std::result::Result<CustomOptions, String> {
+    let mut extensions = HashMap::new();
+    for file in files {
+        let scope = match file.package {
+            Some(ref package) if !package.is_empty() => format!(".{}", package),
+            _ => String::new(),
+        };
+        index_extensions(&scope, &file.extension, &file.message_type, &mut extensions);
+    }
+
+    let mut resolved = CustomOptions::default();
+    for (file, options) in files.iter().zip(custom_options) {
+        for option in options {
+            let error = |message: String| {
+                format!(
+                    "{}:{}",
+                    file.name(),
+                    ParseError::new(option.line, option.column, message)
+                )
+            };
+            let owner = match full_name(file, &option.owner) {
+                Some(owner) => owner,
+                None => continue,
+            };
+            let is_field = option.owner.len() >= 2 && option.owner[option.owner.len() - 2] == 2;
+            let extendee = if is_field {
+                ".google.protobuf.FieldOptions"
+            } else {
+                ".google.protobuf.MessageOptions"
+            };
+
+            let extension = resolve_extension(&extensions, &owner, &option.name)
+                .filter(|extension| extension.extendee() == extendee)
+                .ok_or_else(|| error(format!("Option \"({})\" unknown.", option.name)))?;
+            let value = match option_value(extension, &option.value, &option.name) {
+                Ok(Some(value)) => value,
+                Ok(None) => continue,
+                Err(message) => return Err(error(message)),
+            };
+            let number = extension.number() as u32;
+            if is_field {
+                resolved.insert_field_option(owner, number, value);
+            } else {
+                resolved.insert_message_option(owner, number, value);
+            }
+        }
+    }
+    Ok(resolved)
+}
+
+/// Indexes extensions declared in a scope and its messages by fully qualified name.
+fn index_extensions<'a>(
+    scope: &str,
+    extensions: &'a [FieldDescriptorProto],
+    messages: &'a [DescriptorProto],
+    index: &mut HashMap<String, &'a FieldDescriptorProto>,
+) {
+    for extension in extensions {
+        index.insert(format!("{}.{}", scope, extension.name()), extension);
+    }
+    for message in messages {
+        let scope = format!("{}.{}", scope, message.name());
+        index_extensions(&scope, &message.extension, &message.nested_type, index);
+    }
+}
+
+/// Looks up an extension name in the scope of a message or field, from the innermost scope out,
+/// like type names are resolved.
+fn resolve_extension<'a>(
+    extensions: &HashMap<String, &'a FieldDescriptorProto>,
+    scope: &str,
+    name: &str,
+) -> Option<&'a FieldDescriptorProto> {
+    if name.starts_with('.') {
+        return extensions.get(name).cloned();
+    }
+    let mut scope = scope;
+    loop {
+        if let Some(extension) = extensions.get(&format!("{}.{}", scope, name)) {
+            return Some(extension);
+        }
+        match scope.rfind('.') {
+            Some(index) => scope = &scope[..index],
+            None => return None,
+        }
+    }
+}
+
+/// Converts the value of a custom option to the type of its extension.
+///
+/// Returns `None` for the types whose values are not retained.
+fn option_value(
+    extension: &FieldDescriptorProto,
+    value: &OptionValue,
+    name: &str,
+) -> ::std::result::Result<Option<Value>, String> {
+    let name = &format!("({})", name)[..];
+    let integer = |min: i64, max: u64| match *value {
+        OptionValue::Integer(magnitude, false) if magnitude <= max => Ok(magnitude as i64),
+        OptionValue::Integer(magnitude, true) if magnitude <= min.wrapping_neg() as u64 => {
+            Ok((magnitude as i64).wrapping_neg())
+        }
+        OptionValue::Integer(..) => Err(format!("Value out of range for option \"{}\".", name)),
+        _ => Err(format!("Value must be integer for option \"{}\".", name)),
+    };
+    let value = match extension.type_() {
+        Type::Bool => Value::Varint(grammar::bool_value(value, name)? as u64),
+        Type::String | Type::Bytes => match *value {
+            OptionValue::String(ref bytes) => Value::LengthDelimited(bytes.clone()),
+            _ => {
+                return Err(format!(
+                    "Value must be quoted string for string option \"{}\".",
+                    name
+                ))
+            }
+        },
+        Type::Int32 => Value::Varint(integer(i32::MIN as i64, i32::MAX as u64)? as u64),
+        Type::Int64 => Value::Varint(integer(i64::MIN, i64::MAX as u64)? as u64),
+        Type::Uint32 => Value::Varint(integer(0, u32::MAX as u64)? as u64),
+        Type::Uint64 => Value::Varint(match *value {
+            OptionValue::Integer(magnitude, false) => magnitude,
+            _ => integer(0, 0)? as u64,
+        }),
+        Type::Sint32 => {
+            let value = integer(i32::MIN as i64, i32::MAX as u64)? as i32;
+            Value::Varint(((value << 1) ^ (value >> 31)) as u32 as u64)
+        }
+        Type::Sint64 => {
+            let value = integer(i64::MIN, i64::MAX as u64)?;
+            Value::Varint(((value << 1) ^ (value >> 63)) as u64)
+        }
+        _ => return Ok(None),
+    };
+    Ok(Some(value))
+}
+
+/// Returns the path of `proto` relative to `include`, with `/` separators.
+fn relative_name(include: &Path, proto: &Path) -> Option<String> {
+    let include = normalize(include);
+    let proto = normalize(proto);
+    if proto.len() <= include.len() || !proto.starts_with(&include) {
+        return None;
+    }
+    let parts: Vec<_> = proto[include.len()..]
+        .iter()
+        .map(|part| part.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("/"))
+}
+
+fn normalize<'a>(path: &'a Path) -> Vec<Component<'a>> {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+/// Returns the fully qualified name of the message or field at a source location path, e.g.
+/// `.foo.Bar.baz`.
+fn full_name(file: &FileDescriptorProto, path: &[i32]) -> Option<String> {
+    let mut name = match file.package {
+        Some(ref package) if !package.is_empty() => format!(".{}", package),
+        _ => String::new(),
+    };
+    if path.len() < 2 || path[0] != 4 {
+        return None;
+    }
+    let mut message: &DescriptorProto = file.message_type.get(path[1] as usize)?;
+    name.push('.');
+    name.push_str(message.name());
+    for pair in path[2..].chunks(2) {
+        match *pair {
+            [3, index] => {
+                message = message.nested_type.get(index as usize)?;
+                name.push('.');
+                name.push_str(message.name());
+            }
+            [2, index] => {
+                name.push('.');
+                name.push_str(message.field.get(index as usize)?.name());
+            }
+            _ => return None,
+        }
+    }
+    Some(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost_types::field_descriptor_proto::{Label, Type};
+
+    fn parse_tests(protos: &[&str], includes: &[&str]) -> FileDescriptorSet {
+        parse(protos, includes).unwrap().0
+    }
+
+    #[test]
+    fn test_relative_name() {
+        assert_eq!(
+            relative_name(Path::new("./src"), Path::new("src/foo/bar.proto")),
+            Some("foo/bar.proto".to_string())
+        );
+        assert_eq!(
+            relative_name(Path::new("src/"), Path::new("tests/bar.proto")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_imports() {
+        let files = parse_tests(
+            &["../tests/src/packages/widget_factory.proto"],
+            &["../tests/src/packages"],
+        )
+        .file;
+        let names: Vec<_> = files.iter().map(|file| file.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "root.proto",
+                "gizmo.proto",
+                "widget.proto",
+                "widget_factory.proto"
+            ]
+        );
+
+        let factory = &files[3];
+        assert_eq!(factory.package(), "packages.widget.factory");
+        assert_eq!(factory.syntax(), "proto3");
+        let fields: Vec<_> = factory.message_type[0]
+            .field
+            .iter()
+            .map(|field| (field.name(), field.type_(), field.type_name()))
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                (
+                    "inner",
+                    Type::Message,
+                    ".packages.widget.factory.WidgetFactory.Inner"
+                ),
+                ("root", Type::Message, ".packages.Root"),
+                ("root_inner", Type::Message, ".packages.Root.Inner"),
+                ("widget", Type::Message, ".packages.widget.Widget"),
+                (
+                    "widget_inner",
+                    Type::Message,
+                    ".packages.widget.Widget.Inner"
+                ),
+                ("widget_type", Type::Enum, ".packages.widget.Widget.Type"),
+                ("gizmo", Type::Message, ".packages.gizmo.Gizmo"),
+                ("gizmo_inner", Type::Message, ".packages.gizmo.Gizmo.Inner"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_maps_and_oneofs() {
+        let files = parse_tests(
+            &[
+                "../tests/src/nesting.proto",
+                "../tests/src/recursive_oneof.proto",
+                "../tests/src/ident_conversion.proto",
+            ],
+            &["../tests/src"],
+        )
+        .file;
+
+        let a = &files[0].message_type[0];
+        assert_eq!(a.nested_type.len(), 2);
+        let entry = &a.nested_type[0];
+        assert_eq!(entry.name(), "MapAEntry");
+        assert_eq!(entry.options.as_ref().unwrap().map_entry, Some(true));
+        assert_eq!(entry.field[0].type_(), Type::Int32);
+        assert_eq!(entry.field[1].type_name(), ".nesting.A");
+        let map_a = &a.field[2];
+        assert_eq!(map_a.label(), Label::Repeated);
+        assert_eq!(map_a.type_name(), ".nesting.A.MapAEntry");
+        assert_eq!(map_a.json_name(), "mapA");
+
+        let oneof = &files[1].message_type[0];
+        assert_eq!(oneof.oneof_decl[0].name(), "kind");
+        assert!(oneof.field.iter().all(|field| field.oneof_index == Some(0)));
+
+        let foo = &files[2].message_type[0];
+        assert_eq!(files[2].package(), "Foo.Bar_Baz");
+        assert_eq!(foo.field[0].json_name(), "fooBarBaz");
+        assert_eq!(
+            foo.field[2].type_name(),
+            ".Foo.Bar_Baz.Foo_barBaz.StrawberryRhubarbPIE"
+        );
+        assert_eq!(
+            foo.nested_type[0].field[1].type_name(),
+            ".Foo.Bar_Baz.Foo_barBaz.fuzz_buster"
+        );
+    }
+
+    #[test]
+    fn test_proto3_optional() {
+        let files = parse_tests(&["../tests/src/proto3_optional.proto"], &["../tests/src"]).file;
+
+        let params = &files[0].message_type[0];
+        let names = params
+            .oneof_decl
+            .iter()
+            .map(|oneof| oneof.name())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            names,
+            ["change", "_max_validators", "_memo", "_level", "_nested"]
+        );
+        assert_eq!(params.field[0].proto3_optional, Some(true));
+        assert_eq!(params.field[0].label(), Label::Optional);
+        assert_eq!(params.field[0].oneof_index, Some(1));
+        assert_eq!(params.field[3].oneof_index, Some(4));
+        assert_eq!(params.field[4].proto3_optional, None);
+        assert_eq!(params.field[4].oneof_index, Some(0));
+    }
+
+    #[test]
+    fn test_comments() {
+        let files = parse_tests(
+            &[
+                "../tests/src/custom_attributes.proto",
+                "src/smoke_test.proto",
+            ],
+            &["../tests/src", "src"],
+        )
+        .file;
+
+        let comments = |file: &FileDescriptorProto, path: &[i32]| {
+            let location = file
+                .source_code_info
+                .as_ref()
+                .unwrap()
+                .location
+                .iter()
+                .find(|location| location.path == path)
+                .unwrap();
+            (
+                location.leading_comments.clone(),
+                location.trailing_comments.clone(),
+                location.leading_detached_comments.clone(),
+            )
+        };
+
+        let smoke_test = &files[1];
+        assert_eq!(
+            comments(smoke_test, &[6, 0]),
+            (
+                Some(" Just a smoke test service.\n".to_string()),
+                None,
+                Vec::new()
+            )
+        );
+        assert_eq!(
+            comments(smoke_test, &[6, 0, 2, 0]),
+            (
+                Some(" Blow some smoke.\n".to_string()),
+                None,
+                vec![" A detached comment block.\n".to_string()]
+            )
+        );
+
+        // Spans are 0-based, and omit the end line when it is the start line.
+        let location = smoke_test.source_code_info.as_ref().unwrap().location[..]
+            .iter()
+            .find(|location| location.path == [6, 0, 2, 0])
+            .unwrap();
+        assert_eq!(location.span, vec![16, 4, 56]);
+    }
+
+    #[test]
+    fn test_custom_options() {
+        let (files, custom_options) =
+            parse(&["../tests/src/amino_options.proto"], &["../tests/src"]).unwrap();
+        let names: Vec<_> = files.file.iter().map(|file| file.name()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "google/protobuf/descriptor.proto",
+                "amino/amino.proto",
+                "amino_options.proto"
+            ]
+        );
+        assert_eq!(
+            custom_options.message_string(".amino_options.PubKeyEd25519", amino::NAME_OPTION),
+            Some("tendermint/PubKeyEd25519".to_string())
+        );
+        assert_eq!(
+            custom_options
+                .field_string(".amino_options.Validator.pub_key", amino::FIELD_NAME_OPTION),
+            Some("tendermint/PubKeyEd25519".to_string())
+        );
+        assert_eq!(
+            custom_options.message_string(".amino_options.Validator", amino::NAME_OPTION),
+            None
+        );
+
+        let (_, custom_options) =
+            parse(&["../tests/src/gogo_options.proto"], &["../tests/src"]).unwrap();
+        assert_eq!(
+            custom_options.field_bool(".gogo_options.Block.header", gogo::NULLABLE_OPTION),
+            Some(false)
+        );
+        assert_eq!(
+            custom_options.field_string(".gogo_options.Block.last_commit", gogo::CUSTOMNAME_OPTION),
+            Some("PrevCommit".to_string())
+        );
+    }
+
+    /// Checks that the built-in parser produces the same descriptors as `protoc`, apart from the
+    /// source locations which are not recorded. `proto3_optional.proto` is left out, since the
+    /// bundled `protoc` predates proto3 optional fields.
+    #[test]
+    fn test_protoc() {
+        let tests: &[(&[&str], &[&str])] = &[
+            (&["src/smoke_test.proto"], &["src"]),
+            (
+                &["../tests/src/packages/widget_factory.proto"],
+                &["../tests/src/packages"],
+            ),
+            (
+                &[
+                    "../tests/src/amino_options.proto",
+                    "../tests/src/builder.proto",
+                    "../tests/src/custom_attributes.proto",
+                    "../tests/src/extensions.proto",
+                    "../tests/src/extern_paths.proto",
+                    "../tests/src/gogo_options.proto",
+                    "../tests/src/groups.proto",
+                    "../tests/src/ident_conversion.proto",
+                    "../tests/src/json.proto",
+                    "../tests/src/nesting.proto",
+                    "../tests/src/no_unused_results.proto",
+                    "../tests/src/oneof_attributes.proto",
+                    "../tests/src/recursive_oneof.proto",
+                    "../tests/src/socket_service.proto",
+                    "../tests/src/text_format.proto",
+                ],
+                &["../tests/src"],
+            ),
+        ];
+        for &(protos, includes) in tests {
+            let (expected, expected_options) = ::run_protoc(protos, includes).unwrap();
+            let (files, custom_options) = parse(protos, includes).unwrap();
+            assert_eq!(custom_options, expected_options);
+            assert_eq!(files.file.len(), expected.file.len());
+            for (file, mut expected) in files.file.into_iter().zip(expected.file) {
+                if let (Some(info), Some(expected_info)) = (
+                    file.source_code_info.as_ref(),
+                    expected.source_code_info.as_mut(),
+                ) {
+                    let paths: HashSet<_> = info.location.iter().map(|l| &l.path).collect();
+                    expected_info
+                        .location
+                        .retain(|location| paths.contains(&location.path));
+                }
+                assert_eq!(file, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = grammar::parse("syntax = \"proto2\";\nmessage Foo {\n  int32 foo = 1;\n}\n")
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "3:3: Expected \"required\", \"optional\", or \"repeated\"."
+        );
+
+        let error = parse(&["../tests/src/nesting.proto"], &["src"]).unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("File does not reside within any path"));
+
+        let tempdir = ::tempdir::TempDir::new("prost-build-parser").unwrap();
+        let proto = tempdir.path().join("unknown_option.proto");
+        fs::write(
+            &proto,
+            "syntax = \"proto3\";\nmessage Foo {\n  int32 foo = 1 [(bar) = true];\n}\n",
+        )
+        .unwrap();
+        let error = parse(&[proto], &[tempdir.path().to_path_buf()]).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unknown_option.proto:3:18: Option \"(bar)\" unknown."
+        );
+
+        // Errors found once the types are resolved.
+        let proto = tempdir.path().join("invalid.proto");
+        let error = |source: &str| {
+            fs::write(&proto, source).unwrap();
+            parse(&[&proto], &[&tempdir.path().to_path_buf()])
+                .unwrap_err()
+                .to_string()
+        };
+        let proto2 = |body: &str| error(&format!("syntax = \"proto2\";\n{}", body));
+        let proto3 = |body: &str| error(&format!("syntax = \"proto3\";\n{}", body));
+        assert_eq!(
+            proto3("package foo;\nmessage Foo {\n  int32 a = 1;\n  int32 b = 1;\n}\n"),
+            "invalid.proto:5:13: Field number 1 has already been used in \"foo.Foo\" by field \"a\"."
+        );
+        assert_eq!(
+            proto3("message Foo {\n  int32 a = 0;\n}\n"),
+            "invalid.proto:3:13: Field numbers must be positive integers."
+        );
+        assert_eq!(
+            proto3("message Foo {\n  int32 a = 536870912;\n}\n"),
+            "invalid.proto:3:13: Field numbers cannot be greater than 536870911."
+        );
+        assert_eq!(
+            proto3("message Foo {\n  int32 a = 19500;\n}\n"),
+            "invalid.proto:3:13: Field numbers 19000 through 19999 are reserved for the protocol \
+             buffer library implementation."
+        );
+        assert_eq!(
+            proto3("message Foo {\n  reserved 2 to 4;\n  int32 a = 3;\n}\n"),
+            "invalid.proto: Field \"a\" uses reserved number 3."
+        );
+        assert_eq!(
+            proto3("package foo;\nmessage Foo {}\nmessage Foo {}\n"),
+            "invalid.proto:4:9: \"Foo\" is already defined in \"foo\"."
+        );
+        assert_eq!(
+            proto3("enum Foo {\n  A = 1;\n}\n"),
+            "invalid.proto:3:7: The first enum value must be zero in proto3."
+        );
+        assert_eq!(
+            proto3("message Foo {\n  map<float, string> a = 1;\n}\n"),
+            "invalid.proto:3:3: Key in map fields cannot be float/double, bytes or message types."
+        );
+        assert_eq!(
+            proto3("message Foo {\n  required int32 a = 1;\n}\n"),
+            "invalid.proto: Required fields are not allowed in proto3."
+        );
+        assert_eq!(
+            proto3("message Foo {\n  extensions 100 to 200;\n}\n"),
+            "invalid.proto: Extension ranges are not allowed in proto3."
+        );
+        assert_eq!(
+            proto2("message Foo {\n  extensions 100 to 200;\n}\nextend Foo {\n  optional int32 a = 10;\n}\n"),
+            "invalid.proto:6:22: \"Foo\" does not declare 10 as an extension number."
+        );
+        assert_eq!(
+            proto2("message Foo {\n  repeated int32 a = 1 [default = 1];\n}\n"),
+            "invalid.proto:3:35: Repeated fields can't have default values."
+        );
+    }
+}
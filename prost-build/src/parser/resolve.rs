@@ -0,0 +1,403 @@
+//! Resolves the type names of parsed files to fully qualified names.
+//!
+//! Names are looked up in the scope of the declaration using them, then in the enclosing scopes,
+//! like `protoc` does. Only types declared in the file itself, its imports, and the files those
+//! publicly import are visible.
+
+use std::collections::{HashMap, HashSet};
+
+use prost_types::field_descriptor_proto::Type;
+use prost_types::{DescriptorProto, FieldDescriptorProto, FileDescriptorProto};
+
+use ident::to_json_name;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Message,
+    Enum,
+    Service,
+}
+
+struct Symbol {
+    kind: Kind,
+    file: usize,
+    /// The value names of an enum.
+    values: Vec<String>,
+}
+
+struct Symbols {
+    symbols: HashMap<String, Symbol>,
+    /// The files declaring each package, including the enclosing packages.
+    packages: HashMap<String, Vec<usize>>,
+}
+
+/// Resolves the type names of the files, which must be ordered such that imports precede the
+/// files importing them. Also sets the JSON names of fields which don't set one explicitly.
+pub fn resolve(files: &mut [FileDescriptorProto]) -> Result<(), String> {
+    let symbols = Symbols::new(files);
+
+    let mut indices = HashMap::new();
+    for (index, file) in files.iter().enumerate() {
+        indices.insert(file.name.clone().unwrap_or_default(), index);
+    }
+
+    for index in 0..files.len() {
+        let visible = visible_files(files, &indices, index);
+        let resolver = Resolver {
+            symbols: &symbols,
+            visible: &visible,
+            file_name: files[index].name.clone().unwrap_or_default(),
+        };
+        resolver.resolve_file(&mut files[index])?;
+    }
+    Ok(())
+}
+
+/// Returns the files whose symbols are visible from a file: the file itself, its imports, and
+/// the public imports of those, transitively.
+fn visible_files(
+    files: &[FileDescriptorProto],
+    indices: &HashMap<String, usize>,
+    index: usize,
+) -> HashSet<usize> {
+    let mut visible = HashSet::new();
+    visible.insert(index);
+    let mut queue: Vec<usize> = files[index]
+        .dependency
+        .iter()
+        .filter_map(|dependency| indices.get(dependency).cloned())
+        .collect();
+    while let Some(dependency) = queue.pop() {
+        if visible.insert(dependency) {
+            let file = &files[dependency];
+            for &public in &file.public_dependency {
+                if let Some(&public) = file
+                    .dependency
+                    .get(public as usize)
+                    .and_then(|name| indices.get(name))
+                {
+                    queue.push(public);
+                }
+            }
+        }
+    }
+    visible
+}
+
+impl Symbols {
+    fn new(files: &[FileDescriptorProto]) -> Symbols {
+        let mut symbols = Symbols {
+            symbols: HashMap::new(),
+            packages: HashMap::new(),
+        };
+        for (index, file) in files.iter().enumerate() {
+            let package = file.package.clone().unwrap_or_default();
+            if !package.is_empty() {
+                let mut end = 0;
+                for part in package.split('.') {
+                    end += part.len();
+                    symbols
+                        .packages
+                        .entry(package[..end].to_string())
+                        .or_insert_with(Vec::new)
+                        .push(index);
+                    end += 1;
+                }
+            }
+            for message in &file.message_type {
+                symbols.add_message(index, &package, message);
+            }
+            for enum_type in &file.enum_type {
+                symbols.add_enum(index, &package, enum_type);
+            }
+            for service in &file.service {
+                let name = qualify(&package, service.name());
+                symbols.add(name, index, Kind::Service, Vec::new());
+            }
+        }
+        symbols
+    }
+
+    fn add(&mut self, name: String, file: usize, kind: Kind, values: Vec<String>) {
+        self.symbols
+            .entry(name)
+            .or_insert(Symbol { kind, file, values });
+    }
+
+    fn add_message(&mut self, file: usize, scope: &str, message: &DescriptorProto) {
+        let name = qualify(scope, message.name());
+        for nested in &message.nested_type {
+            self.add_message(file, &name, nested);
+        }
+        for enum_type in &message.enum_type {
+            self.add_enum(file, &name, enum_type);
+        }
+        self.add(name, file, Kind::Message, Vec::new());
+    }
+
+    fn add_enum(
+        &mut self,
+        file: usize,
+        scope: &str,
+        enum_type: &::prost_types::EnumDescriptorProto,
+    ) {
+        let values = enum_type
+            .value
+            .iter()
+            .map(|value| value.name().to_string())
+            .collect();
+        self.add(qualify(scope, enum_type.name()), file, Kind::Enum, values);
+    }
+}
+
+/// The kind of symbol a lookup found.
+enum Found<'a> {
+    Symbol(&'a Symbol),
+    Package,
+}
+
+struct Resolver<'a> {
+    symbols: &'a Symbols,
+    visible: &'a HashSet<usize>,
+    file_name: String,
+}
+
+impl<'a> Resolver<'a> {
+    /// Finds a symbol by its fully qualified name, without the leading `.`.
+    fn find(&self, name: &str) -> Option<Found<'a>> {
+        if let Some(symbol) = self.symbols.symbols.get(name) {
+            if self.visible.contains(&symbol.file) {
+                return Some(Found::Symbol(symbol));
+            }
+        }
+        match self.symbols.packages.get(name) {
+            Some(files) if files.iter().any(|file| self.visible.contains(file)) => {
+                Some(Found::Package)
+            }
+            _ => None,
+        }
+    }
+
+    /// Looks up a type name relative to the fully qualified name of the declaration using it.
+    ///
+    /// The first part of the name is looked up in each enclosing scope, starting with the
+    /// innermost one. Once it is found, the rest of the name must be found within it.
+    fn lookup(&self, name: &str, relative_to: &str) -> Option<(String, Found<'a>)> {
+        if name.starts_with('.') {
+            return self
+                .find(&name[1..])
+                .map(|found| (name[1..].to_string(), found));
+        }
+
+        let first_part = name.split('.').next().unwrap();
+        let mut scope = relative_to.to_string();
+        loop {
+            match scope.rfind('.') {
+                Some(dot) => scope.truncate(dot),
+                None => return self.find(name).map(|found| (name.to_string(), found)),
+            }
+
+            let candidate = format!("{}.{}", scope, first_part);
+            match self.find(&candidate) {
+                Some(Found::Symbol(symbol))
+                    if first_part.len() == name.len() && symbol.kind != Kind::Service =>
+                {
+                    return Some((candidate, Found::Symbol(symbol)));
+                }
+                Some(_) if first_part.len() < name.len() => {
+                    // Only the first part of the name was found, so the rest of the name must be
+                    // found within it.
+                    let full_name = format!("{}{}", candidate, &name[first_part.len()..]);
+                    return self.find(&full_name).map(|found| (full_name, found));
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn resolve_file(&self, file: &mut FileDescriptorProto) -> Result<(), String> {
+        let package = file.package.clone().unwrap_or_default();
+        for message in &mut file.message_type {
+            self.resolve_message(&package, message)?;
+        }
+        for field in &mut file.extension {
+            self.resolve_field(&package, field)?;
+        }
+        for service in &mut file.service {
+            let service_name = qualify(&package, service.name());
+            for method in &mut service.method {
+                let method_name = qualify(&service_name, method.name());
+                for type_name in [&mut method.input_type, &mut method.output_type] {
+                    if let Some(ref mut type_name) = *type_name {
+                        *type_name = self.resolve_message_type(type_name, &method_name)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn resolve_message(&self, scope: &str, message: &mut DescriptorProto) -> Result<(), String> {
+        let name = qualify(scope, message.name());
+        for field in &mut message.field {
+            self.resolve_field(&name, field)?;
+        }
+        for field in &mut message.extension {
+            self.resolve_field(&name, field)?;
+        }
+        for nested in &mut message.nested_type {
+            self.resolve_message(&name, nested)?;
+        }
+        Ok(())
+    }
+
+    fn resolve_field(&self, scope: &str, field: &mut FieldDescriptorProto) -> Result<(), String> {
+        let field_name = qualify(scope, field.name());
+        if field.json_name.is_none() {
+            field.json_name = Some(to_json_name(field.name()));
+        }
+
+        if let Some(extendee) = field.extendee.take() {
+            field.extendee = Some(self.resolve_message_type(&extendee, &field_name)?);
+        }
+
+        let type_name = match field.type_name.take() {
+            Some(type_name) => type_name,
+            None => return Ok(()),
+        };
+        let (full_name, symbol) = match self.lookup(&type_name, &field_name) {
+            Some((full_name, Found::Symbol(symbol))) => (full_name, symbol),
+            Some(..) => return Err(self.error(format!("\"{}\" is not a type.", type_name))),
+            None => return Err(self.error(format!("\"{}\" is not defined.", type_name))),
+        };
+
+        match (symbol.kind, field.type_) {
+            (Kind::Message, None) => field.type_ = Some(Type::Message as i32),
+            (Kind::Message, Some(ty)) if ty == Type::Group as i32 => (),
+            (Kind::Enum, None) => field.type_ = Some(Type::Enum as i32),
+            (Kind::Service, _) => {
+                return Err(self.error(format!("\"{}\" is not a type.", type_name)));
+            }
+            _ => {
+                return Err(self.error(format!("\"{}\" is not a message type.", type_name)));
+            }
+        }
+
+        if let Some(ref default_value) = field.default_value {
+            if symbol.kind == Kind::Message {
+                return Err(self.error("Messages can't have default values."));
+            }
+            if !symbol.values.contains(default_value) {
+                return Err(self.error(format!(
+                    "Enum type \"{}\" has no value named \"{}\".",
+                    full_name, default_value
+                )));
+            }
+        }
+
+        field.type_name = Some(format!(".{}", full_name));
+        Ok(())
+    }
+
+    fn resolve_message_type(&self, name: &str, relative_to: &str) -> Result<String, String> {
+        match self.lookup(name, relative_to) {
+            Some((full_name, Found::Symbol(symbol))) if symbol.kind == Kind::Message => {
+                Ok(format!(".{}", full_name))
+            }
+            Some(..) => Err(self.error(format!("\"{}\" is not a message type.", name))),
+            None => Err(self.error(format!("\"{}\" is not defined.", name))),
+        }
+    }
+
+    fn error<S>(&self, message: S) -> String
+    where
+        S: AsRef<str>,
+    {
+        format!("{}: {}", self.file_name, message.as_ref())
+    }
+}
+
+pub fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", scope, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use prost_types::EnumDescriptorProto;
+
+    fn message(name: &str, nested: Vec<DescriptorProto>) -> DescriptorProto {
+        DescriptorProto {
+            name: Some(name.to_string()),
+            nested_type: nested,
+            ..DescriptorProto::default()
+        }
+    }
+
+    fn field(name: &str, type_name: &str) -> FieldDescriptorProto {
+        FieldDescriptorProto {
+            name: Some(name.to_string()),
+            type_name: Some(type_name.to_string()),
+            ..FieldDescriptorProto::default()
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut outer = message("Outer", vec![message("Inner", Vec::new())]);
+        outer.enum_type.push(EnumDescriptorProto {
+            name: Some("Kind".to_string()),
+            ..EnumDescriptorProto::default()
+        });
+        outer.field = vec![
+            field("inner", "Inner"),
+            field("kind", "Kind"),
+            field("other_inner", "other.Other.Inner"),
+            field("outer", ".a.b.Outer"),
+        ];
+        let a = FileDescriptorProto {
+            name: Some("a.proto".to_string()),
+            package: Some("a.b".to_string()),
+            dependency: vec!["other.proto".to_string()],
+            message_type: vec![outer],
+            ..FileDescriptorProto::default()
+        };
+        let other = FileDescriptorProto {
+            name: Some("other.proto".to_string()),
+            package: Some("a.other".to_string()),
+            message_type: vec![message("Other", vec![message("Inner", Vec::new())])],
+            ..FileDescriptorProto::default()
+        };
+        let mut files = vec![other, a];
+        resolve(&mut files).unwrap();
+
+        let fields = &files[1].message_type[0].field;
+        let resolved: Vec<_> = fields
+            .iter()
+            .map(|field| (field.type_name(), field.type_(), field.json_name()))
+            .collect();
+        assert_eq!(
+            resolved,
+            vec![
+                (".a.b.Outer.Inner", Type::Message, "inner"),
+                (".a.b.Outer.Kind", Type::Enum, "kind"),
+                (".a.other.Other.Inner", Type::Message, "otherInner"),
+                (".a.b.Outer", Type::Message, "outer"),
+            ]
+        );
+
+        // Types of files which are not imported are not visible.
+        let mut files = vec![files[0].clone(), files[1].clone()];
+        files[1].dependency.clear();
+        files[1].message_type[0].field = vec![field("other", "other.Other")];
+        assert_eq!(
+            resolve(&mut files).unwrap_err(),
+            "a.proto: \"other.Other\" is not defined."
+        );
+    }
+}
@@ -0,0 +1,825 @@
+//! Splits `.proto` source text into tokens.
+//!
+//! This follows the tokenizer of `protoc`, including the way it computes columns and collects
+//! comments, so that the source locations of parsed files match the ones produced by `protoc`.
+
+use std::mem;
+
+use super::ParseError;
+
+/// The width of a tab, which `protoc` uses to compute columns.
+const TAB_WIDTH: i32 = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Before the first token is read.
+    Start,
+    /// After the last token is read.
+    End,
+    Identifier,
+    Integer,
+    Float,
+    String,
+    Symbol,
+}
+
+#[derive(Clone, Debug)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub line: i32,
+    pub column: i32,
+    pub end_column: i32,
+}
+
+/// The comments surrounding a token, as collected by `Tokenizer::next_with_comments`.
+#[derive(Default)]
+pub struct Comments {
+    /// The comment trailing the previous token.
+    pub trailing: String,
+    /// Comments which are not attached to either token.
+    pub detached: Vec<String>,
+    /// The comment leading the next token.
+    pub leading: String,
+}
+
+enum CommentStart {
+    Line,
+    Block,
+    SlashNotComment,
+    None,
+}
+
+pub struct Tokenizer<'a> {
+    input: &'a [u8],
+    pos: usize,
+    line: i32,
+    column: i32,
+    current: Token,
+    previous: Token,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(input: &'a str) -> Tokenizer<'a> {
+        let start = Token {
+            kind: TokenKind::Start,
+            text: String::new(),
+            line: 0,
+            column: 0,
+            end_column: 0,
+        };
+        Tokenizer {
+            input: input.as_bytes(),
+            pos: 0,
+            line: 0,
+            column: 0,
+            current: start.clone(),
+            previous: start,
+        }
+    }
+
+    pub fn current(&self) -> &Token {
+        &self.current
+    }
+
+    pub fn previous(&self) -> &Token {
+        &self.previous
+    }
+
+    /// Returns an error located at the current position of the tokenizer.
+    fn error<S>(&self, message: S) -> ParseError
+    where
+        S: Into<String>,
+    {
+        ParseError::new(self.line, self.column, message)
+    }
+
+    fn current_char(&self) -> u8 {
+        self.input.get(self.pos).cloned().unwrap_or(0)
+    }
+
+    fn next_char(&mut self) {
+        match self.current_char() {
+            b'\n' => {
+                self.line += 1;
+                self.column = 0;
+            }
+            b'\t' => self.column += TAB_WIDTH - self.column % TAB_WIDTH,
+            _ => self.column += 1,
+        }
+        self.pos += 1;
+    }
+
+    fn at_end(&self) -> bool {
+        self.pos >= self.input.len()
+    }
+
+    fn try_consume(&mut self, c: u8) -> bool {
+        if !self.at_end() && self.current_char() == c {
+            self.next_char();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn try_consume_one<F>(&mut self, class: F) -> bool
+    where
+        F: Fn(u8) -> bool,
+    {
+        if !self.at_end() && class(self.current_char()) {
+            self.next_char();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn consume_zero_or_more<F>(&mut self, class: F)
+    where
+        F: Fn(u8) -> bool,
+    {
+        while self.try_consume_one(&class) {}
+    }
+
+    fn try_consume_comment_start(&mut self) -> CommentStart {
+        if !self.try_consume(b'/') {
+            return CommentStart::None;
+        }
+        if self.try_consume(b'/') {
+            CommentStart::Line
+        } else if self.try_consume(b'*') {
+            CommentStart::Block
+        } else {
+            // It was just a slash, which is returned as a symbol.
+            self.current = Token {
+                kind: TokenKind::Symbol,
+                text: "/".to_string(),
+                line: self.line,
+                column: self.column - 1,
+                end_column: self.column,
+            };
+            CommentStart::SlashNotComment
+        }
+    }
+
+    /// Consumes the rest of a line comment, including the newline.
+    fn consume_line_comment(&mut self, content: Option<&mut Vec<u8>>) {
+        let start = self.pos;
+        while !self.at_end() && self.current_char() != b'\n' {
+            self.next_char();
+        }
+        self.try_consume(b'\n');
+        if let Some(content) = content {
+            content.extend_from_slice(&self.input[start..self.pos]);
+        }
+    }
+
+    /// Consumes the rest of a block comment. The leading whitespace and asterisk of each
+    /// continuation line are not part of the content.
+    fn consume_block_comment(
+        &mut self,
+        mut content: Option<&mut Vec<u8>>,
+    ) -> Result<(), ParseError> {
+        let mut start = self.pos;
+        loop {
+            while !self.at_end() {
+                match self.current_char() {
+                    b'*' | b'/' | b'\n' => break,
+                    _ => self.next_char(),
+                }
+            }
+
+            if self.try_consume(b'\n') {
+                if let Some(ref mut content) = content {
+                    content.extend_from_slice(&self.input[start..self.pos]);
+                }
+                self.consume_zero_or_more(is_whitespace_no_newline);
+                if self.try_consume(b'*') && self.try_consume(b'/') {
+                    return Ok(());
+                }
+                start = self.pos;
+            } else if self.try_consume(b'*') && self.try_consume(b'/') {
+                if let Some(ref mut content) = content {
+                    content.extend_from_slice(&self.input[start..self.pos - 2]);
+                }
+                return Ok(());
+            } else if self.try_consume(b'/') && self.current_char() == b'*' {
+                return Err(
+                    self.error("\"/*\" inside block comment.  Block comments cannot be nested.")
+                );
+            } else if self.at_end() {
+                return Err(self.error("End-of-file inside block comment."));
+            }
+        }
+    }
+
+    /// Advances to the next token, skipping whitespace and comments.
+    ///
+    /// Returns `false` if the end of the input is reached.
+    pub fn next(&mut self) -> Result<bool, ParseError> {
+        self.previous = self.current.clone();
+
+        loop {
+            self.consume_zero_or_more(is_whitespace);
+            match self.try_consume_comment_start() {
+                CommentStart::Line => {
+                    self.consume_line_comment(None);
+                    continue;
+                }
+                CommentStart::Block => {
+                    self.consume_block_comment(None)?;
+                    continue;
+                }
+                CommentStart::SlashNotComment => return Ok(true),
+                CommentStart::None => (),
+            }
+
+            if self.at_end() {
+                self.current = Token {
+                    kind: TokenKind::End,
+                    text: String::new(),
+                    line: self.line,
+                    column: self.column,
+                    end_column: self.column,
+                };
+                return Ok(false);
+            }
+
+            let c = self.current_char();
+            if c < b' ' {
+                return Err(self.error("Invalid control characters encountered in text."));
+            }
+            if c >= 0x80 {
+                return Err(self.error("Interpreting non ascii codepoint."));
+            }
+
+            let start = self.pos;
+            let line = self.line;
+            let column = self.column;
+            let kind = if self.try_consume_one(is_letter) {
+                self.consume_zero_or_more(is_alphanumeric);
+                TokenKind::Identifier
+            } else if self.try_consume(b'0') {
+                self.consume_number(true, false)?
+            } else if self.try_consume(b'.') {
+                // This could be the beginning of a floating-point number, or just a '.' symbol.
+                if self.try_consume_one(is_digit) {
+                    if self.previous.kind == TokenKind::Identifier
+                        && self.previous.line == line
+                        && self.previous.end_column == column
+                    {
+                        return Err(ParseError::new(
+                            line,
+                            column - 2,
+                            "Need space between identifier and decimal point.",
+                        ));
+                    }
+                    self.consume_number(false, true)?
+                } else {
+                    TokenKind::Symbol
+                }
+            } else if self.try_consume_one(is_digit) {
+                self.consume_number(false, false)?
+            } else if self.try_consume(b'"') {
+                self.consume_string(b'"')?;
+                TokenKind::String
+            } else if self.try_consume(b'\'') {
+                self.consume_string(b'\'')?;
+                TokenKind::String
+            } else {
+                self.next_char();
+                TokenKind::Symbol
+            };
+
+            self.current = Token {
+                kind,
+                // The input is a `str`, and tokens start and end at ASCII characters.
+                text: String::from_utf8_lossy(&self.input[start..self.pos]).into_owned(),
+                line,
+                column,
+                end_column: self.column,
+            };
+            return Ok(true);
+        }
+    }
+
+    /// Advances to the next token, collecting the comments between the current token and the
+    /// next one.
+    ///
+    /// A comment on the same line as the current token, or on the following lines with no blank
+    /// line in between, trails the current token. A comment directly preceding the next token
+    /// leads the next token. Other comments are detached.
+    pub fn next_with_comments(&mut self) -> Result<Comments, ParseError> {
+        let mut collector = CommentCollector::default();
+        let result = self.collect_comments(&mut collector);
+        let mut comments = collector.comments;
+        if collector.has_comment {
+            comments.leading = to_string(collector.buffer);
+        }
+        result.map(|_| comments)
+    }
+
+    fn collect_comments(&mut self, collector: &mut CommentCollector) -> Result<(), ParseError> {
+        if self.current.kind == TokenKind::Start {
+            // Skip a UTF-8 byte order mark at the beginning of the file.
+            if self.input.starts_with(b"\xef\xbb\xbf") {
+                for _ in 0..3 {
+                    self.next_char();
+                }
+            }
+            collector.detach_from_prev();
+        } else {
+            // A comment appearing on the same line must be attached to the previous declaration.
+            self.consume_zero_or_more(is_whitespace_no_newline);
+            match self.try_consume_comment_start() {
+                CommentStart::Line => {
+                    self.consume_line_comment(Some(collector.buffer_for_line_comment()));
+                    // Don't allow comments on subsequent lines to be attached to a trailing
+                    // comment.
+                    collector.flush();
+                }
+                CommentStart::Block => {
+                    self.consume_block_comment(Some(collector.buffer_for_block_comment()))?;
+                    self.consume_zero_or_more(is_whitespace_no_newline);
+                    if !self.try_consume(b'\n') {
+                        // The next token is on the same line, so it is unclear which token the
+                        // comment belongs to.
+                        collector.clear_buffer();
+                        return self.next().map(|_| ());
+                    }
+                    collector.flush();
+                }
+                CommentStart::SlashNotComment => return Ok(()),
+                CommentStart::None => {
+                    if !self.try_consume(b'\n') {
+                        // The next token is on the same line, so there are no comments.
+                        return self.next().map(|_| ());
+                    }
+                }
+            }
+        }
+
+        // We are now on the line after the previous token.
+        loop {
+            self.consume_zero_or_more(is_whitespace_no_newline);
+            match self.try_consume_comment_start() {
+                CommentStart::Line => {
+                    self.consume_line_comment(Some(collector.buffer_for_line_comment()));
+                }
+                CommentStart::Block => {
+                    self.consume_block_comment(Some(collector.buffer_for_block_comment()))?;
+                    // Consume the rest of the line so that it isn't interpreted as a blank line.
+                    self.consume_zero_or_more(is_whitespace_no_newline);
+                    self.try_consume(b'\n');
+                }
+                CommentStart::SlashNotComment => return Ok(()),
+                CommentStart::None => {
+                    if self.try_consume(b'\n') {
+                        // A blank line.
+                        collector.flush();
+                        collector.detach_from_prev();
+                    } else {
+                        let more = self.next()?;
+                        let text = &self.current.text;
+                        if !more || text == "}" || text == "]" || text == ")" {
+                            // At the end of a scope it makes no sense to attach a comment to the
+                            // following token.
+                            collector.flush();
+                        }
+                        return Ok(());
+                    }
+                }
+            }
+        }
+    }
+
+    /// Consumes the rest of a number, returning whether it is an integer or a float.
+    fn consume_number(
+        &mut self,
+        started_with_zero: bool,
+        started_with_dot: bool,
+    ) -> Result<TokenKind, ParseError> {
+        let mut is_float = false;
+        if started_with_zero && (self.try_consume(b'x') || self.try_consume(b'X')) {
+            if !self.try_consume_one(is_hex_digit) {
+                return Err(self.error("\"0x\" must be followed by hex digits."));
+            }
+            self.consume_zero_or_more(is_hex_digit);
+        } else if started_with_zero && is_digit(self.current_char()) {
+            self.consume_zero_or_more(is_octal_digit);
+            if is_digit(self.current_char()) {
+                return Err(self.error("Numbers starting with leading zero must be in octal."));
+            }
+        } else {
+            if started_with_dot {
+                is_float = true;
+                self.consume_zero_or_more(is_digit);
+            } else {
+                self.consume_zero_or_more(is_digit);
+                if self.try_consume(b'.') {
+                    is_float = true;
+                    self.consume_zero_or_more(is_digit);
+                }
+            }
+
+            if self.try_consume(b'e') || self.try_consume(b'E') {
+                is_float = true;
+                let _ = self.try_consume(b'-') || self.try_consume(b'+');
+                if !self.try_consume_one(is_digit) {
+                    return Err(self.error("\"e\" must be followed by exponent."));
+                }
+                self.consume_zero_or_more(is_digit);
+            }
+
+            if self.try_consume(b'f') || self.try_consume(b'F') {
+                is_float = true;
+            }
+        }
+
+        if is_letter(self.current_char()) {
+            return Err(self.error("Need space between number and identifier."));
+        }
+        if self.current_char() == b'.' {
+            return Err(if is_float {
+                self.error("Already saw decimal point or exponent; can't have another one.")
+            } else {
+                self.error("Hex and octal numbers must be integers.")
+            });
+        }
+
+        Ok(if is_float {
+            TokenKind::Float
+        } else {
+            TokenKind::Integer
+        })
+    }
+
+    /// Consumes the rest of a string literal, validating its escape sequences.
+    fn consume_string(&mut self, delimiter: u8) -> Result<(), ParseError> {
+        loop {
+            if self.at_end() {
+                return Err(self.error("Unexpected end of string."));
+            }
+            match self.current_char() {
+                b'\n' => return Err(self.error("String literals cannot cross line boundaries.")),
+                b'\\' => {
+                    self.next_char();
+                    if self.try_consume_one(is_escape) || self.try_consume_one(is_octal_digit) {
+                        // A valid escape sequence. Octal escapes may be followed by two more
+                        // digits, which are consumed as regular characters.
+                    } else if self.try_consume(b'x') {
+                        if !self.try_consume_one(is_hex_digit) {
+                            return Err(self.error("Expected hex digits for escape sequence."));
+                        }
+                    } else if self.try_consume(b'u') {
+                        for _ in 0..4 {
+                            if !self.try_consume_one(is_hex_digit) {
+                                return Err(
+                                    self.error("Expected four hex digits for \\u escape sequence.")
+                                );
+                            }
+                        }
+                    } else if self.try_consume(b'U') {
+                        for _ in 0..8 {
+                            if !self.try_consume_one(is_hex_digit) {
+                                return Err(self.error(
+                                    "Expected eight hex digits up to 10ffff for \\U escape \
+                                     sequence",
+                                ));
+                            }
+                        }
+                    } else {
+                        return Err(self.error("Invalid escape sequence in string literal."));
+                    }
+                }
+                c if c == delimiter => {
+                    self.next_char();
+                    return Ok(());
+                }
+                _ => self.next_char(),
+            }
+        }
+    }
+}
+
+/// Collects comments while advancing to the next token.
+struct CommentCollector {
+    comments: Comments,
+    buffer: Vec<u8>,
+    has_comment: bool,
+    is_line_comment: bool,
+    can_attach_to_prev: bool,
+}
+
+impl Default for CommentCollector {
+    fn default() -> CommentCollector {
+        CommentCollector {
+            comments: Comments::default(),
+            buffer: Vec::new(),
+            has_comment: false,
+            is_line_comment: false,
+            can_attach_to_prev: true,
+        }
+    }
+}
+
+impl CommentCollector {
+    fn buffer_for_line_comment(&mut self) -> &mut Vec<u8> {
+        // Consecutive line comments are combined, but not with block comments.
+        if self.has_comment && !self.is_line_comment {
+            self.flush();
+        }
+        self.has_comment = true;
+        self.is_line_comment = true;
+        &mut self.buffer
+    }
+
+    fn buffer_for_block_comment(&mut self) -> &mut Vec<u8> {
+        if self.has_comment {
+            self.flush();
+        }
+        self.has_comment = true;
+        self.is_line_comment = false;
+        &mut self.buffer
+    }
+
+    fn clear_buffer(&mut self) {
+        self.buffer.clear();
+        self.has_comment = false;
+    }
+
+    /// Called once the buffered comment is known not to lead the next token.
+    fn flush(&mut self) {
+        if self.has_comment {
+            let comment = to_string(mem::replace(&mut self.buffer, Vec::new()));
+            if self.can_attach_to_prev {
+                self.comments.trailing.push_str(&comment);
+                self.can_attach_to_prev = false;
+            } else {
+                self.comments.detached.push(comment);
+            }
+            self.clear_buffer();
+        }
+    }
+
+    fn detach_from_prev(&mut self) {
+        self.can_attach_to_prev = false;
+    }
+}
+
+fn to_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|error| String::from_utf8_lossy(error.as_bytes()).into_owned())
+}
+
+/// Parses the text of an integer token, returning `None` if it is larger than `max_value`.
+pub fn parse_integer(text: &str, max_value: u64) -> Option<u64> {
+    let (digits, radix) = if text.starts_with("0x") || text.starts_with("0X") {
+        (&text[2..], 16)
+    } else if text.len() > 1 && text.starts_with('0') {
+        (&text[1..], 8)
+    } else {
+        (text, 10)
+    };
+    u64::from_str_radix(digits, radix).ok().and_then(|value| {
+        if value > max_value {
+            None
+        } else {
+            Some(value)
+        }
+    })
+}
+
+/// Parses the text of a float token.
+pub fn parse_float(text: &str) -> f64 {
+    let text = text.trim_end_matches(&['f', 'F'][..]);
+    // Rust doesn't accept a decimal point directly followed by an exponent.
+    let text = text.replace(".e", ".0e").replace(".E", ".0E");
+    text.parse().unwrap_or(0.0)
+}
+
+/// Parses the text of a string token, appending the unescaped bytes to `output`.
+pub fn parse_string_append(text: &str, output: &mut Vec<u8>) {
+    let bytes = text.as_bytes();
+    if bytes.is_empty() {
+        return;
+    }
+    let quote = bytes[0];
+    let mut i = 1;
+    while i < bytes.len() {
+        let c = bytes[i];
+        if c == b'\\' && i + 1 < bytes.len() {
+            i += 1;
+            let c = bytes[i];
+            if is_octal_digit(c) {
+                let mut code = u32::from(c - b'0');
+                for _ in 0..2 {
+                    if i + 1 < bytes.len() && is_octal_digit(bytes[i + 1]) {
+                        i += 1;
+                        code = code * 8 + u32::from(bytes[i] - b'0');
+                    }
+                }
+                output.push(code as u8);
+            } else if c == b'x' {
+                let mut code = 0;
+                for _ in 0..2 {
+                    if i + 1 < bytes.len() && is_hex_digit(bytes[i + 1]) {
+                        i += 1;
+                        code = code * 16 + hex_value(bytes[i]);
+                    }
+                }
+                output.push(code as u8);
+            } else if c == b'u' || c == b'U' {
+                let len = if c == b'u' { 4 } else { 8 };
+                let mut code = hex_code(&bytes[i + 1..], len);
+                i += len;
+                // Combine UTF-16 surrogate pairs.
+                if (0xd800..0xdc00).contains(&code) && bytes[i + 1..].starts_with(b"\\u") {
+                    let trail = hex_code(&bytes[i + 3..], 4);
+                    if (0xdc00..0xe000).contains(&trail) {
+                        code = 0x10000 + ((code - 0xd800) << 10) + (trail - 0xdc00);
+                        i += 6;
+                    }
+                }
+                append_utf8(code, output);
+            } else {
+                output.push(match c {
+                    b'a' => 0x07,
+                    b'b' => 0x08,
+                    b'f' => 0x0c,
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    b'v' => 0x0b,
+                    c => c,
+                });
+            }
+        } else if c == quote && i + 1 == bytes.len() {
+            // The closing quote.
+        } else {
+            output.push(c);
+        }
+        i += 1;
+    }
+}
+
+fn hex_code(bytes: &[u8], len: usize) -> u32 {
+    bytes[..len]
+        .iter()
+        .fold(0, |code, &c| code * 16 + hex_value(c))
+}
+
+/// Appends the UTF-8 encoding of a code point. Lone surrogates are encoded like other code points,
+/// as `protoc` does.
+fn append_utf8(code: u32, output: &mut Vec<u8>) {
+    if code < 0x80 {
+        output.push(code as u8);
+    } else if code < 0x800 {
+        output.push(0xc0 | (code >> 6) as u8);
+        output.push(0x80 | (code & 0x3f) as u8);
+    } else if code < 0x10000 {
+        output.push(0xe0 | (code >> 12) as u8);
+        output.push(0x80 | ((code >> 6) & 0x3f) as u8);
+        output.push(0x80 | (code & 0x3f) as u8);
+    } else {
+        output.push(0xf0 | (code >> 18) as u8);
+        output.push(0x80 | ((code >> 12) & 0x3f) as u8);
+        output.push(0x80 | ((code >> 6) & 0x3f) as u8);
+        output.push(0x80 | (code & 0x3f) as u8);
+    }
+}
+
+fn hex_value(c: u8) -> u32 {
+    (c as char).to_digit(16).unwrap_or(0)
+}
+
+fn is_whitespace(c: u8) -> bool {
+    c == b'\n' || is_whitespace_no_newline(c)
+}
+
+fn is_whitespace_no_newline(c: u8) -> bool {
+    match c {
+        b' ' | b'\t' | b'\r' | 0x0b | 0x0c => true,
+        _ => false,
+    }
+}
+
+fn is_letter(c: u8) -> bool {
+    (c as char).is_ascii_alphabetic() || c == b'_'
+}
+
+fn is_alphanumeric(c: u8) -> bool {
+    is_letter(c) || is_digit(c)
+}
+
+fn is_digit(c: u8) -> bool {
+    (c as char).is_ascii_digit()
+}
+
+fn is_octal_digit(c: u8) -> bool {
+    (b'0'..=b'7').contains(&c)
+}
+
+fn is_hex_digit(c: u8) -> bool {
+    (c as char).is_ascii_hexdigit()
+}
+
+fn is_escape(c: u8) -> bool {
+    match c {
+        b'a' | b'b' | b'f' | b'n' | b'r' | b't' | b'v' | b'\\' | b'?' | b'\'' | b'"' => true,
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(input: &str) -> Vec<(TokenKind, String, i32, i32, i32)> {
+        let mut tokenizer = Tokenizer::new(input);
+        let mut tokens = Vec::new();
+        while tokenizer.next().unwrap() {
+            let token = tokenizer.current();
+            tokens.push((
+                token.kind,
+                token.text.clone(),
+                token.line,
+                token.column,
+                token.end_column,
+            ));
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_tokens() {
+        assert_eq!(
+            tokens("foo = 0x1F;\n\tbar: 1.5e3 'a\\'b' .5f"),
+            vec![
+                (TokenKind::Identifier, "foo".to_string(), 0, 0, 3),
+                (TokenKind::Symbol, "=".to_string(), 0, 4, 5),
+                (TokenKind::Integer, "0x1F".to_string(), 0, 6, 10),
+                (TokenKind::Symbol, ";".to_string(), 0, 10, 11),
+                (TokenKind::Identifier, "bar".to_string(), 1, 8, 11),
+                (TokenKind::Symbol, ":".to_string(), 1, 11, 12),
+                (TokenKind::Float, "1.5e3".to_string(), 1, 13, 18),
+                (TokenKind::String, "'a\\'b'".to_string(), 1, 19, 25),
+                (TokenKind::Float, ".5f".to_string(), 1, 26, 29),
+            ]
+        );
+        assert!(Tokenizer::new("0x").next().is_err());
+        assert!(Tokenizer::new("\"abc").next().is_err());
+        assert!(Tokenizer::new("/* /* */").next().is_err());
+    }
+
+    #[test]
+    fn test_parse_values() {
+        assert_eq!(parse_integer("0x1f", u64::max_value()), Some(31));
+        assert_eq!(parse_integer("017", u64::max_value()), Some(15));
+        assert_eq!(parse_integer("0", u64::max_value()), Some(0));
+        assert_eq!(parse_integer("2147483648", 2147483647), None);
+        assert_eq!(parse_float("1.e2"), 100.0);
+        assert_eq!(parse_float("2.5f"), 2.5);
+
+        let mut output = Vec::new();
+        parse_string_append(r#""a\n\101\x42é😀\"""#, &mut output);
+        assert_eq!(output, "a\nAB\u{e9}\u{1f600}\"".as_bytes());
+    }
+
+    #[test]
+    fn test_comments() {
+        let input = "\
+// Detached.
+
+// Leading.
+foo; // Trailing.
+
+// Next leading.
+/* Block
+ * comment. */
+bar;
+// Scope end.
+}";
+        let mut tokenizer = Tokenizer::new(input);
+        let comments = tokenizer.next_with_comments().unwrap();
+        assert_eq!(comments.trailing, "");
+        assert_eq!(comments.detached, vec![" Detached.\n".to_string()]);
+        assert_eq!(comments.leading, " Leading.\n");
+        assert_eq!(tokenizer.current().text, "foo");
+
+        tokenizer.next().unwrap();
+        let comments = tokenizer.next_with_comments().unwrap();
+        assert_eq!(comments.trailing, " Trailing.\n");
+        assert_eq!(comments.detached, vec![" Next leading.\n".to_string()]);
+        assert_eq!(comments.leading, " Block\n comment. ");
+        assert_eq!(tokenizer.current().text, "bar");
+
+        tokenizer.next().unwrap();
+        let comments = tokenizer.next_with_comments().unwrap();
+        assert_eq!(comments.trailing, " Scope end.\n");
+        assert!(comments.detached.is_empty());
+        assert_eq!(comments.leading, "");
+        assert_eq!(tokenizer.current().text, "}");
+    }
+}
@@ -0,0 +1,313 @@
+//! Checks resolved files for the errors `protoc` reports once the types are known, such as
+//! conflicting field numbers, with the messages `protoc` uses.
+//!
+//! Errors are located at the part of the declaration `protoc` reports them at, e.g. the number of
+//! a field, or only by the file name for parts whose location `protoc` does not record.
+
+use std::collections::HashMap;
+
+use prost_types::field_descriptor_proto::{Label, Type};
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, FieldDescriptorProto, FileDescriptorProto,
+};
+
+use super::grammar::MAX_FIELD_NUMBER;
+use super::resolve::qualify;
+use super::ParseError;
+
+/// The field numbers reserved for the protocol buffer library implementation.
+const FIRST_RESERVED_NUMBER: i32 = 19_000;
+const LAST_RESERVED_NUMBER: i32 = 19_999;
+
+/// The part of a declaration an error is reported at, like the `ErrorLocation` of `protoc`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorLocation {
+    Name,
+    Number,
+    Type,
+    DefaultValue,
+    /// The declaration as a whole, which is not located.
+    Other,
+}
+
+/// The lines and columns of the parts of declarations, by the source location path of the
+/// declaration.
+pub type ErrorLocations = HashMap<(Vec<i32>, ErrorLocation), (i32, i32)>;
+
+/// Validates resolved files, which must be ordered such that imports precede the files importing
+/// them, using the error locations recorded when each file was parsed.
+pub fn validate(files: &[FileDescriptorProto], locations: &[ErrorLocations]) -> Result<(), String> {
+    let mut messages = HashMap::new();
+    for file in files {
+        let scope = match file.package {
+            Some(ref package) if !package.is_empty() => format!(".{}", package),
+            _ => String::new(),
+        };
+        index_messages(&scope, &file.message_type, &mut messages);
+    }
+
+    let mut symbols = HashMap::new();
+    for (file, locations) in files.iter().zip(locations) {
+        let validator = Validator {
+            file,
+            locations,
+            messages: &messages,
+            proto3: file.syntax() == "proto3",
+        };
+        validator.validate_file(&mut symbols)?;
+    }
+    Ok(())
+}
+
+/// Indexes messages and their nested messages by fully qualified name, e.g. `.foo.Bar`.
+fn index_messages<'a>(
+    scope: &str,
+    messages: &'a [DescriptorProto],
+    index: &mut HashMap<String, &'a DescriptorProto>,
+) {
+    for message in messages {
+        let name = format!("{}.{}", scope, message.name());
+        index_messages(&name, &message.nested_type, index);
+        index.insert(name, message);
+    }
+}
+
+fn child(path: &[i32], child: &[i32]) -> Vec<i32> {
+    let mut path = path.to_vec();
+    path.extend_from_slice(child);
+    path
+}
+
+struct Validator<'a> {
+    file: &'a FileDescriptorProto,
+    locations: &'a ErrorLocations,
+    /// The messages of all files, by fully qualified name.
+    messages: &'a HashMap<String, &'a DescriptorProto>,
+    proto3: bool,
+}
+
+impl<'a> Validator<'a> {
+    /// Validates the declarations of the file, adding the names of its messages and enums to
+    /// `symbols`, along with the file declaring them.
+    fn validate_file(&self, symbols: &mut HashMap<String, &'a str>) -> Result<(), String> {
+        let package = self.file.package();
+        for (index, message) in self.file.message_type.iter().enumerate() {
+            self.validate_message(package, message, &[4, index as i32], symbols)?;
+        }
+        for (index, enum_type) in self.file.enum_type.iter().enumerate() {
+            self.validate_enum(package, enum_type, &[5, index as i32], symbols)?;
+        }
+        for (index, extension) in self.file.extension.iter().enumerate() {
+            self.validate_extension(extension, &[7, index as i32])?;
+        }
+        Ok(())
+    }
+
+    fn validate_message(
+        &self,
+        scope: &str,
+        message: &DescriptorProto,
+        path: &[i32],
+        symbols: &mut HashMap<String, &'a str>,
+    ) -> Result<(), String> {
+        let name = qualify(scope, message.name());
+        self.add_symbol(&name, path, symbols)?;
+
+        let mut numbers = HashMap::new();
+        for (index, field) in message.field.iter().enumerate() {
+            let field_path = child(path, &[2, index as i32]);
+            self.validate_field(field, &field_path)?;
+            self.validate_map_key(field, &field_path)?;
+
+            let number = field.number();
+            for (index, range) in message.reserved_range.iter().enumerate() {
+                if range.start() <= number && number < range.end() {
+                    return Err(self.error(
+                        &child(path, &[9, index as i32]),
+                        ErrorLocation::Number,
+                        format!(
+                            "Field \"{}\" uses reserved number {}.",
+                            field.name(),
+                            number
+                        ),
+                    ));
+                }
+            }
+            if let Some(other) = numbers.insert(number, field.name()) {
+                return Err(self.error(
+                    &field_path,
+                    ErrorLocation::Number,
+                    format!(
+                        "Field number {} has already been used in \"{}\" by field \"{}\".",
+                        number, name, other
+                    ),
+                ));
+            }
+        }
+        if self.proto3 && !message.extension_range.is_empty() {
+            return Err(self.error(
+                path,
+                ErrorLocation::Other,
+                "Extension ranges are not allowed in proto3.",
+            ));
+        }
+
+        for (index, nested) in message.nested_type.iter().enumerate() {
+            self.validate_message(&name, nested, &child(path, &[3, index as i32]), symbols)?;
+        }
+        for (index, enum_type) in message.enum_type.iter().enumerate() {
+            self.validate_enum(&name, enum_type, &child(path, &[4, index as i32]), symbols)?;
+        }
+        for (index, extension) in message.extension.iter().enumerate() {
+            self.validate_extension(extension, &child(path, &[6, index as i32]))?;
+        }
+        Ok(())
+    }
+
+    fn validate_field(&self, field: &FieldDescriptorProto, path: &[i32]) -> Result<(), String> {
+        let number = field.number();
+        let number_error = if number <= 0 {
+            Some("Field numbers must be positive integers.".to_string())
+        } else if field.extendee.is_none() && number > MAX_FIELD_NUMBER {
+            Some(format!(
+                "Field numbers cannot be greater than {}.",
+                MAX_FIELD_NUMBER
+            ))
+        } else if (FIRST_RESERVED_NUMBER..=LAST_RESERVED_NUMBER).contains(&number) {
+            Some(format!(
+                "Field numbers {} through {} are reserved for the protocol buffer library \
+                 implementation.",
+                FIRST_RESERVED_NUMBER, LAST_RESERVED_NUMBER
+            ))
+        } else {
+            None
+        };
+        if let Some(message) = number_error {
+            return Err(self.error(path, ErrorLocation::Number, message));
+        }
+
+        if field.label() == Label::Repeated && field.default_value.is_some() {
+            return Err(self.error(
+                path,
+                ErrorLocation::DefaultValue,
+                "Repeated fields can't have default values.",
+            ));
+        }
+        if self.proto3 && field.label() == Label::Required {
+            return Err(self.error(
+                path,
+                ErrorLocation::Other,
+                "Required fields are not allowed in proto3.",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Checks the key type of a map field, which is the first field of its map entry message.
+    fn validate_map_key(&self, field: &FieldDescriptorProto, path: &[i32]) -> Result<(), String> {
+        let entry = match self.messages.get(field.type_name()) {
+            Some(entry) if entry.options.as_ref().map_or(false, |o| o.map_entry()) => entry,
+            _ => return Ok(()),
+        };
+        let message = match entry.field.first().map(FieldDescriptorProto::type_) {
+            Some(Type::Enum) => "Key in map fields cannot be enum types.",
+            Some(Type::Float) | Some(Type::Double) | Some(Type::Bytes) | Some(Type::Message)
+            | Some(Type::Group) => {
+                "Key in map fields cannot be float/double, bytes or message types."
+            }
+            _ => return Ok(()),
+        };
+        Err(self.error(path, ErrorLocation::Type, message))
+    }
+
+    fn validate_enum(
+        &self,
+        scope: &str,
+        enum_type: &EnumDescriptorProto,
+        path: &[i32],
+        symbols: &mut HashMap<String, &'a str>,
+    ) -> Result<(), String> {
+        self.add_symbol(&qualify(scope, enum_type.name()), path, symbols)?;
+        match enum_type.value.first() {
+            Some(value) if self.proto3 && value.number() != 0 => Err(self.error(
+                &child(path, &[2, 0]),
+                ErrorLocation::Number,
+                "The first enum value must be zero in proto3.",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks an extension field, which must use one of the extension numbers of its extendee.
+    fn validate_extension(
+        &self,
+        extension: &FieldDescriptorProto,
+        path: &[i32],
+    ) -> Result<(), String> {
+        self.validate_field(extension, path)?;
+        let extendee = match self.messages.get(extension.extendee()) {
+            Some(extendee) => extendee,
+            None => return Ok(()),
+        };
+        let number = extension.number();
+        if extendee
+            .extension_range
+            .iter()
+            .any(|range| range.start() <= number && number < range.end())
+        {
+            return Ok(());
+        }
+        Err(self.error(
+            path,
+            ErrorLocation::Number,
+            format!(
+                "\"{}\" does not declare {} as an extension number.",
+                extension.extendee().trim_start_matches('.'),
+                number
+            ),
+        ))
+    }
+
+    /// Adds the fully qualified name of a message or enum to `symbols`, unless it is already
+    /// defined.
+    fn add_symbol(
+        &self,
+        name: &str,
+        path: &[i32],
+        symbols: &mut HashMap<String, &'a str>,
+    ) -> Result<(), String> {
+        let file: &'a FileDescriptorProto = self.file;
+        let message = match symbols.get(name) {
+            None => {
+                symbols.insert(name.to_string(), file.name());
+                return Ok(());
+            }
+            Some(&other) if other != file.name() => {
+                format!("\"{}\" is already defined in file \"{}\".", name, other)
+            }
+            Some(_) => match name.rfind('.') {
+                Some(dot) => format!(
+                    "\"{}\" is already defined in \"{}\".",
+                    &name[dot + 1..],
+                    &name[..dot]
+                ),
+                None => format!("\"{}\" is already defined.", name),
+            },
+        };
+        Err(self.error(path, ErrorLocation::Name, message))
+    }
+
+    fn error<S>(&self, path: &[i32], part: ErrorLocation, message: S) -> String
+    where
+        S: Into<String>,
+    {
+        match self.locations.get(&(path.to_vec(), part)) {
+            Some(&(line, column)) => format!(
+                "{}:{}",
+                self.file.name(),
+                ParseError::new(line, column, message)
+            ),
+            None => format!("{}: {}", self.file.name(), message.into()),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! Support for running as a `protoc` plugin.
+//!
+//! The plugin parameter is a comma separated list of options, which are applied to the `Config`:
+//!
+//! - `btree_map=PATH`: `Config::btree_map`.
+//! - `builder=PATH`: `Config::builder`.
+//! - `type_attribute=PATH=ATTRIBUTE`: `Config::type_attribute`.
+//! - `field_attribute=PATH=ATTRIBUTE`: `Config::field_attribute`.
+//! - `amino_name=PATH=NAME`: `Config::amino_name`.
+//! - `extern_path=PATH=RUST_PATH`: `Config::extern_path`.
+//! - `custom_type=GO_TYPE=RUST_TYPE`: `Config::custom_type`.
+//! - `compile_well_known_types`: `Config::compile_well_known_types`.
+//! - `retain_enum_prefix`: `Config::retain_enum_prefix`.
+//! - `include_file=PATH`: `Config::include_file`.
+//! - `socket_services`: `Config::service_generator` with a `SocketServiceGenerator`.
+//!
+//! Commas within brackets, parentheses, braces or quotes do not separate options, so that
+//! attributes such as `#[derive(Eq, Hash)]` can be passed as is.
+
+use {Config, SocketServiceGenerator};
+
+/// Applies a plugin parameter to a configuration.
+pub fn apply_parameter(config: &mut Config, parameter: &str) -> Result<(), String> {
+    for option in split_options(parameter) {
+        let option = option.trim();
+        if option.is_empty() {
+            continue;
+        }
+        let (key, value) = match option.find('=') {
+            Some(index) => (&option[..index], Some(&option[index + 1..])),
+            None => (option, None),
+        };
+        match (key, value) {
+            ("btree_map", Some(path)) => {
+                config.btree_map(&[path]);
+            }
+            ("builder", Some(path)) => {
+                config.builder(&[path]);
+            }
+            ("type_attribute", Some(value)) => {
+                let (path, attribute) = path_value(key, value)?;
+                config.type_attribute(path, attribute);
+            }
+            ("field_attribute", Some(value)) => {
+                let (path, attribute) = path_value(key, value)?;
+                config.field_attribute(path, attribute);
+            }
+            ("amino_name", Some(value)) => {
+                let (path, name) = path_value(key, value)?;
+                config.amino_name(path, name);
+            }
+            ("extern_path", Some(value)) => {
+                let (proto_path, rust_path) = path_value(key, value)?;
+                config.extern_path(proto_path, rust_path);
+            }
+            ("custom_type", Some(value)) => {
+                let (go_type, rust_type) = path_value(key, value)?;
+                config.custom_type(go_type, rust_type);
+            }
+            ("include_file", Some(path)) => {
+                config.include_file(path);
+            }
+            ("compile_well_known_types", None) => {
+                config.compile_well_known_types();
+            }
+            ("retain_enum_prefix", None) => {
+                config.retain_enum_prefix();
+            }
+            ("socket_services", None) => {
+                config.service_generator(Box::new(SocketServiceGenerator));
+            }
+            _ => return Err(format!("invalid plugin parameter option: {}", option)),
+        }
+    }
+    Ok(())
+}
+
+/// Splits a `PATH=VALUE` option value.
+fn path_value<'a>(key: &str, value: &'a str) -> Result<(&'a str, &'a str), String> {
+    match value.find('=') {
+        Some(index) if index > 0 => Ok((&value[..index], &value[index + 1..])),
+        _ => Err(format!(
+            "invalid plugin parameter option: {}={} (expected {}=PATH=VALUE)",
+            key, value, key
+        )),
+    }
+}
+
+/// Splits a parameter on the commas which are not nested in brackets or quotes.
+fn split_options(parameter: &str) -> Vec<&str> {
+    let mut options = Vec::new();
+    let mut depth = 0usize;
+    let mut quote = None;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in parameter.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                options.push(&parameter[start..index]);
+                start = index + 1;
+            }
+            _ => (),
+        }
+    }
+    options.push(&parameter[start..]);
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_options() {
+        assert_eq!(
+            split_options(
+                "btree_map=.,type_attribute=.foo.Bar=#[derive(Eq, Hash)],\
+                 field_attribute=baz=#[serde(rename = \"a,b\")]"
+            ),
+            vec![
+                "btree_map=.",
+                "type_attribute=.foo.Bar=#[derive(Eq, Hash)]",
+                "field_attribute=baz=#[serde(rename = \"a,b\")]",
+            ]
+        );
+        assert_eq!(split_options(""), vec![""]);
+    }
+
+    #[test]
+    fn test_apply_parameter() {
+        let mut config = Config::new();
+        apply_parameter(
+            &mut config,
+            "btree_map=.foo, amino_name=.foo.Bar=foo/Bar,retain_enum_prefix,\
+             type_attribute=Bar=#[derive(Eq, Hash)],socket_services",
+        )
+        .unwrap();
+        assert_eq!(config.btree_map, vec![".foo".to_string()]);
+        assert_eq!(
+            config.amino_names,
+            vec![(".foo.Bar".to_string(), "foo/Bar".to_string())]
+        );
+        assert_eq!(
+            config.type_attributes,
+            vec![("Bar".to_string(), "#[derive(Eq, Hash)]".to_string())]
+        );
+        assert!(!config.strip_enum_prefix);
+        assert!(config.service_generator.is_some());
+
+        assert_eq!(
+            apply_parameter(&mut config, "btree_map").unwrap_err(),
+            "invalid plugin parameter option: btree_map"
+        );
+        assert_eq!(
+            apply_parameter(&mut config, "amino_name=foo/Bar").unwrap_err(),
+            "invalid plugin parameter option: amino_name=foo/Bar (expected amino_name=PATH=VALUE)"
+        );
+    }
+}
@@ -0,0 +1,337 @@
+//! A service generator for request/response services over length-delimited streams.
+
+use ident::{to_snake, to_upper_camel};
+use {Method, Service, ServiceGenerator};
+
+/// A service generator for services which exchange length-delimited messages over a stream, such
+/// as a Unix socket or a TCP connection, in the style of ABCI and the Tendermint remote signer.
+///
+/// For a service `Foo`, the generator emits:
+///
+/// - A `Foo` trait with a method per RPC, which is implemented by the application.
+/// - `FooRequest` and `FooResponse` envelope messages, with a oneof variant per RPC, tagged by the
+///   position of the RPC in the service.
+/// - A `FooDispatcher`, which reads requests from a stream, calls the `Foo` implementation and
+///   writes back the responses.
+/// - A `FooClient`, which sends requests over a stream and waits for the responses.
+///
+/// The generated code works with any `Read + Write` stream, with the framing of
+/// `prost_amino::stream`. Streaming RPCs don't fit the request/response model, and are skipped.
+#[derive(Debug, Default)]
+pub struct SocketServiceGenerator;
+
+impl ServiceGenerator for SocketServiceGenerator {
+    fn generate(&mut self, service: Service, buf: &mut String) {
+        let methods: Vec<&Method> = service
+            .methods
+            .iter()
+            .filter(|method| {
+                let streaming = method.client_streaming || method.server_streaming;
+                if streaming {
+                    warn!(
+                        "skipping streaming method {}.{}",
+                        service.proto_name, method.proto_name
+                    );
+                }
+                !streaming
+            })
+            .collect();
+        if methods.is_empty() {
+            warn!(
+                "skipping service {} without unary methods",
+                service.proto_name
+            );
+            return;
+        }
+
+        append_trait(&service, &methods, buf);
+        append_envelope(
+            &service,
+            &methods,
+            "Request",
+            |method| &method.input_type,
+            buf,
+        );
+        append_envelope(
+            &service,
+            &methods,
+            "Response",
+            |method| &method.output_type,
+            buf,
+        );
+        append_dispatcher(&service, &methods, buf);
+        append_client(&service, &methods, buf);
+    }
+}
+
+fn append_trait(service: &Service, methods: &[&Method], buf: &mut String) {
+    service.comments.append_with_indent(0, buf);
+    buf.push_str(&format!("pub trait {} {{\n", service.name));
+    for method in methods {
+        method.comments.append_with_indent(1, buf);
+        buf.push_str(&format!(
+            "    fn {}(&mut self, request: {}) -> {};\n",
+            method.name, method.input_type, method.output_type
+        ));
+    }
+    buf.push_str("}\n");
+}
+
+/// Appends a request or response envelope message, with a oneof variant per method.
+fn append_envelope<F>(service: &Service, methods: &[&Method], kind: &str, ty: F, buf: &mut String)
+where
+    F: Fn(&Method) -> &str,
+{
+    let name = format!("{}{}", service.name, kind);
+    let module = to_snake(&name);
+    buf.push_str(&format!(
+        "/// {} envelope of the `{}` service.\n",
+        kind, service.proto_name
+    ));
+    buf.push_str("#[derive(Clone, PartialEq, Message)]\n");
+    buf.push_str(&format!("pub struct {} {{\n", name));
+    buf.push_str(&format!(
+        "    #[prost(oneof=\"{}::Value\", tags=\"{}\")]\n",
+        module,
+        (1..=methods.len())
+            .map(|tag| tag.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    ));
+    buf.push_str(&format!(
+        "    pub value: ::std::option::Option<{}::Value>,\n",
+        module
+    ));
+    buf.push_str("}\n");
+
+    buf.push_str(&format!("pub mod {} {{\n", module));
+    buf.push_str("    #[derive(Clone, Oneof, PartialEq)]\n");
+    buf.push_str("    pub enum Value {\n");
+    for (index, method) in methods.iter().enumerate() {
+        // The envelope module is nested in the module of the service.
+        let ty = ty(method);
+        let ty = if ty.starts_with("::") {
+            ty.to_string()
+        } else {
+            format!("super::{}", ty)
+        };
+        buf.push_str(&format!(
+            "        #[prost(message, tag=\"{}\")]\n",
+            index + 1
+        ));
+        buf.push_str(&format!(
+            "        {}({}),\n",
+            to_upper_camel(&method.proto_name),
+            ty
+        ));
+    }
+    buf.push_str("    }\n");
+    buf.push_str("}\n");
+}
+
+fn append_dispatcher(service: &Service, methods: &[&Method], buf: &mut String) {
+    let name = &service.name;
+    let request = to_snake(&format!("{}Request", name));
+    let response = to_snake(&format!("{}Response", name));
+
+    buf.push_str(&format!(
+        "/// Dispatches requests of the `{}` service to an implementation.\n",
+        service.proto_name
+    ));
+    buf.push_str(&format!("pub struct {}Dispatcher<S> {{\n", name));
+    buf.push_str("    service: S,\n");
+    buf.push_str("}\n");
+    buf.push_str(&format!(
+        "impl<S> {}Dispatcher<S> where S: {} {{\n",
+        name, name
+    ));
+    buf.push_str("    pub fn new(service: S) -> Self {\n");
+    buf.push_str(&format!("        {}Dispatcher {{ service }}\n", name));
+    buf.push_str("    }\n");
+    buf.push_str("    pub fn into_inner(self) -> S {\n");
+    buf.push_str("        self.service\n");
+    buf.push_str("    }\n");
+
+    buf.push_str("    /// Calls the service method of a request, and returns its response.\n");
+    buf.push_str(&format!(
+        "    pub fn dispatch(&mut self, request: {}Request) -> ::std::io::Result<{}Response> {{\n",
+        name, name
+    ));
+    buf.push_str("        let value = match request.value {\n");
+    for method in methods {
+        let variant = to_upper_camel(&method.proto_name);
+        buf.push_str(&format!(
+            "            ::std::option::Option::Some({}::Value::{}(request)) => \
+             {}::Value::{}(self.service.{}(request)),\n",
+            request, variant, response, variant, method.name
+        ));
+    }
+    buf.push_str("            ::std::option::Option::None => return ::std::result::Result::Err(\n");
+    buf.push_str(&format!(
+        "                ::std::io::Error::new(::std::io::ErrorKind::InvalidData, \
+         \"empty {} request\"),\n",
+        service.proto_name
+    ));
+    buf.push_str("            ),\n");
+    buf.push_str("        };\n");
+    buf.push_str(&format!(
+        "        ::std::result::Result::Ok({}Response {{ value: ::std::option::Option::Some(value) }})\n",
+        name
+    ));
+    buf.push_str("    }\n");
+
+    buf.push_str("    /// Serves requests from a stream until it is closed.\n");
+    buf.push_str("    pub fn serve<T>(&mut self, stream: &mut T) -> ::std::io::Result<()>\n");
+    buf.push_str("    where\n");
+    buf.push_str("        T: ::std::io::Read + ::std::io::Write,\n");
+    buf.push_str("    {\n");
+    buf.push_str(&format!(
+        "        while let ::std::option::Option::Some(request) = \
+         ::prost_amino::stream::read_length_delimited::<{}Request, _>(stream)? {{\n",
+        name
+    ));
+    buf.push_str("            let response = self.dispatch(request)?;\n");
+    buf.push_str(
+        "            ::prost_amino::stream::write_length_delimited(&response, stream)?;\n",
+    );
+    buf.push_str("            ::std::io::Write::flush(stream)?;\n");
+    buf.push_str("        }\n");
+    buf.push_str("        ::std::result::Result::Ok(())\n");
+    buf.push_str("    }\n");
+    buf.push_str("}\n");
+}
+
+fn append_client(service: &Service, methods: &[&Method], buf: &mut String) {
+    let name = &service.name;
+    let request = to_snake(&format!("{}Request", name));
+    let response = to_snake(&format!("{}Response", name));
+
+    buf.push_str(&format!(
+        "/// A client of the `{}` service.\n",
+        service.proto_name
+    ));
+    buf.push_str(&format!("pub struct {}Client<T> {{\n", name));
+    buf.push_str("    stream: T,\n");
+    buf.push_str("}\n");
+    buf.push_str(&format!(
+        "impl<T> {}Client<T> where T: ::std::io::Read + ::std::io::Write {{\n",
+        name
+    ));
+    buf.push_str("    pub fn new(stream: T) -> Self {\n");
+    buf.push_str(&format!("        {}Client {{ stream }}\n", name));
+    buf.push_str("    }\n");
+    buf.push_str("    pub fn into_inner(self) -> T {\n");
+    buf.push_str("        self.stream\n");
+    buf.push_str("    }\n");
+
+    buf.push_str("    /// Sends a request, and waits for its response.\n");
+    buf.push_str(&format!(
+        "    pub fn call(&mut self, request: {}Request) -> ::std::io::Result<{}Response> {{\n",
+        name, name
+    ));
+    buf.push_str(
+        "        ::prost_amino::stream::write_length_delimited(&request, &mut self.stream)?;\n",
+    );
+    buf.push_str("        ::std::io::Write::flush(&mut self.stream)?;\n");
+    buf.push_str(
+        "        match ::prost_amino::stream::read_length_delimited(&mut self.stream)? {\n",
+    );
+    buf.push_str("            ::std::option::Option::Some(response) => ::std::result::Result::Ok(response),\n");
+    buf.push_str("            ::std::option::Option::None => ::std::result::Result::Err(::std::io::Error::new(\n");
+    buf.push_str("                ::std::io::ErrorKind::UnexpectedEof,\n");
+    buf.push_str(&format!(
+        "                \"stream closed before the {} response\",\n",
+        service.proto_name
+    ));
+    buf.push_str("            )),\n");
+    buf.push_str("        }\n");
+    buf.push_str("    }\n");
+
+    for method in methods {
+        let variant = to_upper_camel(&method.proto_name);
+        method.comments.append_with_indent(1, buf);
+        buf.push_str(&format!(
+            "    pub fn {}(&mut self, request: {}) -> ::std::io::Result<{}> {{\n",
+            method.name, method.input_type, method.output_type
+        ));
+        buf.push_str(&format!(
+            "        let request = {}Request {{ value: ::std::option::Option::Some({}::Value::{}(request)) }};\n",
+            name, request, variant
+        ));
+        buf.push_str("        #[allow(unreachable_patterns)]\n");
+        buf.push_str("        match self.call(request)?.value {\n");
+        buf.push_str(&format!(
+            "            ::std::option::Option::Some({}::Value::{}(response)) => \
+             ::std::result::Result::Ok(response),\n",
+            response, variant
+        ));
+        buf.push_str("            _ => ::std::result::Result::Err(::std::io::Error::new(\n");
+        buf.push_str("                ::std::io::ErrorKind::InvalidData,\n");
+        buf.push_str(&format!(
+            "                \"unexpected response to {}.{}\",\n",
+            service.proto_name, method.proto_name
+        ));
+        buf.push_str("            )),\n");
+        buf.push_str("        }\n");
+        buf.push_str("    }\n");
+    }
+    buf.push_str("}\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ast::Comments;
+
+    fn method(name: &str, input_type: &str, output_type: &str) -> Method {
+        Method {
+            name: to_snake(name),
+            proto_name: name.to_string(),
+            comments: Comments {
+                leading_detached: Vec::new(),
+                leading: Vec::new(),
+                trailing: Vec::new(),
+            },
+            input_type: input_type.to_string(),
+            output_type: output_type.to_string(),
+            input_proto_type: String::new(),
+            output_proto_type: String::new(),
+            options: Default::default(),
+            client_streaming: false,
+            server_streaming: false,
+        }
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut stream = method("Stream", "Echo", "Echo");
+        stream.server_streaming = true;
+        let service = Service {
+            name: "Signer".to_string(),
+            proto_name: "Signer".to_string(),
+            package: "signer".to_string(),
+            comments: Comments {
+                leading_detached: Vec::new(),
+                leading: Vec::new(),
+                trailing: Vec::new(),
+            },
+            methods: vec![
+                method("SignVote", "SignVoteRequest", "SignedVote"),
+                stream,
+                method("PubKey", "::keys::PubKeyRequest", "super::keys::PubKey"),
+            ],
+            options: Default::default(),
+        };
+        let mut buf = String::new();
+        SocketServiceGenerator.generate(service, &mut buf);
+
+        assert!(buf.contains("fn sign_vote(&mut self, request: SignVoteRequest) -> SignedVote;"));
+        assert!(!buf.contains("Stream"));
+        assert!(buf.contains("#[prost(oneof=\"signer_request::Value\", tags=\"1, 2\")]"));
+        assert!(buf.contains("SignVote(super::SignVoteRequest),"));
+        assert!(buf.contains("PubKey(::keys::PubKeyRequest),"));
+        assert!(buf.contains("PubKey(super::super::keys::PubKey),"));
+        assert!(buf.contains("pub struct SignerDispatcher<S>"));
+        assert!(buf.contains("pub struct SignerClient<T>"));
+    }
+}
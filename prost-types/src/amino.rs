@@ -0,0 +1,18 @@
+/// The amino name the message type is registered with.
+pub const NAME: ::prost_amino::extension::Extension<super::MessageOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "amino.name",
+        number: 50101,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+/// The amino name of the registered type of the field's values.
+pub const FIELD_NAME: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "amino.field_name",
+        number: 50102,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
@@ -1,5 +1,6 @@
 /// The version number of protocol compiler.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.compiler.Version")]
 pub struct Version {
     #[prost(int32, optional, tag="1")]
     pub major: ::std::option::Option<i32>,
@@ -14,6 +15,7 @@ pub struct Version {
 }
 /// An encoded CodeGeneratorRequest is written to the plugin's stdin.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.compiler.CodeGeneratorRequest")]
 pub struct CodeGeneratorRequest {
     /// The .proto files that were explicitly listed on the command-line.  The
     /// code generator should generate code only for these files.  Each file's
@@ -45,6 +47,7 @@ pub struct CodeGeneratorRequest {
 }
 /// The plugin writes an encoded CodeGeneratorResponse to stdout.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.compiler.CodeGeneratorResponse")]
 pub struct CodeGeneratorResponse {
     /// Error message.  If non-empty, code generation failed.  The plugin process
     /// should exit with status code zero even if it reports an error in this way.
@@ -62,6 +65,7 @@ pub struct CodeGeneratorResponse {
 pub mod code_generator_response {
     /// Represents a single generated file.
     #[derive(Clone, PartialEq, Message)]
+    #[prost(full_name="google.protobuf.compiler.CodeGeneratorResponse.File")]
     pub struct File {
         /// The file name, relative to the output directory.  The name must not
         /// contain "." or ".." components and must be relative, not be absolute (so,
@@ -0,0 +1,204 @@
+//! Conversion of a `FileDescriptorSet` into a `DescriptorPool` of dynamic message types.
+
+use std::collections::HashMap;
+
+use prost_amino::dynamic::{DescriptorPool, EnumType, FieldType, Kind, MessageType};
+use prost_amino::reflect::{Label, Type};
+
+use field_descriptor_proto;
+use DescriptorProto;
+use EnumDescriptorProto;
+use FieldDescriptorProto;
+use FileDescriptorSet;
+
+impl<'a> From<&'a FileDescriptorSet> for DescriptorPool {
+    /// Builds a pool containing every message and enumeration type of the file descriptor set.
+    ///
+    /// Map entry messages are folded into the map fields which use them.
+    fn from(set: &'a FileDescriptorSet) -> DescriptorPool {
+        // Map entry messages are referenced by name from map fields, so collect them up front.
+        let mut map_entries = HashMap::new();
+        for file in &set.file {
+            let package = package_prefix(file.package());
+            for message in &file.message_type {
+                collect_map_entries(&package, message, &mut map_entries);
+            }
+        }
+
+        let mut pool = DescriptorPool::new();
+        for file in &set.file {
+            let package = package_prefix(file.package());
+            let proto3 = file.syntax() == "proto3";
+            for message in &file.message_type {
+                add_message(&mut pool, &package, message, proto3, &map_entries);
+            }
+            for enumeration in &file.enum_type {
+                add_enum(&mut pool, &package, enumeration);
+            }
+        }
+        pool
+    }
+}
+
+/// Returns the prefix of fully qualified names in a package, e.g. `foo.bar.`.
+fn package_prefix(package: &str) -> String {
+    if package.is_empty() {
+        String::new()
+    } else {
+        format!("{}.", package)
+    }
+}
+
+fn collect_map_entries<'a>(
+    prefix: &str,
+    message: &'a DescriptorProto,
+    map_entries: &mut HashMap<String, &'a DescriptorProto>,
+) {
+    let name = format!("{}{}", prefix, message.name());
+    let nested_prefix = format!("{}.", name);
+    for nested in &message.nested_type {
+        collect_map_entries(&nested_prefix, nested, map_entries);
+    }
+    if message
+        .options
+        .as_ref()
+        .and_then(|options| options.map_entry)
+        .unwrap_or(false)
+    {
+        map_entries.insert(name, message);
+    }
+}
+
+fn add_message(
+    pool: &mut DescriptorPool,
+    prefix: &str,
+    message: &DescriptorProto,
+    proto3: bool,
+    map_entries: &HashMap<String, &DescriptorProto>,
+) {
+    let name = format!("{}{}", prefix, message.name());
+    let nested_prefix = format!("{}.", name);
+    for nested in &message.nested_type {
+        add_message(pool, &nested_prefix, nested, proto3, map_entries);
+    }
+    for enumeration in &message.enum_type {
+        add_enum(pool, &nested_prefix, enumeration);
+    }
+    if map_entries.contains_key(&name) {
+        return;
+    }
+
+    let fields = message
+        .field
+        .iter()
+        .filter_map(|field| field_type(field, proto3, map_entries))
+        .collect();
+    let synthetic_oneofs = message
+        .field
+        .iter()
+        .filter(|field| field.proto3_optional())
+        .count();
+    pool.add_message(MessageType {
+        name: name,
+        fields: fields,
+        // The synthetic oneofs of proto3 optional fields follow the declared oneofs.
+        oneofs: message
+            .oneof_decl
+            .iter()
+            .take(message.oneof_decl.len() - synthetic_oneofs)
+            .map(|oneof| oneof.name().to_string())
+            .collect(),
+        amino_name: None,
+        prefix: None,
+    });
+}
+
+fn add_enum(pool: &mut DescriptorPool, prefix: &str, enumeration: &EnumDescriptorProto) {
+    pool.add_enum(EnumType {
+        name: format!("{}{}", prefix, enumeration.name()),
+        values: enumeration
+            .value
+            .iter()
+            .map(|value| (value.name().to_string(), value.number()))
+            .collect(),
+    });
+}
+
+/// Converts a field descriptor, or returns `None` for map fields with an invalid entry type.
+fn field_type(
+    field: &FieldDescriptorProto,
+    proto3: bool,
+    map_entries: &HashMap<String, &DescriptorProto>,
+) -> Option<FieldType> {
+    let kind = match kind(field) {
+        Kind::Message(name) => match map_entries.get(&name) {
+            Some(entry) => {
+                let key = entry.field.iter().find(|f| f.number() == 1)?;
+                let value = entry.field.iter().find(|f| f.number() == 2)?;
+                match kind(key) {
+                    Kind::Scalar(key) => Kind::Map(key, Box::new(kind(value))),
+                    _ => return None,
+                }
+            }
+            None => Kind::Message(name),
+        },
+        kind => kind,
+    };
+
+    let packed = field.options.as_ref().and_then(|options| options.packed);
+    let label = match field.label() {
+        field_descriptor_proto::Label::Repeated => match kind {
+            Kind::Scalar(Type::String) | Kind::Scalar(Type::Bytes) => Label::Repeated,
+            Kind::Scalar(_) | Kind::Enumeration(_) if packed.unwrap_or(proto3) => Label::Packed,
+            _ => Label::Repeated,
+        },
+        field_descriptor_proto::Label::Required => Label::Required,
+        field_descriptor_proto::Label::Optional => match kind {
+            Kind::Scalar(_) | Kind::Enumeration(_) if proto3 && field.oneof_index.is_none() => {
+                Label::Plain
+            }
+            _ => Label::Optional,
+        },
+    };
+
+    Some(FieldType {
+        name: field.name().to_string(),
+        json_name: field.json_name().to_string(),
+        tag: field.number() as u32,
+        label: label,
+        kind: kind,
+        oneof: field
+            .oneof_index
+            .filter(|_| !field.proto3_optional())
+            .map(|index| index as usize),
+        write_empty: false,
+    })
+}
+
+/// Returns the kind of a field, without resolving map entries.
+fn kind(field: &FieldDescriptorProto) -> Kind {
+    use field_descriptor_proto::Type as T;
+
+    // Type names of message and enumeration fields are fully qualified, with a leading '.'.
+    let type_name = field.type_name().trim_start_matches('.').to_string();
+    match field.type_() {
+        T::Double => Kind::Scalar(Type::Double),
+        T::Float => Kind::Scalar(Type::Float),
+        T::Int64 => Kind::Scalar(Type::Int64),
+        T::Uint64 => Kind::Scalar(Type::Uint64),
+        T::Int32 => Kind::Scalar(Type::Int32),
+        T::Fixed64 => Kind::Scalar(Type::Fixed64),
+        T::Fixed32 => Kind::Scalar(Type::Fixed32),
+        T::Bool => Kind::Scalar(Type::Bool),
+        T::String => Kind::Scalar(Type::String),
+        T::Bytes => Kind::Scalar(Type::Bytes),
+        T::Uint32 => Kind::Scalar(Type::Uint32),
+        T::Sfixed32 => Kind::Scalar(Type::Sfixed32),
+        T::Sfixed64 => Kind::Scalar(Type::Sfixed64),
+        T::Sint32 => Kind::Scalar(Type::Sint32),
+        T::Sint64 => Kind::Scalar(Type::Sint64),
+        T::Enum => Kind::Enumeration(type_name),
+        T::Message => Kind::Message(type_name),
+        T::Group => Kind::Group(type_name),
+    }
+}
@@ -0,0 +1,616 @@
+pub const GOPROTO_ENUM_PREFIX: ::prost_amino::extension::Extension<super::EnumOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_enum_prefix",
+        number: 62001,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_ENUM_STRINGER: ::prost_amino::extension::Extension<super::EnumOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_enum_stringer",
+        number: 62021,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const ENUM_STRINGER: ::prost_amino::extension::Extension<super::EnumOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.enum_stringer",
+        number: 62022,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const ENUM_CUSTOMNAME: ::prost_amino::extension::Extension<super::EnumOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.enum_customname",
+        number: 62023,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const ENUMDECL: ::prost_amino::extension::Extension<super::EnumOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.enumdecl",
+        number: 62024,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const ENUMVALUE_CUSTOMNAME: ::prost_amino::extension::Extension<super::EnumValueOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.enumvalue_customname",
+        number: 66001,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_GETTERS_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_getters_all",
+        number: 63001,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_ENUM_PREFIX_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_enum_prefix_all",
+        number: 63002,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_STRINGER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_stringer_all",
+        number: 63003,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const VERBOSE_EQUAL_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.verbose_equal_all",
+        number: 63004,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const FACE_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.face_all",
+        number: 63005,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOSTRING_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.gostring_all",
+        number: 63006,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const POPULATE_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.populate_all",
+        number: 63007,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const STRINGER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.stringer_all",
+        number: 63008,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const ONLYONE_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.onlyone_all",
+        number: 63009,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const EQUAL_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.equal_all",
+        number: 63013,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const DESCRIPTION_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.description_all",
+        number: 63014,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const TESTGEN_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.testgen_all",
+        number: 63015,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const BENCHGEN_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.benchgen_all",
+        number: 63016,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const MARSHALER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.marshaler_all",
+        number: 63017,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const UNMARSHALER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.unmarshaler_all",
+        number: 63018,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const STABLE_MARSHALER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.stable_marshaler_all",
+        number: 63019,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const SIZER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.sizer_all",
+        number: 63020,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_ENUM_STRINGER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_enum_stringer_all",
+        number: 63021,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const ENUM_STRINGER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.enum_stringer_all",
+        number: 63022,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const UNSAFE_MARSHALER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.unsafe_marshaler_all",
+        number: 63023,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const UNSAFE_UNMARSHALER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.unsafe_unmarshaler_all",
+        number: 63024,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_EXTENSIONS_MAP_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_extensions_map_all",
+        number: 63025,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_UNRECOGNIZED_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_unrecognized_all",
+        number: 63026,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOGOPROTO_IMPORT: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.gogoproto_import",
+        number: 63027,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const PROTOSIZER_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.protosizer_all",
+        number: 63028,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const COMPARE_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.compare_all",
+        number: 63029,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const TYPEDECL_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.typedecl_all",
+        number: 63030,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const ENUMDECL_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.enumdecl_all",
+        number: 63031,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_REGISTRATION: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_registration",
+        number: 63032,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const MESSAGENAME_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.messagename_all",
+        number: 63033,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_SIZECACHE_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_sizecache_all",
+        number: 63034,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_UNKEYED_ALL: ::prost_amino::extension::Extension<super::FileOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_unkeyed_all",
+        number: 63035,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_GETTERS: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_getters",
+        number: 64001,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_STRINGER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_stringer",
+        number: 64003,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const VERBOSE_EQUAL: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.verbose_equal",
+        number: 64004,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const FACE: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.face",
+        number: 64005,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOSTRING: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.gostring",
+        number: 64006,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const POPULATE: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.populate",
+        number: 64007,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const STRINGER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.stringer",
+        number: 67008,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const ONLYONE: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.onlyone",
+        number: 64009,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const EQUAL: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.equal",
+        number: 64013,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const DESCRIPTION: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.description",
+        number: 64014,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const TESTGEN: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.testgen",
+        number: 64015,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const BENCHGEN: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.benchgen",
+        number: 64016,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const MARSHALER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.marshaler",
+        number: 64017,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const UNMARSHALER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.unmarshaler",
+        number: 64018,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const STABLE_MARSHALER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.stable_marshaler",
+        number: 64019,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const SIZER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.sizer",
+        number: 64020,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const UNSAFE_MARSHALER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.unsafe_marshaler",
+        number: 64023,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const UNSAFE_UNMARSHALER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.unsafe_unmarshaler",
+        number: 64024,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_EXTENSIONS_MAP: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_extensions_map",
+        number: 64025,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_UNRECOGNIZED: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_unrecognized",
+        number: 64026,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const PROTOSIZER: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.protosizer",
+        number: 64028,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const COMPARE: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.compare",
+        number: 64029,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const TYPEDECL: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.typedecl",
+        number: 64030,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const MESSAGENAME: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.messagename",
+        number: 64033,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_SIZECACHE: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_sizecache",
+        number: 64034,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const GOPROTO_UNKEYED: ::prost_amino::extension::Extension<super::MessageOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.goproto_unkeyed",
+        number: 64035,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const NULLABLE: ::prost_amino::extension::Extension<super::FieldOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.nullable",
+        number: 65001,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const EMBED: ::prost_amino::extension::Extension<super::FieldOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.embed",
+        number: 65002,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const CUSTOMTYPE: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.customtype",
+        number: 65003,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const CUSTOMNAME: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.customname",
+        number: 65004,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const JSONTAG: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.jsontag",
+        number: 65005,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const MORETAGS: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.moretags",
+        number: 65006,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const CASTTYPE: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.casttype",
+        number: 65007,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const CASTKEY: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.castkey",
+        number: 65008,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const CASTVALUE: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.castvalue",
+        number: 65009,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const STDTIME: ::prost_amino::extension::Extension<super::FieldOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.stdtime",
+        number: 65010,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const STDDURATION: ::prost_amino::extension::Extension<super::FieldOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.stdduration",
+        number: 65011,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const WKTPOINTER: ::prost_amino::extension::Extension<super::FieldOptions, bool> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.wktpointer",
+        number: 65012,
+        encode: |tag, value, buf| ::prost_amino::encoding::bool::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::bool::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
+pub const CASTREPEATED: ::prost_amino::extension::Extension<super::FieldOptions, String> =
+    ::prost_amino::extension::Extension {
+        name: "gogoproto.castrepeated",
+        number: 65013,
+        encode: |tag, value, buf| ::prost_amino::encoding::string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| ::prost_amino::encoding::string::merge(wire_type, value, buf),
+        extendee: ::std::marker::PhantomData,
+    };
@@ -7,8 +7,13 @@
 //!
 //! See the [Protobuf reference][1] for more information about well-known types.
 //!
+//! The `amino` and `gogoproto` modules hold the descriptors of the custom options declared in
+//! `amino/amino.proto` and `gogoproto/gogo.proto`, which are read from the options messages with
+//! `get_extension`.
+//!
 //! [1]: https://developers.google.com/protocol-buffers/docs/reference/google.protobuf
 
+extern crate prost_amino;
 #[macro_use]
 extern crate prost_amino_derive;
 
@@ -16,11 +21,25 @@ use std::i32;
 use std::i64;
 use std::time;
 
+use prost_amino::proxy::Proxy;
+use prost_amino::DecodeError;
+
 include!("protobuf.rs");
 pub mod compiler {
     include!("compiler.rs");
 }
 
+/// Extensions of the options messages for amino, declared in `amino/amino.proto`.
+pub mod amino {
+    include!("amino.rs");
+}
+/// Extensions of the options messages for gogoproto, declared in `gogoproto/gogo.proto`.
+pub mod gogoproto {
+    include!("gogoproto.rs");
+}
+
+mod descriptor_pool;
+
 // The Protobuf `Duration` and `Timestamp` types can't delegate to the standard library equivalents
 // because the Protobuf versions are signed. To make them easier to work with, `From` conversions
 // are defined in both directions.
@@ -94,6 +113,27 @@ impl From<Duration> for Result<time::Duration, time::Duration> {
     }
 }
 
+/// Encodes `(gogoproto.stdduration)` fields as a `Duration`. Negative durations, which
+/// `std::time::Duration` can't represent, are rejected when they are decoded.
+impl Proxy<Duration> for time::Duration {
+    fn to_proxy(&self) -> Duration {
+        Duration::from(*self)
+    }
+
+    fn from_proxy(duration: Duration) -> Result<time::Duration, DecodeError> {
+        // The total nanoseconds can't overflow an `i128`, unlike normalizing the duration.
+        let nanos_per_second = i128::from(NANOS_PER_SECOND);
+        let nanos = i128::from(duration.seconds) * nanos_per_second + i128::from(duration.nanos);
+        if nanos < 0 {
+            return Err(DecodeError::new("negative duration"));
+        }
+        Ok(time::Duration::new(
+            (nanos / nanos_per_second) as u64,
+            (nanos % nanos_per_second) as u32,
+        ))
+    }
+}
+
 impl Timestamp {
     /// Normalizes the timestamp to a canonical format.
     ///
@@ -121,11 +161,46 @@ impl Timestamp {
 /// Converts a `std::time::SystemTime` to a `Timestamp`.
 impl From<time::SystemTime> for Timestamp {
     fn from(time: time::SystemTime) -> Timestamp {
-        let duration = Duration::from(time.duration_since(time::UNIX_EPOCH).unwrap());
-        Timestamp {
+        let duration = match time.duration_since(time::UNIX_EPOCH) {
+            Ok(duration) => Duration::from(duration),
+            Err(error) => {
+                let duration = Duration::from(error.duration());
+                Duration {
+                    seconds: -duration.seconds,
+                    nanos: -duration.nanos,
+                }
+            }
+        };
+        let mut timestamp = Timestamp {
             seconds: duration.seconds,
             nanos: duration.nanos,
-        }
+        };
+        timestamp.normalize();
+        timestamp
+    }
+}
+
+/// Encodes `(gogoproto.stdtime)` fields as a `Timestamp`, which may fall before the Unix epoch.
+/// Timestamps which `std::time::SystemTime` can't represent are rejected when they are decoded.
+impl Proxy<Timestamp> for time::SystemTime {
+    fn to_proxy(&self) -> Timestamp {
+        Timestamp::from(*self)
+    }
+
+    fn from_proxy(timestamp: Timestamp) -> Result<time::SystemTime, DecodeError> {
+        // The total nanoseconds can't overflow an `i128`, unlike normalizing the timestamp.
+        let nanos_per_second = i128::from(NANOS_PER_SECOND);
+        let nanos = i128::from(timestamp.seconds) * nanos_per_second + i128::from(timestamp.nanos);
+        let duration = time::Duration::new(
+            (nanos.abs() / nanos_per_second) as u64,
+            (nanos.abs() % nanos_per_second) as u32,
+        );
+        let time = if nanos >= 0 {
+            time::UNIX_EPOCH.checked_add(duration)
+        } else {
+            time::UNIX_EPOCH.checked_sub(duration)
+        };
+        time.ok_or_else(|| DecodeError::new("timestamp out of range"))
     }
 }
 
@@ -1,12 +1,14 @@
 /// The protocol compiler can output a FileDescriptorSet containing the .proto
 /// files it parses.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.FileDescriptorSet")]
 pub struct FileDescriptorSet {
     #[prost(message, repeated, tag="1")]
     pub file: ::std::vec::Vec<FileDescriptorProto>,
 }
 /// Describes a complete .proto file.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.FileDescriptorProto")]
 pub struct FileDescriptorProto {
     /// file name, relative to root of source tree
     #[prost(string, optional, tag="1")]
@@ -48,6 +50,7 @@ pub struct FileDescriptorProto {
 }
 /// Describes a message type.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.DescriptorProto")]
 pub struct DescriptorProto {
     #[prost(string, optional, tag="1")]
     pub name: ::std::option::Option<String>,
@@ -74,6 +77,7 @@ pub struct DescriptorProto {
 }
 pub mod descriptor_proto {
     #[derive(Clone, PartialEq, Message)]
+    #[prost(full_name="google.protobuf.DescriptorProto.ExtensionRange")]
     pub struct ExtensionRange {
         #[prost(int32, optional, tag="1")]
         pub start: ::std::option::Option<i32>,
@@ -86,6 +90,7 @@ pub mod descriptor_proto {
     /// fields or extension ranges in the same message. Reserved ranges may
     /// not overlap.
     #[derive(Clone, PartialEq, Message)]
+    #[prost(full_name="google.protobuf.DescriptorProto.ReservedRange")]
     pub struct ReservedRange {
         /// Inclusive.
         #[prost(int32, optional, tag="1")]
@@ -96,13 +101,17 @@ pub mod descriptor_proto {
     }
 }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.ExtensionRangeOptions")]
 pub struct ExtensionRangeOptions {
     /// The parser stores options it doesn't recognize here. See above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 /// Describes a field within a message.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.FieldDescriptorProto")]
 pub struct FieldDescriptorProto {
     #[prost(string, optional, tag="1")]
     pub name: ::std::option::Option<String>,
@@ -144,53 +153,89 @@ pub struct FieldDescriptorProto {
     pub json_name: ::std::option::Option<String>,
     #[prost(message, optional, tag="8")]
     pub options: ::std::option::Option<FieldOptions>,
+    /// If true, this is a proto3 "optional". When a proto3 field is optional, it
+    /// tracks presence regardless of field type.
+    ///
+    /// When proto3_optional is true, this field must be belong to a oneof to
+    /// signal to old proto3 clients that presence is tracked for this field. This
+    /// oneof is known as a "synthetic" oneof, and this field must be its sole
+    /// member.
+    ///
+    /// Proto2 optional fields do not set this flag, because they already indicate
+    /// optional with `LABEL_OPTIONAL`.
+    #[prost(bool, optional, tag="17")]
+    pub proto3_optional: ::std::option::Option<bool>,
 }
 pub mod field_descriptor_proto {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+    #[prost(full_name="google.protobuf.FieldDescriptorProto.Type")]
     pub enum Type {
         /// 0 is reserved for errors.
         /// Order is weird for historical reasons.
+        #[prost(proto_name="TYPE_DOUBLE")]
         Double = 1,
+        #[prost(proto_name="TYPE_FLOAT")]
         Float = 2,
         /// Not ZigZag encoded.  Negative numbers take 10 bytes.  Use TYPE_SINT64 if
         /// negative values are likely.
+        #[prost(proto_name="TYPE_INT64")]
         Int64 = 3,
+        #[prost(proto_name="TYPE_UINT64")]
         Uint64 = 4,
         /// Not ZigZag encoded.  Negative numbers take 10 bytes.  Use TYPE_SINT32 if
         /// negative values are likely.
+        #[prost(proto_name="TYPE_INT32")]
         Int32 = 5,
+        #[prost(proto_name="TYPE_FIXED64")]
         Fixed64 = 6,
+        #[prost(proto_name="TYPE_FIXED32")]
         Fixed32 = 7,
+        #[prost(proto_name="TYPE_BOOL")]
         Bool = 8,
+        #[prost(proto_name="TYPE_STRING")]
         String = 9,
         /// Tag-delimited aggregate.
         /// Group type is deprecated and not supported in proto3. However, Proto3
         /// implementations should still be able to parse the group wire format and
         /// treat group fields as unknown fields.
+        #[prost(proto_name="TYPE_GROUP")]
         Group = 10,
         /// Length-delimited aggregate.
+        #[prost(proto_name="TYPE_MESSAGE")]
         Message = 11,
         /// New in version 2.
+        #[prost(proto_name="TYPE_BYTES")]
         Bytes = 12,
+        #[prost(proto_name="TYPE_UINT32")]
         Uint32 = 13,
+        #[prost(proto_name="TYPE_ENUM")]
         Enum = 14,
+        #[prost(proto_name="TYPE_SFIXED32")]
         Sfixed32 = 15,
+        #[prost(proto_name="TYPE_SFIXED64")]
         Sfixed64 = 16,
         /// Uses ZigZag encoding.
+        #[prost(proto_name="TYPE_SINT32")]
         Sint32 = 17,
         /// Uses ZigZag encoding.
+        #[prost(proto_name="TYPE_SINT64")]
         Sint64 = 18,
     }
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+    #[prost(full_name="google.protobuf.FieldDescriptorProto.Label")]
     pub enum Label {
         /// 0 is reserved for errors
+        #[prost(proto_name="LABEL_OPTIONAL")]
         Optional = 1,
+        #[prost(proto_name="LABEL_REQUIRED")]
         Required = 2,
+        #[prost(proto_name="LABEL_REPEATED")]
         Repeated = 3,
     }
 }
 /// Describes a oneof.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.OneofDescriptorProto")]
 pub struct OneofDescriptorProto {
     #[prost(string, optional, tag="1")]
     pub name: ::std::option::Option<String>,
@@ -199,6 +244,7 @@ pub struct OneofDescriptorProto {
 }
 /// Describes an enum type.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.EnumDescriptorProto")]
 pub struct EnumDescriptorProto {
     #[prost(string, optional, tag="1")]
     pub name: ::std::option::Option<String>,
@@ -224,6 +270,7 @@ pub mod enum_descriptor_proto {
     /// is inclusive such that it can appropriately represent the entire int32
     /// domain.
     #[derive(Clone, PartialEq, Message)]
+    #[prost(full_name="google.protobuf.EnumDescriptorProto.EnumReservedRange")]
     pub struct EnumReservedRange {
         /// Inclusive.
         #[prost(int32, optional, tag="1")]
@@ -235,6 +282,7 @@ pub mod enum_descriptor_proto {
 }
 /// Describes a value within an enum.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.EnumValueDescriptorProto")]
 pub struct EnumValueDescriptorProto {
     #[prost(string, optional, tag="1")]
     pub name: ::std::option::Option<String>,
@@ -245,6 +293,7 @@ pub struct EnumValueDescriptorProto {
 }
 /// Describes a service.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.ServiceDescriptorProto")]
 pub struct ServiceDescriptorProto {
     #[prost(string, optional, tag="1")]
     pub name: ::std::option::Option<String>,
@@ -255,6 +304,7 @@ pub struct ServiceDescriptorProto {
 }
 /// Describes a method of a service.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.MethodDescriptorProto")]
 pub struct MethodDescriptorProto {
     #[prost(string, optional, tag="1")]
     pub name: ::std::option::Option<String>,
@@ -306,6 +356,7 @@ pub struct MethodDescriptorProto {
 //   to automatically assign option numbers.
 
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.FileOptions")]
 pub struct FileOptions {
     /// Sets the Java package where classes generated from this .proto will be
     /// placed.  By default, the proto package is used, but this is often
@@ -402,10 +453,13 @@ pub struct FileOptions {
     /// See the documentation for the "Options" section above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 pub mod file_options {
     /// Generated classes can be optimized for speed or code size.
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+    #[prost(full_name="google.protobuf.FileOptions.OptimizeMode")]
     pub enum OptimizeMode {
         /// Generate complete code for parsing, serialization,
         Speed = 1,
@@ -418,6 +472,7 @@ pub mod file_options {
     }
 }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.MessageOptions")]
 pub struct MessageOptions {
     /// Set true to use the old proto1 MessageSet wire format for extensions.
     /// This is provided for backwards-compatibility with the MessageSet wire
@@ -476,8 +531,11 @@ pub struct MessageOptions {
     /// The parser stores options it doesn't recognize here. See above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.FieldOptions")]
 pub struct FieldOptions {
     /// The ctype option instructs the C++ code generator to use a different
     /// representation of the field than it normally would.  See the specific
@@ -547,9 +605,12 @@ pub struct FieldOptions {
     /// The parser stores options it doesn't recognize here. See above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 pub mod field_options {
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+    #[prost(full_name="google.protobuf.FieldOptions.CType")]
     pub enum CType {
         /// Default mode.
         String = 0,
@@ -557,6 +618,7 @@ pub mod field_options {
         StringPiece = 2,
     }
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+    #[prost(full_name="google.protobuf.FieldOptions.JSType")]
     pub enum JsType {
         /// Use the default type.
         JsNormal = 0,
@@ -567,12 +629,16 @@ pub mod field_options {
     }
 }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.OneofOptions")]
 pub struct OneofOptions {
     /// The parser stores options it doesn't recognize here. See above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.EnumOptions")]
 pub struct EnumOptions {
     /// Set this option to true to allow mapping different tag names to the same
     /// value.
@@ -587,8 +653,11 @@ pub struct EnumOptions {
     /// The parser stores options it doesn't recognize here. See above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.EnumValueOptions")]
 pub struct EnumValueOptions {
     /// Is this enum value deprecated?
     /// Depending on the target platform, this can emit Deprecated annotations
@@ -599,8 +668,11 @@ pub struct EnumValueOptions {
     /// The parser stores options it doesn't recognize here. See above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.ServiceOptions")]
 pub struct ServiceOptions {
     // Note:  Field numbers 1 through 32 are reserved for Google's internal RPC
     //   framework.  We apologize for hoarding these numbers to ourselves, but
@@ -616,8 +688,11 @@ pub struct ServiceOptions {
     /// The parser stores options it doesn't recognize here. See above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.MethodOptions")]
 pub struct MethodOptions {
     // Note:  Field numbers 1 through 32 are reserved for Google's internal RPC
     //   framework.  We apologize for hoarding these numbers to ourselves, but
@@ -635,12 +710,15 @@ pub struct MethodOptions {
     /// The parser stores options it doesn't recognize here. See above.
     #[prost(message, repeated, tag="999")]
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
+    #[prost(extensions="1000..536870912")]
+    pub extension_set: ::prost_amino::extension::ExtensionSet,
 }
 pub mod method_options {
     /// Is this method side-effect-free (or safe in HTTP parlance), or idempotent,
     /// or neither? HTTP based RPC implementation may choose GET verb for safe
     /// methods, and PUT verb for idempotent methods instead of the default POST.
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+    #[prost(full_name="google.protobuf.MethodOptions.IdempotencyLevel")]
     pub enum IdempotencyLevel {
         IdempotencyUnknown = 0,
         /// implies idempotent
@@ -656,6 +734,7 @@ pub mod method_options {
 /// or produced by Descriptor::CopyTo()) will never have UninterpretedOptions
 /// in them.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.UninterpretedOption")]
 pub struct UninterpretedOption {
     #[prost(message, repeated, tag="2")]
     pub name: ::std::vec::Vec<uninterpreted_option::NamePart>,
@@ -681,6 +760,7 @@ pub mod uninterpreted_option {
     /// E.g.,{ ["foo", false], ["bar.baz", true], ["qux", false] } represents
     /// "foo.(bar.baz).qux".
     #[derive(Clone, PartialEq, Message)]
+    #[prost(full_name="google.protobuf.UninterpretedOption.NamePart")]
     pub struct NamePart {
         #[prost(string, required, tag="1")]
         pub name_part: String,
@@ -694,6 +774,7 @@ pub mod uninterpreted_option {
 /// Encapsulates information about the original source file from which a
 /// FileDescriptorProto was generated.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.SourceCodeInfo")]
 pub struct SourceCodeInfo {
     /// A Location identifies a piece of source code in a .proto file which
     /// corresponds to a particular definition.  This information is intended
@@ -743,6 +824,7 @@ pub struct SourceCodeInfo {
 }
 pub mod source_code_info {
     #[derive(Clone, PartialEq, Message)]
+    #[prost(full_name="google.protobuf.SourceCodeInfo.Location")]
     pub struct Location {
         /// Identifies which part of the FileDescriptorProto was defined at this
         /// location.
@@ -835,6 +917,7 @@ pub mod source_code_info {
 /// file. A GeneratedCodeInfo message is associated with only one generated
 /// source file, but may contain references to different source .proto files.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.GeneratedCodeInfo")]
 pub struct GeneratedCodeInfo {
     /// An Annotation connects some span of text in generated code to an element
     /// of its generating .proto file.
@@ -843,6 +926,7 @@ pub struct GeneratedCodeInfo {
 }
 pub mod generated_code_info {
     #[derive(Clone, PartialEq, Message)]
+    #[prost(full_name="google.protobuf.GeneratedCodeInfo.Annotation")]
     pub struct Annotation {
         /// Identifies the element in the original source .proto file. This field
         /// is formatted the same as SourceCodeInfo.Location.path.
@@ -943,6 +1027,7 @@ pub mod generated_code_info {
 ///     }
 ///
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Any")]
 pub struct Any {
     /// A URL/resource name whose content describes the type of the
     /// serialized protocol buffer message.
@@ -975,6 +1060,7 @@ pub struct Any {
 /// `SourceContext` represents information about the source of a
 /// protobuf element, like the file in which it is defined.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.SourceContext")]
 pub struct SourceContext {
     /// The path-qualified name of the .proto file that contained the associated
     /// protobuf element.  For example: `"google/protobuf/source_context.proto"`.
@@ -983,6 +1069,7 @@ pub struct SourceContext {
 }
 /// A protocol buffer message type.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Type")]
 pub struct Type {
     /// The fully qualified message name.
     #[prost(string, tag="1")]
@@ -1005,6 +1092,7 @@ pub struct Type {
 }
 /// A single field of a message type.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Field")]
 pub struct Field {
     /// The field type.
     #[prost(enumeration="field::Kind", tag="1")]
@@ -1042,6 +1130,7 @@ pub struct Field {
 pub mod field {
     /// Basic field types.
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+    #[prost(full_name="google.protobuf.Field.Kind")]
     pub enum Kind {
         /// Field type unknown.
         TypeUnknown = 0,
@@ -1084,19 +1173,25 @@ pub mod field {
     }
     /// Whether a field is optional, required, or repeated.
     #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+    #[prost(full_name="google.protobuf.Field.Cardinality")]
     pub enum Cardinality {
         /// For fields with unknown cardinality.
+        #[prost(proto_name="CARDINALITY_UNKNOWN")]
         Unknown = 0,
         /// For optional fields.
+        #[prost(proto_name="CARDINALITY_OPTIONAL")]
         Optional = 1,
         /// For required fields. Proto2 syntax only.
+        #[prost(proto_name="CARDINALITY_REQUIRED")]
         Required = 2,
         /// For repeated fields.
+        #[prost(proto_name="CARDINALITY_REPEATED")]
         Repeated = 3,
     }
 }
 /// Enum type definition.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Enum")]
 pub struct Enum {
     /// Enum type name.
     #[prost(string, tag="1")]
@@ -1116,6 +1211,7 @@ pub struct Enum {
 }
 /// Enum value definition.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.EnumValue")]
 pub struct EnumValue {
     /// Enum value name.
     #[prost(string, tag="1")]
@@ -1130,6 +1226,7 @@ pub struct EnumValue {
 /// A protocol buffer option, which can be attached to a message, field,
 /// enumeration, etc.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Option")]
 pub struct Option {
     /// The option's name. For protobuf built-in options (options defined in
     /// descriptor.proto), this is the short name. For example, `"map_entry"`.
@@ -1146,10 +1243,13 @@ pub struct Option {
 }
 /// The syntax in which a protocol buffer element is defined.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[prost(full_name="google.protobuf.Syntax")]
 pub enum Syntax {
     /// Syntax `proto2`.
+    #[prost(proto_name="SYNTAX_PROTO2")]
     Proto2 = 0,
     /// Syntax `proto3`.
+    #[prost(proto_name="SYNTAX_PROTO3")]
     Proto3 = 1,
 }
 /// Api is a light-weight descriptor for an API Interface.
@@ -1162,6 +1262,7 @@ pub enum Syntax {
 /// this message itself. See https://cloud.google.com/apis/design/glossary for
 /// detailed terminology.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Api")]
 pub struct Api {
     /// The fully qualified name of this interface, including package name
     /// followed by the interface's simple name.
@@ -1209,6 +1310,7 @@ pub struct Api {
 }
 /// Method represents a method of an API interface.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Method")]
 pub struct Method {
     /// The simple name of this method.
     #[prost(string, tag="1")]
@@ -1311,6 +1413,7 @@ pub struct Method {
 ///       ...
 ///     }
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Mixin")]
 pub struct Mixin {
     /// The fully qualified name of the interface which is included.
     #[prost(string, tag="1")]
@@ -1381,6 +1484,7 @@ pub struct Mixin {
 ///
 ///
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Duration")]
 pub struct Duration {
     /// Signed seconds of the span of time. Must be from -315,576,000,000
     /// to +315,576,000,000 inclusive. Note: these bounds are computed from:
@@ -1604,6 +1708,7 @@ pub struct Duration {
 /// field in the request, should verify the included field paths, and return
 /// `INVALID_ARGUMENT` error if any path is duplicated or unmappable.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.FieldMask")]
 pub struct FieldMask {
     /// The set of field mask paths.
     #[prost(string, repeated, tag="1")]
@@ -1618,6 +1723,7 @@ pub struct FieldMask {
 ///
 /// The JSON representation for `Struct` is JSON object.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Struct")]
 pub struct Struct {
     /// Unordered map of dynamically typed values.
     #[prost(btree_map="string, message", tag="1")]
@@ -1630,6 +1736,7 @@ pub struct Struct {
 ///
 /// The JSON representation for `Value` is JSON value.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Value")]
 pub struct Value {
     /// The kind of value.
     #[prost(oneof="value::Kind", tags="1, 2, 3, 4, 5, 6")]
@@ -1663,6 +1770,7 @@ pub mod value {
 ///
 /// The JSON representation for `ListValue` is JSON array.
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.ListValue")]
 pub struct ListValue {
     /// Repeated field of dynamically typed values.
     #[prost(message, repeated, tag="1")]
@@ -1673,6 +1781,7 @@ pub struct ListValue {
 ///
 ///  The JSON representation for `NullValue` is JSON `null`.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Enumeration)]
+#[prost(full_name="google.protobuf.NullValue")]
 pub enum NullValue {
     /// Null value.
     NullValue = 0,
@@ -1756,6 +1865,7 @@ pub enum NullValue {
 ///
 ///
 #[derive(Clone, PartialEq, Message)]
+#[prost(full_name="google.protobuf.Timestamp")]
 pub struct Timestamp {
     /// Represents seconds of UTC time since Unix epoch
     /// 1970-01-01T00:00:00Z. Must be from 0001-01-01T00:00:00Z to
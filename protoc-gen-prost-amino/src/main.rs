@@ -0,0 +1,58 @@
+//! `protoc-gen-prost-amino` is a `protoc` plugin which generates prost-amino code, as an
+//! alternative to compiling `.proto` files from a `build.rs` with `prost-build`.
+//!
+//! ```text
+//! protoc --plugin=protoc-gen-prost-amino --prost-amino_out=OUT_DIR foo.proto
+//! protoc --prost-amino_out=btree_map=.,amino_name=.foo.Bar=foo/Bar:OUT_DIR foo.proto
+//! ```
+//!
+//! One `.rs` file is written per package, like `prost_build::compile_protos`. The plugin
+//! parameter is a comma separated list of options:
+//!
+//! - `btree_map=PATH`: generate `BTreeMap` fields for the matching map fields.
+//! - `builder=PATH`: generate builder types for the matching messages.
+//! - `type_attribute=PATH=ATTRIBUTE`: add an attribute to the matching messages and enums.
+//! - `field_attribute=PATH=ATTRIBUTE`: add an attribute to the matching fields.
+//! - `amino_name=PATH=NAME`: register the matching messages with an amino name.
+//! - `extern_path=PATH=RUST_PATH`: refer to the matching types through a Rust path instead of
+//!   generating them.
+//! - `custom_type=GO_TYPE=RUST_TYPE`: generate fields with a `(gogoproto.casttype)` or
+//!   `(gogoproto.customtype)` option naming the Go type with the Rust type.
+//! - `compile_well_known_types`: generate the well-known types instead of using `prost-types`.
+//! - `retain_enum_prefix`: keep the enum name prefix of enum variants.
+//! - `include_file=PATH`: also write a file including the packages' modules in a hierarchy of
+//!   `pub mod`s.
+//! - `socket_services`: generate a trait, a dispatcher and a client for each service, which
+//!   exchange length-delimited messages over a stream.
+//!
+//! Commas within brackets, parentheses, braces or quotes do not separate options, so attributes
+//! like `#[derive(Eq, Hash)]` can be used as is.
+
+extern crate prost_amino as prost;
+extern crate prost_build;
+
+use std::io::{self, Read, Write};
+use std::process;
+
+use prost::Message;
+
+fn main() {
+    let mut request = Vec::new();
+    let response = io::stdin()
+        .read_to_end(&mut request)
+        .and_then(|_| prost_build::Config::new().compile_request(&request));
+    let response = match response {
+        Ok(response) => response,
+        Err(error) => {
+            eprintln!("protoc-gen-prost-amino: {}", error);
+            process::exit(1);
+        }
+    };
+
+    let mut buf = Vec::with_capacity(response.encoded_len());
+    response.encode(&mut buf).unwrap();
+    if let Err(error) = io::stdout().write_all(&buf) {
+        eprintln!("protoc-gen-prost-amino: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,52 @@
+//! Amino type registration.
+//!
+//! Registered types are encoded with the prefix bytes of their amino name. The prefix and
+//! disambiguation bytes are derived from the SHA-256 hash of the name, as in go-amino.
+
+use sha2::{Digest, Sha256};
+
+/// A message type registered with an amino name.
+///
+/// Implemented by `#[derive(Message)]` for types with an `#[amino_name = "..."]` attribute, with
+/// constants computed at compile time.
+pub trait AminoRegistered {
+    /// The registered amino name, e.g. `tendermint/PubKeyEd25519`.
+    const AMINO_NAME: &'static str;
+    /// The prefix bytes which precede the encoded fields of the type.
+    const PREFIX: [u8; 4];
+    /// The disambiguation bytes of the name.
+    const DISAMB: [u8; 3];
+}
+
+/// Computes the disambiguation and prefix bytes of an amino name.
+///
+/// The name is hashed with SHA-256, and zero bytes are dropped from the hash. The first three
+/// remaining bytes are the disambiguation bytes, and the following four are the prefix bytes.
+pub fn name_to_disfix(name: &str) -> ([u8; 3], [u8; 4]) {
+    let hash = Sha256::digest(name.as_bytes());
+    let mut bytes = hash.iter().cloned().filter(|&b| b != 0x00);
+    let mut disamb = [0; 3];
+    let mut prefix = [0; 4];
+    for b in disamb.iter_mut().chain(prefix.iter_mut()) {
+        *b = bytes.next().unwrap_or(0);
+    }
+    (disamb, prefix)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn disfix() {
+        // Test vectors generated with go-amino's `NameToDisfix`.
+        assert_eq!(
+            name_to_disfix("test"),
+            ([0x9f, 0x86, 0xd0], [0x81, 0x88, 0x4c, 0x7d])
+        );
+        assert_eq!(
+            name_to_disfix("tendermint/socketpv/SignHeartbeatMsg"),
+            ([0x85, 0x6a, 0x57], [0xbf, 0x58, 0xca, 0xef])
+        );
+    }
+}
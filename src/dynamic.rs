@@ -0,0 +1,1640 @@
+//! Dynamic messages, decoded and encoded using a schema loaded at runtime.
+//!
+//! A `DescriptorPool` indexes message and enumeration types by their fully qualified protobuf name
+//! (without the leading `.`). `prost-types` builds a pool from a `FileDescriptorSet`, and pools may
+//! also be assembled by hand. A `DynamicMessage` holds the field values of a message of one of the
+//! pool types, keyed by tag. It can be decoded, inspected, modified and re-encoded without a
+//! generated Rust type. Fields which are not part of the schema are kept, and re-encoded as is.
+//!
+//! Pools may also be built from the reflection descriptors of derived messages, with
+//! `DescriptorPool::add_reflected`, which is how derived messages are converted to and from JSON.
+//!
+//! Amino registered types are framed like derived registered messages: the encoded fields are
+//! preceded by the varint encoded length of the fields plus prefix, and the 4 prefix bytes. As a
+//! field of another message, a registered message is preceded by the same length again, which
+//! does not count the framing length itself.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error;
+use std::fmt;
+use std::ptr;
+use std::sync::Arc;
+
+use bytes::{Buf, BufMut};
+
+use amino::name_to_disfix;
+use encoding::*;
+use reflect::{EnumDescriptor, FieldKind, Label, MessageDescriptor, Type};
+use DecodeError;
+use EncodeError;
+
+/// A set of message and enumeration types, indexed by fully qualified name.
+///
+/// Pools are cheap to clone; every `DynamicMessage` holds a reference to its pool.
+#[derive(Clone, Debug, Default)]
+pub struct DescriptorPool {
+    inner: Arc<PoolInner>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct PoolInner {
+    messages: HashMap<String, Arc<MessageType>>,
+    enums: HashMap<String, Arc<EnumType>>,
+    /// The descriptors of the types added by `add_reflected`, by name.
+    reflected_messages: HashMap<String, &'static MessageDescriptor>,
+    reflected_enums: HashMap<String, &'static EnumDescriptor>,
+}
+
+impl DescriptorPool {
+    /// Creates an empty pool.
+    pub fn new() -> DescriptorPool {
+        DescriptorPool::default()
+    }
+
+    /// Adds a message type to the pool, replacing any type with the same name.
+    pub fn add_message(&mut self, message: MessageType) {
+        Arc::make_mut(&mut self.inner)
+            .messages
+            .insert(message.name.clone(), Arc::new(message));
+    }
+
+    /// Adds an enumeration type to the pool, replacing any type with the same name.
+    pub fn add_enum(&mut self, enumeration: EnumType) {
+        Arc::make_mut(&mut self.inner)
+            .enums
+            .insert(enumeration.name.clone(), Arc::new(enumeration));
+    }
+
+    /// Registers the message type `message` with an amino name, which sets the amino prefix of
+    /// the type.
+    ///
+    /// Returns `false` if the pool does not contain the message type.
+    pub fn set_amino_name(&mut self, message: &str, amino_name: &str) -> bool {
+        let inner = Arc::make_mut(&mut self.inner);
+        match inner.messages.get_mut(message) {
+            Some(ty) => {
+                let ty = Arc::make_mut(ty);
+                ty.amino_name = Some(amino_name.to_string());
+                ty.prefix = Some(name_to_disfix(amino_name).1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Registers message types with amino names, given a map from fully qualified message names to
+    /// amino names. Names of types which are not in the pool are ignored.
+    pub fn set_amino_names(&mut self, amino_names: &HashMap<String, String>) {
+        for (message, amino_name) in amino_names {
+            self.set_amino_name(message, amino_name);
+        }
+    }
+
+    /// Adds a derived message type to the pool, along with the message and enumeration types it
+    /// references, as described by their reflection descriptors.
+    ///
+    /// Types are named by the `full_name` of their descriptors, and types which are already in the
+    /// pool are not replaced. The `full_name` defaults to the name of the Rust type, so derived
+    /// types with the same name in different modules collide: an error is returned if the
+    /// descriptor of another reflected type has the same name.
+    pub fn add_reflected(
+        &mut self,
+        descriptor: &'static MessageDescriptor,
+    ) -> Result<(), PoolError> {
+        let name = descriptor.full_name;
+        match self.inner.reflected_messages.get(name) {
+            Some(&existing) if ptr::eq(existing, descriptor) => return Ok(()),
+            Some(_) => return Err(PoolError::DuplicateName(name.to_string())),
+            None => (),
+        }
+        Arc::make_mut(&mut self.inner)
+            .reflected_messages
+            .insert(name.to_string(), descriptor);
+        if self.message(name).is_some() {
+            return Ok(());
+        }
+
+        let mut fields = Vec::new();
+        let mut oneofs = Vec::new();
+        let mut messages = Vec::new();
+        for field in descriptor.fields {
+            if let FieldKind::Oneof(oneof) = field.kind {
+                for variant in oneof().fields {
+                    if let Some(kind) = self.reflected_kind(&variant.kind, &mut messages)? {
+                        fields.push(FieldType {
+                            name: variant.proto_name.to_string(),
+                            json_name: variant.json_name.to_string(),
+                            tag: variant.tags[0],
+                            label: Label::Optional,
+                            kind,
+                            oneof: Some(oneofs.len()),
+                            write_empty: false,
+                        });
+                    }
+                }
+                oneofs.push(field.proto_name.to_string());
+            } else if let Some(kind) = self.reflected_kind(&field.kind, &mut messages)? {
+                fields.push(FieldType {
+                    name: field.proto_name.to_string(),
+                    json_name: field.json_name.to_string(),
+                    tag: field.tag(),
+                    label: field.label,
+                    kind,
+                    oneof: None,
+                    write_empty: field.write_empty,
+                });
+            }
+        }
+        self.add_message(MessageType {
+            name: descriptor.full_name.to_string(),
+            fields,
+            oneofs,
+            amino_name: descriptor.amino_name.map(str::to_string),
+            prefix: descriptor.prefix,
+        });
+
+        // The message type is added first, so that recursive types terminate.
+        for message in messages {
+            self.add_reflected(message)?;
+        }
+        Ok(())
+    }
+
+    /// Converts a reflected field type, adding referenced enumeration types to the pool and
+    /// collecting referenced message types. Returns `None` for oneof fields, whose variants are
+    /// separate fields.
+    fn reflected_kind(
+        &mut self,
+        kind: &FieldKind,
+        messages: &mut Vec<&'static MessageDescriptor>,
+    ) -> Result<Option<Kind>, PoolError> {
+        Ok(Some(match *kind {
+            FieldKind::Scalar(ty) => Kind::Scalar(ty),
+            FieldKind::Enumeration(descriptor) => {
+                let descriptor = descriptor();
+                let name = descriptor.full_name;
+                match self.inner.reflected_enums.get(name) {
+                    Some(&existing) if !ptr::eq(existing, descriptor) => {
+                        return Err(PoolError::DuplicateName(name.to_string()));
+                    }
+                    Some(_) => (),
+                    None => {
+                        Arc::make_mut(&mut self.inner)
+                            .reflected_enums
+                            .insert(name.to_string(), descriptor);
+                    }
+                }
+                if self.enumeration(name).is_none() {
+                    self.add_enum(EnumType {
+                        name: descriptor.full_name.to_string(),
+                        values: descriptor
+                            .values
+                            .iter()
+                            .map(|value| (value.proto_name.to_string(), value.number))
+                            .collect(),
+                    });
+                }
+                Kind::Enumeration(descriptor.full_name.to_string())
+            }
+            FieldKind::Message(descriptor) => {
+                let descriptor = descriptor();
+                messages.push(descriptor);
+                Kind::Message(descriptor.full_name.to_string())
+            }
+            FieldKind::Map { key, value } => match self.reflected_kind(value, messages)? {
+                Some(value) => Kind::Map(key, Box::new(value)),
+                None => return Ok(None),
+            },
+            FieldKind::Group(descriptor) => {
+                let descriptor = descriptor();
+                messages.push(descriptor);
+                Kind::Group(descriptor.full_name.to_string())
+            }
+            FieldKind::Oneof(..) => return Ok(None),
+        }))
+    }
+
+    /// Returns the message type with the given fully qualified name, if any.
+    pub fn message(&self, name: &str) -> Option<&MessageType> {
+        self.inner.messages.get(name).map(|ty| &**ty)
+    }
+
+    /// Returns the registered message type with the given amino prefix, if any.
+    pub fn message_by_prefix(&self, prefix: &[u8]) -> Option<&MessageType> {
+        self.inner
+            .messages
+            .values()
+            .find(|ty| ty.prefix.as_ref().map(|p| &p[..]) == Some(prefix))
+            .map(|ty| &**ty)
+    }
+
+    /// Returns the enumeration type with the given fully qualified name, if any.
+    pub fn enumeration(&self, name: &str) -> Option<&EnumType> {
+        self.inner.enums.get(name).map(|ty| &**ty)
+    }
+
+    /// Returns an iterator over the message types in the pool, in no particular order.
+    pub fn messages(&self) -> impl Iterator<Item = &MessageType> {
+        self.inner.messages.values().map(|ty| &**ty)
+    }
+
+    /// Creates an empty message of the given type, or `None` if the pool does not contain the
+    /// type.
+    pub fn new_message(&self, name: &str) -> Option<DynamicMessage> {
+        self.inner.messages.get(name).map(|ty| {
+            let mut message = DynamicMessage {
+                pool: self.clone(),
+                ty: ty.clone(),
+                fields: BTreeMap::new(),
+                unknown: Vec::new(),
+            };
+            message.set_write_empty_defaults();
+            message
+        })
+    }
+
+    /// Decodes a message of the given type from a buffer.
+    ///
+    /// The entire buffer will be consumed.
+    pub fn decode<B>(&self, name: &str, buf: B) -> Result<DynamicMessage, DecodeError>
+    where
+        B: Buf,
+    {
+        let mut message = self
+            .new_message(name)
+            .ok_or_else(|| DecodeError::new(format!("unknown message type: {}", name)))?;
+        message.merge(buf)?;
+        Ok(message)
+    }
+}
+
+/// A message type.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MessageType {
+    /// The fully qualified name of the message type.
+    pub name: String,
+    /// The message fields.
+    pub fields: Vec<FieldType>,
+    /// The names of the oneofs of the message, see `FieldType::oneof`.
+    pub oneofs: Vec<String>,
+    /// The amino name the type is registered with, if any.
+    pub amino_name: Option<String>,
+    /// The amino prefix bytes of the registered type, if any.
+    pub prefix: Option<[u8; 4]>,
+}
+
+impl MessageType {
+    /// Returns the field with the given name, if any.
+    pub fn field_by_name(&self, name: &str) -> Option<&FieldType> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Returns the field with the given tag, if any.
+    pub fn field_by_tag(&self, tag: u32) -> Option<&FieldType> {
+        self.fields.iter().find(|field| field.tag == tag)
+    }
+
+    /// Returns the field with the given JSON name, if any.
+    pub fn field_by_json_name(&self, json_name: &str) -> Option<&FieldType> {
+        self.fields
+            .iter()
+            .find(|field| field.json_name == json_name)
+    }
+}
+
+/// A message field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldType {
+    /// The name of the field.
+    pub name: String,
+    /// The name of the field in the proto3 JSON mapping.
+    pub json_name: String,
+    /// The field tag.
+    pub tag: u32,
+    /// The field label. Map fields are `Repeated`.
+    pub label: Label,
+    /// The field type.
+    pub kind: Kind,
+    /// The index of the oneof containing the field in `MessageType::oneofs`, if any.
+    pub oneof: Option<usize>,
+    /// Whether a plain field is encoded even if it has the default value. Such fields are set to
+    /// the default value when a message is created.
+    pub write_empty: bool,
+}
+
+/// Field types.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Kind {
+    /// A scalar field.
+    Scalar(Type),
+    /// An enumeration field, with the fully qualified name of the enumeration type.
+    Enumeration(String),
+    /// A message field, with the fully qualified name of the message type.
+    Message(String),
+    /// A group field, with the fully qualified name of the message type of the group.
+    Group(String),
+    /// A map field, with the key type and the value type. The value is never a map.
+    Map(Type, Box<Kind>),
+}
+
+/// An enumeration type.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EnumType {
+    /// The fully qualified name of the enumeration type.
+    pub name: String,
+    /// The enumeration variant names and values, in declaration order.
+    pub values: Vec<(String, i32)>,
+}
+
+impl EnumType {
+    /// Returns the name of the variant with the given value, if any.
+    pub fn value_name(&self, number: i32) -> Option<&str> {
+        self.values
+            .iter()
+            .find(|&&(_, n)| n == number)
+            .map(|(name, _)| &name[..])
+    }
+
+    /// Returns the value of the variant with the given name, if any.
+    pub fn value_number(&self, name: &str) -> Option<i32> {
+        self.values
+            .iter()
+            .find(|&(n, _)| n == name)
+            .map(|&(_, number)| number)
+    }
+}
+
+/// A dynamic field value.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    U32(u32),
+    U64(u64),
+    F32(f32),
+    F64(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    /// An enumeration value. Values which are not variants of the enumeration are preserved.
+    Enum(i32),
+    Message(DynamicMessage),
+    /// The values of a repeated field.
+    List(Vec<Value>),
+    /// The entries of a map field.
+    Map(BTreeMap<MapKey, Value>),
+}
+
+impl Value {
+    /// Returns `true` if the value is the default value of its type. Default values of plain
+    /// fields are not encoded.
+    pub fn is_default(&self) -> bool {
+        match *self {
+            Value::Bool(value) => !value,
+            Value::I32(value) | Value::Enum(value) => value == 0,
+            Value::I64(value) => value == 0,
+            Value::U32(value) => value == 0,
+            Value::U64(value) => value == 0,
+            Value::F32(value) => value == 0.0,
+            Value::F64(value) => value == 0.0,
+            Value::String(ref value) => value.is_empty(),
+            Value::Bytes(ref value) => value.is_empty(),
+            Value::Message(_) => false,
+            Value::List(ref values) => values.is_empty(),
+            Value::Map(ref entries) => entries.is_empty(),
+        }
+    }
+
+    /// Returns the default value of a scalar type.
+    pub fn default_scalar(ty: Type) -> Value {
+        match ty {
+            Type::Double => Value::F64(0.0),
+            Type::Float => Value::F32(0.0),
+            Type::Int32 | Type::Sint32 | Type::Sfixed32 => Value::I32(0),
+            Type::Int64 | Type::Sint64 | Type::Sfixed64 => Value::I64(0),
+            Type::Uint32 | Type::Fixed32 => Value::U32(0),
+            Type::Uint64 | Type::Fixed64 => Value::U64(0),
+            Type::Bool => Value::Bool(false),
+            Type::String => Value::String(String::new()),
+            Type::Bytes => Value::Bytes(Vec::new()),
+        }
+    }
+
+    /// Returns `true` if the value is a valid value of a scalar type.
+    fn is_scalar(&self, ty: Type) -> bool {
+        matches!(
+            (ty, self),
+            (Type::Double, &Value::F64(_))
+                | (Type::Float, &Value::F32(_))
+                | (Type::Int32, &Value::I32(_))
+                | (Type::Sint32, &Value::I32(_))
+                | (Type::Sfixed32, &Value::I32(_))
+                | (Type::Int64, &Value::I64(_))
+                | (Type::Sint64, &Value::I64(_))
+                | (Type::Sfixed64, &Value::I64(_))
+                | (Type::Uint32, &Value::U32(_))
+                | (Type::Fixed32, &Value::U32(_))
+                | (Type::Uint64, &Value::U64(_))
+                | (Type::Fixed64, &Value::U64(_))
+                | (Type::Bool, &Value::Bool(_))
+                | (Type::String, &Value::String(_))
+                | (Type::Bytes, &Value::Bytes(_))
+        )
+    }
+
+    /// Returns `true` if the value is a valid singular value of the field type.
+    fn is_valid(&self, kind: &Kind) -> bool {
+        match (kind, self) {
+            (&Kind::Scalar(ty), value) => value.is_scalar(ty),
+            (&Kind::Enumeration(_), &Value::Enum(_)) => true,
+            (&Kind::Message(ref name), &Value::Message(ref message))
+            | (&Kind::Group(ref name), &Value::Message(ref message)) => {
+                message.descriptor().name == *name
+            }
+            (&Kind::Map(key, ref value), Value::Map(entries)) => entries
+                .iter()
+                .all(|(k, v)| k.clone().into_value().is_scalar(key) && v.is_valid(value)),
+            _ => false,
+        }
+    }
+}
+
+/// A map key value.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MapKey {
+    Bool(bool),
+    I32(i32),
+    I64(i64),
+    U32(u32),
+    U64(u64),
+    String(String),
+}
+
+impl MapKey {
+    /// Converts a scalar value into a map key, or returns `None` if the value type can not be used
+    /// as a map key.
+    pub fn from_value(value: Value) -> Option<MapKey> {
+        Some(match value {
+            Value::Bool(value) => MapKey::Bool(value),
+            Value::I32(value) => MapKey::I32(value),
+            Value::I64(value) => MapKey::I64(value),
+            Value::U32(value) => MapKey::U32(value),
+            Value::U64(value) => MapKey::U64(value),
+            Value::String(value) => MapKey::String(value),
+            _ => return None,
+        })
+    }
+
+    /// Converts the map key into a scalar value.
+    pub fn into_value(self) -> Value {
+        match self {
+            MapKey::Bool(value) => Value::Bool(value),
+            MapKey::I32(value) => Value::I32(value),
+            MapKey::I64(value) => Value::I64(value),
+            MapKey::U32(value) => Value::U32(value),
+            MapKey::U64(value) => Value::U64(value),
+            MapKey::String(value) => Value::String(value),
+        }
+    }
+}
+
+/// An error setting the value of a dynamic message field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldError {
+    /// The message type has no such field.
+    UnknownField(String),
+    /// The value does not match the field type.
+    InvalidValue(String),
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldError::UnknownField(ref field) => write!(f, "unknown field: {}", field),
+            FieldError::InvalidValue(ref field) => write!(f, "invalid value for field: {}", field),
+        }
+    }
+}
+
+impl error::Error for FieldError {
+    fn description(&self) -> &str {
+        match *self {
+            FieldError::UnknownField(..) => "unknown field",
+            FieldError::InvalidValue(..) => "invalid field value",
+        }
+    }
+}
+
+/// An error adding reflected types to a `DescriptorPool`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PoolError {
+    /// Different reflected types have the same fully qualified name. Names can be set with the
+    /// `full_name` message and enumeration attribute.
+    DuplicateName(String),
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            PoolError::DuplicateName(ref name) => {
+                write!(f, "different types with the same name: {}", name)
+            }
+        }
+    }
+}
+
+impl error::Error for PoolError {
+    fn description(&self) -> &str {
+        match *self {
+            PoolError::DuplicateName(..) => "different types with the same name",
+        }
+    }
+}
+
+/// A message of a type from a `DescriptorPool`.
+#[derive(Clone)]
+pub struct DynamicMessage {
+    pool: DescriptorPool,
+    ty: Arc<MessageType>,
+    /// Field values, keyed by tag.
+    fields: BTreeMap<u32, Value>,
+    /// Encoded fields which are not part of the message type.
+    unknown: Vec<u8>,
+}
+
+impl DynamicMessage {
+    /// Returns the type of the message.
+    pub fn descriptor(&self) -> &MessageType {
+        &self.ty
+    }
+
+    /// Returns the pool containing the message type.
+    pub fn pool(&self) -> &DescriptorPool {
+        &self.pool
+    }
+
+    /// Returns the value of the field with the given tag, or `None` if the field is not set.
+    pub fn get_by_tag(&self, tag: u32) -> Option<&Value> {
+        self.fields.get(&tag)
+    }
+
+    /// Returns the value of the field with the given name, or `None` if the field is not set.
+    pub fn get_by_name(&self, name: &str) -> Option<&Value> {
+        self.ty
+            .field_by_name(name)
+            .and_then(|field| self.fields.get(&field.tag))
+    }
+
+    /// Sets the value of the field with the given tag.
+    ///
+    /// Repeated fields take a `Value::List`, and map fields a `Value::Map`. Setting a field of a
+    /// oneof clears the other fields of the oneof.
+    pub fn set_by_tag(&mut self, tag: u32, value: Value) -> Result<(), FieldError> {
+        let ty = self.ty.clone();
+        let field = ty
+            .field_by_tag(tag)
+            .ok_or_else(|| FieldError::UnknownField(tag.to_string()))?;
+        self.set(field, value)
+    }
+
+    /// Sets the value of the field with the given name.
+    ///
+    /// See `set_by_tag`.
+    pub fn set_by_name(&mut self, name: &str, value: Value) -> Result<(), FieldError> {
+        let ty = self.ty.clone();
+        let field = ty
+            .field_by_name(name)
+            .ok_or_else(|| FieldError::UnknownField(name.to_string()))?;
+        self.set(field, value)
+    }
+
+    /// Clears the field with the given tag, returning its value if it was set.
+    pub fn clear_by_tag(&mut self, tag: u32) -> Option<Value> {
+        self.fields.remove(&tag)
+    }
+
+    /// Clears the field with the given name, returning its value if it was set.
+    pub fn clear_by_name(&mut self, name: &str) -> Option<Value> {
+        let tag = self.ty.field_by_name(name)?.tag;
+        self.fields.remove(&tag)
+    }
+
+    /// Returns an iterator over the set fields and their values, in tag order.
+    pub fn fields(&self) -> impl Iterator<Item = (&FieldType, &Value)> {
+        let ty = &self.ty;
+        self.fields
+            .iter()
+            .map(move |(tag, value)| (ty.field_by_tag(*tag).unwrap(), value))
+    }
+
+    /// Returns the encoded fields which are not part of the message type.
+    pub fn unknown_fields(&self) -> &[u8] {
+        &self.unknown
+    }
+
+    /// Clears all fields, including unknown fields.
+    pub fn clear(&mut self) {
+        self.fields.clear();
+        self.unknown.clear();
+        self.set_write_empty_defaults();
+    }
+
+    /// Sets the `write_empty` fields to the default value, so that they are encoded like the
+    /// fields of derived messages.
+    fn set_write_empty_defaults(&mut self) {
+        for field in &self.ty.fields {
+            if field.write_empty {
+                if let Ok(value) = default_value(&self.pool, &field.kind) {
+                    self.fields.insert(field.tag, value);
+                }
+            }
+        }
+    }
+
+    fn set(&mut self, field: &FieldType, value: Value) -> Result<(), FieldError> {
+        let valid = match (field.label, &field.kind, &value) {
+            (_, &Kind::Map(..), value) => value.is_valid(&field.kind),
+            (Label::Repeated, _, &Value::List(ref values))
+            | (Label::Packed, _, &Value::List(ref values)) => {
+                values.iter().all(|value| value.is_valid(&field.kind))
+            }
+            (Label::Repeated, _, _) | (Label::Packed, _, _) => false,
+            _ => value.is_valid(&field.kind),
+        };
+        if !valid {
+            return Err(FieldError::InvalidValue(field.name.clone()));
+        }
+        self.clear_oneof(field);
+        self.fields.insert(field.tag, value);
+        Ok(())
+    }
+
+    /// Clears the fields in the same oneof as `field`.
+    fn clear_oneof(&mut self, field: &FieldType) {
+        if let Some(oneof) = field.oneof {
+            for other in &self.ty.fields {
+                if other.oneof == Some(oneof) && other.tag != field.tag {
+                    self.fields.remove(&other.tag);
+                }
+            }
+        }
+    }
+
+    /// Decodes an instance of the message from a buffer, and merges it into `self`.
+    ///
+    /// The entire buffer will be consumed.
+    pub fn merge<B>(&mut self, mut buf: B) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        if let Some(prefix) = self.ty.prefix {
+            self.merge_prefix(prefix, &mut buf)?;
+        }
+        while buf.has_remaining() {
+            self.merge_field(&mut buf, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Decodes the length and the prefix bytes preceding the fields of a registered message, and
+    /// returns the length of the fields.
+    fn merge_prefix<B>(&self, prefix: [u8; 4], buf: &mut B) -> Result<usize, DecodeError>
+    where
+        B: Buf,
+    {
+        let len = decode_varint(buf)?;
+        if len < prefix.len() as u64 || buf.remaining() < prefix.len() {
+            return Err(DecodeError::new("buffer underflow"));
+        }
+        let mut actual = [0; 4];
+        buf.copy_to_slice(&mut actual);
+        if actual != prefix {
+            return Err(DecodeError::new(format!(
+                "invalid amino prefix for {}: {:?}",
+                self.ty.name, actual
+            )));
+        }
+        Ok(len as usize - prefix.len())
+    }
+
+    /// Decodes the message as the value of a length delimited field, with fields nested in `depth`
+    /// messages or groups, and merges it into `self`.
+    fn merge_nested<B>(&mut self, buf: &mut B, depth: u32) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        let prefix = match self.ty.prefix {
+            Some(prefix) => prefix,
+            None => return merge_loop(self, buf, |message, buf| message.merge_field(buf, depth)),
+        };
+
+        // The field length is repeated by the registered message encoding.
+        let len = decode_varint(buf)?;
+        let fields_len = self.merge_prefix(prefix, buf)?;
+        if len != (fields_len + prefix.len()) as u64 {
+            return Err(DecodeError::new(format!(
+                "invalid length of registered message {}",
+                self.ty.name
+            )));
+        }
+        if fields_len > buf.remaining() {
+            return Err(DecodeError::new("buffer underflow"));
+        }
+        let limit = buf.remaining() - fields_len;
+        while buf.remaining() > limit {
+            self.merge_field(buf, depth)?;
+        }
+        if buf.remaining() != limit {
+            return Err(DecodeError::new("delimited length exceeded"));
+        }
+        Ok(())
+    }
+
+    /// Returns the encoded length of the message, including the amino prefix of registered types.
+    pub fn encoded_len(&self) -> usize {
+        self.encoded_len_from_body(self.body_len())
+    }
+
+    /// Returns the encoded length of the message from the length returned by `body_len`, so that
+    /// nested messages are only measured once.
+    fn encoded_len_from_body(&self, body_len: usize) -> usize {
+        match self.ty.prefix {
+            Some(_) => encoded_len_varint(body_len as u64) + body_len,
+            None => body_len,
+        }
+    }
+
+    /// Encodes the message to a buffer.
+    ///
+    /// An error will be returned if the buffer does not have sufficient capacity.
+    pub fn encode<B>(&self, buf: &mut B) -> Result<(), EncodeError>
+    where
+        B: BufMut,
+    {
+        let required = self.encoded_len();
+        let remaining = buf.remaining_mut();
+        if required > remaining {
+            return Err(EncodeError::new(required, remaining));
+        }
+        self.encode_raw(buf);
+        Ok(())
+    }
+
+    fn encode_raw<B>(&self, buf: &mut B)
+    where
+        B: BufMut,
+    {
+        if let Some(prefix) = self.ty.prefix {
+            encode_varint(self.body_len() as u64, buf);
+            buf.put_slice(&prefix);
+        }
+        self.encode_fields(buf);
+    }
+
+    /// Returns the encoded length of the prefix bytes and the fields, which is the length of a
+    /// message field, like the encoded length of a derived message.
+    fn body_len(&self) -> usize {
+        self.ty.prefix.map_or(0, |prefix| prefix.len()) + self.fields_encoded_len()
+    }
+
+    fn fields_encoded_len(&self) -> usize {
+        self.fields()
+            .map(|(field, value)| field_encoded_len(field, value))
+            .sum::<usize>()
+            + self.unknown.len()
+    }
+
+    fn encode_fields<B>(&self, buf: &mut B)
+    where
+        B: BufMut,
+    {
+        for (field, value) in self.fields() {
+            encode_field(field, value, buf);
+        }
+        buf.put_slice(&self.unknown);
+    }
+
+    /// Decodes a field nested in `depth` messages or groups from a buffer, and merges it into
+    /// `self`.
+    fn merge_field<B>(&mut self, buf: &mut B, depth: u32) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        let (tag, wire_type) = decode_key(buf)?;
+        self.merge_tagged_field(tag, wire_type, buf, depth)
+    }
+
+    /// Decodes the value of a field with the given tag and wire type, and merges it into `self`.
+    fn merge_tagged_field<B>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        depth: u32,
+    ) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        let ty = self.ty.clone();
+        let field = match ty.field_by_tag(tag) {
+            Some(field) => field,
+            None => return self.merge_unknown(tag, wire_type, buf),
+        };
+
+        match (field.label, &field.kind) {
+            (_, &Kind::Map(key, ref value)) => {
+                self.merge_map_entry(field.tag, key, value, wire_type, buf, depth)
+            }
+            (Label::Repeated, kind) | (Label::Packed, kind) => {
+                let pool = self.pool.clone();
+                let values = match *self
+                    .fields
+                    .entry(field.tag)
+                    .or_insert_with(|| Value::List(Vec::new()))
+                {
+                    Value::List(ref mut values) => values,
+                    ref value => return Err(invalid_value(kind, value)),
+                };
+                merge_repeated(&pool, field.tag, kind, wire_type, values, buf, depth)
+            }
+            (_, kind) => {
+                self.clear_oneof(field);
+                let pool = self.pool.clone();
+                match self.fields.get_mut(&field.tag) {
+                    Some(value) => merge_value(field.tag, kind, wire_type, value, buf, depth),
+                    None => {
+                        let mut value = default_value(&pool, kind)?;
+                        merge_value(field.tag, kind, wire_type, &mut value, buf, depth)?;
+                        self.fields.insert(field.tag, value);
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    fn merge_map_entry<B>(
+        &mut self,
+        tag: u32,
+        key_ty: Type,
+        value_kind: &Kind,
+        wire_type: WireType,
+        buf: &mut B,
+        depth: u32,
+    ) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        check_wire_type(WireType::LengthDelimited, wire_type)?;
+        let pool = self.pool.clone();
+        let mut key = Value::default_scalar(key_ty);
+        let mut value = default_value(&pool, value_kind)?;
+        merge_loop(
+            &mut (&mut key, &mut value),
+            buf,
+            |&mut (ref mut key, ref mut value), buf| {
+                let (tag, wire_type) = decode_key(buf)?;
+                match tag {
+                    1 => merge_value(1, &Kind::Scalar(key_ty), wire_type, key, buf, depth),
+                    2 => merge_value(2, value_kind, wire_type, value, buf, depth),
+                    _ => skip_field(wire_type, tag, buf),
+                }
+            },
+        )?;
+        let key =
+            MapKey::from_value(key).ok_or_else(|| DecodeError::new("invalid map key type"))?;
+        match *self
+            .fields
+            .entry(tag)
+            .or_insert_with(|| Value::Map(BTreeMap::new()))
+        {
+            Value::Map(ref mut entries) => entries.insert(key, value),
+            ref value => return Err(invalid_value(value_kind, value)),
+        };
+        Ok(())
+    }
+
+    /// Copies an unknown field to the unknown fields of the message.
+    fn merge_unknown<B>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+    ) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        copy_field(wire_type, tag, buf, &mut self.unknown)
+    }
+}
+
+impl PartialEq for DynamicMessage {
+    fn eq(&self, other: &DynamicMessage) -> bool {
+        self.ty.name == other.ty.name
+            && self.fields == other.fields
+            && self.unknown == other.unknown
+    }
+}
+
+impl fmt::Debug for DynamicMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut builder = f.debug_struct(&self.ty.name);
+        for (field, value) in self.fields() {
+            builder.field(&field.name, value);
+        }
+        builder.finish()
+    }
+}
+
+/// Dispatches on a scalar type, binding `$module` to the corresponding `encoding` module.
+macro_rules! scalar {
+    ($ty:expr, $module:ident => $body:expr) => {
+        match $ty {
+            Type::Double => {
+                use encoding::double as $module;
+                $body
+            }
+            Type::Float => {
+                use encoding::float as $module;
+                $body
+            }
+            Type::Int32 => {
+                use encoding::int32 as $module;
+                $body
+            }
+            Type::Int64 => {
+                use encoding::int64 as $module;
+                $body
+            }
+            Type::Uint32 => {
+                use encoding::uint32 as $module;
+                $body
+            }
+            Type::Uint64 => {
+                use encoding::uint64 as $module;
+                $body
+            }
+            Type::Sint32 => {
+                use encoding::sint32 as $module;
+                $body
+            }
+            Type::Sint64 => {
+                use encoding::sint64 as $module;
+                $body
+            }
+            Type::Fixed32 => {
+                use encoding::fixed32 as $module;
+                $body
+            }
+            Type::Fixed64 => {
+                use encoding::fixed64 as $module;
+                $body
+            }
+            Type::Sfixed32 => {
+                use encoding::sfixed32 as $module;
+                $body
+            }
+            Type::Sfixed64 => {
+                use encoding::sfixed64 as $module;
+                $body
+            }
+            Type::Bool => {
+                use encoding::bool as $module;
+                $body
+            }
+            Type::String => {
+                use encoding::string as $module;
+                $body
+            }
+            Type::Bytes => {
+                use encoding::bytes as $module;
+                $body
+            }
+        }
+    };
+}
+
+/// Like `scalar!`, for operations which are only defined for numeric types.
+macro_rules! numeric {
+    ($ty:expr, $module:ident => $body:expr) => {
+        match $ty {
+            Type::Double => {
+                use encoding::double as $module;
+                $body
+            }
+            Type::Float => {
+                use encoding::float as $module;
+                $body
+            }
+            Type::Int32 => {
+                use encoding::int32 as $module;
+                $body
+            }
+            Type::Int64 => {
+                use encoding::int64 as $module;
+                $body
+            }
+            Type::Uint32 => {
+                use encoding::uint32 as $module;
+                $body
+            }
+            Type::Uint64 => {
+                use encoding::uint64 as $module;
+                $body
+            }
+            Type::Sint32 => {
+                use encoding::sint32 as $module;
+                $body
+            }
+            Type::Sint64 => {
+                use encoding::sint64 as $module;
+                $body
+            }
+            Type::Fixed32 => {
+                use encoding::fixed32 as $module;
+                $body
+            }
+            Type::Fixed64 => {
+                use encoding::fixed64 as $module;
+                $body
+            }
+            Type::Sfixed32 => {
+                use encoding::sfixed32 as $module;
+                $body
+            }
+            Type::Sfixed64 => {
+                use encoding::sfixed64 as $module;
+                $body
+            }
+            Type::Bool => {
+                use encoding::bool as $module;
+                $body
+            }
+            Type::String | Type::Bytes => unreachable!("packed {} field", $ty),
+        }
+    };
+}
+
+/// Returns `true` for the scalar types which can be packed.
+fn is_numeric(ty: Type) -> bool {
+    ty != Type::String && ty != Type::Bytes
+}
+
+/// Conversions between Rust scalar types and `Value`.
+trait ScalarValue: Clone + Default {
+    fn from_value(value: &Value) -> &Self;
+    fn into_value(self) -> Value;
+}
+
+macro_rules! scalar_value {
+    ($ty:ty, $variant:ident) => {
+        impl ScalarValue for $ty {
+            fn from_value(value: &Value) -> &$ty {
+                match *value {
+                    Value::$variant(ref value) => value,
+                    ref value => panic!("invalid {} value: {:?}", stringify!($ty), value),
+                }
+            }
+            fn into_value(self) -> Value {
+                Value::$variant(self)
+            }
+        }
+    };
+}
+
+scalar_value!(bool, Bool);
+scalar_value!(u32, U32);
+scalar_value!(u64, U64);
+scalar_value!(f32, F32);
+scalar_value!(f64, F64);
+scalar_value!(String, String);
+scalar_value!(Vec<u8>, Bytes);
+scalar_value!(i64, I64);
+
+// Enumeration values are encoded as `int32`.
+impl ScalarValue for i32 {
+    fn from_value(value: &Value) -> &i32 {
+        match *value {
+            Value::I32(ref value) | Value::Enum(ref value) => value,
+            ref value => panic!("invalid i32 value: {:?}", value),
+        }
+    }
+    fn into_value(self) -> Value {
+        Value::I32(self)
+    }
+}
+
+fn typed<T>(values: &[Value]) -> Vec<T>
+where
+    T: ScalarValue,
+{
+    values
+        .iter()
+        .map(|value| T::from_value(value).clone())
+        .collect()
+}
+
+/// Returns the default value of a singular field.
+fn default_value(pool: &DescriptorPool, kind: &Kind) -> Result<Value, DecodeError> {
+    Ok(match *kind {
+        Kind::Scalar(ty) => Value::default_scalar(ty),
+        Kind::Enumeration(ref name) => Value::Enum(
+            pool.enumeration(name)
+                .and_then(|ty| ty.values.first())
+                .map_or(0, |&(_, number)| number),
+        ),
+        Kind::Message(ref name) | Kind::Group(ref name) => Value::Message(
+            pool.new_message(name)
+                .ok_or_else(|| DecodeError::new(format!("unknown message type: {}", name)))?,
+        ),
+        Kind::Map(..) => Value::Map(BTreeMap::new()),
+    })
+}
+
+/// Decodes a field value nested in `depth` messages or groups, and merges it into `value`.
+fn merge_value<B>(
+    tag: u32,
+    kind: &Kind,
+    wire_type: WireType,
+    value: &mut Value,
+    buf: &mut B,
+    depth: u32,
+) -> Result<(), DecodeError>
+where
+    B: Buf,
+{
+    match (kind, value) {
+        (&Kind::Scalar(ty), value) => scalar!(ty, module => {
+            let mut v = Default::default();
+            module::merge(wire_type, &mut v, buf)?;
+            *value = ScalarValue::into_value(v);
+            Ok(())
+        }),
+        (&Kind::Enumeration(_), &mut Value::Enum(ref mut value)) => {
+            int32::merge(wire_type, value, buf)
+        }
+        (&Kind::Message(_), &mut Value::Message(ref mut message)) => {
+            check_wire_type(WireType::LengthDelimited, wire_type)?;
+            check_depth(depth)?;
+            message.merge_nested(buf, depth + 1)
+        }
+        (&Kind::Group(_), &mut Value::Message(ref mut message)) => {
+            // Like `group::merge`, the fields are merged up to the end group key.
+            check_wire_type(WireType::StartGroup, wire_type)?;
+            check_depth(depth)?;
+            loop {
+                let (field_tag, field_wire_type) = decode_key(buf)?;
+                if field_wire_type == WireType::EndGroup {
+                    return check_end_group(tag, field_tag);
+                }
+                message.merge_tagged_field(field_tag, field_wire_type, buf, depth + 1)?;
+            }
+        }
+        (kind, value) => Err(invalid_value(kind, value)),
+    }
+}
+
+/// Returns the error for a field value which does not match the field type.
+fn invalid_value(kind: &Kind, value: &Value) -> DecodeError {
+    DecodeError::new(format!("invalid {:?} value: {:?}", kind, value))
+}
+
+fn merge_repeated<B>(
+    pool: &DescriptorPool,
+    tag: u32,
+    kind: &Kind,
+    wire_type: WireType,
+    values: &mut Vec<Value>,
+    buf: &mut B,
+    depth: u32,
+) -> Result<(), DecodeError>
+where
+    B: Buf,
+{
+    match *kind {
+        Kind::Scalar(ty) => scalar!(ty, module => {
+            let mut vs = Vec::new();
+            module::merge_repeated(wire_type, &mut vs, buf)?;
+            values.extend(vs.into_iter().map(ScalarValue::into_value));
+            Ok(())
+        }),
+        Kind::Enumeration(_) => {
+            let mut vs = Vec::new();
+            int32::merge_repeated(wire_type, &mut vs, buf)?;
+            values.extend(vs.into_iter().map(Value::Enum));
+            Ok(())
+        }
+        _ => {
+            let mut value = default_value(pool, kind)?;
+            merge_value(tag, kind, wire_type, &mut value, buf, depth)?;
+            values.push(value);
+            Ok(())
+        }
+    }
+}
+
+fn encode_field<B>(field: &FieldType, value: &Value, buf: &mut B)
+where
+    B: BufMut,
+{
+    let tag = field.tag;
+    match (field.label, &field.kind, value) {
+        (_, &Kind::Map(key, ref value_kind), Value::Map(entries)) => {
+            for (k, v) in entries {
+                let k = k.clone().into_value();
+                let len = map_entry_len(key, &k, value_kind, v);
+                encode_key(tag, WireType::LengthDelimited, buf);
+                encode_varint(len as u64, buf);
+                if !k.is_default() {
+                    encode_value(1, &Kind::Scalar(key), &k, buf);
+                }
+                if !v.is_default() {
+                    encode_value(2, value_kind, v, buf);
+                }
+            }
+        }
+        (Label::Packed, &Kind::Scalar(ty), Value::List(values)) if is_numeric(ty) => {
+            numeric!(ty, module => module::encode_packed(tag, &typed(values), buf))
+        }
+        (Label::Packed, &Kind::Enumeration(_), Value::List(values)) => {
+            int32::encode_packed(tag, &typed(values), buf)
+        }
+        (_, kind, Value::List(values)) => {
+            for value in values {
+                encode_value(tag, kind, value, buf);
+            }
+        }
+        (Label::Plain, _, value) if value.is_default() && !field.write_empty => (),
+        (_, kind, value) => encode_value(tag, kind, value, buf),
+    }
+}
+
+fn field_encoded_len(field: &FieldType, value: &Value) -> usize {
+    let tag = field.tag;
+    match (field.label, &field.kind, value) {
+        (_, &Kind::Map(key, ref value_kind), Value::Map(entries)) => entries
+            .iter()
+            .map(|(k, v)| {
+                let len = map_entry_len(key, &k.clone().into_value(), value_kind, v);
+                key_len(tag) + encoded_len_varint(len as u64) + len
+            })
+            .sum(),
+        (Label::Packed, &Kind::Scalar(ty), Value::List(values)) if is_numeric(ty) => {
+            numeric!(ty, module => module::encoded_len_packed(tag, &typed(values)))
+        }
+        (Label::Packed, &Kind::Enumeration(_), Value::List(values)) => {
+            int32::encoded_len_packed(tag, &typed(values))
+        }
+        (_, kind, Value::List(values)) => values
+            .iter()
+            .map(|value| value_encoded_len(tag, kind, value))
+            .sum(),
+        (Label::Plain, _, value) if value.is_default() && !field.write_empty => 0,
+        (_, kind, value) => value_encoded_len(tag, kind, value),
+    }
+}
+
+/// Returns the encoded length of a map entry. Default keys and values are not encoded.
+fn map_entry_len(key_ty: Type, key: &Value, value_kind: &Kind, value: &Value) -> usize {
+    let key_len = if key.is_default() {
+        0
+    } else {
+        value_encoded_len(1, &Kind::Scalar(key_ty), key)
+    };
+    let value_len = if value.is_default() {
+        0
+    } else {
+        value_encoded_len(2, value_kind, value)
+    };
+    key_len + value_len
+}
+
+fn encode_value<B>(tag: u32, kind: &Kind, value: &Value, buf: &mut B)
+where
+    B: BufMut,
+{
+    match (kind, value) {
+        (&Kind::Scalar(ty), value) => {
+            scalar!(ty, module => module::encode(tag, ScalarValue::from_value(value), buf))
+        }
+        (&Kind::Enumeration(_), Value::Enum(value)) => int32::encode(tag, value, buf),
+        (&Kind::Message(_), Value::Message(message)) => {
+            encode_key(tag, WireType::LengthDelimited, buf);
+            encode_varint(message.body_len() as u64, buf);
+            message.encode_raw(buf);
+        }
+        (&Kind::Group(_), Value::Message(message)) => {
+            encode_key(tag, WireType::StartGroup, buf);
+            message.encode_raw(buf);
+            encode_key(tag, WireType::EndGroup, buf);
+        }
+        // Field values are checked when they are set, and decoded values have the field type.
+        (kind, value) => unreachable!("invalid {:?} value: {:?}", kind, value),
+    }
+}
+
+fn value_encoded_len(tag: u32, kind: &Kind, value: &Value) -> usize {
+    match (kind, value) {
+        (&Kind::Scalar(ty), value) => {
+            scalar!(ty, module => module::encoded_len(tag, ScalarValue::from_value(value)))
+        }
+        (&Kind::Enumeration(_), Value::Enum(value)) => int32::encoded_len(tag, value),
+        (&Kind::Message(_), Value::Message(message)) => {
+            let body_len = message.body_len();
+            key_len(tag)
+                + encoded_len_varint(body_len as u64)
+                + message.encoded_len_from_body(body_len)
+        }
+        (&Kind::Group(_), Value::Message(message)) => key_len(tag) * 2 + message.encoded_len(),
+        // Field values are checked when they are set, and decoded values have the field type.
+        (kind, value) => unreachable!("invalid {:?} value: {:?}", kind, value),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn pool() -> DescriptorPool {
+        let mut pool = DescriptorPool::new();
+        pool.add_enum(EnumType {
+            name: "test.Kind".to_string(),
+            values: vec![("A".to_string(), 0), ("B".to_string(), 1)],
+        });
+        pool.add_message(MessageType {
+            name: "test.Node".to_string(),
+            fields: vec![
+                FieldType {
+                    name: "id".to_string(),
+                    json_name: "id".to_string(),
+                    tag: 1,
+                    label: Label::Plain,
+                    kind: Kind::Scalar(Type::Sint64),
+                    oneof: None,
+                    write_empty: false,
+                },
+                FieldType {
+                    name: "kinds".to_string(),
+                    json_name: "kinds".to_string(),
+                    tag: 2,
+                    label: Label::Packed,
+                    kind: Kind::Enumeration("test.Kind".to_string()),
+                    oneof: None,
+                    write_empty: false,
+                },
+                FieldType {
+                    name: "children".to_string(),
+                    json_name: "children".to_string(),
+                    tag: 3,
+                    label: Label::Repeated,
+                    kind: Kind::Message("test.Node".to_string()),
+                    oneof: None,
+                    write_empty: false,
+                },
+                FieldType {
+                    name: "labels".to_string(),
+                    json_name: "labels".to_string(),
+                    tag: 4,
+                    label: Label::Repeated,
+                    kind: Kind::Map(Type::String, Box::new(Kind::Scalar(Type::Uint32))),
+                    oneof: None,
+                    write_empty: false,
+                },
+                FieldType {
+                    name: "name".to_string(),
+                    json_name: "name".to_string(),
+                    tag: 5,
+                    label: Label::Optional,
+                    kind: Kind::Scalar(Type::String),
+                    oneof: Some(0),
+                    write_empty: false,
+                },
+                FieldType {
+                    name: "number".to_string(),
+                    json_name: "number".to_string(),
+                    tag: 6,
+                    label: Label::Optional,
+                    kind: Kind::Scalar(Type::Fixed32),
+                    oneof: Some(0),
+                    write_empty: false,
+                },
+            ],
+            oneofs: vec!["key".to_string()],
+            amino_name: None,
+            prefix: None,
+        });
+        pool
+    }
+
+    #[test]
+    fn roundtrip() {
+        let pool = pool();
+        let mut child = pool.new_message("test.Node").unwrap();
+        child.set_by_name("id", Value::I64(-2)).unwrap();
+
+        let mut labels = BTreeMap::new();
+        labels.insert(MapKey::String("a".to_string()), Value::U32(1));
+        labels.insert(MapKey::String(String::new()), Value::U32(0));
+
+        let mut node = pool.new_message("test.Node").unwrap();
+        node.set_by_name("id", Value::I64(0)).unwrap();
+        node.set_by_name("kinds", Value::List(vec![Value::Enum(1), Value::Enum(7)]))
+            .unwrap();
+        node.set_by_tag(3, Value::List(vec![Value::Message(child)]))
+            .unwrap();
+        node.set_by_name("labels", Value::Map(labels)).unwrap();
+        node.set_by_name("name", Value::String("foo".to_string()))
+            .unwrap();
+
+        let mut buf = Vec::new();
+        node.encode(&mut buf).unwrap();
+        assert_eq!(buf.len(), node.encoded_len());
+
+        let decoded = pool.decode("test.Node", &buf[..]).unwrap();
+        // The plain field with a default value is not encoded.
+        assert_eq!(decoded.get_by_name("id"), None);
+        node.clear_by_name("id");
+        assert_eq!(decoded, node);
+    }
+
+    #[test]
+    fn oneof() {
+        let pool = pool();
+        let mut node = pool.new_message("test.Node").unwrap();
+        node.set_by_name("name", Value::String("foo".to_string()))
+            .unwrap();
+        node.set_by_name("number", Value::U32(0)).unwrap();
+        assert_eq!(node.get_by_name("name"), None);
+        assert_eq!(node.get_by_name("number"), Some(&Value::U32(0)));
+
+        // Oneof fields are encoded even if they hold the default value.
+        let mut buf = Vec::new();
+        node.encode(&mut buf).unwrap();
+        assert_eq!(buf, vec![0x35, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn invalid_values() {
+        let pool = pool();
+        let mut node = pool.new_message("test.Node").unwrap();
+        assert_eq!(
+            node.set_by_name("id", Value::I32(1)),
+            Err(FieldError::InvalidValue("id".to_string()))
+        );
+        assert_eq!(
+            node.set_by_name("kinds", Value::Enum(1)),
+            Err(FieldError::InvalidValue("kinds".to_string()))
+        );
+        assert_eq!(
+            node.set_by_name("labels", Value::List(vec![Value::Map(BTreeMap::new())])),
+            Err(FieldError::InvalidValue("labels".to_string()))
+        );
+        assert_eq!(
+            node.set_by_name("missing", Value::Bool(true)),
+            Err(FieldError::UnknownField("missing".to_string()))
+        );
+
+        // Field 4, a map field, with the varint wire type.
+        assert!(pool.decode("test.Node", &[0x20, 0x01][..]).is_err());
+    }
+
+    #[test]
+    fn reflected_duplicate_names() {
+        static FIRST: MessageDescriptor = MessageDescriptor {
+            name: "Header",
+            full_name: "Header",
+            amino_name: None,
+            prefix: None,
+            fields: &[],
+        };
+        static SECOND: MessageDescriptor = MessageDescriptor {
+            name: "Header",
+            full_name: "Header",
+            amino_name: None,
+            prefix: None,
+            fields: &[],
+        };
+
+        let mut pool = DescriptorPool::new();
+        pool.add_reflected(&FIRST).unwrap();
+        pool.add_reflected(&FIRST).unwrap();
+        assert_eq!(
+            pool.add_reflected(&SECOND),
+            Err(PoolError::DuplicateName("Header".to_string()))
+        );
+    }
+
+    #[test]
+    fn write_empty() {
+        let mut pool = DescriptorPool::new();
+        pool.add_message(MessageType {
+            name: "test.Empty".to_string(),
+            fields: vec![FieldType {
+                name: "count".to_string(),
+                json_name: "count".to_string(),
+                tag: 1,
+                label: Label::Plain,
+                kind: Kind::Scalar(Type::Uint32),
+                oneof: None,
+                write_empty: true,
+            }],
+            oneofs: Vec::new(),
+            amino_name: None,
+            prefix: None,
+        });
+
+        // The field is set to the default value, and encoded like a derived `write_empty` field.
+        let mut message = pool.new_message("test.Empty").unwrap();
+        assert_eq!(message.get_by_name("count"), Some(&Value::U32(0)));
+        let mut buf = Vec::new();
+        message.encode(&mut buf).unwrap();
+        assert_eq!(buf, vec![0x08, 0x00]);
+        assert_eq!(buf.len(), message.encoded_len());
+
+        message.clear();
+        assert_eq!(message.get_by_name("count"), Some(&Value::U32(0)));
+    }
+
+    #[test]
+    fn unknown_fields() {
+        let pool = pool();
+        // Field 1: sint64 -1, field 15: varint 150, field 16: bytes "hi", field 17: fixed64 1.
+        let buf = vec![
+            0x08, 0x01, 0x78, 0x96, 0x01, 0x82, 0x01, 0x02, b'h', b'i', 0x89, 0x01, 1, 0, 0, 0, 0,
+            0, 0, 0,
+        ];
+        let node = pool.decode("test.Node", &buf[..]).unwrap();
+        assert_eq!(node.get_by_tag(1), Some(&Value::I64(-1)));
+        assert_eq!(node.unknown_fields(), &buf[2..]);
+
+        let mut encoded = Vec::new();
+        node.encode(&mut encoded).unwrap();
+        assert_eq!(encoded, buf);
+    }
+
+    #[test]
+    fn nested_messages() {
+        // Nodes nested in their `children` field (tag 3).
+        let nested = |depth: usize| {
+            let mut reversed = Vec::new();
+            for _ in 0..depth {
+                let mut len = Vec::new();
+                encode_varint(reversed.len() as u64, &mut len);
+                reversed.extend(len.iter().rev());
+                reversed.push(0x1A);
+            }
+            reversed.reverse();
+            reversed
+        };
+
+        let pool = pool();
+        let encoded = nested(100);
+        let node = pool.decode("test.Node", &encoded[..]).unwrap();
+        assert_eq!(node.encoded_len(), encoded.len());
+
+        // Deeper messages are rejected instead of overflowing the stack.
+        assert!(pool.decode("test.Node", &nested(101)[..]).is_err());
+        assert!(pool.decode("test.Node", &nested(200_000)[..]).is_err());
+    }
+
+    #[test]
+    fn amino_registered() {
+        let mut pool = pool();
+        assert!(pool.set_amino_name("test.Node", "test"));
+        assert!(!pool.set_amino_name("test.Missing", "test"));
+        assert_eq!(
+            pool.message_by_prefix(&[0x81, 0x88, 0x4c, 0x7d])
+                .map(|ty| &ty.name[..]),
+            Some("test.Node")
+        );
+
+        let mut node = pool.new_message("test.Node").unwrap();
+        node.set_by_name("id", Value::I64(1)).unwrap();
+        let mut buf = Vec::new();
+        node.encode(&mut buf).unwrap();
+        assert_eq!(buf, vec![0x06, 0x81, 0x88, 0x4c, 0x7d, 0x08, 0x02]);
+        assert_eq!(pool.decode("test.Node", &buf[..]).unwrap(), node);
+
+        buf[1] = 0;
+        assert!(pool.decode("test.Node", &buf[..]).is_err());
+
+        // Nested registered messages are preceded by the length of the prefix and fields twice.
+        let child = node.clone();
+        node.set_by_name("children", Value::List(vec![Value::Message(child)]))
+            .unwrap();
+        let mut buf = Vec::new();
+        node.encode(&mut buf).unwrap();
+        assert_eq!(
+            buf,
+            vec![
+                0x0f, 0x81, 0x88, 0x4c, 0x7d, 0x08, 0x02, 0x1a, 0x06, 0x06, 0x81, 0x88, 0x4c, 0x7d,
+                0x08, 0x02,
+            ]
+        );
+        assert_eq!(buf.len(), node.encoded_len());
+        assert_eq!(pool.decode("test.Node", &buf[..]).unwrap(), node);
+
+        buf[8] = 0x05;
+        assert!(pool.decode("test.Node", &buf[..]).is_err());
+    }
+}
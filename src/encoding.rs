@@ -189,6 +189,8 @@ pub enum WireType {
     Varint = 0,
     SixtyFourBit = 1,
     LengthDelimited = 2,
+    StartGroup = 3,
+    EndGroup = 4,
     ThirtyTwoBit = 5,
 }
 
@@ -203,6 +205,8 @@ impl WireType {
             0 => Ok(WireType::Varint),
             1 => Ok(WireType::SixtyFourBit),
             2 => Ok(WireType::LengthDelimited),
+            3 => Ok(WireType::StartGroup),
+            4 => Ok(WireType::EndGroup),
             5 => Ok(WireType::ThirtyTwoBit),
             _ => Err(DecodeError::new(format!(
                 "invalid wire type value: {}",
@@ -289,7 +293,26 @@ where
     Ok(())
 }
 
-pub fn skip_field<B>(wire_type: WireType, buf: &mut B) -> Result<(), DecodeError>
+/// The maximum nesting depth of groups, and of the messages of dynamic messages.
+const RECURSION_LIMIT: u32 = 100;
+
+/// Skips the value of a field with the given tag.
+///
+/// A group is skipped up to and including its end group key, which must have the same tag.
+pub fn skip_field<B>(wire_type: WireType, tag: u32, buf: &mut B) -> Result<(), DecodeError>
+where
+    B: Buf,
+{
+    skip_nested_field(wire_type, tag, buf, 0)
+}
+
+/// Skips the value of a field with the given tag, nested in `depth` groups.
+fn skip_nested_field<B>(
+    wire_type: WireType,
+    tag: u32,
+    buf: &mut B,
+    depth: u32,
+) -> Result<(), DecodeError>
 where
     B: Buf,
 {
@@ -298,6 +321,18 @@ where
         WireType::ThirtyTwoBit => 4,
         WireType::SixtyFourBit => 8,
         WireType::LengthDelimited => decode_varint(buf)?,
+        WireType::StartGroup => {
+            check_depth(depth)?;
+            loop {
+                let (field_tag, field_wire_type) = decode_key(buf)?;
+                if field_wire_type == WireType::EndGroup {
+                    check_end_group(tag, field_tag)?;
+                    break 0;
+                }
+                skip_nested_field(field_wire_type, field_tag, buf, depth + 1)?;
+            }
+        }
+        WireType::EndGroup => return Err(DecodeError::new("unexpected end group tag")),
     };
 
     if len > buf.remaining() as u64 {
@@ -308,6 +343,113 @@ where
     Ok(())
 }
 
+/// Copies the key and value of a field with the given tag from the buffer to `out`, without
+/// decoding the value.
+///
+/// This is used to retain fields which are decoded later, such as extension fields.
+pub fn copy_field<B>(
+    wire_type: WireType,
+    tag: u32,
+    buf: &mut B,
+    out: &mut Vec<u8>,
+) -> Result<(), DecodeError>
+where
+    B: Buf,
+{
+    copy_nested_field(wire_type, tag, buf, out, 0)
+}
+
+/// Copies the fields of a group with the given tag from the buffer to `out`, and consumes the end
+/// group key.
+pub fn copy_group<B>(tag: u32, buf: &mut B, out: &mut Vec<u8>) -> Result<(), DecodeError>
+where
+    B: Buf,
+{
+    copy_nested_group(tag, buf, out, 0)
+}
+
+/// Copies the key and value of a field with the given tag, nested in `depth` groups.
+pub(crate) fn copy_nested_field<B>(
+    wire_type: WireType,
+    tag: u32,
+    buf: &mut B,
+    out: &mut Vec<u8>,
+    depth: u32,
+) -> Result<(), DecodeError>
+where
+    B: Buf,
+{
+    encode_key(tag, wire_type, out);
+    let len = match wire_type {
+        WireType::Varint => {
+            encode_varint(decode_varint(buf)?, out);
+            0
+        }
+        WireType::ThirtyTwoBit => 4,
+        WireType::SixtyFourBit => 8,
+        WireType::LengthDelimited => {
+            let len = decode_varint(buf)?;
+            encode_varint(len, out);
+            len
+        }
+        WireType::StartGroup => {
+            copy_nested_group(tag, buf, out, depth)?;
+            encode_key(tag, WireType::EndGroup, out);
+            0
+        }
+        WireType::EndGroup => return Err(DecodeError::new("unexpected end group tag")),
+    };
+    if len > buf.remaining() as u64 {
+        return Err(DecodeError::new("buffer underflow"));
+    }
+    let start = out.len();
+    out.resize(start + len as usize, 0);
+    buf.copy_to_slice(&mut out[start..]);
+    Ok(())
+}
+
+/// Copies the fields of a group with the given tag, nested in `depth` groups.
+fn copy_nested_group<B>(
+    tag: u32,
+    buf: &mut B,
+    out: &mut Vec<u8>,
+    depth: u32,
+) -> Result<(), DecodeError>
+where
+    B: Buf,
+{
+    check_depth(depth)?;
+    loop {
+        let (field_tag, field_wire_type) = decode_key(buf)?;
+        if field_wire_type == WireType::EndGroup {
+            return check_end_group(tag, field_tag);
+        }
+        copy_nested_field(field_wire_type, field_tag, buf, out, depth + 1)?;
+    }
+}
+
+/// Checks that a group or message nested in `depth` groups or messages does not exceed the
+/// recursion limit.
+#[inline]
+pub(crate) fn check_depth(depth: u32) -> Result<(), DecodeError> {
+    if depth >= RECURSION_LIMIT {
+        return Err(DecodeError::new("recursion limit reached"));
+    }
+    Ok(())
+}
+
+/// Checks that an end group key closes the group with the given tag.
+#[inline]
+pub(crate) fn check_end_group(tag: u32, end_tag: u32) -> Result<(), DecodeError> {
+    if tag != end_tag {
+        return Err(DecodeError::new(format!(
+            "unexpected end group tag: {} (expected {})",
+            end_tag, tag
+        )));
+    }
+    Ok(())
+}
+
 /// Helper macro which emits an `encode_repeated` function for the type.
 macro_rules! encode_repeated {
     ($ty:ty) => {
@@ -356,6 +498,45 @@ macro_rules! merge_repeated_numeric {
     };
 }
 
+/// Helper macro which emits a `merge_array` function for the numeric type.
+macro_rules! merge_array_numeric {
+    ($ty:ty,
+     $wire_type:expr,
+     $merge:ident) => {
+        /// Merges a packed fixed-size array, which must hold exactly as many values as the array.
+        pub fn merge_array<B>(
+            wire_type: WireType,
+            values: &mut [$ty],
+            buf: &mut B,
+        ) -> Result<(), DecodeError>
+        where
+            B: Buf,
+        {
+            check_wire_type(WireType::LengthDelimited, wire_type)?;
+            let mut len = 0;
+            merge_loop(&mut len, buf, |len, buf| {
+                if *len == values.len() {
+                    return Err(DecodeError::new(format!(
+                        "invalid fixed-size array: more than {} values",
+                        values.len()
+                    )));
+                }
+                $merge($wire_type, &mut values[*len], buf)?;
+                *len += 1;
+                Ok(())
+            })?;
+            if len != values.len() {
+                return Err(DecodeError::new(format!(
+                    "invalid fixed-size array: {} values (expected {})",
+                    len,
+                    values.len()
+                )));
+            }
+            Ok(())
+        }
+    };
+}
+
 /// Macro which emits a module containing a set of encoding functions for a
 /// variable width numeric type.
 macro_rules! varint {
@@ -409,6 +590,7 @@ macro_rules! varint {
             }
 
             merge_repeated_numeric!($ty, WireType::Varint, merge, merge_repeated);
+            merge_array_numeric!($ty, WireType::Varint, merge);
 
             #[inline]
             pub fn encoded_len(tag: u32, $to_uint64_value: &$ty) -> usize {
@@ -488,6 +670,49 @@ varint!(i64, sint64,
             ((value >> 1) as i64) ^ (-((value & 1) as i64))
         });
 
+// go-amino encodes `int8` and `int16` as signed (zig-zag) varints, and `uint8` and `uint16` as
+// unsigned varints. Decoded values which don't fit the type are rejected.
+varint!(i8, int8,
+        to_uint64(value) {
+            let value = i64::from(*value);
+            ((value << 1) ^ (value >> 63)) as u64
+        },
+        from_uint64(value) {
+            let value = ((value >> 1) as i64) ^ (-((value & 1) as i64));
+            if value < i64::from(i8::MIN) || value > i64::from(i8::MAX) {
+                return Err(DecodeError::new("invalid int8 value: out of range"));
+            }
+            value as i8
+        });
+varint!(i16, int16,
+        to_uint64(value) {
+            let value = i64::from(*value);
+            ((value << 1) ^ (value >> 63)) as u64
+        },
+        from_uint64(value) {
+            let value = ((value >> 1) as i64) ^ (-((value & 1) as i64));
+            if value < i64::from(i16::MIN) || value > i64::from(i16::MAX) {
+                return Err(DecodeError::new("invalid int16 value: out of range"));
+            }
+            value as i16
+        });
+varint!(u8, uint8,
+        to_uint64(value) u64::from(*value),
+        from_uint64(value) {
+            if value > u64::from(u8::MAX) {
+                return Err(DecodeError::new("invalid uint8 value: out of range"));
+            }
+            value as u8
+        });
+varint!(u16, uint16,
+        to_uint64(value) u64::from(*value),
+        from_uint64(value) {
+            if value > u64::from(u16::MAX) {
+                return Err(DecodeError::new("invalid uint16 value: out of range"));
+            }
+            value as u16
+        });
+
 /// Macro which emits a module containing a set of encoding functions for a
 /// fixed width numeric type.
 macro_rules! fixed_width {
@@ -555,6 +780,7 @@ macro_rules! fixed_width {
             }
 
             merge_repeated_numeric!($ty, $wire_type, merge, merge_repeated);
+            merge_array_numeric!($ty, $wire_type, merge);
 
             #[inline]
             pub fn encoded_len(tag: u32, _: &$ty) -> usize {
@@ -814,6 +1040,46 @@ pub mod bytes {
     }
 
     length_delimited!(Vec<u8>);
+
+    /// Encodes a fixed-size byte array, such as a go-amino `[20]byte`.
+    pub fn encode_array<B>(tag: u32, value: &[u8], buf: &mut B)
+    where
+        B: BufMut,
+    {
+        encode_key(tag, WireType::LengthDelimited, buf);
+        encode_varint(value.len() as u64, buf);
+        buf.put_slice(value);
+    }
+
+    /// Merges a fixed-size byte array, which must be encoded with exactly the array length.
+    pub fn merge_array<B>(
+        wire_type: WireType,
+        value: &mut [u8],
+        buf: &mut B,
+    ) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        check_wire_type(WireType::LengthDelimited, wire_type)?;
+        let len = decode_varint(buf)?;
+        if len != value.len() as u64 {
+            return Err(DecodeError::new(format!(
+                "invalid fixed-size byte array: {} bytes (expected {})",
+                len,
+                value.len()
+            )));
+        }
+        if len > buf.remaining() as u64 {
+            return Err(DecodeError::new("buffer underflow"));
+        }
+        buf.copy_to_slice(value);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn encoded_len_array(tag: u32, value: &[u8]) -> usize {
+        key_len(tag) + encoded_len_varint(value.len() as u64) + value.len()
+    }
 }
 
 pub mod message {
@@ -896,6 +1162,144 @@ pub mod message {
                 .map(|len| len + encoded_len_varint(len as u64))
                 .sum::<usize>()
     }
+
+    /// Encodes repeated optional messages the way go-amino encodes slices of struct pointers:
+    /// `None` elements are encoded as empty messages, with a zero length.
+    pub fn encode_repeated_nullable<M, B>(tag: u32, messages: &[Option<M>], buf: &mut B)
+    where
+        M: Message,
+        B: BufMut,
+    {
+        for msg in messages {
+            match *msg {
+                Some(ref msg) => encode(tag, msg, buf),
+                None => {
+                    encode_key(tag, WireType::LengthDelimited, buf);
+                    encode_varint(0, buf);
+                }
+            }
+        }
+    }
+
+    /// Merges an element of repeated optional messages. Empty elements are decoded as `None`,
+    /// like nil struct pointers in go-amino, so a message which encodes to zero bytes does not
+    /// round-trip.
+    pub fn merge_repeated_nullable<M, B>(
+        wire_type: WireType,
+        messages: &mut Vec<Option<M>>,
+        buf: &mut B,
+    ) -> Result<(), DecodeError>
+    where
+        M: Message + Default,
+        B: Buf,
+    {
+        check_wire_type(WireType::LengthDelimited, wire_type)?;
+        if buf.has_remaining() && buf.bytes()[0] == 0 {
+            buf.advance(1);
+            messages.push(None);
+            return Ok(());
+        }
+        let mut msg = M::default();
+        merge(WireType::LengthDelimited, &mut msg, buf)?;
+        messages.push(Some(msg));
+        Ok(())
+    }
+
+    #[inline]
+    pub fn encoded_len_repeated_nullable<M>(tag: u32, messages: &[Option<M>]) -> usize
+    where
+        M: Message,
+    {
+        key_len(tag) * messages.len()
+            + messages
+                .iter()
+                .map(|msg| msg.as_ref().map_or(0, Message::encoded_len))
+                .map(|len| len + encoded_len_varint(len as u64))
+                .sum::<usize>()
+    }
+}
+
+pub mod group {
+    use super::*;
+
+    pub fn encode<M, B>(tag: u32, msg: &M, buf: &mut B)
+    where
+        M: Message,
+        B: BufMut,
+    {
+        encode_key(tag, WireType::StartGroup, buf);
+        msg.encode_raw(buf);
+        encode_key(tag, WireType::EndGroup, buf);
+    }
+
+    /// Merges a group nested in `depth` groups into the message.
+    ///
+    /// The fields of the group are merged up to its end group key, which must have the same tag.
+    pub fn merge<M, B>(
+        tag: u32,
+        wire_type: WireType,
+        msg: &mut M,
+        buf: &mut B,
+        depth: u32,
+    ) -> Result<(), DecodeError>
+    where
+        M: Message,
+        B: Buf,
+    {
+        check_wire_type(WireType::StartGroup, wire_type)?;
+        check_depth(depth)?;
+        loop {
+            let (field_tag, field_wire_type) = decode_key(buf)?;
+            if field_wire_type == WireType::EndGroup {
+                return check_end_group(tag, field_tag);
+            }
+            msg.merge_tagged_field(field_tag, field_wire_type, buf, depth + 1)?;
+        }
+    }
+
+    pub fn encode_repeated<M, B>(tag: u32, messages: &[M], buf: &mut B)
+    where
+        M: Message,
+        B: BufMut,
+    {
+        for msg in messages {
+            encode(tag, msg, buf);
+        }
+    }
+
+    pub fn merge_repeated<M, B>(
+        tag: u32,
+        wire_type: WireType,
+        messages: &mut Vec<M>,
+        buf: &mut B,
+        depth: u32,
+    ) -> Result<(), DecodeError>
+    where
+        M: Message + Default,
+        B: Buf,
+    {
+        check_wire_type(WireType::StartGroup, wire_type)?;
+        let mut msg = M::default();
+        merge(tag, WireType::StartGroup, &mut msg, buf, depth)?;
+        messages.push(msg);
+        Ok(())
+    }
+
+    #[inline]
+    pub fn encoded_len<M>(tag: u32, msg: &M) -> usize
+    where
+        M: Message,
+    {
+        2 * key_len(tag) + msg.encoded_len()
+    }
+
+    #[inline]
+    pub fn encoded_len_repeated<M>(tag: u32, messages: &[M]) -> usize
+    where
+        M: Message,
+    {
+        2 * key_len(tag) * messages.len() + messages.iter().map(Message::encoded_len).sum::<usize>()
+    }
 }
 
 /// Rust doesn't have a `Map` trait, so macros are currently the best way to be
@@ -992,7 +1396,7 @@ macro_rules! map {
             VE: Fn(u32, &V, &mut B),
             VL: Fn(u32, &V) -> usize,
         {
-            for (key, val) in values.iter() {
+            for (key, val) in entries(values) {
                 let skip_key = key == &K::default();
                 let skip_val = val == val_default;
 
@@ -1037,7 +1441,7 @@ macro_rules! map {
                     match tag {
                         1 => key_merge(wire_type, key, buf),
                         2 => val_merge(wire_type, val, buf),
-                        _ => skip_field(wire_type, buf),
+                        _ => skip_field(wire_type, tag, buf),
                     }
                 },
             )?;
@@ -1085,10 +1489,28 @@ macro_rules! map {
 
 pub mod hash_map {
     map!(HashMap);
+
+    /// Returns the entries of the map sorted by key, so that the encoding of a map does not
+    /// depend on its iteration order. The entries are borrowed, not cloned.
+    fn entries<K, V>(values: &HashMap<K, V>) -> ::std::vec::IntoIter<(&K, &V)>
+    where
+        K: Ord,
+    {
+        let mut entries = values.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+        entries.into_iter()
+    }
 }
 
 pub mod btree_map {
     map!(BTreeMap);
+
+    /// Returns the entries of the map, which are already sorted by key.
+    fn entries<'a, K, V>(
+        values: &'a BTreeMap<K, V>,
+    ) -> ::std::collections::btree_map::Iter<'a, K, V> {
+        values.iter()
+    }
 }
 
 #[cfg(test)]
@@ -1338,6 +1760,162 @@ mod test {
         );
     }
 
+    #[test]
+    fn small_integers() {
+        let mut buf = Vec::new();
+        int8::encode(1, &-2, &mut buf);
+        assert_eq!(buf, [0x08, 0x03]);
+
+        let mut value = 0i8;
+        let mut decode = &buf[1..];
+        int8::merge(WireType::Varint, &mut value, &mut decode).unwrap();
+        assert_eq!(value, -2);
+
+        // 128 doesn't fit into an int8, nor 65536 into a uint16.
+        let mut decode = &[0x80, 0x02][..];
+        assert!(int8::merge(WireType::Varint, &mut value, &mut decode).is_err());
+        let mut decode = &[0x80, 0x80, 0x04][..];
+        assert!(uint16::merge(WireType::Varint, &mut 0u16, &mut decode).is_err());
+        let mut decode = &[0xFF, 0x01][..];
+        uint8::merge(WireType::Varint, &mut 0u8, &mut decode).unwrap();
+    }
+
+    #[test]
+    fn fixed_size_arrays() {
+        let mut buf = Vec::new();
+        bytes::encode_array(1, &[1, 2, 3], &mut buf);
+        assert_eq!(buf.len(), bytes::encoded_len_array(1, &[1, 2, 3]));
+
+        let mut value = [0u8; 3];
+        bytes::merge_array(WireType::LengthDelimited, &mut value, &mut &buf[1..]).unwrap();
+        assert_eq!(value, [1, 2, 3]);
+        let mut short = [0u8; 2];
+        assert!(bytes::merge_array(WireType::LengthDelimited, &mut short, &mut &buf[1..]).is_err());
+
+        let mut buf = Vec::new();
+        int64::encode_packed(1, &[1, 2, 3], &mut buf);
+        let mut values = [0i64; 3];
+        int64::merge_array(WireType::LengthDelimited, &mut values, &mut &buf[1..]).unwrap();
+        assert_eq!(values, [1, 2, 3]);
+        let mut values = [0i64; 2];
+        assert!(
+            int64::merge_array(WireType::LengthDelimited, &mut values, &mut &buf[1..]).is_err()
+        );
+        let mut values = [0i64; 4];
+        assert!(
+            int64::merge_array(WireType::LengthDelimited, &mut values, &mut &buf[1..]).is_err()
+        );
+    }
+
+    #[test]
+    fn hash_map_deterministic() {
+        use std::collections::{BTreeMap, HashMap};
+
+        let sorted = (0..100)
+            .map(|i| (format!("key{}", i), i))
+            .collect::<BTreeMap<String, u32>>();
+        let mut expected = Vec::new();
+        ::encoding::btree_map::encode(
+            string::encode,
+            string::encoded_len,
+            uint32::encode,
+            uint32::encoded_len,
+            1,
+            &sorted,
+            &mut expected,
+        );
+
+        // Hash maps are encoded in key order, whatever their iteration order.
+        for _ in 0..10 {
+            let values = sorted.clone().into_iter().collect::<HashMap<String, u32>>();
+            let mut buf = Vec::new();
+            ::encoding::hash_map::encode(
+                string::encode,
+                string::encoded_len,
+                uint32::encode,
+                uint32::encoded_len,
+                1,
+                &values,
+                &mut buf,
+            );
+            assert_eq!(buf, expected);
+        }
+    }
+
+    #[test]
+    fn groups() {
+        // A group with tag 2 containing a string field, and a nested group with tag 3.
+        let encoded = [
+            0x13, 0x0A, 0x01, b'a', 0x1B, 0x08, 0x01, 0x1C, 0x14, 0x28, 0x05,
+        ];
+
+        let mut buf = &encoded[..];
+        let (tag, wire_type) = decode_key(&mut buf).unwrap();
+        assert_eq!((tag, wire_type), (2, WireType::StartGroup));
+        skip_field(wire_type, tag, &mut buf).unwrap();
+        assert_eq!(buf, [0x28, 0x05]);
+
+        let mut buf = &encoded[1..];
+        let mut copied = Vec::new();
+        copy_field(WireType::StartGroup, 2, &mut buf, &mut copied).unwrap();
+        assert_eq!(copied, &encoded[..9]);
+
+        // The nested group is skipped when the group is merged into a string message.
+        let mut buf = &encoded[1..];
+        let mut value = String::new();
+        group::merge(2, WireType::StartGroup, &mut value, &mut buf, 0).unwrap();
+        assert_eq!(value, "a");
+
+        let mut buf = Vec::new();
+        group::encode(2, &value, &mut buf);
+        assert_eq!(buf, [0x13, 0x0A, 0x01, b'a', 0x14]);
+        assert_eq!(group::encoded_len(2, &value), buf.len());
+
+        // The end group key must match the start group key.
+        let mut buf = &[0x0A, 0x01, b'a', 0x1C][..];
+        assert!(skip_field(WireType::StartGroup, 2, &mut buf).is_err());
+        let mut buf = &[0x14][..];
+        let (tag, wire_type) = decode_key(&mut buf).unwrap();
+        assert!(skip_field(wire_type, tag, &mut buf).is_err());
+    }
+
+    #[test]
+    fn nested_groups() {
+        let nested = |depth: usize| {
+            let mut buf = vec![0x0B; depth];
+            buf.extend(vec![0x0C; depth]);
+            buf
+        };
+
+        let encoded = nested(RECURSION_LIMIT as usize);
+        let mut buf = &encoded[..];
+        let (tag, wire_type) = decode_key(&mut buf).unwrap();
+        skip_field(wire_type, tag, &mut buf).unwrap();
+        assert!(buf.is_empty());
+        let mut buf = &encoded[..];
+        let (tag, wire_type) = decode_key(&mut buf).unwrap();
+        let mut copied = Vec::new();
+        copy_field(wire_type, tag, &mut buf, &mut copied).unwrap();
+        assert_eq!(copied, encoded);
+        let mut buf = &encoded[1..];
+        group::merge(1, WireType::StartGroup, &mut (), &mut buf, 0).unwrap();
+        assert!(buf.is_empty());
+
+        // Deeper groups are rejected instead of overflowing the stack.
+        let encoded = nested(1_000_000);
+        let mut buf = &encoded[..];
+        let (tag, wire_type) = decode_key(&mut buf).unwrap();
+        assert!(skip_field(wire_type, tag, &mut buf).is_err());
+        let mut buf = &encoded[..];
+        let (tag, wire_type) = decode_key(&mut buf).unwrap();
+        assert!(copy_field(wire_type, tag, &mut buf, &mut Vec::new()).is_err());
+        let mut buf = &encoded[1..];
+        assert!(copy_group(1, &mut buf, &mut Vec::new()).is_err());
+        let mut buf = &encoded[1..];
+        assert!(group::merge(1, WireType::StartGroup, &mut (), &mut buf, 0).is_err());
+        assert!(<()>::decode(&encoded[..]).is_err());
+    }
+
     /// This big bowl o' macro soup generates a quickcheck encoding test for each
     /// combination of map type, scalar map key, and value type.
     /// TODO: these tests take a long time to compile, can this be improved?
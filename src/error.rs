@@ -1,4 +1,4 @@
-//! Protobuf encoding and decoding errors.
+//! Protobuf encoding, decoding and building errors.
 
 use std::borrow::Cow;
 use std::error;
@@ -119,3 +119,49 @@ impl From<EncodeError> for io::Error {
         io::Error::new(io::ErrorKind::InvalidInput, error)
     }
 }
+
+/// A message building error.
+///
+/// `BuildError` indicates that a required field of a message was not set on
+/// the message builder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BuildError {
+    message: &'static str,
+    field: &'static str,
+}
+
+impl BuildError {
+    /// Creates a new `BuildError` for a required field which was not set.
+    ///
+    /// Meant to be used only by message builder implementations.
+    #[doc(hidden)]
+    pub fn new(message: &'static str, field: &'static str) -> BuildError {
+        BuildError { message, field }
+    }
+
+    /// Returns the name of the message.
+    pub fn message(&self) -> &str {
+        self.message
+    }
+
+    /// Returns the name of the required field which was not set.
+    pub fn field(&self) -> &str {
+        self.field
+    }
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "failed to build {} message: required field {} is not set",
+            self.message, self.field
+        )
+    }
+}
+
+impl error::Error for BuildError {
+    fn description(&self) -> &str {
+        "failed to build Protobuf message: required field is not set"
+    }
+}
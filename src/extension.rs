@@ -0,0 +1,221 @@
+//! Proto2 extensions.
+//!
+//! Extendable messages have an [ExtensionSet] field, which retains the fields in the message's
+//! extension ranges while decoding, instead of skipping them as unknown fields. The fields are
+//! read and written through the [Extension] descriptors generated for `extend` declarations, with
+//! the `get_extension` and `set_extension` methods of the message.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+use bytes::{Buf, BufMut};
+
+use encoding::{copy_field, decode_key, WireType};
+use DecodeError;
+
+/// The descriptor of an extension of messages of type `M`, with values of type `T`.
+///
+/// Extension descriptors are generated as constants by `prost-build`.
+pub struct Extension<M, T> {
+    /// The fully qualified name of the extension, e.g. `gogoproto.nullable`.
+    pub name: &'static str,
+    /// The field number of the extension.
+    pub number: u32,
+    #[doc(hidden)]
+    pub encode: fn(u32, &T, &mut Vec<u8>),
+    #[doc(hidden)]
+    pub merge: fn(WireType, &mut T, &mut &[u8]) -> Result<(), DecodeError>,
+    #[doc(hidden)]
+    pub extendee: PhantomData<fn(&M)>,
+}
+
+impl<M, T> fmt::Debug for Extension<M, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Extension")
+            .field("name", &self.name)
+            .field("number", &self.number)
+            .finish()
+    }
+}
+
+/// The extension fields of a message.
+///
+/// The fields are kept encoded, keyed by field number, and are only decoded when they are read
+/// through an [Extension] descriptor.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExtensionSet {
+    fields: BTreeMap<u32, Vec<u8>>,
+}
+
+impl ExtensionSet {
+    /// Returns `true` if the set contains no extension fields.
+    pub fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Returns `true` if the extension is set.
+    pub fn has<M, T>(&self, extension: &Extension<M, T>) -> bool {
+        self.fields.contains_key(&extension.number)
+    }
+
+    /// Returns the value of the extension, or `None` if it's not set.
+    ///
+    /// All occurrences of the extension field are merged, as if it was a regular field of the
+    /// message.
+    pub fn get<M, T>(&self, extension: &Extension<M, T>) -> Result<Option<T>, DecodeError>
+    where
+        T: Default,
+    {
+        let field = match self.fields.get(&extension.number) {
+            Some(field) => field,
+            None => return Ok(None),
+        };
+        let mut value = T::default();
+        let mut buf = &field[..];
+        while buf.has_remaining() {
+            let (_, wire_type) = decode_key(&mut buf)?;
+            (extension.merge)(wire_type, &mut value, &mut buf).map_err(|mut error| {
+                error.push("ExtensionSet", extension.name);
+                error
+            })?;
+        }
+        Ok(Some(value))
+    }
+
+    /// Sets the value of the extension, replacing its previous value.
+    ///
+    /// Values which encode to nothing, such as empty repeated values, clear the extension.
+    pub fn set<M, T>(&mut self, extension: &Extension<M, T>, value: &T) {
+        let mut field = Vec::new();
+        (extension.encode)(extension.number, value, &mut field);
+        if field.is_empty() {
+            self.fields.remove(&extension.number);
+        } else {
+            self.fields.insert(extension.number, field);
+        }
+    }
+
+    /// Clears the extension.
+    pub fn clear_extension<M, T>(&mut self, extension: &Extension<M, T>) {
+        self.fields.remove(&extension.number);
+    }
+
+    /// Clears all extension fields.
+    pub fn clear(&mut self) {
+        self.fields.clear();
+    }
+
+    /// Copies an encoded extension field from the buffer into the set.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    pub fn merge_field<B>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+    ) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        // The field is copied to a buffer first, so that a truncated field isn't retained.
+        let mut field = Vec::new();
+        copy_field(wire_type, tag, buf, &mut field)?;
+        self.fields
+            .entry(tag)
+            .or_default()
+            .extend_from_slice(&field);
+        Ok(())
+    }
+
+    /// Encodes the extension fields to the buffer, in field number order.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    pub fn encode<B>(&self, buf: &mut B)
+    where
+        B: BufMut,
+    {
+        for field in self.fields.values() {
+            buf.put_slice(field);
+        }
+    }
+
+    /// Returns the encoded length of the extension fields.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    pub fn encoded_len(&self) -> usize {
+        self.fields.values().map(Vec::len).sum()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::marker::PhantomData;
+
+    use super::*;
+    use encoding::{int32, string};
+
+    struct Extendee;
+
+    const NAME: Extension<Extendee, String> = Extension {
+        name: "test.name",
+        number: 1000,
+        encode: |tag, value, buf| string::encode(tag, value, buf),
+        merge: |wire_type, value, buf| string::merge(wire_type, value, buf),
+        extendee: PhantomData,
+    };
+
+    const NUMBERS: Extension<Extendee, Vec<i32>> = Extension {
+        name: "test.numbers",
+        number: 1001,
+        encode: |tag, values, buf| int32::encode_repeated(tag, values, buf),
+        merge: |wire_type, values, buf| int32::merge_repeated(wire_type, values, buf),
+        extendee: PhantomData,
+    };
+
+    #[test]
+    fn set_and_get() {
+        let mut set = ExtensionSet::default();
+        assert!(set.is_empty());
+        assert_eq!(set.get(&NAME), Ok(None));
+
+        set.set(&NAME, &"foo".to_string());
+        set.set(&NUMBERS, &vec![1, 2]);
+        assert!(set.has(&NAME));
+        assert_eq!(set.get(&NAME), Ok(Some("foo".to_string())));
+        assert_eq!(set.get(&NUMBERS), Ok(Some(vec![1, 2])));
+
+        let mut buf = Vec::new();
+        set.encode(&mut buf);
+        assert_eq!(buf.len(), set.encoded_len());
+        assert_eq!(
+            buf,
+            [0xC2, 0x3E, 3, b'f', b'o', b'o', 0xC8, 0x3E, 1, 0xC8, 0x3E, 2]
+        );
+
+        set.set(&NUMBERS, &Vec::new());
+        assert!(!set.has(&NUMBERS));
+        set.clear_extension(&NAME);
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn merge_fields() {
+        let mut buf = &[0xC8, 0x3E, 1, 0xC2, 0x3E, 1, b'a', 0xC8, 0x3E, 2][..];
+        let mut set = ExtensionSet::default();
+        while buf.has_remaining() {
+            let (tag, wire_type) = decode_key(&mut buf).unwrap();
+            set.merge_field(tag, wire_type, &mut buf).unwrap();
+        }
+        assert_eq!(set.get(&NAME), Ok(Some("a".to_string())));
+        assert_eq!(set.get(&NUMBERS), Ok(Some(vec![1, 2])));
+
+        let mut truncated = &[3, b'a'][..];
+        assert!(set
+            .merge_field(1000, WireType::LengthDelimited, &mut truncated)
+            .is_err());
+    }
+}
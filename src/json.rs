@@ -0,0 +1,1506 @@
+//! The proto3 JSON mapping.
+//!
+//! `to_json` and `from_json` convert messages to and from their canonical proto3 JSON
+//! representation. Messages are converted through a `DynamicMessage`, using a `DescriptorPool`
+//! built from the reflection descriptors of the message type, so any derived message can be
+//! converted. `print` and `parse` convert dynamic messages directly.
+//!
+//! The well-known types have special representations, which are selected by the fully qualified
+//! name of the message type: `Timestamp` as an RFC 3339 string, `Duration` as a string of seconds
+//! such as `"1.5s"`, `FieldMask` as a comma separated list of `lowerCamelCase` paths, `Struct`,
+//! `Value` and `ListValue` as arbitrary JSON, the wrapper types as their wrapped value, and `Any`
+//! as the JSON of the packed message, with an additional `@type` member.
+//!
+//! `Any` values are resolved by the name following the last `/` of their type URL, which must be
+//! the name of a message type in the pool. When converting derived messages, that is any message
+//! type reachable from the converted message type.
+
+use std::collections::{BTreeMap, HashSet};
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+
+use dynamic::{DescriptorPool, DynamicMessage, FieldType, Kind, MapKey, Value};
+use reflect::{Label, Reflect, Type};
+use Message;
+
+const ANY: &str = "google.protobuf.Any";
+const DURATION: &str = "google.protobuf.Duration";
+const FIELD_MASK: &str = "google.protobuf.FieldMask";
+const LIST_VALUE: &str = "google.protobuf.ListValue";
+const NULL_VALUE: &str = "google.protobuf.NullValue";
+const STRUCT: &str = "google.protobuf.Struct";
+const TIMESTAMP: &str = "google.protobuf.Timestamp";
+const VALUE: &str = "google.protobuf.Value";
+
+const WRAPPERS: &[&str] = &[
+    "google.protobuf.DoubleValue",
+    "google.protobuf.FloatValue",
+    "google.protobuf.Int64Value",
+    "google.protobuf.UInt64Value",
+    "google.protobuf.Int32Value",
+    "google.protobuf.UInt32Value",
+    "google.protobuf.BoolValue",
+    "google.protobuf.StringValue",
+    "google.protobuf.BytesValue",
+];
+
+/// The maximum nesting depth of parsed JSON arrays and objects.
+const RECURSION_LIMIT: usize = 100;
+
+/// `0001-01-01T00:00:00Z`, the earliest valid `Timestamp`.
+const MIN_TIMESTAMP_SECONDS: i64 = -62_135_596_800;
+/// `9999-12-31T23:59:59Z`, the latest valid `Timestamp`.
+const MAX_TIMESTAMP_SECONDS: i64 = 253_402_300_799;
+/// Approximately 10,000 years, the largest valid `Duration`.
+const MAX_DURATION_SECONDS: i64 = 315_576_000_000;
+
+const NANOS_PER_SECOND: i32 = 1_000_000_000;
+
+/// An error converting a message to or from JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonError {
+    description: String,
+}
+
+impl JsonError {
+    fn new<S>(description: S) -> JsonError
+    where
+        S: Into<String>,
+    {
+        JsonError {
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("failed to convert JSON: ")?;
+        f.write_str(&self.description)
+    }
+}
+
+impl error::Error for JsonError {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// Converts a message to its canonical proto3 JSON representation.
+pub fn to_json<M>(message: &M) -> Result<String, JsonError>
+where
+    M: Message + Reflect,
+{
+    let descriptor = M::descriptor();
+    let mut pool = DescriptorPool::new();
+    pool.add_reflected(descriptor)
+        .map_err(|error| JsonError::new(error.to_string()))?;
+
+    let mut buf = Vec::with_capacity(message.encoded_len());
+    message
+        .encode(&mut buf)
+        .map_err(|error| JsonError::new(error.to_string()))?;
+    let message = pool
+        .decode(descriptor.full_name, &buf[..])
+        .map_err(|error| JsonError::new(error.to_string()))?;
+    print(&message)
+}
+
+/// Parses a message from its proto3 JSON representation.
+pub fn from_json<M>(json: &str) -> Result<M, JsonError>
+where
+    M: Message + Reflect + Default,
+{
+    let descriptor = M::descriptor();
+    let mut pool = DescriptorPool::new();
+    pool.add_reflected(descriptor)
+        .map_err(|error| JsonError::new(error.to_string()))?;
+
+    let message = parse(&pool, descriptor.full_name, json)?;
+    let mut buf = Vec::with_capacity(message.encoded_len());
+    message
+        .encode(&mut buf)
+        .map_err(|error| JsonError::new(error.to_string()))?;
+    M::decode(&buf[..]).map_err(|error| JsonError::new(error.to_string()))
+}
+
+/// Converts a dynamic message to its canonical proto3 JSON representation.
+pub fn print(message: &DynamicMessage) -> Result<String, JsonError> {
+    let mut out = String::new();
+    print_message(message, &mut out)?;
+    Ok(out)
+}
+
+/// Parses a dynamic message of the given type from its proto3 JSON representation.
+pub fn parse(pool: &DescriptorPool, name: &str, json: &str) -> Result<DynamicMessage, JsonError> {
+    let json = Parser::new(json).parse()?;
+    parse_message(pool, name, &json)
+}
+
+/// Returns `true` if the message type has a special JSON representation, which is nested in a
+/// `value` member when packed in an `Any`.
+fn is_special(name: &str) -> bool {
+    match name {
+        ANY | DURATION | FIELD_MASK | LIST_VALUE | STRUCT | TIMESTAMP | VALUE => true,
+        _ => WRAPPERS.contains(&name),
+    }
+}
+
+/// Returns the field of the message with the given tag.
+fn field(message: &DynamicMessage, tag: u32) -> Result<FieldType, JsonError> {
+    message
+        .descriptor()
+        .field_by_tag(tag)
+        .cloned()
+        .ok_or_else(|| {
+            JsonError::new(format!(
+                "{} has no field with tag {}",
+                message.descriptor().name,
+                tag
+            ))
+        })
+}
+
+fn set(message: &mut DynamicMessage, tag: u32, value: Value) -> Result<(), JsonError> {
+    message
+        .set_by_tag(tag, value)
+        .map_err(|error| JsonError::new(error.to_string()))
+}
+
+fn get_i64(message: &DynamicMessage, tag: u32) -> i64 {
+    match message.get_by_tag(tag) {
+        Some(&Value::I64(value)) => value,
+        _ => 0,
+    }
+}
+
+fn get_i32(message: &DynamicMessage, tag: u32) -> i32 {
+    match message.get_by_tag(tag) {
+        Some(&Value::I32(value)) => value,
+        _ => 0,
+    }
+}
+
+fn get_str(message: &DynamicMessage, tag: u32) -> &str {
+    match message.get_by_tag(tag) {
+        Some(Value::String(value)) => value,
+        _ => "",
+    }
+}
+
+fn get_bytes(message: &DynamicMessage, tag: u32) -> &[u8] {
+    match message.get_by_tag(tag) {
+        Some(Value::Bytes(value)) => value,
+        _ => &[],
+    }
+}
+
+fn print_message(message: &DynamicMessage, out: &mut String) -> Result<(), JsonError> {
+    let name = &message.descriptor().name[..];
+    match name {
+        ANY => print_any(message, out),
+        TIMESTAMP => {
+            let timestamp = format_timestamp(get_i64(message, 1), get_i32(message, 2))?;
+            print_string(&timestamp, out);
+            Ok(())
+        }
+        DURATION => {
+            let duration = format_duration(get_i64(message, 1), get_i32(message, 2))?;
+            print_string(&duration, out);
+            Ok(())
+        }
+        FIELD_MASK => {
+            let mut paths = Vec::new();
+            if let Some(Value::List(values)) = message.get_by_tag(1) {
+                for value in values {
+                    if let Value::String(ref path) = *value {
+                        paths.push(format_path(path)?);
+                    }
+                }
+            }
+            print_string(&paths.join(","), out);
+            Ok(())
+        }
+        STRUCT => match message.get_by_tag(1) {
+            Some(value) => print_value(message.pool(), &field(message, 1)?.kind, value, out),
+            None => {
+                out.push_str("{}");
+                Ok(())
+            }
+        },
+        LIST_VALUE => match message.get_by_tag(1) {
+            Some(value) => print_value(message.pool(), &field(message, 1)?.kind, value, out),
+            None => {
+                out.push_str("[]");
+                Ok(())
+            }
+        },
+        VALUE => match message.fields().next() {
+            Some((field, &Value::F64(number))) if field.tag == 2 => {
+                if !number.is_finite() {
+                    return Err(JsonError::new(format!(
+                        "invalid google.protobuf.Value number: {}",
+                        number
+                    )));
+                }
+                print_value(message.pool(), &field.kind, &Value::F64(number), out)
+            }
+            Some((field, value)) => print_value(message.pool(), &field.kind, value, out),
+            None => Err(JsonError::new("google.protobuf.Value has no value")),
+        },
+        _ if WRAPPERS.contains(&name) => {
+            let field = field(message, 1)?;
+            match (message.get_by_tag(1), &field.kind) {
+                (Some(value), kind) => print_value(message.pool(), kind, value, out),
+                (None, &Kind::Scalar(ty)) => {
+                    print_value(message.pool(), &field.kind, &Value::default_scalar(ty), out)
+                }
+                (None, _) => Err(JsonError::new(format!("invalid wrapper type: {}", name))),
+            }
+        }
+        _ => {
+            out.push('{');
+            print_fields(message, true, out)?;
+            out.push('}');
+            Ok(())
+        }
+    }
+}
+
+/// Prints the set fields of the message as JSON object members. Fields without presence are
+/// omitted if they have the default value, unless they are `write_empty` fields.
+fn print_fields(
+    message: &DynamicMessage,
+    mut first: bool,
+    out: &mut String,
+) -> Result<(), JsonError> {
+    for field in &message.descriptor().fields {
+        let value = match message.get_by_tag(field.tag) {
+            Some(value) => value,
+            None => continue,
+        };
+        let presence = field.oneof.is_some()
+            || field.label == Label::Optional
+            || field.label == Label::Required
+            || field.write_empty;
+        if !presence && value.is_default() {
+            continue;
+        }
+        if !first {
+            out.push(',');
+        }
+        first = false;
+        print_string(&field.json_name, out);
+        out.push(':');
+        print_value(message.pool(), &field.kind, value, out)?;
+    }
+    Ok(())
+}
+
+fn print_any(message: &DynamicMessage, out: &mut String) -> Result<(), JsonError> {
+    let type_url = get_str(message, 1);
+    let value = get_bytes(message, 2);
+    if type_url.is_empty() {
+        if !value.is_empty() {
+            return Err(JsonError::new(
+                "google.protobuf.Any has a value but no type URL",
+            ));
+        }
+        out.push_str("{}");
+        return Ok(());
+    }
+
+    let name = type_url.rsplit('/').next().unwrap_or(type_url);
+    let packed = message
+        .pool()
+        .decode(name, value)
+        .map_err(|error| JsonError::new(error.to_string()))?;
+
+    out.push('{');
+    print_string("@type", out);
+    out.push(':');
+    print_string(type_url, out);
+    if is_special(name) {
+        out.push(',');
+        print_string("value", out);
+        out.push(':');
+        print_message(&packed, out)?;
+    } else {
+        print_fields(&packed, false, out)?;
+    }
+    out.push('}');
+    Ok(())
+}
+
+/// Prints a field value. `kind` is the type of the field, or of the elements of repeated fields.
+fn print_value(
+    pool: &DescriptorPool,
+    kind: &Kind,
+    value: &Value,
+    out: &mut String,
+) -> Result<(), JsonError> {
+    match *value {
+        Value::Bool(value) => out.push_str(if value { "true" } else { "false" }),
+        Value::I32(value) => write!(out, "{}", value).unwrap(),
+        Value::U32(value) => write!(out, "{}", value).unwrap(),
+        // 64-bit integers are quoted, since they can't be represented exactly as JSON numbers by
+        // many implementations.
+        Value::I64(value) => write!(out, "\"{}\"", value).unwrap(),
+        Value::U64(value) => write!(out, "\"{}\"", value).unwrap(),
+        Value::F32(value) => {
+            if value.is_finite() {
+                write!(out, "{}", value).unwrap();
+            } else {
+                print_string(&format_non_finite(f64::from(value)), out);
+            }
+        }
+        Value::F64(value) => {
+            if value.is_finite() {
+                write!(out, "{}", value).unwrap();
+            } else {
+                print_string(&format_non_finite(value), out);
+            }
+        }
+        Value::String(ref value) => print_string(value, out),
+        Value::Bytes(ref value) => print_string(&base64_encode(value), out),
+        Value::Enum(number) => {
+            let name = match *kind {
+                Kind::Enumeration(ref name) => name,
+                _ => {
+                    return Err(JsonError::new(
+                        "enumeration value of a non-enumeration field",
+                    ))
+                }
+            };
+            if name == NULL_VALUE {
+                out.push_str("null");
+            } else {
+                match pool.enumeration(name).and_then(|ty| ty.value_name(number)) {
+                    Some(value_name) => print_string(value_name, out),
+                    None => write!(out, "{}", number).unwrap(),
+                }
+            }
+        }
+        Value::Message(ref message) => print_message(message, out)?,
+        Value::List(ref values) => {
+            out.push('[');
+            for (i, value) in values.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                print_value(pool, kind, value, out)?;
+            }
+            out.push(']');
+        }
+        Value::Map(ref entries) => {
+            let value_kind = match *kind {
+                Kind::Map(_, ref value_kind) => value_kind,
+                _ => return Err(JsonError::new("map value of a non-map field")),
+            };
+            out.push('{');
+            for (i, (key, value)) in entries.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                let key = match *key {
+                    MapKey::Bool(key) => key.to_string(),
+                    MapKey::I32(key) => key.to_string(),
+                    MapKey::I64(key) => key.to_string(),
+                    MapKey::U32(key) => key.to_string(),
+                    MapKey::U64(key) => key.to_string(),
+                    MapKey::String(ref key) => key.clone(),
+                };
+                print_string(&key, out);
+                out.push(':');
+                print_value(pool, value_kind, value, out)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn format_non_finite(value: f64) -> String {
+    if value.is_nan() {
+        "NaN".to_string()
+    } else if value > 0.0 {
+        "Infinity".to_string()
+    } else {
+        "-Infinity".to_string()
+    }
+}
+
+fn print_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Formats the fractional seconds with 0, 3, 6 or 9 digits.
+fn format_nanos(nanos: i32, out: &mut String) {
+    if nanos == 0 {
+    } else if nanos % 1_000_000 == 0 {
+        write!(out, ".{:03}", nanos / 1_000_000).unwrap();
+    } else if nanos % 1_000 == 0 {
+        write!(out, ".{:06}", nanos / 1_000).unwrap();
+    } else {
+        write!(out, ".{:09}", nanos).unwrap();
+    }
+}
+
+/// Formats a `Timestamp` as an RFC 3339 date, in UTC.
+fn format_timestamp(seconds: i64, nanos: i32) -> Result<String, JsonError> {
+    if !(MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS).contains(&seconds)
+        || !(0..NANOS_PER_SECOND).contains(&nanos)
+    {
+        return Err(JsonError::new(format!(
+            "google.protobuf.Timestamp out of range: {}s {}ns",
+            seconds, nanos
+        )));
+    }
+    let days = seconds.div_euclid(86_400);
+    let time = seconds.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    let mut out = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    );
+    format_nanos(nanos, &mut out);
+    out.push('Z');
+    Ok(out)
+}
+
+/// Formats a `Duration` as a decimal number of seconds, suffixed with `s`.
+fn format_duration(seconds: i64, nanos: i32) -> Result<String, JsonError> {
+    if !(-MAX_DURATION_SECONDS..=MAX_DURATION_SECONDS).contains(&seconds)
+        || nanos.abs() >= NANOS_PER_SECOND
+        || (seconds < 0 && nanos > 0)
+        || (seconds > 0 && nanos < 0)
+    {
+        return Err(JsonError::new(format!(
+            "google.protobuf.Duration out of range: {}s {}ns",
+            seconds, nanos
+        )));
+    }
+    let mut out = String::new();
+    if seconds < 0 || nanos < 0 {
+        out.push('-');
+    }
+    write!(out, "{}", seconds.abs()).unwrap();
+    format_nanos(nanos.abs(), &mut out);
+    out.push('s');
+    Ok(out)
+}
+
+/// Converts a `snake_case` field mask path to `lowerCamelCase`.
+fn format_path(path: &str) -> Result<String, JsonError> {
+    let mut out = String::with_capacity(path.len());
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '_' => match chars.next() {
+                Some(next) if next.is_ascii_lowercase() => out.push(next.to_ascii_uppercase()),
+                _ => return Err(JsonError::new(format!("invalid field mask path: {}", path))),
+            },
+            c if c.is_ascii_uppercase() => {
+                return Err(JsonError::new(format!("invalid field mask path: {}", path)))
+            }
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// Converts days since the Unix epoch to a (year, month, day) date of the proleptic Gregorian
+/// calendar.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Converts a date of the proleptic Gregorian calendar to days since the Unix epoch.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn parse_message(
+    pool: &DescriptorPool,
+    name: &str,
+    json: &Json,
+) -> Result<DynamicMessage, JsonError> {
+    let mut message = pool
+        .new_message(name)
+        .ok_or_else(|| JsonError::new(format!("unknown message type: {}", name)))?;
+    match name {
+        ANY => parse_any(&mut message, json)?,
+        TIMESTAMP => {
+            let (seconds, nanos) = parse_timestamp(expect_string(json, name)?)?;
+            set(&mut message, 1, Value::I64(seconds))?;
+            set(&mut message, 2, Value::I32(nanos))?;
+        }
+        DURATION => {
+            let (seconds, nanos) = parse_duration(expect_string(json, name)?)?;
+            set(&mut message, 1, Value::I64(seconds))?;
+            set(&mut message, 2, Value::I32(nanos))?;
+        }
+        FIELD_MASK => {
+            let paths = expect_string(json, name)?;
+            let mut values = Vec::new();
+            if !paths.is_empty() {
+                for path in paths.split(',') {
+                    values.push(Value::String(parse_path(path)?));
+                }
+            }
+            set(&mut message, 1, Value::List(values))?;
+        }
+        STRUCT | LIST_VALUE => {
+            let field = field(&message, 1)?;
+            let value = parse_field_value(pool, &field, json)?;
+            set(&mut message, 1, value)?;
+        }
+        VALUE => {
+            let (tag, value) = match *json {
+                Json::Null => (1, Value::Enum(0)),
+                Json::Number(ref raw) => (2, Value::F64(parse_double(raw)?)),
+                Json::String(ref value) => (3, Value::String(value.clone())),
+                Json::Bool(value) => (4, Value::Bool(value)),
+                Json::Object(..) => (5, Value::Message(parse_message(pool, STRUCT, json)?)),
+                Json::Array(..) => (6, Value::Message(parse_message(pool, LIST_VALUE, json)?)),
+            };
+            set(&mut message, tag, value)?;
+        }
+        _ if WRAPPERS.contains(&name) => {
+            let field = field(&message, 1)?;
+            let value = parse_singular(pool, &field.kind, json)?;
+            set(&mut message, 1, value)?;
+        }
+        _ => match *json {
+            Json::Object(ref members) => parse_fields(&mut message, members)?,
+            _ => return Err(JsonError::new(format!("expected an object for {}", name))),
+        },
+    }
+    Ok(message)
+}
+
+fn parse_fields(message: &mut DynamicMessage, members: &[(String, Json)]) -> Result<(), JsonError> {
+    let ty = message.descriptor().clone();
+    let pool = message.pool().clone();
+    let mut fields = HashSet::new();
+    let mut oneofs = HashSet::new();
+    for (key, json) in members {
+        let field = ty
+            .field_by_json_name(key)
+            .or_else(|| ty.field_by_name(key))
+            .ok_or_else(|| JsonError::new(format!("unknown field of {}: {}", ty.name, key)))?;
+        if !fields.insert(field.tag) {
+            return Err(JsonError::new(format!("duplicate field: {}", key)));
+        }
+
+        // Null is the default value of a field, except for singular fields of the types which
+        // represent JSON null.
+        if let Json::Null = *json {
+            let repeated = field.label == Label::Repeated || field.label == Label::Packed;
+            if repeated || !accepts_null(&field.kind) {
+                continue;
+            }
+        }
+
+        if let Some(oneof) = field.oneof {
+            if !oneofs.insert(oneof) {
+                return Err(JsonError::new(format!(
+                    "multiple fields of oneof {}: {}",
+                    ty.oneofs[oneof], key
+                )));
+            }
+        }
+        let value = parse_field_value(&pool, field, json)?;
+        set(message, field.tag, value)?;
+    }
+    Ok(())
+}
+
+fn accepts_null(kind: &Kind) -> bool {
+    match *kind {
+        Kind::Enumeration(ref name) => name == NULL_VALUE,
+        Kind::Message(ref name) => name == VALUE,
+        _ => false,
+    }
+}
+
+fn parse_any(message: &mut DynamicMessage, json: &Json) -> Result<(), JsonError> {
+    let members = match *json {
+        Json::Object(ref members) => members,
+        _ => return Err(JsonError::new("expected an object for google.protobuf.Any")),
+    };
+    let type_url = match members.iter().find(|&(key, _)| key == "@type") {
+        Some(&(_, Json::String(ref type_url))) => type_url,
+        Some(_) => return Err(JsonError::new("invalid google.protobuf.Any @type")),
+        None if members.is_empty() => return Ok(()),
+        None => return Err(JsonError::new("google.protobuf.Any is missing @type")),
+    };
+
+    let name = type_url.rsplit('/').next().unwrap_or(type_url);
+    let pool = message.pool().clone();
+    let packed = if is_special(name) {
+        let mut value = None;
+        for (key, json) in members {
+            match &key[..] {
+                "@type" => (),
+                "value" => value = Some(json),
+                _ => return Err(JsonError::new(format!("unknown field of {}: {}", ANY, key))),
+            }
+        }
+        let value = value.ok_or_else(|| {
+            JsonError::new(format!("google.protobuf.Any of {} is missing value", name))
+        })?;
+        parse_message(&pool, name, value)?
+    } else {
+        let fields = members
+            .iter()
+            .filter(|&(key, _)| key != "@type")
+            .cloned()
+            .collect();
+        parse_message(&pool, name, &Json::Object(fields))?
+    };
+
+    let mut value = Vec::with_capacity(packed.encoded_len());
+    packed
+        .encode(&mut value)
+        .map_err(|error| JsonError::new(error.to_string()))?;
+    set(message, 1, Value::String(type_url.clone()))?;
+    set(message, 2, Value::Bytes(value))
+}
+
+/// Parses the value of a field, which is a list for repeated fields and a map for map fields.
+fn parse_field_value(
+    pool: &DescriptorPool,
+    field: &FieldType,
+    json: &Json,
+) -> Result<Value, JsonError> {
+    match field.kind {
+        Kind::Map(key_ty, ref value_kind) => {
+            let members = match *json {
+                Json::Object(ref members) => members,
+                _ => {
+                    return Err(JsonError::new(format!(
+                        "expected an object for {}",
+                        field.name
+                    )))
+                }
+            };
+            let mut entries = BTreeMap::new();
+            for (key, json) in members {
+                let key = parse_map_key(key_ty, key)?;
+                let value = parse_singular(pool, value_kind, json)?;
+                if entries.insert(key, value).is_some() {
+                    return Err(JsonError::new(format!(
+                        "duplicate map key of {}",
+                        field.name
+                    )));
+                }
+            }
+            Ok(Value::Map(entries))
+        }
+        ref kind if field.label == Label::Repeated || field.label == Label::Packed => {
+            let elements = match *json {
+                Json::Array(ref elements) => elements,
+                _ => {
+                    return Err(JsonError::new(format!(
+                        "expected an array for {}",
+                        field.name
+                    )))
+                }
+            };
+            elements
+                .iter()
+                .map(|json| parse_singular(pool, kind, json))
+                .collect::<Result<_, _>>()
+                .map(Value::List)
+        }
+        ref kind => parse_singular(pool, kind, json),
+    }
+}
+
+fn parse_map_key(ty: Type, key: &str) -> Result<MapKey, JsonError> {
+    let json = Json::String(key.to_string());
+    Ok(match ty {
+        Type::Bool => match key {
+            "true" => MapKey::Bool(true),
+            "false" => MapKey::Bool(false),
+            _ => return Err(JsonError::new(format!("invalid bool map key: {}", key))),
+        },
+        Type::String => MapKey::String(key.to_string()),
+        ty => match MapKey::from_value(parse_scalar(ty, &json)?) {
+            Some(key) => key,
+            None => return Err(JsonError::new(format!("invalid map key type: {}", ty))),
+        },
+    })
+}
+
+/// Parses a singular value, or an element of a repeated field.
+fn parse_singular(pool: &DescriptorPool, kind: &Kind, json: &Json) -> Result<Value, JsonError> {
+    match *kind {
+        Kind::Scalar(ty) => parse_scalar(ty, json),
+        Kind::Enumeration(ref name) => match *json {
+            Json::Null if name == NULL_VALUE => Ok(Value::Enum(0)),
+            Json::String(ref value_name) => pool
+                .enumeration(name)
+                .and_then(|ty| ty.value_number(value_name))
+                .map(Value::Enum)
+                .ok_or_else(|| {
+                    JsonError::new(format!("unknown value of {}: {}", name, value_name))
+                }),
+            Json::Number(..) => parse_integer(json, i64::from(i32::MIN), i64::from(i32::MAX))
+                .map(|number| Value::Enum(number as i32)),
+            _ => Err(JsonError::new(format!(
+                "expected an enumeration value of {}",
+                name
+            ))),
+        },
+        Kind::Message(ref name) | Kind::Group(ref name) => {
+            parse_message(pool, name, json).map(Value::Message)
+        }
+        Kind::Map(..) => Err(JsonError::new("nested map value")),
+    }
+}
+
+fn parse_scalar(ty: Type, json: &Json) -> Result<Value, JsonError> {
+    Ok(match ty {
+        Type::Int32 | Type::Sint32 | Type::Sfixed32 => {
+            Value::I32(parse_integer(json, i64::from(i32::MIN), i64::from(i32::MAX))? as i32)
+        }
+        Type::Int64 | Type::Sint64 | Type::Sfixed64 => {
+            Value::I64(parse_integer(json, i64::MIN, i64::MAX)?)
+        }
+        Type::Uint32 | Type::Fixed32 => {
+            Value::U32(parse_unsigned(json, u64::from(u32::MAX))? as u32)
+        }
+        Type::Uint64 | Type::Fixed64 => Value::U64(parse_unsigned(json, u64::MAX)?),
+        Type::Float => {
+            let value = parse_float(json)?;
+            if value.is_finite() && value.abs() > f64::from(f32::MAX) {
+                return Err(JsonError::new(format!("float out of range: {}", value)));
+            }
+            Value::F32(value as f32)
+        }
+        Type::Double => Value::F64(parse_float(json)?),
+        Type::Bool => match *json {
+            Json::Bool(value) => Value::Bool(value),
+            _ => return Err(JsonError::new("expected a bool")),
+        },
+        Type::String => match *json {
+            Json::String(ref value) => Value::String(value.clone()),
+            _ => return Err(JsonError::new("expected a string")),
+        },
+        Type::Bytes => match *json {
+            Json::String(ref value) => Value::Bytes(base64_decode(value)?),
+            _ => return Err(JsonError::new("expected a base64 string")),
+        },
+    })
+}
+
+/// Returns the number literal of a JSON number, or of a string containing a number.
+fn number_literal(json: &Json) -> Result<&str, JsonError> {
+    match *json {
+        Json::Number(ref raw) => Ok(raw),
+        Json::String(ref raw) if is_number(raw) => Ok(raw),
+        _ => Err(JsonError::new("expected a number")),
+    }
+}
+
+/// Parses an integer. Numbers with a fraction or an exponent are accepted if they are integral.
+fn parse_integer(json: &Json, min: i64, max: i64) -> Result<i64, JsonError> {
+    let raw = number_literal(json)?;
+    let value = if raw.contains(&['.', 'e', 'E'][..]) {
+        let value = parse_double(raw)?;
+        if value.fract() != 0.0 || value < min as f64 || value > max as f64 {
+            return Err(JsonError::new(format!("invalid integer: {}", raw)));
+        }
+        value as i64
+    } else {
+        raw.parse::<i64>()
+            .map_err(|_| JsonError::new(format!("integer out of range: {}", raw)))?
+    };
+    if value < min || value > max {
+        return Err(JsonError::new(format!("integer out of range: {}", raw)));
+    }
+    Ok(value)
+}
+
+fn parse_unsigned(json: &Json, max: u64) -> Result<u64, JsonError> {
+    let raw = number_literal(json)?;
+    let value = if raw.contains(&['.', 'e', 'E'][..]) {
+        let value = parse_double(raw)?;
+        if value.fract() != 0.0 || value < 0.0 || value > max as f64 {
+            return Err(JsonError::new(format!("invalid integer: {}", raw)));
+        }
+        value as u64
+    } else if raw == "-0" {
+        0
+    } else {
+        raw.parse::<u64>()
+            .map_err(|_| JsonError::new(format!("integer out of range: {}", raw)))?
+    };
+    if value > max {
+        return Err(JsonError::new(format!("integer out of range: {}", raw)));
+    }
+    Ok(value)
+}
+
+fn parse_float(json: &Json) -> Result<f64, JsonError> {
+    match *json {
+        Json::String(ref value) if value == "NaN" => Ok(f64::NAN),
+        Json::String(ref value) if value == "Infinity" => Ok(f64::INFINITY),
+        Json::String(ref value) if value == "-Infinity" => Ok(f64::NEG_INFINITY),
+        ref json => parse_double(number_literal(json)?),
+    }
+}
+
+/// Parses a finite double from a number literal.
+fn parse_double(raw: &str) -> Result<f64, JsonError> {
+    match raw.parse::<f64>() {
+        Ok(value) if value.is_finite() => Ok(value),
+        _ => Err(JsonError::new(format!("number out of range: {}", raw))),
+    }
+}
+
+fn expect_string<'a>(json: &'a Json, name: &str) -> Result<&'a str, JsonError> {
+    match *json {
+        Json::String(ref value) => Ok(value),
+        _ => Err(JsonError::new(format!("expected a string for {}", name))),
+    }
+}
+
+/// Converts a `lowerCamelCase` field mask path to `snake_case`.
+fn parse_path(path: &str) -> Result<String, JsonError> {
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c == '_' {
+            return Err(JsonError::new(format!("invalid field mask path: {}", path)));
+        } else if c.is_ascii_uppercase() {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    Ok(out)
+}
+
+/// Parses `len` ASCII digits at the start of `s`.
+fn parse_digits(s: &[u8], len: usize) -> Option<i64> {
+    if s.len() < len || !s[..len].iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+    Some(
+        s[..len]
+            .iter()
+            .fold(0, |value, &digit| value * 10 + i64::from(digit - b'0')),
+    )
+}
+
+/// Parses up to 9 fractional digits following a `.` at the start of `s`, returning the
+/// nanoseconds and the remainder of `s`.
+fn parse_fraction(s: &[u8]) -> Option<(i32, &[u8])> {
+    if s.first() != Some(&b'.') {
+        return Some((0, s));
+    }
+    let s = &s[1..];
+    let len = s.iter().take_while(|b| b.is_ascii_digit()).count();
+    if len == 0 || len > 9 {
+        return None;
+    }
+    let nanos = parse_digits(s, len)? * 10i64.pow(9 - len as u32);
+    Some((nanos as i32, &s[len..]))
+}
+
+/// Parses an RFC 3339 date, such as `1972-01-01T10:00:20.021-05:00`.
+fn parse_timestamp(value: &str) -> Result<(i64, i32), JsonError> {
+    let invalid = || JsonError::new(format!("invalid google.protobuf.Timestamp: {}", value));
+    let s = value.as_bytes();
+    if s.len() < 20
+        || s[4] != b'-'
+        || s[7] != b'-'
+        || s[10] != b'T'
+        || s[13] != b':'
+        || s[16] != b':'
+    {
+        return Err(invalid());
+    }
+    let year = parse_digits(s, 4).ok_or_else(invalid)?;
+    let month = parse_digits(&s[5..], 2).ok_or_else(invalid)?;
+    let day = parse_digits(&s[8..], 2).ok_or_else(invalid)?;
+    let hour = parse_digits(&s[11..], 2).ok_or_else(invalid)?;
+    let minute = parse_digits(&s[14..], 2).ok_or_else(invalid)?;
+    let second = parse_digits(&s[17..], 2).ok_or_else(invalid)?;
+    let (nanos, rest) = parse_fraction(&s[19..]).ok_or_else(invalid)?;
+
+    let offset = match rest {
+        b"Z" => 0,
+        _ if rest.len() == 6 && (rest[0] == b'+' || rest[0] == b'-') && rest[3] == b':' => {
+            let hours = parse_digits(&rest[1..], 2).ok_or_else(invalid)?;
+            let minutes = parse_digits(&rest[4..], 2).ok_or_else(invalid)?;
+            if hours > 23 || minutes > 59 {
+                return Err(invalid());
+            }
+            let offset = hours * 3600 + minutes * 60;
+            if rest[0] == b'-' {
+                -offset
+            } else {
+                offset
+            }
+        }
+        _ => return Err(invalid()),
+    };
+
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return Err(invalid());
+    }
+
+    let seconds =
+        days_from_civil(year, month, day) * 86_400 + hour * 3600 + minute * 60 + second - offset;
+    if !(MIN_TIMESTAMP_SECONDS..=MAX_TIMESTAMP_SECONDS).contains(&seconds) {
+        return Err(invalid());
+    }
+    Ok((seconds, nanos))
+}
+
+/// Parses a duration, such as `-1.5s`.
+fn parse_duration(value: &str) -> Result<(i64, i32), JsonError> {
+    let invalid = || JsonError::new(format!("invalid google.protobuf.Duration: {}", value));
+    let s = value.as_bytes();
+    let (negative, s) = match s.first() {
+        Some(&b'-') => (true, &s[1..]),
+        _ => (false, s),
+    };
+    let len = s.iter().take_while(|b| b.is_ascii_digit()).count();
+    if len == 0 || len > 12 {
+        return Err(invalid());
+    }
+    let seconds = parse_digits(s, len).ok_or_else(invalid)?;
+    let (nanos, rest) = parse_fraction(&s[len..]).ok_or_else(invalid)?;
+    if rest != b"s" || seconds > MAX_DURATION_SECONDS {
+        return Err(invalid());
+    }
+    if negative {
+        Ok((-seconds, -nanos))
+    } else {
+        Ok((seconds, nanos))
+    }
+}
+
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes with the standard base64 alphabet, with padding.
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).cloned().unwrap_or(0),
+            chunk.get(2).cloned().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Decodes base64 with either the standard or the URL safe alphabet, with or without padding.
+fn base64_decode(value: &str) -> Result<Vec<u8>, JsonError> {
+    let invalid = || JsonError::new(format!("invalid base64: {}", value));
+    let unpadded = value.trim_end_matches('=');
+    let padding = value.len() - unpadded.len();
+    if padding > 2 || (padding > 0 && !value.len().is_multiple_of(4)) || unpadded.len() % 4 == 1 {
+        return Err(invalid());
+    }
+    let mut out = Vec::with_capacity(unpadded.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut len = 0;
+    for b in unpadded.bytes() {
+        let sextet = match b {
+            b'A'..=b'Z' => b - b'A',
+            b'a'..=b'z' => b - b'a' + 26,
+            b'0'..=b'9' => b - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return Err(invalid()),
+        };
+        bits = (bits << 6) | u32::from(sextet);
+        len += 6;
+        if len >= 8 {
+            len -= 8;
+            out.push((bits >> len) as u8);
+        }
+    }
+    Ok(out)
+}
+
+/// Returns `true` if the string is a JSON number literal.
+fn is_number(s: &str) -> bool {
+    let mut parser = Parser::new(s);
+    parser.number().is_ok() && parser.pos == s.len()
+}
+
+/// A parsed JSON value. Numbers are kept as literals, so that integers are not rounded, and
+/// object members are kept in order, so that duplicates can be detected.
+#[derive(Clone, Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(String, Json)>),
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Parser<'a> {
+        Parser {
+            input,
+            pos: 0,
+            depth: 0,
+        }
+    }
+
+    fn error(&self, description: &str) -> JsonError {
+        JsonError::new(format!("{} at offset {}", description, self.pos))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.as_bytes().get(self.pos).cloned()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, b: u8) -> Result<(), JsonError> {
+        if self.peek() != Some(b) {
+            return Err(self.error(&format!("expected '{}'", b as char)));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    /// Parses a complete JSON document.
+    fn parse(mut self) -> Result<Json, JsonError> {
+        let value = self.value()?;
+        self.skip_whitespace();
+        if self.pos != self.input.len() {
+            return Err(self.error("trailing characters"));
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Json, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'{') => self.object(),
+            Some(b'[') => self.array(),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b't') => self.literal("true", Json::Bool(true)),
+            Some(b'f') => self.literal("false", Json::Bool(false)),
+            Some(b'n') => self.literal("null", Json::Null),
+            Some(b'-') | Some(b'0'..=b'9') => {
+                let start = self.pos;
+                self.number()?;
+                Ok(Json::Number(self.input[start..self.pos].to_string()))
+            }
+            _ => Err(self.error("expected a value")),
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: Json) -> Result<Json, JsonError> {
+        if !self.input[self.pos..].starts_with(literal) {
+            return Err(self.error("invalid literal"));
+        }
+        self.pos += literal.len();
+        Ok(value)
+    }
+
+    fn enter(&mut self) -> Result<(), JsonError> {
+        self.depth += 1;
+        if self.depth > RECURSION_LIMIT {
+            return Err(self.error("recursion limit reached"));
+        }
+        Ok(())
+    }
+
+    fn object(&mut self) -> Result<Json, JsonError> {
+        self.enter()?;
+        self.expect(b'{')?;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b'}') {
+            self.pos += 1;
+        } else {
+            loop {
+                self.skip_whitespace();
+                let key = self.string()?;
+                self.skip_whitespace();
+                self.expect(b':')?;
+                let value = self.value()?;
+                members.push((key, value));
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b'}') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(self.error("expected ',' or '}'")),
+                }
+            }
+        }
+        self.depth -= 1;
+        Ok(Json::Object(members))
+    }
+
+    fn array(&mut self) -> Result<Json, JsonError> {
+        self.enter()?;
+        self.expect(b'[')?;
+        let mut elements = Vec::new();
+        self.skip_whitespace();
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+        } else {
+            loop {
+                elements.push(self.value()?);
+                self.skip_whitespace();
+                match self.peek() {
+                    Some(b',') => self.pos += 1,
+                    Some(b']') => {
+                        self.pos += 1;
+                        break;
+                    }
+                    _ => return Err(self.error("expected ',' or ']'")),
+                }
+            }
+        }
+        self.depth -= 1;
+        Ok(Json::Array(elements))
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect(b'"')?;
+        let mut out = String::new();
+        loop {
+            let start = self.pos;
+            while let Some(b) = self.peek() {
+                if b == b'"' || b == b'\\' || b < 0x20 {
+                    break;
+                }
+                self.pos += 1;
+            }
+            // The string is only split at ASCII characters, so the slice is valid UTF-8.
+            out.push_str(&self.input[start..self.pos]);
+            match self.peek() {
+                Some(b'"') => {
+                    self.pos += 1;
+                    return Ok(out);
+                }
+                Some(b'\\') => {
+                    self.pos += 1;
+                    let c = self.escape()?;
+                    out.push(c);
+                }
+                Some(_) => return Err(self.error("control character in string")),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, JsonError> {
+        let b = self
+            .peek()
+            .ok_or_else(|| self.error("unterminated string"))?;
+        self.pos += 1;
+        Ok(match b {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.hex4()?;
+                if (0xD800..0xDC00).contains(&high) {
+                    if !self.input[self.pos..].starts_with("\\u") {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    self.pos += 2;
+                    let low = self.hex4()?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(self.error("unpaired surrogate"));
+                    }
+                    let c = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+                    return ::std::char::from_u32(c).ok_or_else(|| self.error("invalid escape"));
+                }
+                return ::std::char::from_u32(high).ok_or_else(|| self.error("unpaired surrogate"));
+            }
+            _ => return Err(self.error("invalid escape")),
+        })
+    }
+
+    fn hex4(&mut self) -> Result<u32, JsonError> {
+        let digits = self
+            .input
+            .get(self.pos..self.pos + 4)
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        let value =
+            u32::from_str_radix(digits, 16).map_err(|_| self.error("invalid unicode escape"))?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(self.error("invalid unicode escape"));
+        }
+        self.pos += 4;
+        Ok(value)
+    }
+
+    /// Consumes a number literal: `-?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?`.
+    fn number(&mut self) -> Result<(), JsonError> {
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+        match self.peek() {
+            Some(b'0') => self.pos += 1,
+            Some(b'1'..=b'9') => self.digits(),
+            _ => return Err(self.error("invalid number")),
+        }
+        if self.peek() == Some(b'.') {
+            self.pos += 1;
+            if !self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                return Err(self.error("invalid number"));
+            }
+            self.digits();
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            self.pos += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.pos += 1;
+            }
+            if !self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                return Err(self.error("invalid number"));
+            }
+            self.digits();
+        }
+        Ok(())
+    }
+
+    fn digits(&mut self) {
+        while self.peek().is_some_and(|b| b.is_ascii_digit()) {
+            self.pos += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_json() {
+        assert_eq!(
+            Parser::new(r#" {"a": [1, -2.5e3, true, null], "b\n\u00e9\ud83d\ude00": {}} "#).parse(),
+            Ok(Json::Object(vec![
+                (
+                    "a".to_string(),
+                    Json::Array(vec![
+                        Json::Number("1".to_string()),
+                        Json::Number("-2.5e3".to_string()),
+                        Json::Bool(true),
+                        Json::Null,
+                    ])
+                ),
+                ("b\n\u{e9}\u{1F600}".to_string(), Json::Object(Vec::new())),
+            ]))
+        );
+
+        for invalid in &[
+            "",
+            "01",
+            "1.",
+            "-",
+            "+1",
+            "[1,]",
+            "{\"a\" 1}",
+            "\"\\ud83d\"",
+            "\"\t\"",
+            "nul",
+            "[] []",
+        ] {
+            assert!(Parser::new(invalid).parse().is_err(), "{}", invalid);
+        }
+
+        let nested = "[".repeat(RECURSION_LIMIT) + &"]".repeat(RECURSION_LIMIT);
+        assert!(Parser::new(&nested).parse().is_ok());
+        let nested = "[".repeat(RECURSION_LIMIT + 1) + &"]".repeat(RECURSION_LIMIT + 1);
+        assert!(Parser::new(&nested).parse().is_err());
+    }
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(format_timestamp(0, 0).unwrap(), "1970-01-01T00:00:00Z");
+        assert_eq!(
+            format_timestamp(1_553_106_310, 10_000_000).unwrap(),
+            "2019-03-20T18:25:10.010Z"
+        );
+        assert_eq!(
+            format_timestamp(MIN_TIMESTAMP_SECONDS, 1000).unwrap(),
+            "0001-01-01T00:00:00.000001Z"
+        );
+        assert_eq!(
+            format_timestamp(MAX_TIMESTAMP_SECONDS, 999_999_999).unwrap(),
+            "9999-12-31T23:59:59.999999999Z"
+        );
+        assert!(format_timestamp(MAX_TIMESTAMP_SECONDS + 1, 0).is_err());
+        assert!(format_timestamp(0, -1).is_err());
+
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Ok((0, 0)));
+        assert_eq!(
+            parse_timestamp("2019-03-20T13:25:10.01-05:00"),
+            Ok((1_553_106_310, 10_000_000))
+        );
+        assert_eq!(
+            parse_timestamp("2000-02-29T00:00:00.000000001+01:30"),
+            Ok((951_782_400 - 5400, 1))
+        );
+        for invalid in &[
+            "1970-01-01t00:00:00Z",
+            "1970-01-01T00:00:00z",
+            "1970-01-01T00:00:00",
+            "1970-01-01T00:00:00.Z",
+            "1970-01-01T00:00:00.0000000001Z",
+            "1970-13-01T00:00:00Z",
+            "2001-02-29T00:00:00Z",
+            "0000-12-31T23:59:59Z",
+        ] {
+            assert!(parse_timestamp(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(format_duration(0, 0).unwrap(), "0s");
+        assert_eq!(format_duration(1, 500_000_000).unwrap(), "1.500s");
+        assert_eq!(format_duration(0, -1000).unwrap(), "-0.000001s");
+        assert_eq!(format_duration(-3, -1).unwrap(), "-3.000000001s");
+        assert!(format_duration(1, -1).is_err());
+        assert!(format_duration(MAX_DURATION_SECONDS + 1, 0).is_err());
+
+        assert_eq!(parse_duration("1.5s"), Ok((1, 500_000_000)));
+        assert_eq!(parse_duration("-0.5s"), Ok((0, -500_000_000)));
+        assert_eq!(
+            parse_duration("315576000000s"),
+            Ok((MAX_DURATION_SECONDS, 0))
+        );
+        for invalid in &["1", "s", "1.s", "+1s", "315576000001s", "1.0000000001s"] {
+            assert!(parse_duration(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn field_mask_paths() {
+        assert_eq!(format_path("foo_bar.baz").unwrap(), "fooBar.baz");
+        assert!(format_path("fooBar").is_err());
+        assert!(format_path("foo_3").is_err());
+        assert_eq!(parse_path("fooBar.baz").unwrap(), "foo_bar.baz");
+        assert!(parse_path("foo_bar").is_err());
+    }
+
+    #[test]
+    fn base64() {
+        for &(bytes, encoded) in &[
+            (&b""[..], ""),
+            (&b"f"[..], "Zg=="),
+            (&b"fo"[..], "Zm8="),
+            (&b"foo"[..], "Zm9v"),
+            (&[0xFB, 0xFF][..], "+/8="),
+        ] {
+            assert_eq!(base64_encode(bytes), encoded);
+            assert_eq!(base64_decode(encoded).unwrap(), bytes);
+        }
+        assert_eq!(base64_decode("-_8").unwrap(), [0xFB, 0xFF]);
+        assert!(base64_decode("Zg=").is_err());
+        assert!(base64_decode("Z").is_err());
+        assert!(base64_decode("Z*==").is_err());
+    }
+
+    #[test]
+    fn integers() {
+        let number = |raw: &str| Json::Number(raw.to_string());
+        let string = |raw: &str| Json::String(raw.to_string());
+        assert_eq!(parse_integer(&number("-5"), -10, 10), Ok(-5));
+        assert_eq!(parse_integer(&string("1e1"), -10, 10), Ok(10));
+        assert!(parse_integer(&number("11"), -10, 10).is_err());
+        assert!(parse_integer(&number("1.5"), -10, 10).is_err());
+        assert!(parse_integer(&string(" 1"), -10, 10).is_err());
+        assert_eq!(
+            parse_unsigned(&string("18446744073709551615"), u64::MAX),
+            Ok(u64::MAX)
+        );
+        assert!(parse_unsigned(&number("-1"), u64::MAX).is_err());
+        assert!(parse_float(&string("1e309")).is_err());
+        assert!(parse_float(&string("NaN")).unwrap().is_nan());
+    }
+}
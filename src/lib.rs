@@ -1,6 +1,7 @@
 #![doc(html_root_url = "https://docs.rs/prost_amino/0.5.0")]
 
 pub extern crate bytes;
+extern crate sha2;
 
 #[cfg(feature = "prost-derive")]
 #[doc(hidden)]
@@ -10,15 +11,24 @@ pub use bytes;
 #[macro_use]
 extern crate quickcheck;
 
+pub mod amino;
+pub mod dynamic;
 pub mod error;
+pub mod extension;
+pub mod json;
 mod message;
+pub mod proxy;
+pub mod reflect;
+pub mod schema;
+pub mod stream;
+pub mod text_format;
 mod types;
 
 #[doc(hidden)]
 pub mod encoding;
 
-pub use error::{DecodeError, EncodeError};
-pub use message::Message;
+pub use error::{BuildError, DecodeError, EncodeError};
+pub use message::{DynMessage, Message};
 
 use bytes::{Buf, BufMut};
 
@@ -1,12 +1,14 @@
+use std::any::{self, Any};
 use std::fmt::Debug;
 use std::usize;
 
 use bytes::{Buf, BufMut};
 
+use reflect::MessageDescriptor;
 use DecodeError;
 use EncodeError;
 
-use crate::encoding::{encode_varint, encoded_len_varint, message, WireType};
+use crate::encoding::{copy_nested_field, encode_varint, encoded_len_varint, message, WireType};
 /// A Protocol Buffers message.
 pub trait Message: Debug + Send + Sync {
     /// Encodes the message to a buffer.
@@ -29,6 +31,31 @@ pub trait Message: Debug + Send + Sync {
         B: Buf,
         Self: Sized;
 
+    /// Decodes the value of a field with the given tag and wire type from a buffer, and merges it
+    /// into `self`.
+    ///
+    /// This is used to merge the fields of a group, which are read up to the end group key of the
+    /// group. `depth` is the number of groups the field is nested in. The default implementation
+    /// copies the field, and merges it with `merge_field`.
+    ///
+    /// Meant to be used only by `Message` implementations.
+    #[doc(hidden)]
+    fn merge_tagged_field<B>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        depth: u32,
+    ) -> Result<(), DecodeError>
+    where
+        B: Buf,
+        Self: Sized,
+    {
+        let mut field = Vec::new();
+        copy_nested_field(wire_type, tag, buf, &mut field, depth)?;
+        self.merge_field(&mut &field[..])
+    }
+
     /// Returns the encoded length of the message without a length delimiter.
     fn encoded_len(&self) -> usize;
 
@@ -119,6 +146,17 @@ pub trait Message: Debug + Send + Sync {
 
     /// Clears the message, resetting all fields to their default.
     fn clear(&mut self);
+
+    /// Returns the reflection descriptor of the message type, if it has one.
+    ///
+    /// Derived messages return their `Reflect` descriptor. Hand-written implementations without
+    /// a descriptor keep the default, which returns `None`.
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor>
+    where
+        Self: Sized,
+    {
+        None
+    }
 }
 
 impl<M> Message for Box<M>
@@ -137,10 +175,143 @@ where
     {
         (**self).merge_field(buf)
     }
+    fn merge_tagged_field<B>(
+        &mut self,
+        tag: u32,
+        wire_type: WireType,
+        buf: &mut B,
+        depth: u32,
+    ) -> Result<(), DecodeError>
+    where
+        B: Buf,
+    {
+        (**self).merge_tagged_field(tag, wire_type, buf, depth)
+    }
     fn encoded_len(&self) -> usize {
         (**self).encoded_len()
     }
     fn clear(&mut self) {
         (**self).clear()
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        M::reflect_descriptor()
+    }
+}
+
+/// An object-safe counterpart of `Message`, for holding messages of different types behind trait
+/// objects, e.g. in a `Vec<Box<dyn DynMessage>>`.
+///
+/// Implemented for every message type.
+pub trait DynMessage: Debug + Send + Sync {
+    /// Encodes the message to a new buffer.
+    fn encode_to_vec(&self) -> Vec<u8>;
+
+    /// Decodes an instance of the message from a slice, and merges it into `self`.
+    ///
+    /// The entire slice will be consumed.
+    fn merge_from_slice(&mut self, buf: &[u8]) -> Result<(), DecodeError>;
+
+    /// Returns the fully qualified protobuf name of the message type, or the name of the Rust type
+    /// if the message has no reflection descriptor.
+    fn type_name(&self) -> &'static str;
+
+    /// Returns the amino name of the message type, if it's registered.
+    fn amino_name(&self) -> Option<&'static str>;
+
+    /// Returns the message as `Any`, to downcast it to its concrete type.
+    fn as_any(&self) -> &dyn Any;
+
+    /// Returns the message as a mutable `Any`, to downcast it to its concrete type.
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<M> DynMessage for M
+where
+    M: Message + Debug + Send + Sync + 'static,
+{
+    fn encode_to_vec(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.encoded_len());
+        self.encode(&mut buf)
+            .expect("encoding to a vector can not fail, it grows as needed");
+        buf
+    }
+
+    fn merge_from_slice(&mut self, buf: &[u8]) -> Result<(), DecodeError> {
+        self.merge(buf)
+    }
+
+    fn type_name(&self) -> &'static str {
+        M::reflect_descriptor().map_or_else(any::type_name::<M>, |descriptor| descriptor.full_name)
+    }
+
+    fn amino_name(&self) -> Option<&'static str> {
+        M::reflect_descriptor().and_then(|descriptor| descriptor.amino_name)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use encoding::{decode_key, skip_field};
+
+    #[test]
+    fn dyn_message() {
+        let mut messages: Vec<Box<dyn DynMessage>> = vec![Box::new(true), Box::new(42u32)];
+        assert_eq!(
+            messages.iter().map(|m| m.type_name()).collect::<Vec<_>>(),
+            ["google.protobuf.BoolValue", "google.protobuf.UInt32Value"]
+        );
+        assert_eq!(messages[0].amino_name(), None);
+
+        let buf = messages[1].encode_to_vec();
+        assert_eq!(buf, [0x08, 0x2A]);
+        messages[0].merge_from_slice(&[0x08, 0x00]).unwrap();
+        assert_eq!(messages[0].as_any().downcast_ref::<bool>(), Some(&false));
+
+        *messages[1].as_any_mut().downcast_mut::<u32>().unwrap() = 7;
+        assert_eq!(messages[1].encode_to_vec(), [0x08, 0x07]);
+        assert!(messages[1].merge_from_slice(&[0x08]).is_err());
+    }
+
+    /// A hand-written message without a reflection descriptor.
+    #[derive(Debug, Default)]
+    struct Unreflected;
+
+    impl Message for Unreflected {
+        fn encode_raw<B>(&self, _buf: &mut B)
+        where
+            B: BufMut,
+        {
+        }
+        fn merge_field<B>(&mut self, buf: &mut B) -> Result<(), DecodeError>
+        where
+            B: Buf,
+        {
+            let (tag, wire_type) = decode_key(buf)?;
+            skip_field(wire_type, tag, buf)
+        }
+        fn encoded_len(&self) -> usize {
+            0
+        }
+        fn clear(&mut self) {}
+    }
+
+    #[test]
+    fn dyn_message_without_descriptor() {
+        let mut message: Box<dyn DynMessage> = Box::new(Unreflected);
+        assert_eq!(message.type_name(), any::type_name::<Unreflected>());
+        assert_eq!(message.amino_name(), None);
+        assert!(message.encode_to_vec().is_empty());
+        message.merge_from_slice(&[0x08, 0x01]).unwrap();
+        assert!(message.as_any().is::<Unreflected>());
+    }
 }
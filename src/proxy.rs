@@ -0,0 +1,36 @@
+//! Field types encoded as proxy types.
+//!
+//! Fields with the `proxy` attribute, such as the fields generated for the `(gogoproto.stdtime)`
+//! and `(gogoproto.casttype)` options, have a Rust type which is converted to a proxy type when it
+//! is encoded, and back when it is decoded. Fields with a single value, which are neither optional
+//! nor repeated, default to the `Default` value of their type, or to the Unix epoch for
+//! `SystemTime`, rather than to a conversion of the default proxy value, which may be rejected.
+
+use DecodeError;
+
+/// Conversions between a field type and the proxy type `P` it is encoded as.
+///
+/// The trait is implemented for every type with `From` conversions from `P`, and to `P` from a
+/// borrow of the type. Types which can't represent every value of `P` implement it directly, and
+/// return an error for such values when they are decoded.
+pub trait Proxy<P>: Sized {
+    /// Converts the value to the proxy type, to encode it.
+    fn to_proxy(&self) -> P;
+
+    /// Converts a decoded proxy value back to the field type.
+    fn from_proxy(proxy: P) -> Result<Self, DecodeError>;
+}
+
+impl<T, P> Proxy<P> for T
+where
+    T: From<P>,
+    for<'a> P: From<&'a T>,
+{
+    fn to_proxy(&self) -> P {
+        P::from(self)
+    }
+
+    fn from_proxy(proxy: P) -> Result<T, DecodeError> {
+        Ok(T::from(proxy))
+    }
+}
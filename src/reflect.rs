@@ -0,0 +1,274 @@
+//! Static reflection descriptors for `Message` types.
+//!
+//! `#[derive(Message)]` implements `Reflect` for every message type, exposing a
+//! `MessageDescriptor` which describes the message fields as they were declared in the field
+//! attributes: names, tags, labels, field types, and amino registered names and prefixes.
+//! Descriptors are `'static`, and nested message, enumeration and oneof descriptors are linked
+//! through function pointers, so recursive message types are supported.
+//!
+//! `#[derive(Oneof)]` and `#[derive(Enumeration)]` generate an inherent `descriptor` function
+//! returning a `OneofDescriptor` and `EnumDescriptor` respectively.
+
+use std::fmt;
+
+use encoding::WireType;
+
+/// A message type with a static reflection descriptor.
+pub trait Reflect {
+    /// Returns the descriptor of the message type.
+    fn descriptor() -> &'static MessageDescriptor;
+}
+
+/// Describes a message type.
+#[derive(Debug)]
+pub struct MessageDescriptor {
+    /// The name of the message type.
+    pub name: &'static str,
+    /// The fully qualified protobuf name of the message type, e.g. `google.protobuf.Timestamp`.
+    /// Defaults to the name of the Rust type.
+    pub full_name: &'static str,
+    /// The amino name the type is registered with, if any.
+    pub amino_name: Option<&'static str>,
+    /// The amino prefix bytes of the registered type, if any.
+    pub prefix: Option<[u8; 4]>,
+    /// The message fields, in declaration order.
+    pub fields: &'static [FieldDescriptor],
+}
+
+impl MessageDescriptor {
+    /// Returns the field with the given name, if any.
+    ///
+    /// Oneof fields are matched by the name of the message field, not by the variant names.
+    pub fn field_by_name(&self, name: &str) -> Option<&'static FieldDescriptor> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Returns the field with the given tag, if any.
+    ///
+    /// If the tag belongs to a oneof field, the descriptor of the corresponding oneof variant is
+    /// returned.
+    pub fn field_by_tag(&self, tag: u32) -> Option<&'static FieldDescriptor> {
+        for field in self.fields {
+            if !field.tags.contains(&tag) {
+                continue;
+            }
+            return match field.kind {
+                FieldKind::Oneof(oneof) => oneof().field_by_tag(tag),
+                _ => Some(field),
+            };
+        }
+        None
+    }
+
+    /// Returns `true` if the message is an amino registered type.
+    pub fn is_registered(&self) -> bool {
+        self.amino_name.is_some()
+    }
+}
+
+/// Describes a message field, or a variant of a oneof field.
+#[derive(Debug)]
+pub struct FieldDescriptor {
+    /// The name of the field, or of the variant for oneof variants.
+    pub name: &'static str,
+    /// The name of the field in the protobuf schema, e.g. `type` for a `type_` field, or
+    /// `sha256_hash` for a `Sha256Hash` oneof variant.
+    pub proto_name: &'static str,
+    /// The name of the field in the proto3 JSON mapping, e.g. `fooBar` for `foo_bar`.
+    pub json_name: &'static str,
+    /// The field tags. Oneof fields have a tag per variant, all other fields have a single tag.
+    pub tags: &'static [u32],
+    /// The field label.
+    pub label: Label,
+    /// The field type.
+    pub kind: FieldKind,
+    /// The amino name of a registered field type, if any.
+    pub amino_name: Option<&'static str>,
+    /// The amino prefix bytes of a registered field type, if any.
+    pub prefix: Option<[u8; 4]>,
+    /// Whether a plain field is encoded even if it has the default value, like go-amino
+    /// `write_empty` fields.
+    pub write_empty: bool,
+}
+
+impl FieldDescriptor {
+    /// Returns the tag of the field.
+    ///
+    /// Panics if the field is a oneof field, since oneof fields have a tag per variant.
+    pub fn tag(&self) -> u32 {
+        match self.kind {
+            FieldKind::Oneof(..) => panic!("oneof field {} does not have a single tag", self.name),
+            _ => self.tags[0],
+        }
+    }
+
+    /// Returns `true` if the field is a repeated, packed or map field.
+    pub fn is_repeated(&self) -> bool {
+        self.label == Label::Repeated || self.label == Label::Packed
+    }
+}
+
+/// Field labels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Label {
+    /// A proto3 field without presence; default values are not encoded.
+    Plain,
+    /// An optional field.
+    Optional,
+    /// A required field.
+    Required,
+    /// A repeated field.
+    Repeated,
+    /// A packed repeated field.
+    Packed,
+}
+
+/// Field types.
+#[derive(Clone, Copy, Debug)]
+pub enum FieldKind {
+    /// A scalar field.
+    Scalar(Type),
+    /// An enumeration field, encoded as an `int32`.
+    Enumeration(fn() -> &'static EnumDescriptor),
+    /// A message field.
+    Message(fn() -> &'static MessageDescriptor),
+    /// A proto2 group field, with the descriptor of the group's message type.
+    Group(fn() -> &'static MessageDescriptor),
+    /// A map field. The value kind is never a map or a oneof.
+    Map {
+        key: Type,
+        value: &'static FieldKind,
+    },
+    /// A oneof field.
+    Oneof(fn() -> &'static OneofDescriptor),
+}
+
+/// Scalar field types.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    Double,
+    Float,
+    Int32,
+    Int64,
+    Uint32,
+    Uint64,
+    Sint32,
+    Sint64,
+    Fixed32,
+    Fixed64,
+    Sfixed32,
+    Sfixed64,
+    Bool,
+    String,
+    Bytes,
+}
+
+impl Type {
+    /// Returns the type as it appears in protobuf field declarations.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            Type::Double => "double",
+            Type::Float => "float",
+            Type::Int32 => "int32",
+            Type::Int64 => "int64",
+            Type::Uint32 => "uint32",
+            Type::Uint64 => "uint64",
+            Type::Sint32 => "sint32",
+            Type::Sint64 => "sint64",
+            Type::Fixed32 => "fixed32",
+            Type::Fixed64 => "fixed64",
+            Type::Sfixed32 => "sfixed32",
+            Type::Sfixed64 => "sfixed64",
+            Type::Bool => "bool",
+            Type::String => "string",
+            Type::Bytes => "bytes",
+        }
+    }
+
+    /// Returns the wire type used to encode a single value of the type.
+    pub fn wire_type(&self) -> WireType {
+        match *self {
+            Type::Int32
+            | Type::Int64
+            | Type::Uint32
+            | Type::Uint64
+            | Type::Sint32
+            | Type::Sint64
+            | Type::Bool => WireType::Varint,
+            Type::Float | Type::Fixed32 | Type::Sfixed32 => WireType::ThirtyTwoBit,
+            Type::Double | Type::Fixed64 | Type::Sfixed64 => WireType::SixtyFourBit,
+            Type::String | Type::Bytes => WireType::LengthDelimited,
+        }
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Describes a oneof type.
+#[derive(Debug)]
+pub struct OneofDescriptor {
+    /// The name of the oneof type.
+    pub name: &'static str,
+    /// The oneof variants. Each variant has a single tag.
+    pub fields: &'static [FieldDescriptor],
+}
+
+impl OneofDescriptor {
+    /// Returns the variant with the given tag, if any.
+    pub fn field_by_tag(&self, tag: u32) -> Option<&'static FieldDescriptor> {
+        self.fields.iter().find(|field| field.tags.contains(&tag))
+    }
+}
+
+/// Describes an enumeration type.
+#[derive(Debug)]
+pub struct EnumDescriptor {
+    /// The name of the enumeration type.
+    pub name: &'static str,
+    /// The fully qualified protobuf name of the enumeration type. Defaults to the name of the Rust
+    /// type.
+    pub full_name: &'static str,
+    /// The enumeration variants, in declaration order.
+    pub values: &'static [EnumValueDescriptor],
+}
+
+impl EnumDescriptor {
+    /// Returns the name of the variant with the given value, if any.
+    pub fn value_name(&self, number: i32) -> Option<&'static str> {
+        self.values
+            .iter()
+            .find(|value| value.number == number)
+            .map(|value| value.name)
+    }
+
+    /// Returns the value of the variant with the given name, if any.
+    pub fn value_number(&self, name: &str) -> Option<i32> {
+        self.values
+            .iter()
+            .find(|value| value.name == name)
+            .map(|value| value.number)
+    }
+
+    /// Returns the protobuf name of the value, if it is a variant of the enumeration.
+    pub fn value_proto_name(&self, number: i32) -> Option<&'static str> {
+        self.values
+            .iter()
+            .find(|value| value.number == number)
+            .map(|value| value.proto_name)
+    }
+}
+
+/// Describes an enumeration variant.
+#[derive(Debug)]
+pub struct EnumValueDescriptor {
+    /// The name of the variant.
+    pub name: &'static str,
+    /// The name of the value in the protobuf enumeration, e.g. `FOO_BAR` for `FooBar`.
+    pub proto_name: &'static str,
+    /// The value of the variant.
+    pub number: i32,
+}
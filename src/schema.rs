@@ -0,0 +1,296 @@
+//! Generation of `.proto` schemas from reflection descriptors.
+//!
+//! `to_proto` renders a protobuf schema describing a set of derived `Message` types, along with
+//! every message and enumeration type they reference, so that other languages can decode what
+//! the Rust types encode. Amino registered types and fields are annotated with comments noting
+//! their amino names and prefix bytes, since the prefixes are not part of the protobuf schema.
+//!
+//! ```ignore
+//! let schema = prost_amino::schema::to_proto(&[SignHeartbeatMsg::descriptor()]);
+//! ```
+//!
+//! Types are identified by name, so the given types and the types they reference must have
+//! unique names. The schema uses proto3 syntax, unless a type can only be described in proto2:
+//! when a scalar field is optional or required, or an enumeration has no zero value.
+
+use std::collections::{HashSet, VecDeque};
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use reflect::{EnumDescriptor, FieldDescriptor, FieldKind, Label, MessageDescriptor, Type};
+
+/// Returns a `.proto` schema describing the messages, and the types they reference.
+pub fn to_proto(messages: &[&'static MessageDescriptor]) -> String {
+    render(None, messages)
+}
+
+/// Returns a `.proto` schema describing the messages, and the types they reference, declared in
+/// the given package.
+pub fn to_proto_with_package(package: &str, messages: &[&'static MessageDescriptor]) -> String {
+    render(Some(package), messages)
+}
+
+/// Writes a `.proto` schema describing the messages to the given path, creating parent
+/// directories as necessary.
+///
+/// This is intended for build scripts which publish the schema of the crate's message types. If
+/// `package` is empty, no package is declared.
+pub fn write_proto<P>(
+    path: P,
+    package: &str,
+    messages: &[&'static MessageDescriptor],
+) -> io::Result<()>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let package = if package.is_empty() {
+        None
+    } else {
+        Some(package)
+    };
+    fs::write(path, render(package, messages))
+}
+
+/// The message and enumeration types described by a schema, in the order they are emitted.
+#[derive(Default)]
+struct Types {
+    messages: Vec<&'static MessageDescriptor>,
+    enums: Vec<&'static EnumDescriptor>,
+    message_names: HashSet<&'static str>,
+    enum_names: HashSet<&'static str>,
+    queue: VecDeque<&'static MessageDescriptor>,
+}
+
+impl Types {
+    fn collect(roots: &[&'static MessageDescriptor]) -> Types {
+        let mut types = Types::default();
+        types.queue.extend(roots);
+        while let Some(message) = types.queue.pop_front() {
+            if !types.message_names.insert(message.name) {
+                continue;
+            }
+            types.messages.push(message);
+            for field in message.fields {
+                types.visit(&field.kind);
+            }
+        }
+        types
+    }
+
+    fn visit(&mut self, kind: &FieldKind) {
+        match *kind {
+            FieldKind::Scalar(..) => (),
+            FieldKind::Enumeration(descriptor) => {
+                let descriptor = descriptor();
+                if self.enum_names.insert(descriptor.name) {
+                    self.enums.push(descriptor);
+                }
+            }
+            FieldKind::Message(descriptor) => self.queue.push_back(descriptor()),
+            // Groups are declared within the message, the types of their fields are visited.
+            FieldKind::Group(descriptor) => {
+                for field in descriptor().fields {
+                    self.visit(&field.kind);
+                }
+            }
+            FieldKind::Map { value, .. } => self.visit(value),
+            FieldKind::Oneof(descriptor) => {
+                for field in descriptor().fields {
+                    self.visit(&field.kind);
+                }
+            }
+        }
+    }
+
+    /// Returns `true` if the types can not be described with proto3 syntax.
+    fn requires_proto2(&self) -> bool {
+        let proto2_field = |field: &FieldDescriptor| match (field.label, &field.kind) {
+            (_, &FieldKind::Group(..)) => true,
+            (_, &FieldKind::Oneof(..)) => false,
+            (Label::Required, _) => true,
+            (Label::Optional, &FieldKind::Scalar(..))
+            | (Label::Optional, &FieldKind::Enumeration(..)) => true,
+            _ => false,
+        };
+        let proto2_enum = |enumeration: &EnumDescriptor| {
+            enumeration.values.first().map(|value| value.number) != Some(0)
+        };
+        self.messages
+            .iter()
+            .any(|message| message.fields.iter().any(proto2_field))
+            || self
+                .enums
+                .iter()
+                .any(|enumeration| proto2_enum(enumeration))
+    }
+}
+
+fn render(package: Option<&str>, messages: &[&'static MessageDescriptor]) -> String {
+    let types = Types::collect(messages);
+    let proto2 = types.requires_proto2();
+
+    let mut out = String::new();
+    out.push_str("// Generated by prost-amino from Rust message types.\n\n");
+    writeln!(
+        out,
+        "syntax = \"{}\";",
+        if proto2 { "proto2" } else { "proto3" }
+    )
+    .unwrap();
+    if let Some(package) = package {
+        writeln!(out, "\npackage {};", package).unwrap();
+    }
+
+    for message in &types.messages {
+        out.push('\n');
+        write_message(&mut out, message, proto2);
+    }
+    for enumeration in &types.enums {
+        out.push('\n');
+        write_enum(&mut out, enumeration);
+    }
+    out
+}
+
+fn write_message(out: &mut String, message: &MessageDescriptor, proto2: bool) {
+    if let (Some(name), Some(prefix)) = (message.amino_name, message.prefix) {
+        writeln!(out, "// Amino name \"{}\", prefix {}.", name, hex(&prefix)).unwrap();
+        out.push_str("// Encoded as the varint length, the prefix bytes, and then the fields.\n");
+    }
+    writeln!(out, "message {} {{", message.name).unwrap();
+    write_fields(out, message.fields, "  ", proto2);
+    out.push_str("}\n");
+}
+
+fn write_fields(out: &mut String, fields: &[FieldDescriptor], indent: &str, proto2: bool) {
+    let nested_indent = format!("{}  ", indent);
+    for field in fields {
+        match field.kind {
+            FieldKind::Oneof(oneof) => {
+                writeln!(out, "{}oneof {} {{", indent, field.proto_name).unwrap();
+                for variant in oneof().fields {
+                    write_field(out, variant, &nested_indent, "", variant.proto_name, proto2);
+                }
+                writeln!(out, "{}}}", indent).unwrap();
+            }
+            _ => {
+                let label = match (&field.kind, field.label) {
+                    (&FieldKind::Map { .. }, _) => "",
+                    (_, Label::Repeated) | (_, Label::Packed) => "repeated ",
+                    (_, Label::Required) => "required ",
+                    (_, Label::Plain) | (_, Label::Optional) if proto2 => "optional ",
+                    (_, Label::Plain) | (_, Label::Optional) => "",
+                };
+                write_field(out, field, indent, label, field.proto_name, proto2);
+            }
+        }
+    }
+}
+
+fn write_field(
+    out: &mut String,
+    field: &FieldDescriptor,
+    indent: &str,
+    label: &str,
+    name: &str,
+    proto2: bool,
+) {
+    if let (Some(amino_name), Some(prefix)) = (field.amino_name, field.prefix) {
+        writeln!(
+            out,
+            "{}// Amino name \"{}\", values are preceded by the prefix {}.",
+            indent,
+            amino_name,
+            hex(&prefix)
+        )
+        .unwrap();
+    }
+
+    let packable = match field.kind {
+        FieldKind::Scalar(ty) => ty != Type::String && ty != Type::Bytes,
+        FieldKind::Enumeration(..) => true,
+        _ => false,
+    };
+    let options = match field.label {
+        Label::Packed if packable && proto2 => " [packed = true]",
+        Label::Repeated if packable && !proto2 => " [packed = false]",
+        _ => "",
+    };
+
+    // The group's field name is implied by the name of its type.
+    if let FieldKind::Group(descriptor) = field.kind {
+        let descriptor = descriptor();
+        writeln!(
+            out,
+            "{}{}group {} = {} {{",
+            indent, label, descriptor.name, field.tags[0]
+        )
+        .unwrap();
+        write_fields(out, descriptor.fields, &format!("{}  ", indent), proto2);
+        writeln!(out, "{}}}", indent).unwrap();
+        return;
+    }
+
+    writeln!(
+        out,
+        "{}{}{} {} = {}{};",
+        indent,
+        label,
+        type_name(&field.kind),
+        name,
+        field.tags[0],
+        options
+    )
+    .unwrap();
+}
+
+fn write_enum(out: &mut String, enumeration: &EnumDescriptor) {
+    writeln!(out, "enum {} {{", enumeration.name).unwrap();
+    for value in enumeration.values {
+        writeln!(out, "  {} = {};", value.proto_name, value.number).unwrap();
+    }
+    out.push_str("}\n");
+}
+
+fn type_name(kind: &FieldKind) -> String {
+    match *kind {
+        FieldKind::Scalar(ty) => ty.as_str().to_string(),
+        FieldKind::Enumeration(descriptor) => descriptor().name.to_string(),
+        FieldKind::Message(descriptor) | FieldKind::Group(descriptor) => {
+            descriptor().name.to_string()
+        }
+        FieldKind::Map { key, value } => format!("map<{}, {}>", key, type_name(value)),
+        FieldKind::Oneof(..) => panic!("oneof fields do not have a type name"),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wrapper_types() {
+        use reflect::Reflect;
+
+        assert_eq!(
+            to_proto_with_package("wrappers", &[<u64 as Reflect>::descriptor()]),
+            "// Generated by prost-amino from Rust message types.\n\
+             \n\
+             syntax = \"proto3\";\n\
+             \n\
+             package wrappers;\n\
+             \n\
+             message UInt64Value {\n  uint64 value = 1;\n}\n"
+        );
+    }
+}
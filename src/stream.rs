@@ -0,0 +1,112 @@
+//! Length-delimited messages over `std::io` streams.
+//!
+//! Each message is written as a varint length delimiter followed by the encoded message, the same
+//! framing as [Message.encode_length_delimited]. This is the framing used by socket-based services
+//! such as ABCI and the Tendermint remote signer.
+
+use std::io::{self, Read, Write};
+
+use encoding::encoded_len_varint;
+use DecodeError;
+use Message;
+
+/// Writes a length-delimited message to a stream.
+///
+/// The message is encoded into a single buffer before it is written, so the stream does not need
+/// to be buffered. The stream is not flushed.
+pub fn write_length_delimited<M, W>(message: &M, writer: &mut W) -> io::Result<()>
+where
+    M: Message,
+    W: Write,
+{
+    let len = message.encoded_len();
+    let mut buf = Vec::with_capacity(len + encoded_len_varint(len as u64));
+    message.encode_length_delimited(&mut buf)?;
+    writer.write_all(&buf)
+}
+
+/// Reads a length-delimited message from a stream.
+///
+/// Returns `None` if the stream is closed before the first byte of the length delimiter, and an
+/// `UnexpectedEof` error if it's closed in the middle of a message.
+pub fn read_length_delimited<M, R>(reader: &mut R) -> io::Result<Option<M>>
+where
+    M: Message + Default,
+    R: Read,
+{
+    let len = match read_length_delimiter(reader)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+    // The buffer grows as the message is read, so that a corrupt length doesn't allocate.
+    let mut buf = Vec::new();
+    reader.take(len).read_to_end(&mut buf)?;
+    if buf.len() as u64 != len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "stream closed in the middle of a message",
+        ));
+    }
+    M::decode(&buf[..]).map(Some).map_err(From::from)
+}
+
+/// Reads a varint length delimiter from a stream, one byte at a time.
+fn read_length_delimiter<R>(reader: &mut R) -> io::Result<Option<u64>>
+where
+    R: Read,
+{
+    let mut value = 0u64;
+    for count in 0..10 {
+        let mut byte = [0u8];
+        if let Err(error) = reader.read_exact(&mut byte) {
+            if count == 0 && error.kind() == io::ErrorKind::UnexpectedEof {
+                return Ok(None);
+            }
+            return Err(error);
+        }
+        value |= u64::from(byte[0] & 0x7F) << (count * 7);
+        if byte[0] < 0x80 {
+            return Ok(Some(value));
+        }
+    }
+    Err(DecodeError::new("invalid varint").into())
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let mut buf = Vec::new();
+        write_length_delimited(&String::from("hello"), &mut buf).unwrap();
+        write_length_delimited(&vec![7u8; 200], &mut buf).unwrap();
+        assert_eq!(buf[0], 7);
+        assert_eq!(buf[8..10], [0xCB, 0x01]);
+
+        let mut cursor = Cursor::new(buf);
+        let hello: Option<String> = read_length_delimited(&mut cursor).unwrap();
+        assert_eq!(hello, Some("hello".to_string()));
+        let bytes: Option<Vec<u8>> = read_length_delimited(&mut cursor).unwrap();
+        assert_eq!(bytes, Some(vec![7u8; 200]));
+        let end: Option<String> = read_length_delimited(&mut cursor).unwrap();
+        assert_eq!(end, None);
+    }
+
+    #[test]
+    fn truncated() {
+        let mut cursor = Cursor::new(vec![7, 0x0A, 5, b'h']);
+        let error = read_length_delimited::<String, _>(&mut cursor).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut cursor = Cursor::new(vec![0x80]);
+        let error = read_length_delimited::<String, _>(&mut cursor).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+
+        let mut cursor = Cursor::new(vec![0xFF; 11]);
+        let error = read_length_delimited::<String, _>(&mut cursor).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
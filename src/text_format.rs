@@ -0,0 +1,1017 @@
+//! The protobuf text format.
+//!
+//! `to_text` and `from_text` convert messages to and from the text format, as printed by
+//! `protoc --decode`: one `name: value` line per field value, nested messages as `name { ... }`
+//! blocks, enumeration values by name, and strings and bytes as C-escaped string literals. Map
+//! fields are printed as repeated `key`/`value` entries. Like the JSON mapping, messages are
+//! converted through a `DynamicMessage`, using a `DescriptorPool` built from the reflection
+//! descriptors of the message type. `print` and `parse` convert dynamic messages directly.
+//!
+//! The parser also accepts the alternative forms of the format: `<` and `>` message delimiters,
+//! the list syntax `name: [a, b]` of repeated fields, enumeration values by number, `,` and `;`
+//! field separators, and `#` comments.
+//!
+//! Groups are named by their type name, as by protoc. `Any` values whose type is in the pool are
+//! printed as `[type_url] { ... }` expansions of the packed message, and other `Any` values as
+//! their fields. Messages with unknown fields, which include the extensions of derived messages,
+//! can't be printed, and extensions can't be parsed.
+
+use std::collections::{BTreeMap, HashSet};
+use std::error;
+use std::fmt;
+use std::fmt::Write;
+
+use dynamic::{DescriptorPool, DynamicMessage, FieldType, Kind, MapKey, MessageType, Value};
+use reflect::{Label, Reflect, Type};
+use Message;
+
+/// The maximum nesting depth of parsed messages.
+const RECURSION_LIMIT: usize = 100;
+
+const ANY: &str = "google.protobuf.Any";
+
+/// An error converting a message to or from the text format.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextFormatError {
+    description: String,
+}
+
+impl TextFormatError {
+    fn new<S>(description: S) -> TextFormatError
+    where
+        S: Into<String>,
+    {
+        TextFormatError {
+            description: description.into(),
+        }
+    }
+}
+
+impl fmt::Display for TextFormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("failed to convert text format: ")?;
+        f.write_str(&self.description)
+    }
+}
+
+impl error::Error for TextFormatError {
+    fn description(&self) -> &str {
+        &self.description
+    }
+}
+
+/// Converts a message to the text format.
+pub fn to_text<M>(message: &M) -> Result<String, TextFormatError>
+where
+    M: Message + Reflect,
+{
+    let descriptor = M::descriptor();
+    let mut pool = DescriptorPool::new();
+    pool.add_reflected(descriptor)
+        .map_err(|error| TextFormatError::new(error.to_string()))?;
+
+    let mut buf = Vec::with_capacity(message.encoded_len());
+    message
+        .encode(&mut buf)
+        .map_err(|error| TextFormatError::new(error.to_string()))?;
+    let message = pool
+        .decode(descriptor.full_name, &buf[..])
+        .map_err(|error| TextFormatError::new(error.to_string()))?;
+    print(&message)
+}
+
+/// Parses a message from the text format.
+pub fn from_text<M>(text: &str) -> Result<M, TextFormatError>
+where
+    M: Message + Reflect + Default,
+{
+    let descriptor = M::descriptor();
+    let mut pool = DescriptorPool::new();
+    pool.add_reflected(descriptor)
+        .map_err(|error| TextFormatError::new(error.to_string()))?;
+
+    let message = parse(&pool, descriptor.full_name, text)?;
+    let mut buf = Vec::with_capacity(message.encoded_len());
+    message
+        .encode(&mut buf)
+        .map_err(|error| TextFormatError::new(error.to_string()))?;
+    M::decode(&buf[..]).map_err(|error| TextFormatError::new(error.to_string()))
+}
+
+/// Converts a dynamic message to the text format.
+///
+/// An error is returned if the message, or a message nested in it, has unknown fields.
+pub fn print(message: &DynamicMessage) -> Result<String, TextFormatError> {
+    let mut out = String::new();
+    print_fields(message, 0, &mut out)?;
+    Ok(out)
+}
+
+/// Parses a dynamic message of the given type from the text format.
+pub fn parse(
+    pool: &DescriptorPool,
+    name: &str,
+    text: &str,
+) -> Result<DynamicMessage, TextFormatError> {
+    let mut parser = Parser::new(pool, text)?;
+    let message = parser.parse_message(name, None, 0)?;
+    match parser.next() {
+        (Token::End, _) => Ok(message),
+        (token, line) => Err(unexpected(&token, line)),
+    }
+}
+
+/// Prints the set fields of the message, one value per line. Fields without presence are
+/// omitted if they have the default value, unless they are `write_empty` fields.
+fn print_fields(
+    message: &DynamicMessage,
+    indent: usize,
+    out: &mut String,
+) -> Result<(), TextFormatError> {
+    if !message.unknown_fields().is_empty() {
+        return Err(TextFormatError::new(format!(
+            "{} has unknown fields or extensions, which can't be printed",
+            message.descriptor().name
+        )));
+    }
+    if message.descriptor().name == ANY && print_any(message, indent, out)? {
+        return Ok(());
+    }
+
+    for field in &message.descriptor().fields {
+        let value = match message.get_by_tag(field.tag) {
+            Some(value) => value,
+            None => continue,
+        };
+        let presence = field.oneof.is_some()
+            || field.label == Label::Optional
+            || field.label == Label::Required
+            || field.write_empty;
+        if !presence && value.is_default() {
+            continue;
+        }
+        match (value, &field.kind) {
+            (Value::List(values), kind) => {
+                for value in values {
+                    print_field(message.pool(), field_name(field), kind, value, indent, out)?;
+                }
+            }
+            (Value::Map(entries), &Kind::Map(key_ty, ref value_kind)) => {
+                for (key, value) in entries {
+                    push_indent(indent, out);
+                    out.push_str(&field.name);
+                    out.push_str(" {\n");
+                    let key = key.clone().into_value();
+                    print_field(
+                        message.pool(),
+                        "key",
+                        &Kind::Scalar(key_ty),
+                        &key,
+                        indent + 1,
+                        out,
+                    )?;
+                    print_field(message.pool(), "value", value_kind, value, indent + 1, out)?;
+                    push_indent(indent, out);
+                    out.push_str("}\n");
+                }
+            }
+            (value, kind) => {
+                print_field(message.pool(), field_name(field), kind, value, indent, out)?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name of a field in the text format. Groups are named by their type name.
+fn field_name(field: &FieldType) -> &str {
+    match field.kind {
+        Kind::Group(ref name) => group_name(name),
+        _ => &field.name,
+    }
+}
+
+/// Returns the name of a group type, without its package and enclosing message types.
+fn group_name(full_name: &str) -> &str {
+    full_name.rsplit('.').next().unwrap_or(full_name)
+}
+
+/// Prints an `Any` value as the expansion of the packed message, if its type is in the pool.
+/// Returns `false` if the value has to be printed as its fields instead.
+fn print_any(
+    message: &DynamicMessage,
+    indent: usize,
+    out: &mut String,
+) -> Result<bool, TextFormatError> {
+    let type_url = match message.get_by_tag(1) {
+        Some(Value::String(type_url)) if !type_url.is_empty() => type_url,
+        _ => return Ok(false),
+    };
+    let name = type_url.rsplit('/').next().unwrap_or(type_url);
+    if message.pool().message(name).is_none() {
+        return Ok(false);
+    }
+    let value = match message.get_by_tag(2) {
+        Some(Value::Bytes(value)) => &value[..],
+        _ => &[],
+    };
+    let packed = message
+        .pool()
+        .decode(name, value)
+        .map_err(|error| TextFormatError::new(error.to_string()))?;
+
+    push_indent(indent, out);
+    out.push('[');
+    out.push_str(type_url);
+    out.push_str("] {\n");
+    print_fields(&packed, indent + 1, out)?;
+    push_indent(indent, out);
+    out.push_str("}\n");
+    Ok(true)
+}
+
+fn print_field(
+    pool: &DescriptorPool,
+    name: &str,
+    kind: &Kind,
+    value: &Value,
+    indent: usize,
+    out: &mut String,
+) -> Result<(), TextFormatError> {
+    push_indent(indent, out);
+    out.push_str(name);
+    if let Value::Message(ref message) = *value {
+        out.push_str(" {\n");
+        print_fields(message, indent + 1, out)?;
+        push_indent(indent, out);
+        out.push_str("}\n");
+        return Ok(());
+    }
+
+    out.push_str(": ");
+    match *value {
+        Value::Bool(value) => out.push_str(if value { "true" } else { "false" }),
+        Value::I32(value) => write!(out, "{}", value).unwrap(),
+        Value::I64(value) => write!(out, "{}", value).unwrap(),
+        Value::U32(value) => write!(out, "{}", value).unwrap(),
+        Value::U64(value) => write!(out, "{}", value).unwrap(),
+        Value::F32(value) => print_float(f64::from(value), &format!("{:?}", value), out),
+        Value::F64(value) => print_float(value, &format!("{:?}", value), out),
+        Value::String(ref value) => print_bytes(value.as_bytes(), out),
+        Value::Bytes(ref value) => print_bytes(value, out),
+        Value::Enum(number) => {
+            let value_name = match *kind {
+                Kind::Enumeration(ref name) => {
+                    pool.enumeration(name).and_then(|ty| ty.value_name(number))
+                }
+                _ => None,
+            };
+            match value_name {
+                Some(value_name) => out.push_str(value_name),
+                None => write!(out, "{}", number).unwrap(),
+            }
+        }
+        // Lists and maps are flattened by `print_fields`, and can't be nested.
+        Value::Message(..) | Value::List(..) | Value::Map(..) => unreachable!(),
+    }
+    out.push('\n');
+    Ok(())
+}
+
+fn push_indent(indent: usize, out: &mut String) {
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+/// Prints a float, using the names of the non-finite values. `formatted` is the shortest
+/// representation of the value which parses back to it.
+fn print_float(value: f64, formatted: &str, out: &mut String) {
+    if value.is_nan() {
+        out.push_str("nan");
+    } else if value == f64::INFINITY {
+        out.push_str("inf");
+    } else if value == f64::NEG_INFINITY {
+        out.push_str("-inf");
+    } else {
+        out.push_str(formatted);
+    }
+}
+
+/// Prints a string literal. Printable ASCII and UTF-8 characters are printed as is, and other
+/// bytes are escaped.
+fn print_bytes(value: &[u8], out: &mut String) {
+    out.push('"');
+    let mut rest = value;
+    while !rest.is_empty() {
+        // Copy valid UTF-8 sequences verbatim, so that non-ASCII strings stay readable.
+        let valid = match ::std::str::from_utf8(rest) {
+            Ok(valid) => valid,
+            Err(error) => ::std::str::from_utf8(&rest[..error.valid_up_to()]).unwrap(),
+        };
+        for c in valid.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\'' => out.push_str("\\'"),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if (c as u32) < 0x20 || c as u32 == 0x7F => {
+                    write!(out, "\\{:03o}", c as u32).unwrap()
+                }
+                c => out.push(c),
+            }
+        }
+        rest = &rest[valid.len()..];
+        if let Some((&byte, remainder)) = rest.split_first() {
+            write!(out, "\\{:03o}", byte).unwrap();
+            rest = remainder;
+        }
+    }
+    out.push('"');
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    /// An unsigned number literal. Signs are separate `-` symbols.
+    Number(String),
+    /// The unescaped contents of one or more adjacent string literals.
+    String(Vec<u8>),
+    Symbol(char),
+    End,
+}
+
+fn unexpected(token: &Token, line: usize) -> TextFormatError {
+    let found = match *token {
+        Token::Identifier(ref identifier) => format!("`{}`", identifier),
+        Token::Number(ref number) => format!("`{}`", number),
+        Token::String(..) => "a string".to_string(),
+        Token::Symbol(symbol) => format!("`{}`", symbol),
+        Token::End => "the end of the input".to_string(),
+    };
+    TextFormatError::new(format!("line {}: unexpected {}", line, found))
+}
+
+/// Splits the text format into tokens, paired with their line numbers.
+fn tokenize(text: &str) -> Result<Vec<(Token, usize)>, TextFormatError> {
+    let input = text.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut pos = 0;
+    while pos < input.len() {
+        let c = input[pos];
+        match c {
+            b'\n' => {
+                line += 1;
+                pos += 1;
+            }
+            b' ' | b'\t' | b'\r' | b'\x0B' | b'\x0C' => pos += 1,
+            b'#' => {
+                while pos < input.len() && input[pos] != b'\n' {
+                    pos += 1;
+                }
+            }
+            b'"' | b'\'' => {
+                let (value, end) = unescape(input, pos, line)?;
+                pos = end;
+                // Adjacent string literals are concatenated.
+                if let Some(&mut (Token::String(ref mut previous), _)) = tokens.last_mut() {
+                    previous.extend_from_slice(&value);
+                    continue;
+                }
+                tokens.push((Token::String(value), line));
+            }
+            b'0'..=b'9' | b'.'
+                if c != b'.' || input.get(pos + 1).is_some_and(u8::is_ascii_digit) =>
+            {
+                let start = pos;
+                while pos < input.len() {
+                    match input[pos] {
+                        b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z' | b'_' | b'.' => pos += 1,
+                        b'+' | b'-' if matches_exponent(&input[start..pos]) => pos += 1,
+                        _ => break,
+                    }
+                }
+                tokens.push((Token::Number(text[start..pos].to_string()), line));
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                let start = pos;
+                while pos < input.len()
+                    && (input[pos].is_ascii_alphanumeric() || input[pos] == b'_')
+                {
+                    pos += 1;
+                }
+                tokens.push((Token::Identifier(text[start..pos].to_string()), line));
+            }
+            b'{' | b'}' | b'<' | b'>' | b'[' | b']' | b':' | b',' | b';' | b'-' | b'.' | b'/' => {
+                tokens.push((Token::Symbol(c as char), line));
+                pos += 1;
+            }
+            _ => {
+                let c = text[pos..].chars().next().unwrap();
+                return Err(TextFormatError::new(format!(
+                    "line {}: unexpected character `{}`",
+                    line, c
+                )));
+            }
+        }
+    }
+    tokens.push((Token::End, line));
+    Ok(tokens)
+}
+
+/// Returns `true` if the number literal so far ends with the exponent of a decimal float.
+fn matches_exponent(number: &[u8]) -> bool {
+    let hex = number.len() > 1 && (number[1] == b'x' || number[1] == b'X');
+    !hex && (number.last() == Some(&b'e') || number.last() == Some(&b'E'))
+}
+
+/// Unescapes the string literal starting at `start`, returning its contents and the position
+/// following the closing quote.
+fn unescape(input: &[u8], start: usize, line: usize) -> Result<(Vec<u8>, usize), TextFormatError> {
+    let invalid = |description: &str| {
+        TextFormatError::new(format!("line {}: {} in string literal", line, description))
+    };
+    let quote = input[start];
+    let mut value = Vec::new();
+    let mut pos = start + 1;
+    loop {
+        let c = match input.get(pos) {
+            Some(&b'\n') | None => return Err(invalid("unterminated string")),
+            Some(&c) => c,
+        };
+        pos += 1;
+        if c == quote {
+            return Ok((value, pos));
+        }
+        if c != b'\\' {
+            value.push(c);
+            continue;
+        }
+
+        let escape = *input
+            .get(pos)
+            .ok_or_else(|| invalid("unterminated string"))?;
+        pos += 1;
+        match escape {
+            b'n' => value.push(b'\n'),
+            b'r' => value.push(b'\r'),
+            b't' => value.push(b'\t'),
+            b'a' => value.push(0x07),
+            b'b' => value.push(0x08),
+            b'f' => value.push(0x0C),
+            b'v' => value.push(0x0B),
+            b'"' | b'\'' | b'\\' | b'?' => value.push(escape),
+            b'0'..=b'7' => {
+                let mut byte = u32::from(escape - b'0');
+                for _ in 0..2 {
+                    match input.get(pos) {
+                        Some(&digit @ b'0'..=b'7') => {
+                            byte = byte * 8 + u32::from(digit - b'0');
+                            pos += 1;
+                        }
+                        _ => break,
+                    }
+                }
+                if byte > 0xFF {
+                    return Err(invalid("octal escape out of range"));
+                }
+                value.push(byte as u8);
+            }
+            b'x' | b'X' => {
+                let mut byte = 0;
+                let mut digits = 0;
+                while digits < 2 {
+                    match input
+                        .get(pos)
+                        .and_then(|&digit| (digit as char).to_digit(16))
+                    {
+                        Some(digit) => byte = byte * 16 + digit,
+                        None => break,
+                    }
+                    pos += 1;
+                    digits += 1;
+                }
+                if digits == 0 {
+                    return Err(invalid("invalid hex escape"));
+                }
+                value.push(byte as u8);
+            }
+            _ => return Err(invalid("invalid escape")),
+        }
+    }
+}
+
+struct Parser<'a> {
+    pool: &'a DescriptorPool,
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(pool: &'a DescriptorPool, text: &str) -> Result<Parser<'a>, TextFormatError> {
+        Ok(Parser {
+            pool,
+            tokens: tokenize(text)?,
+            pos: 0,
+        })
+    }
+
+    fn peek(&self) -> &Token {
+        &self.tokens[self.pos].0
+    }
+
+    fn line(&self) -> usize {
+        self.tokens[self.pos].1
+    }
+
+    fn next(&mut self) -> (Token, usize) {
+        let token = self.tokens[self.pos].clone();
+        if token.0 != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    /// Consumes the next token if it is the symbol `c`.
+    fn eat(&mut self, c: char) -> bool {
+        if *self.peek() == Token::Symbol(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), TextFormatError> {
+        if self.eat(c) {
+            Ok(())
+        } else {
+            let (token, line) = self.next();
+            Err(unexpected(&token, line))
+        }
+    }
+
+    fn error<S>(&self, description: S) -> TextFormatError
+    where
+        S: AsRef<str>,
+    {
+        TextFormatError::new(format!("line {}: {}", self.line(), description.as_ref()))
+    }
+
+    /// Parses the fields of a message, up to the `end` delimiter or the end of the input.
+    fn parse_message(
+        &mut self,
+        name: &str,
+        end: Option<char>,
+        depth: usize,
+    ) -> Result<DynamicMessage, TextFormatError> {
+        if depth > RECURSION_LIMIT {
+            return Err(self.error("recursion limit reached"));
+        }
+        let mut message = self
+            .pool
+            .new_message(name)
+            .ok_or_else(|| TextFormatError::new(format!("unknown message type: {}", name)))?;
+        let ty = message.descriptor().clone();
+
+        let mut values = BTreeMap::new();
+        let mut oneofs = HashSet::new();
+        loop {
+            match (self.peek().clone(), end) {
+                (Token::Symbol(c), Some(end)) if c == end => {
+                    self.pos += 1;
+                    break;
+                }
+                (Token::End, None) => break,
+                (Token::Identifier(field_name), _) => {
+                    let field = ty
+                        .field_by_name(&field_name)
+                        .or_else(|| group_field(&ty, &field_name))
+                        .ok_or_else(|| {
+                            self.error(format!("unknown field of {}: {}", ty.name, field_name))
+                        })?;
+                    self.pos += 1;
+                    let repeated = field.label == Label::Repeated || field.label == Label::Packed;
+                    if !repeated && values.contains_key(&field.tag) {
+                        return Err(self.error(format!("duplicate field: {}", field_name)));
+                    }
+                    if let Some(oneof) = field.oneof {
+                        if !oneofs.insert(oneof) {
+                            return Err(self.error(format!(
+                                "multiple fields of oneof {}: {}",
+                                ty.oneofs[oneof], field_name
+                            )));
+                        }
+                    }
+                    self.parse_field(field, &mut values, depth)?;
+                    if !self.eat(',') {
+                        self.eat(';');
+                    }
+                }
+                (Token::Symbol('['), _) if ty.name == ANY => {
+                    if values.contains_key(&1) || values.contains_key(&2) {
+                        return Err(self.error("duplicate google.protobuf.Any value"));
+                    }
+                    let (type_url, value) = self.parse_any(depth)?;
+                    values.insert(1, Value::String(type_url));
+                    values.insert(2, Value::Bytes(value));
+                    if !self.eat(',') {
+                        self.eat(';');
+                    }
+                }
+                (Token::Symbol('['), _) => return Err(self.error("extensions are not supported")),
+                (token, _) => return Err(unexpected(&token, self.line())),
+            }
+        }
+
+        for (tag, value) in values {
+            message
+                .set_by_tag(tag, value)
+                .map_err(|error| TextFormatError::new(error.to_string()))?;
+        }
+        Ok(message)
+    }
+
+    /// Parses the expansion of an `Any` value, `[type_url] { ... }`, returning the type URL and
+    /// the encoded packed message.
+    fn parse_any(&mut self, depth: usize) -> Result<(String, Vec<u8>), TextFormatError> {
+        self.expect('[')?;
+        let mut type_url = String::new();
+        loop {
+            match self.next() {
+                (Token::Identifier(part), _) | (Token::Number(part), _) => type_url.push_str(&part),
+                (Token::Symbol(c), _) if c == '.' || c == '/' || c == '-' => type_url.push(c),
+                (Token::Symbol(']'), _) if !type_url.is_empty() => break,
+                (token, line) => return Err(unexpected(&token, line)),
+            }
+        }
+
+        let name = type_url.rsplit('/').next().unwrap_or(&type_url).to_string();
+        self.eat(':');
+        let end = self.parse_message_start()?;
+        let message = self.parse_message(&name, Some(end), depth + 1)?;
+        let mut value = Vec::with_capacity(message.encoded_len());
+        message
+            .encode(&mut value)
+            .map_err(|error| TextFormatError::new(error.to_string()))?;
+        Ok((type_url, value))
+    }
+
+    /// Parses the value of a field following its name, adding it to `values`.
+    fn parse_field(
+        &mut self,
+        field: &FieldType,
+        values: &mut BTreeMap<u32, Value>,
+        depth: usize,
+    ) -> Result<(), TextFormatError> {
+        // The separator is optional before messages.
+        match field.kind {
+            Kind::Scalar(..) | Kind::Enumeration(..) => self.expect(':')?,
+            Kind::Message(..) | Kind::Group(..) | Kind::Map(..) => {
+                self.eat(':');
+            }
+        }
+
+        let repeated = field.label == Label::Repeated || field.label == Label::Packed;
+        if !repeated {
+            let value = self.parse_value(&field.kind, depth)?;
+            values.insert(field.tag, value);
+            return Ok(());
+        }
+
+        let mut elements = Vec::new();
+        if self.eat('[') {
+            if !self.eat(']') {
+                loop {
+                    elements.push(self.parse_value(&field.kind, depth)?);
+                    if self.eat(']') {
+                        break;
+                    }
+                    self.expect(',')?;
+                }
+            }
+        } else {
+            elements.push(self.parse_value(&field.kind, depth)?);
+        }
+
+        if let Kind::Map(..) = field.kind {
+            let entries = values
+                .entry(field.tag)
+                .or_insert_with(|| Value::Map(BTreeMap::new()));
+            if let Value::Map(ref mut entries) = *entries {
+                for element in elements {
+                    if let Value::List(mut entry) = element {
+                        let value = entry.pop().unwrap();
+                        let key = MapKey::from_value(entry.pop().unwrap()).unwrap();
+                        entries.insert(key, value);
+                    }
+                }
+            }
+        } else {
+            let list = values
+                .entry(field.tag)
+                .or_insert_with(|| Value::List(Vec::new()));
+            if let Value::List(ref mut list) = *list {
+                list.extend(elements);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses a singular value, or an element of a repeated field. Map entries are returned as a
+    /// list of the key and the value.
+    fn parse_value(&mut self, kind: &Kind, depth: usize) -> Result<Value, TextFormatError> {
+        match *kind {
+            Kind::Scalar(ty) => self.parse_scalar(ty),
+            Kind::Enumeration(ref name) => {
+                let line = self.line();
+                match self.next() {
+                    (Token::Identifier(value_name), _) => self
+                        .pool
+                        .enumeration(name)
+                        .and_then(|ty| ty.value_number(&value_name))
+                        .map(Value::Enum)
+                        .ok_or_else(|| {
+                            TextFormatError::new(format!(
+                                "line {}: unknown value of {}: {}",
+                                line, name, value_name
+                            ))
+                        }),
+                    (Token::Number(..), _) | (Token::Symbol('-'), _) => {
+                        self.pos -= 1;
+                        self.parse_scalar(Type::Int32).map(|value| match value {
+                            Value::I32(number) => Value::Enum(number),
+                            _ => unreachable!(),
+                        })
+                    }
+                    (token, line) => Err(unexpected(&token, line)),
+                }
+            }
+            Kind::Message(ref name) | Kind::Group(ref name) => {
+                let end = self.parse_message_start()?;
+                self.parse_message(name, Some(end), depth + 1)
+                    .map(Value::Message)
+            }
+            Kind::Map(key_ty, ref value_kind) => {
+                let end = self.parse_message_start()?;
+                let mut key = None;
+                let mut value = None;
+                while !self.eat(end) {
+                    let line = self.line();
+                    match self.next() {
+                        (Token::Identifier(ref name), _) if name == "key" && key.is_none() => {
+                            self.expect(':')?;
+                            key = Some(self.parse_scalar(key_ty)?);
+                        }
+                        (Token::Identifier(ref name), _) if name == "value" && value.is_none() => {
+                            // Parse the value as the field of a map entry message.
+                            let field = FieldType {
+                                name: "value".to_string(),
+                                json_name: "value".to_string(),
+                                tag: 2,
+                                label: Label::Optional,
+                                kind: (**value_kind).clone(),
+                                oneof: None,
+                                write_empty: false,
+                            };
+                            let mut values = BTreeMap::new();
+                            self.parse_field(&field, &mut values, depth + 1)?;
+                            value = values.remove(&2);
+                        }
+                        (Token::Identifier(ref name), _) if name == "key" || name == "value" => {
+                            return Err(TextFormatError::new(format!(
+                                "line {}: duplicate map entry field: {}",
+                                line, name
+                            )));
+                        }
+                        (token, line) => return Err(unexpected(&token, line)),
+                    }
+                    if !self.eat(',') {
+                        self.eat(';');
+                    }
+                }
+
+                let key = key.unwrap_or_else(|| Value::default_scalar(key_ty));
+                let value = match value {
+                    Some(value) => value,
+                    None => self.default_value(value_kind)?,
+                };
+                Ok(Value::List(vec![key, value]))
+            }
+        }
+    }
+
+    /// Parses the opening delimiter of a message, returning the closing delimiter.
+    fn parse_message_start(&mut self) -> Result<char, TextFormatError> {
+        if self.eat('{') {
+            Ok('}')
+        } else if self.eat('<') {
+            Ok('>')
+        } else {
+            let (token, line) = self.next();
+            Err(unexpected(&token, line))
+        }
+    }
+
+    fn default_value(&self, kind: &Kind) -> Result<Value, TextFormatError> {
+        match *kind {
+            Kind::Scalar(ty) => Ok(Value::default_scalar(ty)),
+            Kind::Enumeration(..) => Ok(Value::Enum(0)),
+            Kind::Message(ref name) | Kind::Group(ref name) => self
+                .pool
+                .new_message(name)
+                .map(Value::Message)
+                .ok_or_else(|| TextFormatError::new(format!("unknown message type: {}", name))),
+            Kind::Map(..) => Err(TextFormatError::new("nested map value")),
+        }
+    }
+
+    fn parse_scalar(&mut self, ty: Type) -> Result<Value, TextFormatError> {
+        let line = self.line();
+        let negative = self.eat('-');
+        let token = self.next().0;
+        let invalid = || {
+            TextFormatError::new(format!(
+                "line {}: invalid {} value: {}{}",
+                line,
+                ty,
+                if negative { "-" } else { "" },
+                match token {
+                    Token::Identifier(ref raw) | Token::Number(ref raw) => &raw[..],
+                    _ => "",
+                }
+            ))
+        };
+
+        match (ty, &token) {
+            (Type::String, Token::String(value)) if !negative => String::from_utf8(value.clone())
+                .map(Value::String)
+                .map_err(|_| self.error("invalid UTF-8 in string literal")),
+            (Type::Bytes, Token::String(value)) if !negative => Ok(Value::Bytes(value.clone())),
+            (Type::Bool, Token::Identifier(raw)) if !negative => match &raw[..] {
+                "true" | "True" | "t" => Ok(Value::Bool(true)),
+                "false" | "False" | "f" => Ok(Value::Bool(false)),
+                _ => Err(invalid()),
+            },
+            (Type::Bool, Token::Number(raw)) if !negative => match &raw[..] {
+                "1" => Ok(Value::Bool(true)),
+                "0" => Ok(Value::Bool(false)),
+                _ => Err(invalid()),
+            },
+            (Type::Float, &Token::Identifier(ref raw))
+            | (Type::Double, &Token::Identifier(ref raw)) => {
+                let value = match &raw.to_lowercase()[..] {
+                    "inf" | "infinity" => f64::INFINITY,
+                    "nan" => f64::NAN,
+                    _ => return Err(invalid()),
+                };
+                let value = if negative { -value } else { value };
+                Ok(if ty == Type::Float {
+                    Value::F32(value as f32)
+                } else {
+                    Value::F64(value)
+                })
+            }
+            (Type::Float, &Token::Number(ref raw)) | (Type::Double, &Token::Number(ref raw)) => {
+                let value = parse_float(raw).ok_or_else(&invalid)?;
+                let value = if negative { -value } else { value };
+                Ok(if ty == Type::Float {
+                    Value::F32(value as f32)
+                } else {
+                    Value::F64(value)
+                })
+            }
+            (_, Token::Number(raw)) => {
+                let magnitude = parse_integer(raw).ok_or_else(&invalid)?;
+                let signed_max = |max: u64| if negative { max + 1 } else { max };
+                match ty {
+                    Type::Int32 | Type::Sint32 | Type::Sfixed32 => {
+                        if magnitude > signed_max(i32::MAX as u64) {
+                            return Err(invalid());
+                        }
+                        let value = magnitude as i64;
+                        Ok(Value::I32(if negative { -value } else { value } as i32))
+                    }
+                    Type::Int64 | Type::Sint64 | Type::Sfixed64 => {
+                        if magnitude > signed_max(i64::MAX as u64) {
+                            return Err(invalid());
+                        }
+                        let value = magnitude as i64;
+                        Ok(Value::I64(if negative {
+                            value.wrapping_neg()
+                        } else {
+                            value
+                        }))
+                    }
+                    Type::Uint32 | Type::Fixed32 if !negative => {
+                        if magnitude > u64::from(u32::MAX) {
+                            return Err(invalid());
+                        }
+                        Ok(Value::U32(magnitude as u32))
+                    }
+                    Type::Uint64 | Type::Fixed64 if !negative => Ok(Value::U64(magnitude)),
+                    _ => Err(invalid()),
+                }
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Returns the group field of the message type with the given group type name.
+fn group_field<'a>(ty: &'a MessageType, name: &str) -> Option<&'a FieldType> {
+    ty.fields.iter().find(|field| match field.kind {
+        Kind::Group(ref group) => group_name(group) == name,
+        _ => false,
+    })
+}
+
+/// Parses an unsigned decimal, hexadecimal or octal integer literal.
+fn parse_integer(raw: &str) -> Option<u64> {
+    if raw.starts_with("0x") || raw.starts_with("0X") {
+        u64::from_str_radix(&raw[2..], 16).ok()
+    } else if raw.len() > 1 && raw.starts_with('0') {
+        u64::from_str_radix(&raw[1..], 8).ok()
+    } else {
+        raw.parse().ok()
+    }
+}
+
+/// Parses an unsigned float literal, which may be an integer literal or have an `f` suffix.
+fn parse_float(raw: &str) -> Option<f64> {
+    if let Some(integer) = parse_integer(raw) {
+        return Some(integer as f64);
+    }
+    let raw = raw.trim_end_matches(&['f', 'F'][..]);
+    if raw.is_empty() || raw.contains(|c: char| c.is_ascii_alphabetic() && c != 'e' && c != 'E') {
+        return None;
+    }
+    raw.parse().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tokens() {
+        let tokens = tokenize("a: -1.5e-3f # comment\n b { c: 'x\\n' \"\\101\\x42\" } [d]")
+            .unwrap()
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("a".to_string()),
+                Token::Symbol(':'),
+                Token::Symbol('-'),
+                Token::Number("1.5e-3f".to_string()),
+                Token::Identifier("b".to_string()),
+                Token::Symbol('{'),
+                Token::Identifier("c".to_string()),
+                Token::Symbol(':'),
+                Token::String(b"x\nAB".to_vec()),
+                Token::Symbol('}'),
+                Token::Symbol('['),
+                Token::Identifier("d".to_string()),
+                Token::Symbol(']'),
+                Token::End,
+            ]
+        );
+
+        for invalid in &["'a", "\"a\nb\"", "'\\400'", "'\\x'", "'\\q'", "a: $"] {
+            assert!(tokenize(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn escaping() {
+        let mut out = String::new();
+        print_bytes(b"a\"'\\\n\x00\x7F\xFF\xC3\xA9", &mut out);
+        assert_eq!(out, "\"a\\\"\\'\\\\\\n\\000\\177\\377\u{e9}\"");
+        let (value, end) = unescape(out.as_bytes(), 0, 1).unwrap();
+        assert_eq!(value, b"a\"'\\\n\x00\x7F\xFF\xC3\xA9");
+        assert_eq!(end, out.len());
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(parse_integer("42"), Some(42));
+        assert_eq!(parse_integer("0x2A"), Some(42));
+        assert_eq!(parse_integer("052"), Some(42));
+        assert_eq!(parse_integer("0"), Some(0));
+        assert_eq!(parse_integer("1.5"), None);
+        assert_eq!(parse_integer("18446744073709551616"), None);
+
+        assert_eq!(parse_float("1.5"), Some(1.5));
+        assert_eq!(parse_float("1.5f"), Some(1.5));
+        assert_eq!(parse_float("2e3"), Some(2000.0));
+        assert_eq!(parse_float(".5"), Some(0.5));
+        assert_eq!(parse_float("0x10"), Some(16.0));
+        assert_eq!(parse_float("1.5x"), None);
+    }
+}
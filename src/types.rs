@@ -8,6 +8,7 @@
 use bytes::{Buf, BufMut};
 
 use encoding::*;
+use reflect::{FieldDescriptor, FieldKind, Label, MessageDescriptor, Reflect, Type};
 use DecodeError;
 use Message;
 
@@ -29,7 +30,7 @@ impl Message for bool {
         if tag == 1 {
             bool::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -42,6 +43,9 @@ impl Message for bool {
     fn clear(&mut self) {
         *self = false;
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.UInt32Value`
@@ -62,7 +66,7 @@ impl Message for u32 {
         if tag == 1 {
             uint32::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -75,6 +79,9 @@ impl Message for u32 {
     fn clear(&mut self) {
         *self = 0;
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.UInt64Value`
@@ -95,7 +102,7 @@ impl Message for u64 {
         if tag == 1 {
             uint64::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -108,6 +115,9 @@ impl Message for u64 {
     fn clear(&mut self) {
         *self = 0;
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.Int32Value`
@@ -128,7 +138,7 @@ impl Message for i32 {
         if tag == 1 {
             int32::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -141,6 +151,9 @@ impl Message for i32 {
     fn clear(&mut self) {
         *self = 0;
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.Int64Value`
@@ -161,7 +174,7 @@ impl Message for i64 {
         if tag == 1 {
             int64::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -174,6 +187,9 @@ impl Message for i64 {
     fn clear(&mut self) {
         *self = 0;
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.FloatValue`
@@ -194,7 +210,7 @@ impl Message for f32 {
         if tag == 1 {
             float::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -207,6 +223,9 @@ impl Message for f32 {
     fn clear(&mut self) {
         *self = 0.0;
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.DoubleValue`
@@ -227,7 +246,7 @@ impl Message for f64 {
         if tag == 1 {
             double::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -240,6 +259,9 @@ impl Message for f64 {
     fn clear(&mut self) {
         *self = 0.0;
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.StringValue`
@@ -260,7 +282,7 @@ impl Message for String {
         if tag == 1 {
             string::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -273,6 +295,9 @@ impl Message for String {
     fn clear(&mut self) {
         self.clear();
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.BytesValue`
@@ -293,7 +318,7 @@ impl Message for Vec<u8> {
         if tag == 1 {
             bytes::merge(wire_type, self, buf)
         } else {
-            skip_field(wire_type, buf)
+            skip_field(wire_type, tag, buf)
         }
     }
     fn encoded_len(&self) -> usize {
@@ -306,6 +331,9 @@ impl Message for Vec<u8> {
     fn clear(&mut self) {
         self.clear();
     }
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
 }
 
 /// `google.protobuf.Empty`
@@ -319,11 +347,65 @@ impl Message for () {
     where
         B: Buf,
     {
-        let (_, wire_type) = decode_key(buf)?;
-        skip_field(wire_type, buf)
+        let (tag, wire_type) = decode_key(buf)?;
+        skip_field(wire_type, tag, buf)
     }
     fn encoded_len(&self) -> usize {
         0
     }
     fn clear(&mut self) {}
+    fn reflect_descriptor() -> Option<&'static MessageDescriptor> {
+        Some(Self::descriptor())
+    }
+}
+
+/// Implements `Reflect` for a wrapper type with a single `value` field.
+macro_rules! wrapper_descriptor {
+    ($ty:ty, $name:expr, $value:ident) => {
+        impl Reflect for $ty {
+            fn descriptor() -> &'static MessageDescriptor {
+                static DESCRIPTOR: MessageDescriptor = MessageDescriptor {
+                    name: $name,
+                    full_name: concat!("google.protobuf.", $name),
+                    amino_name: None,
+                    prefix: None,
+                    fields: &[FieldDescriptor {
+                        name: "value",
+                        proto_name: "value",
+                        json_name: "value",
+                        tags: &[1],
+                        label: Label::Plain,
+                        kind: FieldKind::Scalar(Type::$value),
+                        amino_name: None,
+                        prefix: None,
+                        write_empty: false,
+                    }],
+                };
+                &DESCRIPTOR
+            }
+        }
+    };
+}
+
+wrapper_descriptor!(bool, "BoolValue", Bool);
+wrapper_descriptor!(u32, "UInt32Value", Uint32);
+wrapper_descriptor!(u64, "UInt64Value", Uint64);
+wrapper_descriptor!(i32, "Int32Value", Int32);
+wrapper_descriptor!(i64, "Int64Value", Int64);
+wrapper_descriptor!(f32, "FloatValue", Float);
+wrapper_descriptor!(f64, "DoubleValue", Double);
+wrapper_descriptor!(String, "StringValue", String);
+wrapper_descriptor!(Vec<u8>, "BytesValue", Bytes);
+
+impl Reflect for () {
+    fn descriptor() -> &'static MessageDescriptor {
+        static DESCRIPTOR: MessageDescriptor = MessageDescriptor {
+            name: "Empty",
+            full_name: "google.protobuf.Empty",
+            amino_name: None,
+            prefix: None,
+            fields: &[],
+        };
+        &DESCRIPTOR
+    }
 }
@@ -1,46 +1,47 @@
 extern crate prost_amino as prost;
 use prost::Message;
 
+#[derive(Clone, PartialEq, Message)]
+pub struct PartsSetHeader {
+    #[prost(sint64, tag = "1")]
+    total: i64,
+    #[prost(bytes, tag = "2")]
+    hash: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct BlockID {
+    #[prost(bytes, tag = "1")]
+    hash: Vec<u8>,
+    #[prost(message, tag = "2")]
+    parts_header: Option<PartsSetHeader>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Heartbeat {
+    #[prost(bytes, tag = "1")]
+    pub validator_address: Vec<u8>,
+    #[prost(sint64)]
+    validator_index: i64,
+    #[prost(sint64)]
+    height: i64,
+    #[prost(sint64)]
+    round: i64,
+    #[prost(sint64)]
+    sequence: i64,
+    #[prost(bytes, optional)]
+    signature: Option<Vec<u8>>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+#[amino_name = "tendermint/socketpv/SignHeartbeatMsg"]
+struct SignHeartbeatMsg {
+    #[prost(message, tag = "1")]
+    heartbeat: Option<Heartbeat>,
+}
+
 #[test]
 fn amino() {
-    #[derive(Clone, PartialEq, Message)]
-    pub struct PartsSetHeader {
-        #[prost(sint64, tag = "1")]
-        total: i64,
-        #[prost(bytes, tag = "2")]
-        hash: Vec<u8>,
-    }
-
-    #[derive(Clone, PartialEq, Message)]
-    pub struct BlockID {
-        #[prost(bytes, tag = "1")]
-        hash: Vec<u8>,
-        #[prost(message, tag = "2")]
-        parts_header: Option<PartsSetHeader>,
-    }
-
-    #[derive(Clone, PartialEq, Message)]
-    struct Heartbeat {
-        #[prost(bytes, tag = "1")]
-        pub validator_address: Vec<u8>,
-        #[prost(sint64)]
-        validator_index: i64,
-        #[prost(sint64)]
-        height: i64,
-        #[prost(sint64)]
-        round: i64,
-        #[prost(sint64)]
-        sequence: i64,
-        #[prost(message)]
-        signature: Option<Vec<u8>>,
-    }
-
-    #[derive(Clone, PartialEq, Message)]
-    #[amino_name = "tendermint/socketpv/SignHeartbeatMsg"]
-    struct SignHeartbeatMsg {
-        #[prost(message, tag = "1")]
-        heartbeat: Option<Heartbeat>,
-    }
     let addr = vec![
         0xa3, 0xb2, 0xcc, 0xdd, 0x71, 0x86, 0xf1, 0x68, 0x5f, 0x21, 0xf2, 0x48, 0x2a, 0xf4, 0xfb,
         0x34, 0x46, 0xa8, 0x4b, 0x35,
@@ -68,6 +69,227 @@ fn amino() {
     ];
     assert_eq!(want, buf);
 
-    let hb2 = SignHeartbeatMsg::decode(want);
+    let hb2 = SignHeartbeatMsg::decode(&want[..]);
     assert_eq!(hb_msg, hb2.unwrap());
+
+    // A present signature is encoded as a plain bytes field, like a non-nil `[]byte` in go-amino.
+    let hb = Heartbeat {
+        signature: Some(vec![0xAB, 0xCD]),
+        ..Heartbeat::default()
+    };
+    let mut buf = vec![];
+    hb.encode(&mut buf).unwrap();
+    assert_eq!(buf, [0x32, 0x02, 0xAB, 0xCD]);
+    assert_eq!(Heartbeat::decode(&buf[..]).unwrap(), hb);
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Part {
+    #[prost(uint32, tag = "1")]
+    index: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Commit {
+    #[prost(sint64, optional, tag = "1")]
+    round: Option<i64>,
+    #[prost(string, optional, tag = "2")]
+    memo: Option<String>,
+    #[prost(message, repeated, nullable, tag = "3")]
+    parts: Vec<Option<Part>>,
+}
+
+#[test]
+fn nullable() {
+    // Present scalars are encoded even if they are zero, and nil elements of a slice of struct
+    // pointers are encoded with a zero length.
+    let commit = Commit {
+        round: Some(0),
+        memo: None,
+        parts: vec![Some(Part { index: 1 }), None, Some(Part { index: 2 })],
+    };
+    let mut buf = vec![];
+    commit.encode(&mut buf).unwrap();
+    assert_eq!(
+        buf,
+        [0x08, 0x00, 0x1A, 0x02, 0x08, 0x01, 0x1A, 0x00, 0x1A, 0x02, 0x08, 0x02]
+    );
+    assert_eq!(commit.encoded_len(), buf.len());
+    assert_eq!(Commit::decode(&buf[..]).unwrap(), commit);
+
+    // As in go-amino, empty elements are decoded as nil.
+    let commit = Commit {
+        parts: vec![Some(Part::default())],
+        ..Commit::default()
+    };
+    let mut buf = vec![];
+    commit.encode(&mut buf).unwrap();
+    assert_eq!(buf, [0x1A, 0x00]);
+    assert_eq!(Commit::decode(&buf[..]).unwrap().parts, vec![None]);
+}
+
+#[derive(Clone, PartialEq, Message)]
+struct Validator {
+    #[prost(bytes, tag = "1")]
+    address: [u8; 4],
+    #[prost(int8, tag = "2")]
+    round: i8,
+    #[prost(uint16, tag = "3")]
+    power: u16,
+    #[prost(sint64, repeated, tag = "4")]
+    votes: [i64; 3],
+}
+
+#[test]
+fn fixed_size_types() {
+    // Arrays are always encoded, even if they only hold zeros.
+    let mut buf = vec![];
+    Validator::default().encode(&mut buf).unwrap();
+    assert_eq!(
+        buf,
+        [0x0A, 0x04, 0x00, 0x00, 0x00, 0x00, 0x22, 0x03, 0x00, 0x00, 0x00]
+    );
+
+    let validator = Validator {
+        address: [0xDE, 0xAD, 0xBE, 0xEF],
+        round: -1,
+        power: 300,
+        votes: [1, -1, 2],
+    };
+    let mut buf = vec![];
+    validator.encode(&mut buf).unwrap();
+    assert_eq!(
+        buf,
+        [
+            0x0A, 0x04, 0xDE, 0xAD, 0xBE, 0xEF, 0x10, 0x01, 0x18, 0xAC, 0x02, 0x22, 0x03, 0x02,
+            0x01, 0x04,
+        ]
+    );
+    assert_eq!(validator.encoded_len(), buf.len());
+    assert_eq!(Validator::decode(&buf[..]).unwrap(), validator);
+
+    // Wrong array lengths and out of range integers are rejected.
+    assert!(Validator::decode(&[0x0A, 0x03, 0xDE, 0xAD, 0xBE][..]).is_err());
+    assert!(Validator::decode(&[0x22, 0x02, 0x02, 0x01][..]).is_err());
+    assert!(Validator::decode(&[0x10, 0x80, 0x02][..]).is_err());
+    assert!(Validator::decode(&[0x18, 0x80, 0x80, 0x04][..]).is_err());
+}
+
+#[derive(Clone, PartialEq, Message)]
+#[prost(amino)]
+struct Proposal {
+    #[prost(int64, binary = "fixed64", tag = "1")]
+    height: i64,
+    #[prost(uint32, binary = "fixed32", tag = "2")]
+    round: u32,
+    #[prost(double, unsafe, tag = "3")]
+    weight: f64,
+    #[prost(int64, write_empty, tag = "4")]
+    pol_round: i64,
+    #[prost(string, write_empty, tag = "5")]
+    memo: String,
+}
+
+#[test]
+fn struct_tags() {
+    // Zero values of write_empty fields are encoded, the other fields are skipped.
+    let mut buf = vec![];
+    Proposal::default().encode(&mut buf).unwrap();
+    assert_eq!(buf, [0x20, 0x00, 0x2A, 0x00]);
+
+    let proposal = Proposal {
+        height: 1,
+        round: 2,
+        weight: 0.5,
+        pol_round: -1,
+        memo: String::new(),
+    };
+    let mut buf = vec![];
+    proposal.encode(&mut buf).unwrap();
+    assert_eq!(
+        &buf[..9],
+        [0x09, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]
+    );
+    assert_eq!(&buf[9..14], [0x15, 0x02, 0x00, 0x00, 0x00]);
+    assert_eq!(proposal.encoded_len(), buf.len());
+    assert_eq!(Proposal::decode(&buf[..]).unwrap(), proposal);
+}
+
+#[test]
+fn generated_amino_names() {
+    use amino_options::{register_all, validator, Heartbeat, PubKeyEd25519, Validator};
+    use prost_amino::reflect::Reflect;
+
+    let names = register_all()
+        .iter()
+        .map(|descriptor| descriptor.amino_name.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        [
+            "tendermint/PubKeyEd25519",
+            "tendermint/Vote",
+            "tendermint/socketpv/SignHeartbeatMsg",
+        ]
+    );
+    assert_eq!(
+        PubKeyEd25519::descriptor().prefix,
+        Some([0x16, 0x24, 0xde, 0x64])
+    );
+    assert!(validator::Vote::descriptor().is_registered());
+    assert!(Heartbeat::descriptor().is_registered());
+    assert!(!Validator::descriptor().is_registered());
+
+    let pub_key = Validator::descriptor().field_by_name("pub_key").unwrap();
+    assert_eq!(pub_key.amino_name, Some("tendermint/PubKeyEd25519"));
+    assert_eq!(pub_key.prefix, Some([0x16, 0x24, 0xde, 0x64]));
+}
+
+#[test]
+fn amino_registered() {
+    use amino_options::{Heartbeat, PubKeyEd25519};
+    use prost_amino::amino::{name_to_disfix, AminoRegistered};
+
+    assert_eq!(PubKeyEd25519::AMINO_NAME, "tendermint/PubKeyEd25519");
+    assert_eq!(PubKeyEd25519::PREFIX, [0x16, 0x24, 0xde, 0x64]);
+    assert_eq!(
+        name_to_disfix(PubKeyEd25519::AMINO_NAME),
+        (PubKeyEd25519::DISAMB, PubKeyEd25519::PREFIX)
+    );
+
+    // The prefix follows the length of registered messages.
+    let mut buf = vec![];
+    Heartbeat::default().encode(&mut buf).unwrap();
+    assert_eq!(buf[1..5], Heartbeat::PREFIX);
+}
+
+#[test]
+fn dyn_messages() {
+    use amino_options::{Heartbeat, Validator};
+    use prost_amino::amino::AminoRegistered;
+    use prost_amino::DynMessage;
+
+    let validator = Validator {
+        address: vec![0xAB],
+        pub_key: vec![],
+        power: 10,
+    };
+    let messages: Vec<Box<dyn DynMessage>> =
+        vec![Box::new(Heartbeat::default()), Box::new(validator.clone())];
+    assert_eq!(
+        messages[0].amino_name(),
+        Some("tendermint/socketpv/SignHeartbeatMsg")
+    );
+    assert_eq!(messages[1].amino_name(), None);
+
+    // Registered messages are encoded with their prefix through the trait objects too.
+    assert_eq!(messages[0].encode_to_vec()[1..5], Heartbeat::PREFIX);
+
+    let buf = messages[1].encode_to_vec();
+    let mut decoded: Box<dyn DynMessage> = Box::new(Validator::default());
+    decoded.merge_from_slice(&buf).unwrap();
+    assert_eq!(
+        decoded.as_any().downcast_ref::<Validator>(),
+        Some(&validator)
+    );
 }
@@ -1,4 +1,3 @@
-use std::env;
 use std::fs;
 use std::io::Read;
 use std::io::Write;
@@ -8,7 +7,8 @@ use prost_build;
 use tempdir;
 
 /// Test which bootstraps protobuf.rs and compiler.rs from the .proto definitions in the Protobuf
-/// repo. Ensures that the checked-in compiled versions are up-to-date.
+/// repo, and amino.rs and gogoproto.rs from the options .protos of prost-build. Ensures that the
+/// checked-in compiled versions are up-to-date.
 #[test]
 fn bootstrap() {
     let protobuf = Path::new(prost_build::protoc_include())
@@ -16,9 +16,9 @@ fn bootstrap() {
         .join("protobuf");
 
     let tempdir = tempdir::TempDir::new("prost-types-bootstrap").unwrap();
-    env::set_var("OUT_DIR", tempdir.path());
 
     let mut config = prost_build::Config::new();
+    config.out_dir(tempdir.path());
     config.compile_well_known_types();
     config.btree_map(&["."]);
     config
@@ -42,6 +42,26 @@ fn bootstrap() {
         )
         .unwrap();
 
+    // The options extend the options messages of prost-types, which are in the parent module.
+    let options = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("no parent")
+        .join("prost-build")
+        .join("proto");
+    let mut config = prost_build::Config::new();
+    config.out_dir(tempdir.path());
+    config.compile_well_known_types();
+    config.extern_path(".google.protobuf", "super");
+    config
+        .compile_protos(
+            &[
+                options.join("amino").join("amino.proto"),
+                options.join("gogoproto").join("gogo.proto"),
+            ],
+            &[options],
+        )
+        .unwrap();
+
     let mut bootstrapped_protobuf = String::new();
     fs::File::open(tempdir.path().join("google.protobuf.rs"))
         .unwrap()
@@ -87,4 +107,26 @@ fn bootstrap() {
 
     assert_eq!(protobuf, bootstrapped_protobuf);
     assert_eq!(compiler, bootstrapped_compiler);
+
+    for name in &["amino.rs", "gogoproto.rs"] {
+        let mut bootstrapped = String::new();
+        fs::File::open(tempdir.path().join(name))
+            .unwrap()
+            .read_to_string(&mut bootstrapped)
+            .unwrap();
+
+        let mut checked_in = String::new();
+        fs::File::open(src.join(name))
+            .unwrap()
+            .read_to_string(&mut checked_in)
+            .unwrap();
+
+        if checked_in != bootstrapped {
+            fs::File::create(src.join(name))
+                .unwrap()
+                .write_all(bootstrapped.as_bytes())
+                .unwrap();
+        }
+        assert_eq!(checked_in, bootstrapped);
+    }
 }
@@ -31,6 +31,9 @@ fn main() {
     prost_build.field_attribute("Foo.Custom.Attrs.AnotherEnum.D", "/// The D docs");
     prost_build.field_attribute("Foo.Custom.Attrs.Msg.field.a", "/// Oneof A docs");
     prost_build.field_attribute("Foo.Custom.Attrs.Msg.field.b", "/// Oneof B docs");
+    // Tests for amino names, in addition to the ones set with options in the .proto file.
+    prost_build.amino_name(".amino_options.Validator.Vote", "tendermint/Vote");
+    prost_build.amino_name("Heartbeat", "tendermint/socketpv/SignHeartbeatMsg");
 
     prost_build
         .compile_protos(
@@ -53,10 +56,55 @@ fn main() {
         )
         .unwrap();
 
-    prost_build
+    // Types of the imported packages are reused from the modules generated above.
+    prost_build::Config::new()
+        .extern_path(".nesting", "::nesting")
+        .extern_path(".recursive_oneof", "::recursive_oneof")
+        .compile_protos(&["src/extern_paths.proto"], &["src"])
+        .unwrap();
+
+    // The nested packages are included through an include file.
+    prost_build::Config::new()
+        .include_file("_packages.rs")
         .compile_protos(&["src/packages/widget_factory.proto"], &["src/packages"])
         .unwrap();
 
+    // Builders are generated for all the messages of the package.
+    prost_build::Config::new()
+        .builder(&[".builder"])
+        .compile_protos(&["src/builder.proto"], &["src"])
+        .unwrap();
+
+    prost_build::Config::new()
+        .compile_protos(&["src/extensions.proto"], &["src"])
+        .unwrap();
+
+    prost_build::Config::new()
+        .compile_protos(&["src/groups.proto"], &["src"])
+        .unwrap();
+
+    prost_build::Config::new()
+        .compile_protos(&["src/json.proto"], &["src"])
+        .unwrap();
+
+    // Proto3 optional fields are newer than the bundled protoc, so they are parsed with the
+    // built-in parser.
+    prost_build::Config::new()
+        .builtin_parser()
+        .compile_protos(&["src/proto3_optional.proto"], &["src"])
+        .unwrap();
+
+    prost_build::Config::new()
+        .compile_protos(&["src/text_format.proto"], &["src"])
+        .unwrap();
+
+    // Services are generated with a dispatcher and a client which exchange length-delimited
+    // messages over a stream.
+    prost_build::Config::new()
+        .service_generator(Box::new(prost_build::SocketServiceGenerator))
+        .compile_protos(&["src/socket_service.proto"], &["src"])
+        .unwrap();
+
     prost_build
         .compile_protos(&["src/ident_conversion.proto"], &["src"])
         .unwrap();
@@ -80,4 +128,18 @@ fn main() {
     prost_build
         .compile_protos(&["src/no_unused_results.proto"], &["src"])
         .unwrap();
+
+    prost_build
+        .compile_protos(&["src/amino_options.proto"], &["src"])
+        .unwrap();
+
+    prost_build
+        .custom_type(
+            "github.com/tendermint/tendermint/libs/bytes.HexBytes",
+            "::gogo_options::HexBytes",
+        )
+        .custom_type("Height", "::gogo_options::Height")
+        .custom_type("github.com/cosmos/cosmos-sdk/types.Int", "::gogo_options::Int")
+        .compile_protos(&["src/gogo_options.proto"], &["src"])
+        .unwrap();
 }
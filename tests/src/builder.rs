@@ -0,0 +1,59 @@
+include!(concat!(env!("OUT_DIR"), "/builder.rs"));
+
+use prost_amino::BuildError;
+
+use check_message;
+
+#[test]
+fn builder() {
+    let validator = Validator::builder()
+        .address("cosmos1validator")
+        .pub_key(PubKey::builder().data(&b"key"[..]).build().unwrap())
+        .power(10)
+        .status(Status::Jailed)
+        .history(vec![Status::Active, Status::Jailed])
+        .aliases(vec!["alice", "bob"])
+        .stakes(vec![("alice".to_string(), 5)])
+        .proposer(Validator::default())
+        .descriptions(vec![validator::Description {
+            moniker: Some("val".to_string()),
+        }])
+        .reward(validator::Reward::Amount(7))
+        .build()
+        .unwrap();
+
+    assert_eq!(validator.address, "cosmos1validator");
+    assert_eq!(validator.pub_key.data, b"key".to_vec());
+    assert_eq!(validator.power, Some(10));
+    assert_eq!(validator.status(), Status::Jailed);
+    assert_eq!(
+        validator.history().collect::<Vec<_>>(),
+        vec![Status::Active, Status::Jailed]
+    );
+    assert_eq!(validator.aliases, vec!["alice", "bob"]);
+    assert_eq!(validator.stakes["alice"], 5);
+    assert_eq!(validator.proposer, Some(Box::new(Validator::default())));
+    assert_eq!(validator.reward, Some(validator::Reward::Amount(7)));
+    check_message(&validator);
+}
+
+#[test]
+fn builder_required_fields() {
+    assert_eq!(
+        Validator::builder().address("cosmos1validator").build(),
+        Err(BuildError::new("Validator", "pub_key"))
+    );
+    let error = PubKey::builder().build().unwrap_err();
+    assert_eq!(error.message(), "PubKey");
+    assert_eq!(error.field(), "data");
+    assert_eq!(
+        error.to_string(),
+        "failed to build PubKey message: required field data is not set"
+    );
+
+    // Optional fields don't have to be set.
+    assert_eq!(
+        validator::Description::builder().build(),
+        Ok(validator::Description::default())
+    );
+}
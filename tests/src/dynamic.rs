@@ -0,0 +1,272 @@
+//! Tests for dynamic messages built from a file descriptor set.
+
+use std::collections::HashMap;
+
+use prost_amino::dynamic::{DescriptorPool, Kind, MapKey, Value};
+use prost_amino::reflect::{Label, Reflect, Type};
+use prost_amino::Message;
+use prost_types::field_descriptor_proto::{Label as ProtoLabel, Type as ProtoType};
+use prost_types::{
+    DescriptorProto, EnumDescriptorProto, EnumValueDescriptorProto, FieldDescriptorProto,
+    FileDescriptorProto, FileDescriptorSet, MessageOptions,
+};
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Inner {
+    #[prost(string, tag = "1")]
+    pub name: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Outer {
+    #[prost(sint32, tag = "1")]
+    pub id: i32,
+    #[prost(message, repeated, tag = "2")]
+    pub inners: Vec<Inner>,
+    #[prost(map = "string, int64", tag = "3")]
+    pub counts: HashMap<String, i64>,
+    #[prost(enumeration = "Color", repeated, tag = "4")]
+    pub colors: Vec<i32>,
+    #[prost(message, optional, tag = "5")]
+    pub inner: Option<Inner>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+#[amino_name = "tendermint/PubKeyEd25519"]
+pub struct PubKey {
+    #[prost(bytes, tag = "1")]
+    pub key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Validator {
+    #[prost(message, optional, tag = "1")]
+    pub pub_key: Option<PubKey>,
+    #[prost(message, repeated, tag = "2")]
+    pub pub_keys: Vec<PubKey>,
+    #[prost(int64, tag = "3")]
+    pub power: i64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Enumeration)]
+pub enum Color {
+    Red = 0,
+    Green = 1,
+}
+
+fn field(name: &str, number: i32, label: ProtoLabel, ty: ProtoType) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        name: Some(name.to_string()),
+        number: Some(number),
+        label: Some(label as i32),
+        type_: Some(ty as i32),
+        ..Default::default()
+    }
+}
+
+fn typed_field(
+    name: &str,
+    number: i32,
+    label: ProtoLabel,
+    ty: ProtoType,
+    type_name: &str,
+) -> FieldDescriptorProto {
+    FieldDescriptorProto {
+        type_name: Some(type_name.to_string()),
+        ..field(name, number, label, ty)
+    }
+}
+
+fn file_descriptor_set() -> FileDescriptorSet {
+    let counts_entry = DescriptorProto {
+        name: Some("CountsEntry".to_string()),
+        field: vec![
+            field("key", 1, ProtoLabel::Optional, ProtoType::String),
+            field("value", 2, ProtoLabel::Optional, ProtoType::Int64),
+        ],
+        options: Some(MessageOptions {
+            map_entry: Some(true),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let outer = DescriptorProto {
+        name: Some("Outer".to_string()),
+        field: vec![
+            field("id", 1, ProtoLabel::Optional, ProtoType::Sint32),
+            typed_field(
+                "inners",
+                2,
+                ProtoLabel::Repeated,
+                ProtoType::Message,
+                ".dynamic.Inner",
+            ),
+            typed_field(
+                "counts",
+                3,
+                ProtoLabel::Repeated,
+                ProtoType::Message,
+                ".dynamic.Outer.CountsEntry",
+            ),
+            typed_field(
+                "colors",
+                4,
+                ProtoLabel::Repeated,
+                ProtoType::Enum,
+                ".dynamic.Color",
+            ),
+            typed_field(
+                "inner",
+                5,
+                ProtoLabel::Optional,
+                ProtoType::Message,
+                ".dynamic.Inner",
+            ),
+        ],
+        nested_type: vec![counts_entry],
+        ..Default::default()
+    };
+    let inner = DescriptorProto {
+        name: Some("Inner".to_string()),
+        field: vec![field("name", 1, ProtoLabel::Optional, ProtoType::String)],
+        ..Default::default()
+    };
+    let color = EnumDescriptorProto {
+        name: Some("Color".to_string()),
+        value: vec![
+            EnumValueDescriptorProto {
+                name: Some("RED".to_string()),
+                number: Some(0),
+                ..Default::default()
+            },
+            EnumValueDescriptorProto {
+                name: Some("GREEN".to_string()),
+                number: Some(1),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    };
+    FileDescriptorSet {
+        file: vec![FileDescriptorProto {
+            name: Some("dynamic.proto".to_string()),
+            package: Some("dynamic".to_string()),
+            message_type: vec![outer, inner],
+            enum_type: vec![color],
+            syntax: Some("proto3".to_string()),
+            ..Default::default()
+        }],
+    }
+}
+
+#[test]
+fn descriptor_pool() {
+    let pool = DescriptorPool::from(&file_descriptor_set());
+    assert!(pool.message("dynamic.Outer.CountsEntry").is_none());
+    assert_eq!(
+        pool.enumeration("dynamic.Color").unwrap().value_name(1),
+        Some("GREEN")
+    );
+
+    let outer = pool.message("dynamic.Outer").unwrap();
+    let id = outer.field_by_name("id").unwrap();
+    assert_eq!(id.label, Label::Plain);
+    assert_eq!(id.kind, Kind::Scalar(Type::Sint32));
+    let counts = outer.field_by_tag(3).unwrap();
+    assert_eq!(counts.label, Label::Repeated);
+    assert_eq!(
+        counts.kind,
+        Kind::Map(Type::String, Box::new(Kind::Scalar(Type::Int64)))
+    );
+    assert_eq!(outer.field_by_name("colors").unwrap().label, Label::Packed);
+}
+
+#[test]
+fn decode_and_reencode() {
+    let mut counts = HashMap::new();
+    counts.insert("a".to_string(), 3);
+    let message = Outer {
+        id: -7,
+        inners: vec![
+            Inner {
+                name: "x".to_string(),
+            },
+            Inner::default(),
+        ],
+        counts: counts,
+        colors: vec![Color::Green as i32, Color::Red as i32],
+        inner: Some(Inner::default()),
+    };
+    let mut buf = Vec::new();
+    message.encode(&mut buf).unwrap();
+
+    let pool = DescriptorPool::from(&file_descriptor_set());
+    let mut dynamic = pool.decode("dynamic.Outer", &buf[..]).unwrap();
+    assert_eq!(dynamic.get_by_name("id"), Some(&Value::I32(-7)));
+    match dynamic.get_by_tag(3) {
+        Some(&Value::Map(ref entries)) => {
+            assert_eq!(
+                entries.get(&MapKey::String("a".to_string())),
+                Some(&Value::I64(3))
+            )
+        }
+        value => panic!("unexpected value: {:?}", value),
+    }
+    assert_eq!(
+        dynamic.get_by_name("colors"),
+        Some(&Value::List(vec![Value::Enum(1), Value::Enum(0)]))
+    );
+
+    let mut reencoded = Vec::new();
+    dynamic.encode(&mut reencoded).unwrap();
+    assert_eq!(buf, reencoded);
+
+    dynamic.set_by_name("id", Value::I32(42)).unwrap();
+    dynamic.clear_by_name("inner");
+    let mut modified = Vec::new();
+    dynamic.encode(&mut modified).unwrap();
+    let decoded = Outer::decode(&modified[..]).unwrap();
+    assert_eq!(decoded.id, 42);
+    assert_eq!(decoded.inner, None);
+    assert_eq!(decoded.inners, message.inners);
+}
+
+#[test]
+fn nested_registered_messages() {
+    let validator = Validator {
+        pub_key: Some(PubKey { key: vec![1, 2, 3] }),
+        pub_keys: vec![PubKey { key: vec![4] }, PubKey::default()],
+        power: 10,
+    };
+    let mut buf = Vec::new();
+    validator.encode(&mut buf).unwrap();
+
+    let mut pool = DescriptorPool::new();
+    pool.add_reflected(Validator::descriptor()).unwrap();
+    let pub_key = |key: Vec<u8>| {
+        let mut pub_key = pool.new_message("PubKey").unwrap();
+        pub_key.set_by_name("key", Value::Bytes(key)).unwrap();
+        Value::Message(pub_key)
+    };
+    let mut dynamic = pool.new_message("Validator").unwrap();
+    dynamic
+        .set_by_name("pub_key", pub_key(vec![1, 2, 3]))
+        .unwrap();
+    dynamic
+        .set_by_name(
+            "pub_keys",
+            Value::List(vec![
+                pub_key(vec![4]),
+                Value::Message(pool.new_message("PubKey").unwrap()),
+            ]),
+        )
+        .unwrap();
+    dynamic.set_by_name("power", Value::I64(10)).unwrap();
+
+    // The registered messages are encoded with their prefix, like the derived messages.
+    let mut encoded = Vec::new();
+    dynamic.encode(&mut encoded).unwrap();
+    assert_eq!(encoded, buf);
+    assert_eq!(dynamic.encoded_len(), buf.len());
+    assert_eq!(pool.decode("Validator", &buf[..]).unwrap(), dynamic);
+}
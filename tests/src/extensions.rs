@@ -0,0 +1,100 @@
+include!(concat!(env!("OUT_DIR"), "/extensions.rs"));
+
+use prost_amino::Message;
+use prost_types::{FieldDescriptorProto, FieldOptions};
+
+use check_message;
+
+#[test]
+fn extensions() {
+    let mut message = Extendable {
+        name: Some("foo".to_string()),
+        ..Extendable::default()
+    };
+    assert_eq!(message.get_extension(&COUNT), Ok(None));
+
+    message.set_extension(&COUNT, 7);
+    message.set_extension(&TAGS, vec!["a".to_string(), "b".to_string()]);
+    message.set_extension(&COLOR, Color::Green as i32);
+    message.set_extension(&DELTAS, vec![-1, 1]);
+    message.set_extension(
+        &note::NOTE,
+        Note {
+            text: Some("bar".to_string()),
+        },
+    );
+    assert!(message.has_extension(&COUNT));
+    check_message(&message);
+
+    let mut buf = Vec::new();
+    message.encode(&mut buf).unwrap();
+    let decoded = Extendable::decode(&buf[..]).unwrap();
+    assert_eq!(decoded.get_extension(&COUNT), Ok(Some(7)));
+    assert_eq!(
+        decoded.get_extension(&TAGS),
+        Ok(Some(vec!["a".to_string(), "b".to_string()]))
+    );
+    assert_eq!(decoded.get_extension(&COLOR), Ok(Some(Color::Green as i32)));
+    assert_eq!(decoded.get_extension(&DELTAS), Ok(Some(vec![-1, 1])));
+    assert_eq!(
+        decoded.get_extension(&note::NOTE).unwrap().unwrap().text,
+        Some("bar".to_string())
+    );
+
+    message.clear_extension(&COUNT);
+    assert!(!message.has_extension(&COUNT));
+    assert_eq!(message.get_extension(&COUNT), Ok(None));
+}
+
+#[test]
+fn extension_descriptors() {
+    assert_eq!(COUNT.name, "extensions.count");
+    assert_eq!(COUNT.number, 100);
+    assert_eq!(note::NOTE.name, "extensions.Note.note");
+    assert_eq!(note::NOTE.number, 1000);
+}
+
+#[test]
+fn fields_outside_extension_ranges_are_skipped() {
+    // Field 2 is neither a field nor in an extension range, field 150 is an extension.
+    let buf = [0x10, 0x01, 0xB0, 0x09, 0x02];
+    let message = Extendable::decode(&buf[..]).unwrap();
+    let mut encoded = Vec::new();
+    message.encode(&mut encoded).unwrap();
+    assert_eq!(encoded, [0xB0, 0x09, 0x02]);
+}
+
+#[test]
+fn custom_options() {
+    let mut options = FieldOptions::default();
+    options.set_extension(&SENSITIVE, true);
+    options.set_extension(&::prost_types::gogoproto::NULLABLE, false);
+    options.set_extension(
+        &::prost_types::amino::FIELD_NAME,
+        "tendermint/PubKeyEd25519".to_string(),
+    );
+    let field = FieldDescriptorProto {
+        name: Some("pub_key".to_string()),
+        options: Some(options),
+        ..FieldDescriptorProto::default()
+    };
+
+    // The options are retained when the descriptors are decoded.
+    let mut buf = Vec::new();
+    field.encode(&mut buf).unwrap();
+    let field = FieldDescriptorProto::decode(&buf[..]).unwrap();
+    let options = field.options.unwrap();
+    assert_eq!(options.get_extension(&SENSITIVE), Ok(Some(true)));
+    assert_eq!(
+        options.get_extension(&::prost_types::gogoproto::NULLABLE),
+        Ok(Some(false))
+    );
+    assert_eq!(
+        options.get_extension(&::prost_types::amino::FIELD_NAME),
+        Ok(Some("tendermint/PubKeyEd25519".to_string()))
+    );
+    assert_eq!(
+        options.get_extension(&::prost_types::gogoproto::CUSTOMNAME),
+        Ok(None)
+    );
+}
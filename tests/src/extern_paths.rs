@@ -0,0 +1,33 @@
+include!(concat!(env!("OUT_DIR"), "/extern_paths.rs"));
+
+use std::collections::HashMap;
+
+use check_message;
+use nesting;
+use recursive_oneof;
+
+#[test]
+fn extern_types() {
+    let outer = Outer {
+        a: Some(nesting::A {
+            b: Some(Box::new(nesting::B { a: None })),
+            ..nesting::A::default()
+        }),
+        b: vec![nesting::B::default()],
+        map_a: HashMap::new(),
+        recursive: Some(recursive_oneof::A {
+            kind: Some(recursive_oneof::a::Kind::C(recursive_oneof::C {})),
+        }),
+        c: Some(recursive_oneof::C {}),
+        kind: nesting::Kind::B as i32,
+        kinds: vec![nesting::Kind::A as i32, nesting::Kind::B as i32],
+        map_kind: vec![("b".to_string(), nesting::Kind::B as i32)]
+            .into_iter()
+            .collect(),
+    };
+    check_message(&outer);
+
+    // The accessors of extern enumeration fields use the extern type.
+    assert_eq!(outer.kind(), nesting::Kind::B);
+    assert_eq!(outer.get_map_kind("b"), Some(nesting::Kind::B));
+}
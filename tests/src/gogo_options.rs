@@ -0,0 +1,194 @@
+include!(concat!(env!("OUT_DIR"), "/gogo_options.rs"));
+
+use std::collections::BTreeMap;
+use std::time::{Duration, UNIX_EPOCH};
+
+use prost_amino::proxy::Proxy;
+use prost_amino::{DecodeError, Message};
+use prost_types;
+
+use check_message;
+use check_serialize_equivalent;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HexBytes(pub Vec<u8>);
+
+impl From<Vec<u8>> for HexBytes {
+    fn from(bytes: Vec<u8>) -> HexBytes {
+        HexBytes(bytes)
+    }
+}
+
+impl<'a> From<&'a HexBytes> for Vec<u8> {
+    fn from(bytes: &'a HexBytes) -> Vec<u8> {
+        bytes.0.clone()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Height(pub i64);
+
+impl From<i64> for Height {
+    fn from(height: i64) -> Height {
+        Height(height)
+    }
+}
+
+impl<'a> From<&'a Height> for i64 {
+    fn from(height: &'a Height) -> i64 {
+        height.0
+    }
+}
+
+/// An integer encoded as a decimal string, like the Cosmos SDK `Int`, which rejects the empty
+/// string that a proto3 string field defaults to.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Int(pub i64);
+
+impl Proxy<String> for Int {
+    fn to_proxy(&self) -> String {
+        self.0.to_string()
+    }
+
+    fn from_proxy(proxy: String) -> Result<Int, DecodeError> {
+        proxy
+            .parse()
+            .map(Int)
+            .map_err(|_| DecodeError::new(format!("invalid integer: {:?}", proxy)))
+    }
+}
+
+/// `Vote`, with the Protobuf field types.
+#[derive(Clone, PartialEq, Message)]
+struct ProtoVote {
+    #[prost(message, required, tag = "1")]
+    timestamp: prost_types::Timestamp,
+    #[prost(message, optional, tag = "2")]
+    expires: Option<prost_types::Timestamp>,
+    #[prost(message, optional, tag = "3")]
+    timeout: Option<prost_types::Duration>,
+    #[prost(bytes, tag = "4")]
+    validator_address: Vec<u8>,
+    #[prost(int64, repeated, packed = "false", tag = "5")]
+    heights: Vec<i64>,
+    #[prost(string, tag = "6")]
+    power: String,
+    #[prost(string, tag = "7")]
+    amount: String,
+}
+
+#[test]
+fn gogo_options() {
+    let block = Block {
+        header: Header { height: 10 },
+        prev_commit: Some(Commit {
+            height: 9,
+            signatures: vec![vec![1, 2, 3]],
+        }),
+        data: Some(Data {
+            txs: vec![vec![4, 5]],
+        }),
+        hash: vec![6; 20],
+        chain_id: "test-chain".to_string(),
+        proofs: BTreeMap::new(),
+    };
+    check_message(&block);
+
+    // A non-nullable message field is always encoded, like in Go.
+    assert_eq!(Block::default().encoded_len(), 2);
+}
+
+#[test]
+fn gogo_custom_types() {
+    let vote = Vote {
+        timestamp: UNIX_EPOCH + Duration::new(1_500_000_000, 123),
+        expires: Some(UNIX_EPOCH - Duration::new(1, 500_000_000)),
+        timeout: Some(Duration::from_millis(1500)),
+        validator_address: HexBytes(vec![1, 2, 3]),
+        heights: vec![Height(1), Height(2)],
+        power: "1.5".to_string(),
+        amount: Int(-7),
+    };
+    check_message(&vote);
+    check_serialize_equivalent(
+        &vote,
+        &ProtoVote {
+            timestamp: prost_types::Timestamp {
+                seconds: 1_500_000_000,
+                nanos: 123,
+            },
+            expires: Some(prost_types::Timestamp {
+                seconds: -2,
+                nanos: 500_000_000,
+            }),
+            timeout: Some(prost_types::Duration {
+                seconds: 1,
+                nanos: 500_000_000,
+            }),
+            validator_address: vec![1, 2, 3],
+            heights: vec![1, 2],
+            power: "1.5".to_string(),
+            amount: "-7".to_string(),
+        },
+    );
+
+    let mut default = Vote::default();
+    assert_eq!(default.timestamp, UNIX_EPOCH);
+    assert_eq!(default.validator_address, HexBytes::default());
+
+    // Fields default to the `Default` value of their type, since the proxy rejects an empty string.
+    assert_eq!(default.amount, Int(0));
+    default.amount = Int(5);
+    default.clear();
+    assert_eq!(default, Vote::default());
+}
+
+#[test]
+fn gogo_custom_types_invalid() {
+    let decode = |proto: ProtoVote| {
+        let mut buf = Vec::new();
+        proto.encode(&mut buf).unwrap();
+        Vote::decode(&buf[..])
+    };
+    let proto = ProtoVote {
+        timestamp: prost_types::Timestamp::default(),
+        expires: None,
+        timeout: None,
+        validator_address: Vec::new(),
+        heights: Vec::new(),
+        power: String::new(),
+        amount: String::new(),
+    };
+    assert_eq!(decode(proto.clone()).unwrap().amount, Int(0));
+    assert!(decode(ProtoVote {
+        amount: "1.5".to_string(),
+        ..proto.clone()
+    })
+    .is_err());
+
+    // Timestamps which `SystemTime` can't represent are rejected instead of panicking.
+    let timestamp = prost_types::Timestamp {
+        seconds: i64::MIN,
+        nanos: -1,
+    };
+    assert!(decode(ProtoVote {
+        expires: Some(timestamp.clone()),
+        ..proto.clone()
+    })
+    .is_err());
+    assert!(decode(ProtoVote {
+        timestamp,
+        ..proto.clone()
+    })
+    .is_err());
+
+    // Negative durations are rejected instead of being truncated to zero.
+    assert!(decode(ProtoVote {
+        timeout: Some(prost_types::Duration {
+            seconds: -5,
+            nanos: 0,
+        }),
+        ..proto
+    })
+    .is_err());
+}
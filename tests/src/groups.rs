@@ -0,0 +1,174 @@
+include!(concat!(env!("OUT_DIR"), "/groups.rs"));
+
+use prost_amino::dynamic::DescriptorPool;
+use prost_amino::json::{from_json, to_json};
+use prost_amino::reflect::Reflect;
+use prost_amino::schema::to_proto_with_package;
+use prost_amino::text_format::{from_text, to_text};
+use prost_amino::Message;
+
+use check_message;
+
+fn groups() -> Groups {
+    Groups {
+        id: Some(1),
+        optional: Some(groups::Optional {
+            name: Some("foo".to_string()),
+            nested: Some(groups::optional::Nested { value: Some(42) }),
+        }),
+        repeated: vec![
+            groups::Repeated { number: Some(-1) },
+            groups::Repeated { number: None },
+        ],
+        choice: Some(groups::Choice::Chosen(groups::Chosen { flag: Some(true) })),
+    }
+}
+
+#[test]
+fn groups_roundtrip() {
+    check_message(&groups());
+    check_message(&RequiredGroup {
+        header: required_group::Header { version: 3 },
+    });
+
+    let mut buf = Vec::new();
+    RequiredGroup::default().encode(&mut buf).unwrap();
+    // The group is delimited by a start group key and an end group key.
+    assert_eq!(buf, [0x0B, 0x10, 0x00, 0x0C]);
+}
+
+#[test]
+fn groups_encoding() {
+    let message = Groups {
+        optional: Some(groups::Optional {
+            name: Some("a".to_string()),
+            nested: None,
+        }),
+        ..Groups::default()
+    };
+    let mut buf = Vec::new();
+    message.encode(&mut buf).unwrap();
+    assert_eq!(buf, [0x13, 0x1A, 0x01, b'a', 0x14]);
+}
+
+#[test]
+fn unknown_groups_are_skipped() {
+    let mut buf = Vec::new();
+    groups().encode(&mut buf).unwrap();
+    let message = Skipping::decode(&buf[..]).unwrap();
+    assert_eq!(message.id, Some(1));
+
+    // The end group key must match the start group key.
+    assert!(Skipping::decode(&[0x13, 0x1C][..]).is_err());
+    // A group must be terminated.
+    assert!(Skipping::decode(&[0x13, 0x08, 0x01][..]).is_err());
+}
+
+/// A group nested in itself, which can't be declared in a .proto file.
+#[derive(Clone, PartialEq, Message)]
+pub struct Tree {
+    #[prost(group, optional, boxed, tag = "1")]
+    pub child: Option<Box<Tree>>,
+}
+
+#[test]
+fn nested_groups() {
+    let nested = |depth: usize| {
+        let mut buf = vec![0x0B; depth];
+        buf.extend(vec![0x0C; depth]);
+        buf
+    };
+
+    let mut depth = 0;
+    let mut tree = Tree::decode(&nested(100)[..]).unwrap();
+    while let Some(child) = tree.child {
+        tree = *child;
+        depth += 1;
+    }
+    assert_eq!(depth, 100);
+
+    // Deeper groups are rejected instead of overflowing the stack.
+    assert!(Tree::decode(&nested(101)[..]).is_err());
+    assert!(Tree::decode(&nested(1_000_000)[..]).is_err());
+
+    let mut pool = DescriptorPool::new();
+    pool.add_reflected(Tree::descriptor()).unwrap();
+    assert!(pool.decode("Tree", &nested(100)[..]).is_ok());
+    assert!(pool.decode("Tree", &nested(101)[..]).is_err());
+    assert!(pool.decode("Tree", &nested(1_000_000)[..]).is_err());
+}
+
+#[test]
+fn groups_dynamic() {
+    let mut pool = DescriptorPool::new();
+    pool.add_reflected(Groups::descriptor()).unwrap();
+
+    let mut buf = Vec::new();
+    groups().encode(&mut buf).unwrap();
+    let message = pool.decode("groups.Groups", &buf[..]).unwrap();
+    assert!(message.unknown_fields().is_empty());
+    assert_eq!(message.encoded_len(), buf.len());
+
+    let mut encoded = Vec::new();
+    message.encode(&mut encoded).unwrap();
+    assert_eq!(encoded, buf);
+
+    // The end group key must match the start group key.
+    assert!(pool.decode("groups.Groups", &[0x13, 0x1C][..]).is_err());
+}
+
+#[test]
+fn groups_json() {
+    let json = to_json(&groups()).unwrap();
+    assert_eq!(
+        json,
+        concat!(
+            r#"{"id":1,"optional":{"name":"foo","nested":{"value":"42"}},"#,
+            r#""repeated":[{"number":-1},{}],"chosen":{"flag":true}}"#
+        )
+    );
+    assert_eq!(from_json::<Groups>(&json), Ok(groups()));
+}
+
+#[test]
+fn groups_text_format() {
+    // Groups are named by their type name.
+    let text = to_text(&groups()).unwrap();
+    assert_eq!(
+        text,
+        r#"id: 1
+Optional {
+  name: "foo"
+  Nested {
+    value: 42
+  }
+}
+Repeated {
+  number: -1
+}
+Repeated {
+}
+Chosen {
+  flag: true
+}
+"#
+    );
+    assert_eq!(from_text::<Groups>(&text), Ok(groups()));
+
+    // Group field names are accepted too.
+    assert_eq!(
+        from_text::<RequiredGroup>("header { version: 3 }"),
+        Ok(RequiredGroup {
+            header: required_group::Header { version: 3 },
+        })
+    );
+}
+
+#[test]
+fn groups_schema() {
+    let schema = to_proto_with_package("groups", &[RequiredGroup::descriptor()]);
+    assert!(schema.contains("syntax = \"proto2\";"));
+    assert!(schema.contains(
+        "message RequiredGroup {\n  required group Header = 1 {\n    required int32 version = 2;\n  }\n}\n"
+    ));
+}
@@ -0,0 +1,238 @@
+include!(concat!(env!("OUT_DIR"), "/json.rs"));
+
+use prost_amino::json::{from_json, to_json};
+use prost_amino::Message;
+use prost_types::{value, Any, Duration, FieldMask, ListValue, Struct, Timestamp, Value};
+
+fn account() -> Account {
+    Account {
+        account_name: "alice".to_string(),
+        balance: -9_007_199_254_740_993,
+        sequence: 7,
+        pub_key: vec![0xFB, 0xFF, 0x00],
+        ratio: 0.5,
+        weight: 1.25,
+        active: true,
+        status: Status::Frozen as i32,
+        scores: vec![1, -2],
+        limits: vec![("daily".to_string(), 100)].into_iter().collect(),
+        parent: Some(Box::new(Account {
+            account_name: "root".to_string(),
+            ..Account::default()
+        })),
+        memo: "hi\n\"there\"".to_string(),
+        owner: Some(account::Owner::Index(u64::max_value())),
+    }
+}
+
+#[test]
+fn print() {
+    assert_eq!(to_json(&Account::default()).unwrap(), "{}");
+    assert_eq!(
+        to_json(&account()).unwrap(),
+        concat!(
+            r#"{"accountName":"alice","balance":"-9007199254740993","sequence":7,"#,
+            r#""pubKey":"+/8A","ratio":0.5,"weight":1.25,"active":true,"status":"STATUS_FROZEN","#,
+            r#""scores":[1,-2],"limits":{"daily":"100"},"parent":{"accountName":"root"},"#,
+            r#""note":"hi\n\"there\"","index":"18446744073709551615"}"#
+        )
+    );
+}
+
+#[test]
+fn parse() {
+    let account = account();
+    assert_eq!(
+        from_json::<Account>(&to_json(&account).unwrap()),
+        Ok(account.clone())
+    );
+
+    // Original field names, quoted numbers, enum numbers and URL safe base64 are accepted, and null
+    // is the default value.
+    let parsed = from_json::<Account>(
+        r#"{
+            "account_name": "alice",
+            "balance": -9007199254740993,
+            "sequence": "7",
+            "pub_key": "-_8A",
+            "ratio": "0.5",
+            "weight": 1.25,
+            "active": true,
+            "status": 2,
+            "scores": [1, "-2e0"],
+            "limits": {"daily": 100},
+            "parent": {"account_name": "root", "balance": null},
+            "note": "hi\n\"there\"",
+            "address": null,
+            "index": "18446744073709551615"
+        }"#,
+    );
+    assert_eq!(parsed, Ok(account));
+
+    for invalid in &[
+        r#"{"unknown": 1}"#,
+        r#"{"sequence": -1}"#,
+        r#"{"sequence": 1.5}"#,
+        r#"{"weight": 1e39}"#,
+        r#"{"status": "FROZEN"}"#,
+        r#"{"memo": "a", "note": "b"}"#,
+        r#"{"address": "a", "index": 1}"#,
+        r#"{"active": "true"}"#,
+        r#"{"scores": 1}"#,
+        r#"[]"#,
+    ] {
+        assert!(from_json::<Account>(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn well_known_types() {
+    let mut packed = Vec::new();
+    Account {
+        account_name: "bob".to_string(),
+        ..Account::default()
+    }
+    .encode(&mut packed)
+    .unwrap();
+    let mut packed_duration = Vec::new();
+    Duration {
+        seconds: 1,
+        nanos: 0,
+    }
+    .encode(&mut packed_duration)
+    .unwrap();
+
+    let event = Event {
+        time: Some(Timestamp {
+            seconds: 1_553_106_310,
+            nanos: 10_000_000,
+        }),
+        timeout: Some(Duration {
+            seconds: -1,
+            nanos: -500_000_000,
+        }),
+        mask: Some(FieldMask {
+            paths: vec!["account.account_name".to_string(), "time".to_string()],
+        }),
+        attributes: Some(Struct {
+            fields: vec![(
+                "key".to_string(),
+                Value {
+                    kind: Some(value::Kind::ListValue(ListValue {
+                        values: vec![
+                            Value {
+                                kind: Some(value::Kind::NullValue(0)),
+                            },
+                            Value {
+                                kind: Some(value::Kind::NumberValue(1.5)),
+                            },
+                        ],
+                    })),
+                },
+            )]
+            .into_iter()
+            .collect(),
+        }),
+        value: Some(Value {
+            kind: Some(value::Kind::StringValue("foo".to_string())),
+        }),
+        values: Some(ListValue { values: Vec::new() }),
+        height: Some(0),
+        label: Some("bar".to_string()),
+        payload: Some(Any {
+            type_url: "type.googleapis.com/json.Account".to_string(),
+            value: packed,
+        }),
+        payloads: vec![Any {
+            type_url: "type.googleapis.com/google.protobuf.Duration".to_string(),
+            value: packed_duration,
+        }],
+        account: None,
+    };
+
+    let json = concat!(
+        r#"{"time":"2019-03-20T18:25:10.010Z","timeout":"-1.500s","#,
+        r#""mask":"account.accountName,time","attributes":{"key":[null,1.5]},"#,
+        r#""value":"foo","values":[],"height":"0","label":"bar","#,
+        r#""payload":{"@type":"type.googleapis.com/json.Account","accountName":"bob"},"#,
+        r#""payloads":[{"@type":"type.googleapis.com/google.protobuf.Duration","value":"1s"}]}"#
+    );
+    assert_eq!(to_json(&event).unwrap(), json);
+    assert_eq!(from_json::<Event>(json), Ok(event));
+
+    let parsed = from_json::<Event>(
+        r#"{"time": "2019-03-20T13:25:10.01-05:00", "value": null, "height": "5"}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        parsed.time,
+        Some(Timestamp {
+            seconds: 1_553_106_310,
+            nanos: 10_000_000,
+        })
+    );
+    assert_eq!(
+        parsed.value,
+        Some(Value {
+            kind: Some(value::Kind::NullValue(0)),
+        })
+    );
+    assert_eq!(parsed.height, Some(5));
+
+    for invalid in &[
+        r#"{"time": "2019-03-20 18:25:10Z"}"#,
+        r#"{"timeout": "1.5"}"#,
+        r#"{"mask": "account_name"}"#,
+        r#"{"payload": {"@type": "type.googleapis.com/json.Unknown"}}"#,
+        r#"{"payload": {"accountName": "bob"}}"#,
+    ] {
+        assert!(from_json::<Event>(invalid).is_err(), "{}", invalid);
+    }
+
+    // Timestamps out of the RFC 3339 range can't be printed.
+    let event = Event {
+        time: Some(Timestamp {
+            seconds: i64::max_value(),
+            nanos: 0,
+        }),
+        ..Event::default()
+    };
+    assert!(to_json(&event).is_err());
+}
+
+mod first {
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Header {
+        #[prost(string, tag = "1")]
+        pub chain_id: String,
+    }
+}
+
+mod second {
+    #[derive(Clone, PartialEq, Message)]
+    pub struct Header {
+        #[prost(uint64, tag = "1")]
+        pub height: u64,
+    }
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Headers {
+    #[prost(message, optional, tag = "1")]
+    pub first: Option<first::Header>,
+    #[prost(message, optional, tag = "2")]
+    pub second: Option<second::Header>,
+}
+
+#[test]
+fn duplicate_names() {
+    // Both header types are named `Header`, so they can't be told apart by name.
+    let headers = Headers {
+        first: Some(first::Header {
+            chain_id: "test-chain".to_string(),
+        }),
+        second: Some(second::Header { height: 1 }),
+    };
+    let error = to_json(&headers).unwrap_err();
+    assert!(error.to_string().contains("Header"), "{}", error);
+}
@@ -1,5 +1,6 @@
 extern crate bytes;
 extern crate prost_amino;
+extern crate prost_amino as prost;
 extern crate prost_types;
 
 #[macro_use]
@@ -17,11 +18,35 @@ pub mod unittest;
 #[cfg(test)]
 mod bootstrap;
 #[cfg(test)]
+mod builder;
+#[cfg(test)]
 mod debug;
 #[cfg(test)]
+mod dynamic;
+#[cfg(test)]
+mod extensions;
+#[cfg(test)]
+mod extern_paths;
+#[cfg(test)]
+mod gogo_options;
+#[cfg(test)]
+mod groups;
+#[cfg(test)]
+mod json;
+#[cfg(test)]
 mod message_encoding;
 #[cfg(test)]
 mod no_unused_results;
+#[cfg(test)]
+mod proto3_optional;
+#[cfg(test)]
+mod reflect;
+#[cfg(test)]
+mod schema;
+#[cfg(all(test, unix))]
+mod socket_service;
+#[cfg(test)]
+mod text_format;
 
 pub mod protobuf_test_messages {
     pub mod proto2 {
@@ -58,6 +83,10 @@ pub mod recursive_oneof {
     include!(concat!(env!("OUT_DIR"), "/recursive_oneof.rs"));
 }
 
+pub mod amino_options {
+    include!(concat!(env!("OUT_DIR"), "/amino_options.rs"));
+}
+
 /// This tests the custom attributes support by abusing docs.
 ///
 /// Docs really are full-blown attributes. So we use them to ensure we can place them on everything
@@ -148,7 +177,7 @@ where
         );
     }
 
-    let roundtrip = match M::decode(&buf1) {
+    let roundtrip = match M::decode(&buf1[..]) {
         Ok(roundtrip) => roundtrip,
         Err(error) => return RoundtripResult::Error(error.into()),
     };
@@ -183,10 +212,11 @@ where
     msg.encode(&mut buf).unwrap();
     assert_eq!(expected_len, buf.len());
 
+    let mut buf = &buf[..];
     let roundtrip = M::decode(&mut buf).unwrap();
 
     if buf.has_remaining() {
-        panic!(format!("expected buffer to be empty: {}", buf.remaining()));
+        panic!("expected buffer to be empty: {}", buf.remaining());
     }
 
     assert_eq!(msg, &roundtrip);
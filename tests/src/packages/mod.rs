@@ -1,22 +1,13 @@
-//! Tests nested packages.
+//! Tests nested packages, included with an include file.
 
-include!(concat!(env!("OUT_DIR"), "/packages.rs"));
-
-pub mod gizmo {
-    include!(concat!(env!("OUT_DIR"), "/packages.gizmo.rs"));
-}
-
-pub mod widget {
-    include!(concat!(env!("OUT_DIR"), "/packages.widget.rs"));
-    pub mod factory {
-        include!(concat!(env!("OUT_DIR"), "/packages.widget.factory.rs"));
-    }
-}
+include!(concat!(env!("OUT_DIR"), "/_packages.rs"));
 
 #[test]
 fn test() {
     use prost::Message;
 
+    use self::packages::*;
+
     let mut widget_factory = widget::factory::WidgetFactory::default();
     assert_eq!(0, widget_factory.encoded_len());
 
@@ -0,0 +1,43 @@
+include!(concat!(env!("OUT_DIR"), "/proto3_optional.rs"));
+
+use prost_amino::Message;
+
+use check_message;
+
+#[test]
+fn proto3_optional_presence() {
+    let mut buf = Vec::new();
+    Params::default().encode(&mut buf).unwrap();
+    assert!(buf.is_empty());
+
+    // Explicitly set default values are encoded.
+    let params = Params {
+        max_validators: Some(0),
+        memo: Some(String::new()),
+        level: Some(Level::Low as i32),
+        ..Params::default()
+    };
+    buf.clear();
+    params.encode(&mut buf).unwrap();
+    assert_eq!(buf, [0x08, 0x00, 0x12, 0x00, 0x18, 0x00]);
+
+    let decoded = Params::decode(&buf[..]).unwrap();
+    assert_eq!(decoded.max_validators, Some(0));
+    assert_eq!(decoded.memo, Some(String::new()));
+    assert_eq!(decoded.level(), Level::Low);
+    assert_eq!(decoded.nested, None);
+}
+
+#[test]
+fn proto3_optional_roundtrip() {
+    check_message(&Params {
+        max_validators: Some(100),
+        memo: None,
+        level: Some(Level::High as i32),
+        nested: Some(Box::new(Params {
+            max_validators: Some(0),
+            ..Params::default()
+        })),
+        change: Some(params::Change::Height(7)),
+    });
+}
@@ -0,0 +1,130 @@
+//! Tests for the reflection descriptors generated by the derive.
+
+use std::collections::HashMap;
+
+use prost_amino::reflect::{FieldKind, Label, Reflect, Type};
+
+// Borrow some types from other places.
+use message_encoding::BasicEnumeration;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Tree {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(enumeration = "BasicEnumeration", repeated, tag = "2")]
+    pub kinds: Vec<i32>,
+    #[prost(message, repeated, tag = "3")]
+    pub children: Vec<Tree>,
+    #[prost(message, optional, boxed, tag = "4")]
+    pub parent: Option<Box<Tree>>,
+    #[prost(map = "string, message", tag = "5")]
+    pub leaves: HashMap<String, Leaf>,
+    #[prost(oneof = "Payload", tags = "6, 7")]
+    pub payload: Option<Payload>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+#[amino_name = "tendermint/socketpv/SignHeartbeatMsg"]
+pub struct Leaf {
+    #[prost(sint64, tag = "1")]
+    pub height: i64,
+    #[prost(bytes, tag = "2", amino_name = "test")]
+    pub key: Vec<u8>,
+}
+
+#[derive(Clone, PartialEq, Oneof)]
+pub enum Payload {
+    #[prost(uint32, tag = "6")]
+    Number(u32),
+    #[prost(message, tag = "7")]
+    Leaf(Leaf),
+}
+
+#[test]
+fn message_descriptor() {
+    let descriptor = Tree::descriptor();
+    assert_eq!(descriptor.name, "Tree");
+    assert_eq!(descriptor.amino_name, None);
+    assert_eq!(descriptor.prefix, None);
+
+    let names = descriptor
+        .fields
+        .iter()
+        .map(|field| field.name)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["name", "kinds", "children", "parent", "leaves", "payload"]
+    );
+
+    let name = descriptor.field_by_name("name").unwrap();
+    assert_eq!(name.tag(), 1);
+    assert_eq!(name.label, Label::Plain);
+    match name.kind {
+        FieldKind::Scalar(ty) => assert_eq!(ty, Type::String),
+        ref kind => panic!("unexpected kind: {:?}", kind),
+    }
+
+    let kinds = descriptor.field_by_tag(2).unwrap();
+    assert_eq!(kinds.label, Label::Packed);
+    match kinds.kind {
+        FieldKind::Enumeration(descriptor) => {
+            let descriptor = descriptor();
+            assert_eq!(descriptor.name, "BasicEnumeration");
+            assert_eq!(descriptor.value_name(2), Some("TWO"));
+            assert_eq!(descriptor.value_number("THREE"), Some(3));
+        }
+        ref kind => panic!("unexpected kind: {:?}", kind),
+    }
+
+    for &(name, label) in &[("children", Label::Repeated), ("parent", Label::Optional)] {
+        let field = descriptor.field_by_name(name).unwrap();
+        assert_eq!(field.label, label);
+        match field.kind {
+            FieldKind::Message(nested) => assert_eq!(nested().name, "Tree"),
+            ref kind => panic!("unexpected kind: {:?}", kind),
+        }
+    }
+
+    let leaves = descriptor.field_by_name("leaves").unwrap();
+    match leaves.kind {
+        FieldKind::Map {
+            key: Type::String,
+            value: &FieldKind::Message(value),
+        } => assert_eq!(value().name, "Leaf"),
+        ref kind => panic!("unexpected kind: {:?}", kind),
+    }
+
+    let payload = descriptor.field_by_name("payload").unwrap();
+    assert_eq!(payload.tags, &[6, 7]);
+    assert_eq!(descriptor.field_by_tag(6).unwrap().name, "Number");
+    assert_eq!(descriptor.field_by_tag(7).unwrap().name, "Leaf");
+    assert!(descriptor.field_by_tag(8).is_none());
+}
+
+#[test]
+fn amino_descriptor() {
+    let descriptor = Leaf::descriptor();
+    assert_eq!(
+        descriptor.amino_name,
+        Some("tendermint/socketpv/SignHeartbeatMsg")
+    );
+    assert_eq!(descriptor.prefix, Some([0xbf, 0x58, 0xca, 0xef]));
+
+    let key = descriptor.field_by_name("key").unwrap();
+    assert_eq!(key.amino_name, Some("test"));
+    assert_eq!(key.prefix, Some([0x81, 0x88, 0x4c, 0x7d]));
+    assert_eq!(descriptor.field_by_name("height").unwrap().prefix, None);
+}
+
+#[test]
+fn oneof_descriptor() {
+    let descriptor = Payload::descriptor();
+    assert_eq!(descriptor.name, "Payload");
+    let number = descriptor.field_by_tag(6).unwrap();
+    assert_eq!(number.label, Label::Required);
+    match number.kind {
+        FieldKind::Scalar(ty) => assert_eq!(ty, Type::Uint32),
+        ref kind => panic!("unexpected kind: {:?}", kind),
+    }
+}
@@ -0,0 +1,63 @@
+//! Tests for `.proto` schemas generated from reflection descriptors.
+
+use prost_amino::reflect::Reflect;
+use prost_amino::schema::to_proto_with_package;
+
+use reflect::{Leaf, Tree};
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Legacy {
+    #[prost(int32, required, tag = "1")]
+    pub id: i32,
+    #[prost(uint64, repeated, packed = "true", tag = "2")]
+    pub values: Vec<u64>,
+}
+
+#[test]
+fn proto3_schema() {
+    assert_eq!(
+        to_proto_with_package("tests", &[Tree::descriptor()]),
+        r#"// Generated by prost-amino from Rust message types.
+
+syntax = "proto3";
+
+package tests;
+
+message Tree {
+  string name = 1;
+  repeated BasicEnumeration kinds = 2;
+  repeated Tree children = 3;
+  Tree parent = 4;
+  map<string, Leaf> leaves = 5;
+  oneof payload {
+    uint32 number = 6;
+    Leaf leaf = 7;
+  }
+}
+
+// Amino name "tendermint/socketpv/SignHeartbeatMsg", prefix bf58caef.
+// Encoded as the varint length, the prefix bytes, and then the fields.
+message Leaf {
+  sint64 height = 1;
+  // Amino name "test", values are preceded by the prefix 81884c7d.
+  bytes key = 2;
+}
+
+enum BasicEnumeration {
+  ZERO = 0;
+  ONE = 1;
+  TWO = 2;
+  THREE = 3;
+}
+"#
+    );
+}
+
+#[test]
+fn proto2_schema() {
+    let schema = to_proto_with_package("tests", &[Legacy::descriptor(), Leaf::descriptor()]);
+    assert!(schema.contains("syntax = \"proto2\";"));
+    assert!(schema.contains("  required int32 id = 1;\n"));
+    assert!(schema.contains("  repeated uint64 values = 2 [packed = true];\n"));
+    assert!(schema.contains("  optional sint64 height = 1;\n"));
+}
@@ -0,0 +1,80 @@
+include!(concat!(env!("OUT_DIR"), "/socket_service.rs"));
+
+use std::io::ErrorKind;
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::thread;
+
+use prost_amino::Message;
+
+/// A signer which "signs" votes by reversing them.
+struct ReverseSigner {
+    pings: usize,
+}
+
+impl Signer for ReverseSigner {
+    fn ping(&mut self, _: PingRequest) -> PingResponse {
+        self.pings += 1;
+        PingResponse {}
+    }
+
+    fn sign_vote(&mut self, request: SignVoteRequest) -> SignedVote {
+        let mut signature = request.vote.clone();
+        signature.reverse();
+        SignedVote {
+            vote: request.vote,
+            signature,
+        }
+    }
+}
+
+#[test]
+fn socket_service() {
+    let (client, mut server) = UnixStream::pair().unwrap();
+    let handle = thread::spawn(move || {
+        let mut dispatcher = SignerDispatcher::new(ReverseSigner { pings: 0 });
+        dispatcher.serve(&mut server).unwrap();
+        dispatcher.into_inner().pings
+    });
+
+    let mut client = SignerClient::new(client);
+    assert_eq!(client.ping(PingRequest {}).unwrap(), PingResponse {});
+    let signed = client
+        .sign_vote(SignVoteRequest {
+            vote: vec![1, 2, 3],
+        })
+        .unwrap();
+    assert_eq!(signed.vote, vec![1, 2, 3]);
+    assert_eq!(signed.signature, vec![3, 2, 1]);
+    assert_eq!(client.ping(PingRequest {}).unwrap(), PingResponse {});
+
+    // The dispatcher stops serving once the client closes the socket.
+    client.into_inner().shutdown(Shutdown::Write).unwrap();
+    assert_eq!(handle.join().unwrap(), 2);
+}
+
+#[test]
+fn socket_service_envelopes() {
+    let request = SignerRequest {
+        value: Some(signer_request::Value::SignVote(SignVoteRequest {
+            vote: vec![1],
+        })),
+    };
+    let mut buf = Vec::new();
+    request.encode(&mut buf).unwrap();
+    assert_eq!(buf, [0x12, 0x03, 0x0A, 0x01, 0x01]);
+
+    let mut dispatcher = SignerDispatcher::new(ReverseSigner { pings: 0 });
+    let error = dispatcher.dispatch(SignerRequest::default()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+
+    // A response for another method is an error.
+    let (client, mut server) = UnixStream::pair().unwrap();
+    let response = SignerResponse {
+        value: Some(signer_response::Value::Ping(PingResponse {})),
+    };
+    ::prost_amino::stream::write_length_delimited(&response, &mut server).unwrap();
+    let mut client = SignerClient::new(client);
+    let error = client.sign_vote(SignVoteRequest::default()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
@@ -0,0 +1,173 @@
+include!(concat!(env!("OUT_DIR"), "/text_format.rs"));
+
+use std::f64;
+
+use prost_amino::dynamic::DescriptorPool;
+use prost_amino::reflect::Reflect;
+use prost_amino::text_format::{self, from_text, to_text};
+use prost_amino::Message;
+use prost_types::Any;
+
+fn proposal() -> Proposal {
+    let validator = Validator {
+        address: vec![0x00, 0x0A, 0xFF, b'a'],
+        power: -10,
+    };
+    Proposal {
+        id: 7,
+        title: "raise \"limit\"\n\u{e9}".to_string(),
+        threshold: f64::INFINITY,
+        quorum: 0.25,
+        final_: true,
+        vote: Vote::No as i32,
+        deltas: vec![-1, 2],
+        validators: vec![validator.clone(), Validator::default()],
+        by_name: vec![("alice".to_string(), validator)].into_iter().collect(),
+        previous: Some(Box::new(Proposal {
+            id: 6,
+            ..Proposal::default()
+        })),
+        attachment: None,
+        result: Some(proposal::Result::Code(0)),
+    }
+}
+
+#[test]
+fn print() {
+    assert_eq!(to_text(&Proposal::default()).unwrap(), "");
+    assert_eq!(
+        to_text(&proposal()).unwrap(),
+        r#"id: 7
+title: "raise \"limit\"\n\u{e9}"
+threshold: inf
+quorum: 0.25
+final: true
+vote: VOTE_NO
+deltas: -1
+deltas: 2
+validators {
+  address: "\000\n\377a"
+  power: -10
+}
+validators {
+}
+by_name {
+  key: "alice"
+  value {
+    address: "\000\n\377a"
+    power: -10
+  }
+}
+previous {
+  id: 6
+}
+code: 0
+"#
+        .replace("\\u{e9}", "\u{e9}")
+    );
+}
+
+#[test]
+fn parse() {
+    let proposal = proposal();
+    assert_eq!(
+        from_text::<Proposal>(&to_text(&proposal).unwrap()),
+        Ok(proposal.clone())
+    );
+
+    // The alternative forms of the format are accepted.
+    let parsed = from_text::<Proposal>(
+        r#"
+        # A proposal.
+        id: 0x7, title: 'raise "limit"' "\n\303\251";
+        threshold: Infinity quorum: .25f final: t vote: 2
+        deltas: [-1, 2]
+        validators: < address: "\x00\n\377" 'a' power: -10 >
+        validators []
+        validators [{}]
+        by_name { value { power: -10 address: "\000\n\377a" } key: "alice" }
+        previous: { id: 6 }
+        code: 0
+        "#,
+    );
+    assert_eq!(parsed, Ok(proposal));
+
+    for invalid in &[
+        "unknown: 1",
+        "id: -1",
+        "id: 1 id: 2",
+        "id 1",
+        "title: 1",
+        "title: '\\377'",
+        "quorum: 1.5x",
+        "final: yes",
+        "vote: YES",
+        "deltas: 2147483648",
+        "reason: 'a' code: 1",
+        "previous { id: 1",
+        "previous { } }",
+        "by_name { key: 'a' key: 'b' }",
+        "[ext.field]: 1",
+        "attachment { [type.googleapis.com/text_format.Missing] {} }",
+        "attachment { [type.googleapis.com/text_format.Validator] {} type_url: 'a' }",
+    ] {
+        assert!(from_text::<Proposal>(invalid).is_err(), "{}", invalid);
+    }
+}
+
+#[test]
+fn any() {
+    let validator = Validator {
+        address: vec![0x01],
+        power: 5,
+    };
+    let mut value = Vec::new();
+    validator.encode(&mut value).unwrap();
+    let proposal = Proposal {
+        attachment: Some(Any {
+            type_url: "type.googleapis.com/text_format.Validator".to_string(),
+            value,
+        }),
+        ..Proposal::default()
+    };
+
+    // `Any` values of types in the pool are expanded.
+    let text = to_text(&proposal).unwrap();
+    assert_eq!(
+        text,
+        r#"attachment {
+  [type.googleapis.com/text_format.Validator] {
+    address: "\001"
+    power: 5
+  }
+}
+"#
+    );
+    assert_eq!(from_text::<Proposal>(&text), Ok(proposal));
+
+    // Other `Any` values are printed as their fields.
+    let proposal = Proposal {
+        attachment: Some(Any {
+            type_url: "example.com/unknown.Type".to_string(),
+            value: vec![0x08, 0x01],
+        }),
+        ..Proposal::default()
+    };
+    let text = to_text(&proposal).unwrap();
+    assert_eq!(
+        text,
+        "attachment {\n  type_url: \"example.com/unknown.Type\"\n  value: \"\\010\\001\"\n}\n"
+    );
+    assert_eq!(from_text::<Proposal>(&text), Ok(proposal));
+}
+
+#[test]
+fn unknown_fields() {
+    let mut pool = DescriptorPool::new();
+    pool.add_reflected(Validator::descriptor()).unwrap();
+    // Field 2: int64 5, field 15: varint 1.
+    let message = pool
+        .decode("text_format.Validator", &[0x10, 0x05, 0x78, 0x01][..])
+        .unwrap();
+    assert!(text_format::print(&message).is_err());
+}